@@ -0,0 +1,53 @@
+use std::env;
+use std::process::Command;
+
+/// Passes the commit the game was built from, and the build profile, on to
+/// the compiler, so that `build_info` may embed them in the binary.
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=BUILD_PROFILE={}", env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string()));
+
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("emscripten") {
+        link_for_the_web();
+    }
+
+    // Pick up new commits, and switches to other branches.
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// Builds the game into a page which runs in a browser, see `phi::web`. SDL
+/// and its extensions come from the ports of Emscripten, and the assets are
+/// bundled into a file which the page fetches before the game starts, where
+/// the game expects them.
+fn link_for_the_web() {
+    let assets = format!("{}/src/assets", env::var("CARGO_MANIFEST_DIR").unwrap());
+    let args = [
+        "-sUSE_SDL=2",
+        "-sUSE_SDL_IMAGE=2",
+        "-sSDL2_IMAGE_FORMATS=[\"png\"]",
+        "-sUSE_SDL_TTF=2",
+        "-sUSE_SDL_MIXER=2",
+        "-sSDL2_MIXER_FORMATS=[\"ogg\"]",
+        "-sALLOW_MEMORY_GROWTH=1",
+        "-sFORCE_FILESYSTEM=1",
+        "-lidbfs.js",
+        "--preload-file",
+        &format!("{}@/assets", assets),
+    ];
+
+    for arg in args {
+        println!("cargo:rustc-link-arg={}", arg);
+    }
+    println!("cargo:rerun-if-changed=src/assets");
+}
@@ -0,0 +1,16 @@
+//! What the running binary was built from, as embedded by `build.rs`.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("GIT_HASH");
+pub const PROFILE: &str = env!("BUILD_PROFILE");
+
+/// The version and commit, short enough to fit in a corner of the screen.
+pub fn short() -> String {
+    format!("v{} ({})", VERSION, GIT_HASH)
+}
+
+/// Everything that is known about the build, e.g. for crash reports.
+pub fn full() -> String {
+    format!("{} {}\ncommit: {}\nprofile: {}",
+        env!("CARGO_PKG_NAME"), VERSION, GIT_HASH, PROFILE)
+}
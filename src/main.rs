@@ -1,11 +1,140 @@
 extern crate sdl2;
 extern crate rand;
 
+mod build_info;
 mod phi;
 mod views;
 
 fn main() {
-    crate::phi::spawn("ArcadeRS Shooter", |phi| {
-        Box::new(crate::views::main_menu::MainMenuView::new(phi))
+    let args: Vec<String> = ::std::env::args().collect();
+    let option = |name: &str| args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+
+    // `version`, or `--version`, prints what the binary was built from.
+    if flag("version") || flag("--version") {
+        println!("{}", crate::build_info::full());
+        return;
+    }
+
+    let manifest = crate::views::integrity::MANIFEST_PATH;
+
+    // `--write-asset-manifest` lists the digests of the assets as they are
+    // now, e.g. when packaging a release.
+    if flag("--write-asset-manifest") {
+        match crate::phi::assets::write_manifest("assets", manifest) {
+            Ok(count) => println!("Listed {} assets in {}", count, manifest),
+            Err(err) => {
+                eprintln!("Could not write the asset manifest: {}", err);
+                ::std::process::exit(1);
+            },
+        }
+
+        // Point out the assets which would be shipped without being
+        // credited, or credited without being shipped.
+        let credits = crate::views::credits::CREDITS_PATH;
+        match crate::phi::assets::load_info(credits) {
+            Ok(infos) => {
+                for path in crate::phi::assets::uncredited(::std::path::Path::new("assets"), &infos).unwrap_or_default() {
+                    println!("Warning: {} is not credited in {}", path.display(), credits);
+                }
+                for info in crate::phi::assets::stale(&infos) {
+                    println!("Warning: {} is credited in {} but missing", info.path, credits);
+                }
+            },
+            Err(err) => println!("Warning: could not read the credits: {}", err),
+        }
+        return;
+    }
+
+    // Check that the assets are intact before anything loads them, unless
+    // `--skip-asset-check` is given.
+    let problems =
+        if flag("--skip-asset-check") { vec![] }
+        else {
+            //? A manifest which cannot be read vouches for nothing, so it is
+            //? reported like any other missing asset rather than passed over.
+            crate::phi::assets::verify(manifest).unwrap_or_else(|err| {
+                println!("Could not read the asset manifest: {}", err);
+                vec![crate::phi::assets::AssetProblem::Missing(manifest.to_string())]
+            })
+        };
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+
+    let headless = flag("--headless");
+
+    let config = crate::phi::PhiConfig {
+        title: "ArcadeRS Shooter",
+        // `--seed N` makes the game play out the same way on every launch.
+        seed: option("--seed").and_then(|seed| seed.parse().ok()),
+        // `--record FILE` saves the session, and `--replay FILE` plays it back.
+        record_path: option("--record"),
+        replay_path: option("--replay"),
+        // `--headless` runs without a window, e.g. to check a replay.
+        headless,
+        // `--trace FILE` writes how long every frame took, in builds with the
+        // `trace` feature.
+        trace_path: option("--trace"),
+        // The game is hard to follow once it is scaled down.
+        resize_policy: crate::phi::ResizePolicy::Forbid,
+        ..crate::phi::PhiConfig::default()
+    };
+
+    let bookmark = option("--bookmark");
+    let trailer = option("--trailer");
+
+    let result = crate::phi::spawn(config, move |phi| {
+        let profile = crate::views::shared::DEFAULT_PROFILE;
+        phi.events.bindings = crate::views::bindings::PlayerBindings::load(profile).bindings;
+
+        // Show the game as the player last picked in the options, if the
+        // screen still supports it.
+        if let Some(mode) = crate::views::options::load_display_mode(profile) {
+            if !headless && phi.display_modes().contains(&mode) {
+                if let Err(err) = phi.set_display_mode(mode) {
+                    println!("Warning: {}", err);
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Ok(Box::new(crate::views::integrity::IntegrityView::new(phi, &problems)?));
+        }
+
+        // `--bookmark NAME` starts over from a moment saved with F8 during a
+        // previous run, e.g. to practice or test it again and again.
+        if let Some(ref name) = bookmark {
+            return Ok(Box::new(crate::views::game::GameView::bookmark(phi, name)?));
+        }
+
+        // `--trailer FILE` plays a timed script, e.g. along with `--seed`, so
+        // that the same footage may be captured on every launch.
+        if let Some(ref path) = trailer {
+            return Ok(Box::new(crate::views::game::GameView::trailer(phi, path)?));
+        }
+
+        // Show what changed since the last time the game was played, once.
+        if crate::views::news::NewsView::has_unseen_news() {
+            Ok(Box::new(crate::views::news::NewsView::new(phi)?))
+        } else {
+            Ok(Box::new(crate::views::main_menu::MainMenuView::new(phi)?))
+        }
+    }, |phi, message| {
+        // Tell which build failed, for whoever reads the crash report.
+        println!("{}", crate::build_info::full());
+
+        // Let the player carry on from the main menu when a view fails.
+        Ok(Box::new(crate::views::error::ErrorView::new(phi, &message)?))
     });
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        eprintln!("{}", crate::build_info::full());
+        ::std::process::exit(1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,159 @@
+//! Reads and writes zip archives, e.g. to move the files of a player from one
+//! machine to another.
+//!
+//! Files are stored as they are, without compression: the files of a player
+//! are small, and this keeps the format simple. Archives written by other
+//! tools may only be read if their entries were stored the same way.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_DIRECTORY: u32 = 0x06054b50;
+
+/// The date written for every entry, i.e. the first day which zip supports.
+//? Dates are stored as days since 1980, in the DOS format: 0x21 is January
+//? 1st, 1980.
+const DOS_DATE: u16 = 0x21;
+
+/// A file in an archive: its path, with forward slashes, and its contents.
+pub type Entry = (String, Vec<u8>);
+
+/// Computes the CRC-32 of `data`, as zip stores it for every entry.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
+
+/// Writes `entries` to a new archive at `path`.
+pub fn write<P: AsRef<Path>>(path: P, entries: &[Entry]) -> io::Result<()> {
+    let mut data = vec![];
+    let mut directory = vec![];
+
+    for (name, contents) in entries {
+        let offset = data.len() as u32;
+        let crc = crc32(contents);
+
+        //? The fields which come after the signature are shared by the local
+        //? header and the central one: version needed, flags, compression,
+        //? time, date, CRC, both sizes, and the length of the name.
+        let mut common = vec![];
+        common.extend_from_slice(&10u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&DOS_DATE.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+        data.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        data.extend_from_slice(&common);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(contents);
+
+        // Made by: version 2.0. Then lengths of the extra field and comment,
+        // disk, and attributes, all left empty.
+        directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&common);
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = data.len() as u32;
+    data.extend_from_slice(&directory);
+
+    data.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    data.extend_from_slice(&directory_offset.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+
+    fs::write(path, data)
+}
+
+/// Reads every entry of the archive at `path`, other than directories.
+/// Returns an error if the archive is damaged, or if some entry is
+/// compressed.
+pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
+    let data = fs::read(path)?;
+    let u16_at = |at: usize| data.get(at..at + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+        .ok_or_else(|| invalid("the archive is truncated"));
+    let u32_at = |at: usize| data.get(at..at + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| invalid("the archive is truncated"));
+
+    //? The end of the central directory is followed by a comment of any
+    //? length, so we look for its signature from the end.
+    let end = (0..data.len().saturating_sub(21)).rev()
+        .find(|&at| u32_at(at).ok() == Some(END_OF_DIRECTORY))
+        .ok_or_else(|| invalid("this is not a zip archive"))?;
+
+    let count = u16_at(end + 10)?;
+    let mut at = u32_at(end + 16)? as usize;
+    let mut entries = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        if u32_at(at)? != CENTRAL_HEADER {
+            return Err(invalid("the archive's directory is damaged"));
+        }
+
+        let compression = u16_at(at + 10)?;
+        let crc = u32_at(at + 16)?;
+        let size = u32_at(at + 20)? as usize;
+        let name_len = u16_at(at + 28)?;
+        let skip = name_len + u16_at(at + 30)? + u16_at(at + 32)?;
+        let offset = u32_at(at + 42)? as usize;
+        let name = data.get(at + 46..at + 46 + name_len)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .ok_or_else(|| invalid("the archive is truncated"))?;
+        at += 46 + skip;
+
+        if name.ends_with('/') {
+            continue;
+        }
+
+        if compression != 0 {
+            return Err(invalid(&format!("{} is compressed, which is not supported", name)));
+        }
+
+        if u32_at(offset)? != LOCAL_HEADER {
+            return Err(invalid(&format!("the header of {} is damaged", name)));
+        }
+
+        let start = offset + 30 + u16_at(offset + 26)? + u16_at(offset + 28)?;
+        let contents = data.get(start..start + size)
+            .ok_or_else(|| invalid("the archive is truncated"))?
+            .to_vec();
+
+        if crc32(&contents) != crc {
+            return Err(invalid(&format!("{} is damaged", name)));
+        }
+
+        entries.push((name, contents));
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,201 @@
+//! Checks that the files shipped with the game are all there and intact,
+//! before anything tries to load them.
+//!
+//! The manifest lists the SHA-256 digest of every file, one per line, in the
+//! same format as `sha256sum`, so that it may also be checked by hand.
+//!
+//! Who made every image, sound and font, and under which license, is listed
+//! apart from the digests, and gathered into the credits of the game. The
+//! manifest is written anew by `write_manifest` for every release, and would
+//! lose whatever was written in it by hand, whereas the metadata is kept up
+//! by hand and only checked against the files. Being one of the files, it is
+//! itself listed in the manifest.
+
+use crate::phi::PhiError;
+use crate::phi::signing;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Something wrong with one of the files listed in the manifest.
+#[derive(Clone, Debug)]
+pub enum AssetProblem {
+    Missing(String),
+    Corrupted(String),
+}
+
+impl fmt::Display for AssetProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssetProblem::Missing(ref path) => write!(f, "{} is missing", path),
+            AssetProblem::Corrupted(ref path) => write!(f, "{} is damaged", path),
+        }
+    }
+}
+
+/// Checks every file listed in the manifest at `manifest`. Fails if the
+/// manifest itself could not be read.
+pub fn verify<P: AsRef<Path>>(manifest: P) -> io::Result<Vec<AssetProblem>> {
+    let contents = fs::read_to_string(manifest)?;
+    let mut problems = vec![];
+
+    for line in contents.lines() {
+        let (expected, path) = match line.split_once("  ") {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        match fs::read(path) {
+            Ok(data) if signing::digest(&data) == expected.trim() => {},
+            Ok(_) => problems.push(AssetProblem::Corrupted(path.to_string())),
+            Err(_) => problems.push(AssetProblem::Missing(path.to_string())),
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Lists every file under `dir`, recursively, in a stable order.
+pub fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            found.extend(files(&path)?);
+        } else {
+            found.push(path);
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+/// Writes to `manifest` the digest of every file under `dir`, except the
+/// manifest itself, e.g. when packaging a release. Returns how many files were
+/// listed.
+pub fn write_manifest<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, manifest: Q) -> io::Result<usize> {
+    let manifest = manifest.as_ref();
+    let mut contents = String::new();
+    let mut count = 0;
+
+    for path in files(dir.as_ref())? {
+        if path == manifest {
+            continue;
+        }
+
+        //? Paths are always written with forward slashes, so that the same
+        //? manifest works on every platform.
+        let name = path.to_string_lossy().replace('\\', "/");
+        contents.push_str(&format!("{}  {}\n", signing::digest(&fs::read(&path)?), name));
+        count += 1;
+    }
+
+    fs::write(manifest, contents)?;
+    Ok(count)
+}
+
+/// The extensions of the files made by artists, which must all be credited.
+const MEDIA_EXTENSIONS: &[&str] = &["png", "jpg", "ogg", "wav", "mp3", "ttf", "otf"];
+
+/// Who made a file shipped with the game, and under which license it is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetInfo {
+    pub path: String,
+    pub author: String,
+    pub license: String,
+
+    /// Where the file was found, e.g. a website, if it was not made for the
+    /// game.
+    pub source: Option<String>,
+}
+
+/// The files which were made by the same author, under the same license.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credit {
+    pub author: String,
+    pub license: String,
+    pub source: Option<String>,
+    pub files: Vec<String>,
+}
+
+/// Reads the metadata of the assets listed at `path`, one per line:
+///     path | author | license [| source]
+/// Empty lines and lines starting with `#` are ignored.
+pub fn load_info(path: &str) -> Result<Vec<AssetInfo>, PhiError> {
+    let contents = fs::read_to_string(path).map_err(PhiError::asset(path))?;
+
+    contents.lines()
+        .map(|line| line.trim())
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| parse_info(line).ok_or_else(|| PhiError::Asset {
+            path: path.to_string(),
+            reason: format!("line {} does not describe an asset", i + 1),
+        }))
+        .collect()
+}
+
+fn parse_info(line: &str) -> Option<AssetInfo> {
+    let fields: Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+    if fields.iter().any(|field| field.is_empty()) {
+        return None;
+    }
+
+    match fields[..] {
+        [path, author, license] | [path, author, license, _] => Some(AssetInfo {
+            path: path.to_string(),
+            author: author.to_string(),
+            license: license.to_string(),
+            source: fields.get(3).map(|source| source.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+/// Gathers the files of every author under every license, in the order in
+/// which they first appear in `infos`.
+pub fn credits(infos: &[AssetInfo]) -> Vec<Credit> {
+    let mut credits: Vec<Credit> = vec![];
+
+    for info in infos {
+        let same = credits.iter_mut().find(|credit| {
+            credit.author == info.author && credit.license == info.license && credit.source == info.source
+        });
+
+        match same {
+            Some(credit) => credit.files.push(info.path.clone()),
+            None => credits.push(Credit {
+                author: info.author.clone(),
+                license: info.license.clone(),
+                source: info.source.clone(),
+                files: vec![info.path.clone()],
+            }),
+        }
+    }
+
+    credits
+}
+
+/// The images, sounds and fonts under `dir` which `infos` says nothing about,
+/// so that new ones are not shipped without being credited.
+pub fn uncredited(dir: &Path, infos: &[AssetInfo]) -> io::Result<Vec<PathBuf>> {
+    let is_media = |path: &Path| path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| MEDIA_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+
+    Ok(files(dir)?.into_iter()
+        .filter(|path| is_media(path))
+        .filter(|path| {
+            let name = path.to_string_lossy().replace('\\', "/");
+            !infos.iter().any(|info| info.path == name)
+        })
+        .collect())
+}
+
+/// The files which `infos` credits but which are not shipped anymore.
+pub fn stale(infos: &[AssetInfo]) -> Vec<&AssetInfo> {
+    infos.iter().filter(|info| !Path::new(&info.path).exists()).collect()
+}
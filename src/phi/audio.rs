@@ -0,0 +1,189 @@
+//! Smoothly lowers the music while the game is in the background, e.g. behind
+//! a menu, rather than cutting it, and fades individual sounds in and out.
+
+use crate::phi::PhiError;
+use sdl2::mixer::{Channel, Chunk, Music, MAX_VOLUME};
+
+/// How loud the music is kept while ducked, relative to its normal volume.
+const DUCKED_LEVEL: f64 = 0.3;
+
+/// How fast the volume moves towards its target, in full volumes per second.
+const DUCK_SPEED: f64 = 3.0;
+
+/// Synthesizes a short, fading sine wave of the given `pitch`, in Hz, which
+/// lasts `length` seconds, in the mixer's format, so that no sound file is
+/// needed.
+pub fn tone(pitch: f64, length: f64) -> Result<Chunk, PhiError> {
+    let (frequency, _, channels) = ::sdl2::mixer::query_spec().map_err(PhiError::Audio)?;
+    let samples = (frequency as f64 * length) as usize;
+
+    let buffer: Vec<i16> = (0..samples)
+        .flat_map(|i| {
+            let t = i as f64 / frequency as f64;
+            let fade = 1.0 - i as f64 / samples as f64;
+            let value = (t * pitch * 2.0 * ::std::f64::consts::PI).sin() * fade * 6000.0;
+            ::std::iter::repeat_n(value as i16, channels as usize)
+        })
+        .collect();
+
+    Chunk::from_raw_buffer(buffer.into_boxed_slice()).map_err(PhiError::Audio)
+}
+
+/// Ducks the music, which SDL plays at a single, global volume.
+pub struct MusicDucking {
+    // The volume chosen by the player, which is restored after ducking.
+    full: i32,
+
+    // The current and the wanted volume, relative to `full`.
+    level: f64,
+    target: f64,
+}
+
+impl MusicDucking {
+    /// Starts from whatever volume the music is currently played at.
+    pub fn new() -> MusicDucking {
+        MusicDucking {
+            full: Music::get_volume(),
+            level: 1.0,
+            target: 1.0,
+        }
+    }
+
+    /// Lowers the music gradually.
+    pub fn duck(&mut self) {
+        self.target = DUCKED_LEVEL;
+    }
+
+    /// Brings the music back to its normal volume gradually.
+    pub fn restore(&mut self) {
+        self.target = 1.0;
+    }
+
+    /// Brings the music back to its normal volume right away, e.g. when the
+    /// view which ducked it is left.
+    pub fn reset(&mut self) {
+        self.level = 1.0;
+        self.target = 1.0;
+        Music::set_volume(self.full);
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.level == self.target {
+            return;
+        }
+
+        let step = DUCK_SPEED * dt;
+        self.level =
+            if self.level < self.target { (self.level + step).min(self.target) }
+            else { (self.level - step).max(self.target) };
+
+        Music::set_volume((self.full as f64 * self.level) as i32);
+    }
+}
+
+/// What a fade changes the volume of.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FadeTarget {
+    /// The sound being played on a mixing channel.
+    Channel(Channel),
+
+    /// The music. This fights with `MusicDucking` over the same volume, so
+    /// only one of them should be used at a time.
+    Music,
+}
+
+impl FadeTarget {
+    fn volume(self) -> i32 {
+        match self {
+            FadeTarget::Channel(channel) => channel.get_volume(),
+            FadeTarget::Music => Music::get_volume(),
+        }
+    }
+
+    fn set_volume(self, volume: i32) {
+        match self {
+            FadeTarget::Channel(channel) => { channel.set_volume(volume); },
+            FadeTarget::Music => Music::set_volume(volume),
+        }
+    }
+}
+
+struct Fade {
+    target: FadeTarget,
+    from: f64,
+    to: f64,
+    duration: f64,
+    elapsed: f64,
+
+    // Called once the volume reached `to`, e.g. to stop the sound.
+    on_done: Option<Box<dyn FnOnce()>>,
+}
+
+/// Changes the volume of sounds and music gradually. Every target may only
+/// have a single fade going on: starting a new one replaces the previous one,
+/// whose callback is then never called.
+#[derive(Default)]
+pub struct Fades {
+    fades: Vec<Fade>,
+}
+
+impl Fades {
+    /// Moves the volume of `target` from its current value to `volume`, in
+    /// `[0, 1]`, over `duration` seconds, then calls `on_done` if given.
+    pub fn fade(&mut self, target: FadeTarget, volume: f64, duration: f64, on_done: Option<Box<dyn FnOnce()>>) {
+        self.fades.retain(|fade| fade.target != target);
+        self.fades.push(Fade {
+            target,
+            from: target.volume() as f64 / MAX_VOLUME as f64,
+            to: volume.clamp(0.0, 1.0),
+            duration,
+            elapsed: 0.0,
+            on_done,
+        });
+    }
+
+    /// Brings `target` from silence to its current volume over `duration`
+    /// seconds.
+    pub fn fade_in(&mut self, target: FadeTarget, duration: f64) {
+        let volume = target.volume() as f64 / MAX_VOLUME as f64;
+        target.set_volume(0);
+        self.fade(target, volume, duration, None);
+    }
+
+    /// Silences `target` over `duration` seconds, then stops it. Its volume
+    /// is restored afterwards, for the next sound played on the channel.
+    pub fn fade_out(&mut self, target: FadeTarget, duration: f64) {
+        let volume = target.volume();
+        self.fade(target, 0.0, duration, Some(Box::new(move || {
+            match target {
+                FadeTarget::Channel(channel) => channel.halt(),
+                FadeTarget::Music => Music::halt(),
+            }
+            target.set_volume(volume);
+        })));
+    }
+
+    /// Moves every fade forward by `dt` seconds, and calls back those which
+    /// are over.
+    pub fn update(&mut self, dt: f64) {
+        let mut done = vec![];
+
+        self.fades.retain_mut(|fade| {
+            fade.elapsed += dt;
+            let progress = if fade.duration > 0.0 { (fade.elapsed / fade.duration).min(1.0) } else { 1.0 };
+            let volume = fade.from + (fade.to - fade.from) * progress;
+            fade.target.set_volume((volume * MAX_VOLUME as f64).round() as i32);
+
+            if progress < 1.0 {
+                return true;
+            }
+
+            done.extend(fade.on_done.take());
+            false
+        });
+
+        for on_done in done {
+            on_done();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+/// How the engine should be set up by `spawn` or `PhiRunner`. Fields which are
+/// not relevant to a game can be left to their default values:
+///
+///     PhiConfig {
+///         title: "My game",
+///         fullscreen: true,
+///         ..PhiConfig::default()
+///     }
+#[derive(Clone, Debug)]
+pub struct PhiConfig {
+    /// The title of the window.
+    pub title: &'static str,
+
+    /// The size of the area in which the game is drawn, in logical pixels.
+    /// This is also the initial size of the window.
+    pub width: u32,
+    pub height: u32,
+
+    /// Whether the window starts fullscreen.
+    pub fullscreen: bool,
+
+    /// Whether presenting a frame waits for the vertical refresh.
+    pub vsync: bool,
+
+    /// The maximum number of frames rendered every second, if any.
+    pub fps_cap: Option<u32>,
+
+    /// The width and height of the largest texture which the game draws, in
+    /// pixels, e.g. that of a sprite sheet. The game draws in software, with
+    /// low effects, if the GPU cannot hold textures this large or the window.
+    pub largest_texture: u32,
+
+    /// The image shown as the icon of the window, if any.
+    pub icon_path: Option<&'static str>,
+
+    /// The seed of the random number generator which the game draws from,
+    /// so that runs may be reproduced. A new one is picked on every launch
+    /// if none is given.
+    pub seed: Option<u64>,
+
+    /// The file to which the input of every tick is written, if any, so that
+    /// the session may be played back later.
+    pub record_path: Option<String>,
+
+    /// The file from which the input is played back, if any, instead of being
+    /// read from the player. The seed saved in it overrides `seed`, and the
+    /// game quits once every recorded tick was played.
+    pub replay_path: Option<String>,
+
+    /// Whether the game runs without showing a window or playing any sound,
+    /// e.g. to play a replay back in tests. Every frame then simulates exactly
+    /// one tick, as fast as possible, instead of following the clock.
+    pub headless: bool,
+
+    /// What happens when the window is made smaller than `width` by `height`.
+    pub resize_policy: ResizePolicy,
+
+    /// The file to which a trace of every frame is written once the game
+    /// exits, if any. Only builds with the `trace` feature record it.
+    pub trace_path: Option<String>,
+
+    pub audio: AudioConfig,
+}
+
+/// What happens when the window is made smaller than the area in which the
+/// game is drawn. Whatever the policy, entities keep living in logical
+/// pixels, so that the size of the window never changes how the game plays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizePolicy {
+    /// The game is scaled down to fit the window, which may make it hard to
+    /// see what is going on.
+    Shrink,
+
+    /// Like `Shrink`, but the current view is paused, as when the window
+    /// loses the focus, so that the player may make the window larger again
+    /// before carrying on.
+    Pause,
+
+    /// The window may not be made smaller. Some window managers ignore this,
+    /// in which case the game is shrunk.
+    Forbid,
+}
+
+/// How the audio device is opened.
+#[derive(Clone, Debug)]
+pub struct AudioConfig {
+    /// The sampling frequency, in Hz.
+    pub frequency: i32,
+
+    /// The number of output channels, e.g. 2 for stereo.
+    pub output_channels: i32,
+
+    /// The size of the audio buffer, in samples.
+    pub chunk_size: i32,
+
+    /// How many sounds may be played at the same time.
+    pub mixing_channels: i32,
+}
+
+impl Default for PhiConfig {
+    fn default() -> PhiConfig {
+        PhiConfig {
+            title: "Phi",
+            width: 800,
+            height: 600,
+            fullscreen: false,
+            vsync: false,
+            fps_cap: Some(60),
+            largest_texture: 2048,
+            icon_path: None,
+            seed: None,
+            record_path: None,
+            replay_path: None,
+            headless: false,
+            resize_policy: ResizePolicy::Shrink,
+            trace_path: None,
+            audio: AudioConfig::default(),
+        }
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> AudioConfig {
+        //? We configure our audio context so that:
+        //?   * The frequency is 44100;
+        //?   * It's also stereo (2 "channels");
+        //?   * Samples are 1024 bytes in size.
+        //? You don't really need to understand what all of this means. I myself just
+        //? copy-pasted this from andelf's demo. ;-)
+        //?
+        //? As for the number of sounds which may be played at the same time,
+        //? while testing, 16 channels seemed to be sufficient. Which means that
+        //? we should probably request 32 of 'em just in case. :-°
+        AudioConfig {
+            frequency: 44100,
+            output_channels: 2,
+            chunk_size: 1024,
+            mixing_channels: 32,
+        }
+    }
+}
@@ -1,4 +1,7 @@
 use sdl2::rect::Rect as SdlRect;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
@@ -10,14 +13,14 @@ pub struct Rectangle {
 
 impl Rectangle {
     /// Generates an SDL-compatible Rect equivalent to `self`
-    /// Panics if it could not be created, for example if a
-    /// coordinate of a corner overflows an `i32`
+    /// A negative width or height is treated as zero, and coordinates which
+    /// overflow an `i32` saturate, so that this never fails.
     pub fn to_sdl(self) -> SdlRect {
         // Reject negative width and height
-        assert!(self.w >= 0.0 && self.h >= 0.0);
+        let (w, h) = (self.w.max(0.0), self.h.max(0.0));
 
         // SdlRect::new : `(i32, i32, i32, i32) -> Result<Option<SdlRect>>
-        SdlRect::new(self.x as i32, self.y as i32, self.w as u32, self.h as u32)
+        SdlRect::new(self.x as i32, self.y as i32, w as u32, h as u32)
     }
 
     /// Return a (perhaps moved) rectangle which is contained by a `parent`
@@ -80,27 +83,526 @@ impl Rectangle {
         }
     }
 
+    /// Return the rectangle `alpha` of the way from `self` to `other`, where
+    /// `alpha` is usually in `[0, 1]`.
+    pub fn lerp(self, other: Rectangle, alpha: f64) -> Rectangle {
+        Rectangle {
+            x: self.x + (other.x - self.x) * alpha,
+            y: self.y + (other.y - self.y) * alpha,
+            w: self.w + (other.w - self.w) * alpha,
+            h: self.h + (other.h - self.h) * alpha,
+        }
+    }
+
     /// Return the center of the rectangle.
     pub fn center(self) -> (f64, f64) {
         let x = self.x + self.w / 2.0;
         let y = self.y + self.h / 2.0;
         (x, y)
     }
+
+    /// How far along its move by `motion`, from 0 to 1, the rectangle first
+    /// touches `other`, or 0 if it already does. Returns `None` if it misses,
+    /// so that something fast may not go through something thin between two
+    /// ticks.
+    pub fn sweep(self, motion: Vec2, other: Rectangle) -> Option<f64> {
+        //? Moving the rectangle against `other` is the same as moving its
+        //? center against `other` grown by the size of the rectangle.
+        let grown = Rectangle {
+            x: other.x - self.w / 2.0,
+            y: other.y - self.h / 2.0,
+            w: other.w + self.w,
+            h: other.h + self.h,
+        };
+
+        let from = Vec2::from(self.center());
+        Segment { from: from.into(), to: (from + motion).into() }.entering(grown)
+    }
+
+    /// The same rectangle, moved by `offset`.
+    pub fn translated(self, offset: Vec2) -> Rectangle {
+        Rectangle {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
+            ..self
+        }
+    }
+}
+
+/// A disc, e.g. the body of something round, which rectangles would fit
+/// poorly around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn overlaps(self, other: Circle) -> bool {
+        (other.center - self.center).length_squared() < (self.radius + other.radius).powi(2)
+    }
+
+    pub fn overlaps_rect(self, rect: Rectangle) -> bool {
+        // The point of the rectangle which is the closest to the center.
+        let closest = Vec2::new(
+            self.center.x.clamp(rect.x, rect.x + rect.w),
+            self.center.y.clamp(rect.y, rect.y + rect.h));
+
+        (closest - self.center).length_squared() < self.radius.powi(2)
+    }
+
+    /// The smallest rectangle which contains the circle.
+    pub fn bounding_box(self) -> Rectangle {
+        Rectangle::with_size(self.radius * 2.0, self.radius * 2.0).center_at(self.center.into())
+    }
+}
+
+/// The shape with which an entity collides with the others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Rect(Rectangle),
+    Circle(Circle),
+}
+
+impl Shape {
+    pub fn overlaps(self, other: Shape) -> bool {
+        match (self, other) {
+            (Shape::Rect(a), Shape::Rect(b)) => a.overlaps(b),
+            (Shape::Circle(a), Shape::Circle(b)) => a.overlaps(b),
+            (Shape::Circle(circle), Shape::Rect(rect)) |
+            (Shape::Rect(rect), Shape::Circle(circle)) => circle.overlaps_rect(rect),
+        }
+    }
+
+    /// The smallest rectangle which contains the shape.
+    pub fn bounding_box(self) -> Rectangle {
+        match self {
+            Shape::Rect(rect) => rect,
+            Shape::Circle(circle) => circle.bounding_box(),
+        }
+    }
+
+    /// Whether `rect`, moving by `motion`, touches the shape at some point
+    /// along the way.
+    pub fn swept_by(self, rect: Rectangle, motion: Vec2) -> bool {
+        let start = match rect.sweep(motion, self.bounding_box()) {
+            Some(start) => start,
+            None => return false,
+        };
+
+        match self {
+            Shape::Rect(_) => true,
+
+            //? The rectangle reached the bounding box of the circle, but may
+            //? still miss its rounded corners. The rest of the move is checked
+            //? in steps shorter than the rectangle, so that none is skipped.
+            Shape::Circle(circle) => {
+                let step = rect.w.min(rect.h).max(1.0) / motion.length().max(1.0);
+                let steps = ((1.0 - start) / step).ceil() as usize;
+
+                (0..=steps).any(|i| {
+                    let t = (start + i as f64 * step).min(1.0);
+                    circle.overlaps_rect(rect.translated(motion * t))
+                })
+            },
+        }
+    }
+}
+
+/// A position, a velocity or a direction in the plane, in pixels, or pixels
+/// per second.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub fn new(x: f64, y: f64) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    /// The unit vector which points `angle` radians clockwise from the right,
+    /// since the y axis points down.
+    pub fn from_angle(angle: f64) -> Vec2 {
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    pub fn length(self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    /// The square of the length, which is cheaper to compute, e.g. to compare
+    /// distances.
+    pub fn length_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    /// The vector of length 1 which points the same way, or zero if `self`
+    /// is zero, since it then points nowhere.
+    pub fn normalize(self) -> Vec2 {
+        let len = self.length();
+        if len > 0.0 { self / len } else { Vec2::ZERO }
+    }
+
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The angle, in radians, from the right to the vector, clockwise.
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// The vector, turned clockwise by `angle` radians.
+    pub fn rotate(self, angle: f64) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// The vector, turned a quarter turn clockwise.
+    pub fn perp(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+}
+
+impl From<(f64, f64)> for Vec2 {
+    fn from((x, y): (f64, f64)) -> Vec2 {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for (f64, f64) {
+    fn from(v: Vec2) -> (f64, f64) {
+        (v.x, v.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: f64) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, divisor: f64) -> Vec2 {
+        Vec2::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for Vec2 {
+    fn mul_assign(&mut self, factor: f64) {
+        *self = *self * factor;
+    }
+}
+
+/// A straight line between two points, e.g. the path along which something
+/// moved during a tick, or a wall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+}
+
+impl Segment {
+    /// How far along `self`, from 0 at `from` to 1 at `to`, it crosses
+    /// `other`. Returns `None` if they do not cross, or if they are parallel.
+    pub fn crossing(self, other: Segment) -> Option<f64> {
+        let (dx, dy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+        let (ex, ey) = (other.to.0 - other.from.0, other.to.1 - other.from.1);
+
+        //? Solves `self.from + t * d = other.from + u * e` for `t` and `u`,
+        //? both of which must be in [0, 1].
+        let denom = dx * ey - dy * ex;
+        if denom == 0.0 {
+            return None;
+        }
+
+        let (fx, fy) = (other.from.0 - self.from.0, other.from.1 - self.from.1);
+        let t = (fx * ey - fy * ex) / denom;
+        let u = (fx * dy - fy * dx) / denom;
+
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// How far along `self`, from 0 at `from` to 1 at `to`, it enters
+    /// `rect`, or 0 if it starts inside of it. Returns `None` if it misses.
+    pub fn entering(self, rect: Rectangle) -> Option<f64> {
+        let (dx, dy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+
+        //? Clips the segment to the horizontal slab of the rectangle, then to
+        //? its vertical slab. What remains of it, if anything, is inside.
+        let mut range = (0.0f64, 1.0f64);
+        for (from, d, min, max) in [(self.from.0, dx, rect.x, rect.x + rect.w), (self.from.1, dy, rect.y, rect.y + rect.h)] {
+            if d == 0.0 {
+                if from < min || from > max {
+                    return None;
+                }
+            } else {
+                let (t0, t1) = ((min - from) / d, (max - from) / d);
+                range = (range.0.max(t0.min(t1)), range.1.min(t0.max(t1)));
+            }
+        }
+
+        if range.0 <= range.1 { Some(range.0) } else { None }
+    }
+
+    /// The unit vector perpendicular to the segment.
+    pub fn normal(self) -> Vec2 {
+        (Vec2::from(self.to) - Vec2::from(self.from)).perp().normalize()
+    }
+
+    /// The mirror image of `point` on the other side of the line which
+    /// extends the segment.
+    pub fn mirror(self, point: (f64, f64)) -> (f64, f64) {
+        let normal = self.normal();
+        let dist = (Vec2::from(point) - Vec2::from(self.from)).dot(normal);
+        (Vec2::from(point) - normal * (2.0 * dist)).into()
+    }
+}
+
+/// The velocity of something which bounced off a surface perpendicular to
+/// `normal`, a unit vector, while moving at `velocity`.
+pub fn reflect(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - normal * (2.0 * velocity.dot(normal))
 }
 
+#[derive(Clone)]
 pub struct MaybeAlive<T> {
     pub alive: bool,
     pub value: T,
 }
 
-impl<T> MaybeAlive<T> {
-    // Check whether the value is still alive. If this is the case, then
-    // return `Some(value)`; otherwise, return `None`
-    pub fn as_option(self) -> Option<T> {
-        if self.alive {
-            Some(self.value)
-        } else {
-            None
+/// A name which identifies an entity in debugging output, e.g. `asteroid#42`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EntityName {
+    pub kind: &'static str,
+    pub id: u64,
+}
+
+impl fmt::Display for EntityName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}#{}", self.kind, self.id)
+    }
+}
+
+/// Hands out the identifiers of entities. Every identifier is only given out
+/// once, regardless of the kind of entity, so that an identifier alone is
+/// enough to trace an entity through the logs.
+#[derive(Default)]
+pub struct IdAllocator {
+    next: u64,
+}
+
+impl IdAllocator {
+    pub fn name(&mut self, kind: &'static str) -> EntityName {
+        self.next += 1;
+        EntityName { kind, id: self.next }
+    }
+}
+
+/// Buckets rectangles by the cells of a uniform grid which they overlap, so
+/// that finding those which overlap some area only looks at its neighbours
+/// rather than at every rectangle.
+///
+/// The grid is meant to be cleared and filled again on every tick. Its cells
+/// should be about as large as the rectangles which are put in it.
+#[derive(Clone)]
+pub struct SpatialGrid<T> {
+    cell_size: f64,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    items: Vec<(Rectangle, T)>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    pub fn new(cell_size: f64) -> SpatialGrid<T> {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            items: vec![],
+        }
+    }
+
+    /// Forgets every rectangle, but keeps the memory which was allocated for
+    /// them.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.items.clear();
+    }
+
+    /// Adds `rect`, which is identified by `value` in queries.
+    pub fn insert(&mut self, rect: Rectangle, value: T) {
+        let index = self.items.len();
+        self.items.push((rect, value));
+
+        let (x0, y0, x1, y1) = self.cell_range(rect);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
         }
     }
-}
\ No newline at end of file
+
+    /// The values of the rectangles which overlap `area`, each of them once,
+    /// in the order in which they were inserted.
+    pub fn query(&self, area: Rectangle) -> Vec<T> {
+        let mut found: Vec<usize> = vec![];
+
+        let (x0, y0, x1, y1) = self.cell_range(area);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(cell.iter().filter(|&&i| self.items[i].0.overlaps(area)));
+                }
+            }
+        }
+
+        //? A rectangle which spans several cells is found in each of them.
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|i| self.items[i].1).collect()
+    }
+
+    /// The values of the rectangles which `ray` goes through, each of them
+    /// once, along with how far along the ray it enters them, from 0 at its
+    /// start to 1 at its end. The closest ones come first.
+    pub fn raycast_all(&self, ray: Segment) -> Vec<(f64, T)> {
+        let mut found: Vec<usize> = vec![];
+        self.walk(ray, |cell, _| {
+            if let Some(cell) = self.cells.get(&cell) {
+                found.extend(cell);
+            }
+            true
+        });
+
+        found.sort_unstable();
+        found.dedup();
+
+        let mut hits: Vec<(f64, T)> = found.into_iter()
+            .filter_map(|i| ray.entering(self.items[i].0).map(|t| (t, self.items[i].1)))
+            .collect();
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        hits
+    }
+
+    /// The value of the first rectangle which `ray` goes through, if any,
+    /// along with how far along the ray it enters it. Cells past it are not
+    /// looked at.
+    pub fn raycast(&self, ray: Segment) -> Option<(f64, T)> {
+        let mut first: Option<(f64, usize)> = None;
+        self.walk(ray, |cell, entered| {
+            //? A rectangle which was entered before this cell cannot be
+            //? beaten by one which overlaps it.
+            if first.is_some_and(|(t, _)| t < entered) {
+                return false;
+            }
+
+            for &i in self.cells.get(&cell).into_iter().flatten() {
+                if let Some(t) = ray.entering(self.items[i].0) {
+                    if first.is_none_or(|(best, _)| t < best) {
+                        first = Some((t, i));
+                    }
+                }
+            }
+            true
+        });
+
+        first.map(|(t, i)| (t, self.items[i].1))
+    }
+
+    /// Visits the cells which `ray` goes through, in order, along with how
+    /// far along the ray it enters them, until `visit` returns `false`.
+    fn walk(&self, ray: Segment, mut visit: impl FnMut((i32, i32), f64) -> bool) {
+        let cell = |v: f64| (v / self.cell_size).floor() as i32;
+        let (mut x, mut y) = (cell(ray.from.0), cell(ray.from.1));
+        let end = (cell(ray.to.0), cell(ray.to.1));
+
+        //? For either axis: the direction in which the ray goes from one cell
+        //? to the next, how far along it the next cell is entered, and how
+        //? much further the one after that is.
+        let axis = |from: f64, to: f64, cell: i32| {
+            let d = to - from;
+            if d > 0.0 {
+                (1, ((cell + 1) as f64 * self.cell_size - from) / d, self.cell_size / d)
+            } else if d < 0.0 {
+                (-1, (cell as f64 * self.cell_size - from) / d, -self.cell_size / d)
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(ray.from.0, ray.to.0, x);
+        let (step_y, mut next_y, delta_y) = axis(ray.from.1, ray.to.1, y);
+        let mut entered = 0.0;
+
+        while visit((x, y), entered) && (x, y) != end {
+            if next_x < next_y {
+                x += step_x;
+                entered = next_x;
+                next_x += delta_x;
+            } else {
+                y += step_y;
+                entered = next_y;
+                next_y += delta_y;
+            }
+
+            if entered > 1.0 {
+                break;
+            }
+        }
+    }
+
+    /// The cells which `rect` overlaps, as the coordinates of the top-left
+    /// and bottom-right ones.
+    fn cell_range(&self, rect: Rectangle) -> (i32, i32, i32, i32) {
+        let cell = |v: f64| (v / self.cell_size).floor() as i32;
+        (cell(rect.x), cell(rect.y), cell(rect.x + rect.w), cell(rect.y + rect.h))
+    }
+}
@@ -0,0 +1,138 @@
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{self, draw, CopySprite, DrawStats, Sprite};
+use sdl2::pixels::Color;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+const FONT_SIZE: i32 = 16;
+const LINE_H: f64 = 18.0;
+const MARGIN: f64 = 6.0;
+
+/// Numbers which views report about themselves on every tick, e.g. how many
+/// entities they hold, to be shown by the debug overlay.
+#[derive(Default)]
+pub struct FrameStats {
+    counters: Vec<(&'static str, usize)>,
+}
+
+impl FrameStats {
+    /// Reports the current value of the counter called `name`.
+    pub fn set(&mut self, name: &'static str, value: usize) {
+        match self.counters.iter_mut().find(|(n, _)| *n == name) {
+            Some(counter) => counter.1 = value,
+            None => self.counters.push((name, value)),
+        }
+    }
+
+    /// Every counter, along with its current value.
+    pub fn counters(&self) -> &[(&'static str, usize)] {
+        &self.counters
+    }
+
+    /// Forgets every counter, so that those of the previous view do not
+    /// linger after switching to another.
+    pub fn clear(&mut self) {
+        self.counters.clear();
+    }
+}
+
+/// Performance information drawn over the game, toggled with a key.
+pub struct DebugOverlay {
+    pub visible: bool,
+
+    // Frame timing, measured over the last second
+    frames: u32,
+    frame_time_total: f64,
+    fps: u32,
+    frame_time: f64,
+
+    // What was drawn during the last frame.
+    draws: DrawStats,
+
+    /// The text currently shown on every line, and its rendered sprite. Lines
+    /// are only re-rendered when their text changes.
+    lines: Vec<(String, Sprite)>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> DebugOverlay {
+        DebugOverlay {
+            visible: false,
+            frames: 0,
+            frame_time_total: 0.0,
+            fps: 0,
+            frame_time: 0.0,
+            draws: DrawStats::default(),
+            lines: vec![],
+        }
+    }
+
+    /// Records that a frame was rendered, `frame_time` seconds after the
+    /// previous one. Once a second's worth of frames were recorded, the
+    /// displayed FPS and average frame time are refreshed.
+    pub fn frame(&mut self, frame_time: f64) {
+        self.frames += 1;
+        self.frame_time_total += frame_time;
+        self.draws = gfx::take_draw_stats();
+
+        if self.frame_time_total >= 1.0 {
+            self.fps = self.frames;
+            self.frame_time = self.frame_time_total / self.frames as f64;
+            self.frames = 0;
+            self.frame_time_total = 0.0;
+        }
+    }
+
+    /// Renders the overlay in the top-left corner of the screen.
+    pub fn render(&mut self, phi: &mut Phi) -> Result<(), PhiError> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let mut texts = vec![
+            format!("FPS: {}", self.fps),
+            format!("Frame time: {:.2} ms", self.frame_time * 1_000.0),
+            format!("Sprites drawn: {}", self.draws.sprites),
+            format!("Texture switches: {}", self.draws.texture_switches),
+            format!("Particles drawn: {}", self.draws.particles),
+            format!("Texts rendered: {}", self.draws.texts),
+            format!("Seed: {}", phi.seed()),
+        ];
+        texts.extend(phi.stats.counters.iter().map(|(name, value)| format!("{}: {}", name, value)));
+
+        // Re-render the lines whose text changed
+        self.lines.truncate(texts.len());
+        for (i, text) in texts.into_iter().enumerate() {
+            if self.lines.get(i).is_none_or(|(current, _)| *current != text) {
+                let sprite = phi.ttf_str_sprite(&text, FONT_PATH, FONT_SIZE, Color::RGB(230, 230, 30))?;
+
+                if i < self.lines.len() {
+                    self.lines[i] = (text, sprite);
+                } else {
+                    self.lines.push((text, sprite));
+                }
+            }
+        }
+
+        // Render a translucent box behind the text, so that it stays readable
+        let box_w = self.lines.iter().map(|(_, sprite)| sprite.size().0).fold(0.0, f64::max);
+        draw::fill_rect(&mut phi.renderer, Rectangle {
+            x: 0.0,
+            y: 0.0,
+            w: box_w + MARGIN * 2.0,
+            h: self.lines.len() as f64 * LINE_H + MARGIN * 2.0,
+        }, Color::RGBA(0, 0, 0, 180));
+
+        for (i, (_, sprite)) in self.lines.iter().enumerate() {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                w,
+                h,
+                x: MARGIN,
+                y: MARGIN + LINE_H * i as f64,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! How hard the game is, shared by everything which spawns or moves what the
+//! player must dodge, and changed from the options menu.
+
+/// The difficulty profile from which a run takes its balance.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Every difficulty, from the easiest to the hardest.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// The name under which the difficulty is shown, and saved.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL.iter().copied().find(|difficulty| difficulty.name() == name)
+    }
+
+    /// The next harder difficulty, going back to the easiest after the
+    /// hardest one.
+    pub fn next(self) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|&difficulty| difficulty == self).unwrap_or(0);
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+
+    /// The factor by which the time between two spawns is divided.
+    pub fn spawn_rate(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// The factor by which the velocity of every asteroid is multiplied.
+    pub fn asteroid_speed(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// The factor by which the speed of every enemy is multiplied.
+    pub fn enemy_speed(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// The size of the part of the player's ship which can be hit, relative
+    /// to the whole ship, when only its core can. Harder difficulties send
+    /// more at the ship, and leave it more room to dodge, and graze, in turn.
+    pub fn core_hitbox(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.45,
+            Difficulty::Normal => 0.35,
+            Difficulty::Hard => 0.25,
+        }
+    }
+
+    /// The number of hits which the player's ship may take, once its shield
+    /// is down, before the run ends.
+    pub fn lives(self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 1,
+        }
+    }
+}
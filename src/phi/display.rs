@@ -0,0 +1,154 @@
+//! The ways in which the game may be shown: in a window of some size, or
+//! fullscreen, either at one of the resolutions which the screen supports or
+//! at that of the desktop.
+
+use crate::phi::{Phi, PhiError};
+use sdl2::video::{FullscreenType, WindowPos};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// A window of `w` by `h` pixels.
+    Windowed { w: u32, h: u32 },
+
+    /// The whole screen, which switches to `w` by `h` pixels refreshed
+    /// `refresh_rate` times per second.
+    Fullscreen { w: u32, h: u32, refresh_rate: i32 },
+
+    /// The whole screen, at the resolution of the desktop.
+    Desktop,
+}
+
+impl DisplayMode {
+    /// The name of the mode, as shown to the player.
+    pub fn name(self) -> String {
+        match self {
+            DisplayMode::Windowed { w, h } => format!("{}x{} window", w, h),
+            DisplayMode::Fullscreen { w, h, refresh_rate } => format!("{}x{} {}Hz", w, h, refresh_rate),
+            DisplayMode::Desktop => "Fullscreen desktop".to_string(),
+        }
+    }
+
+    /// The mode as a single line, in the form read by `parse`, e.g.
+    /// `windowed 1280 720`, `fullscreen 1920 1080 60` or `desktop`.
+    pub fn to_line(self) -> String {
+        match self {
+            DisplayMode::Windowed { w, h } => format!("windowed {} {}", w, h),
+            DisplayMode::Fullscreen { w, h, refresh_rate } => format!("fullscreen {} {} {}", w, h, refresh_rate),
+            DisplayMode::Desktop => "desktop".to_string(),
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<DisplayMode> {
+        let mut words = line.split_whitespace();
+
+        let mode = match words.next()? {
+            "windowed" => DisplayMode::Windowed {
+                w: words.next()?.parse().ok()?,
+                h: words.next()?.parse().ok()?,
+            },
+            "fullscreen" => DisplayMode::Fullscreen {
+                w: words.next()?.parse().ok()?,
+                h: words.next()?.parse().ok()?,
+                refresh_rate: words.next()?.parse().ok()?,
+            },
+            "desktop" => DisplayMode::Desktop,
+            _ => return None,
+        };
+
+        match words.next() {
+            None => Some(mode),
+            Some(_) => None,
+        }
+    }
+}
+
+impl Phi {
+    /// The modes which the screen showing the window supports, windows first,
+    /// then fullscreen modes, from the smallest to the largest. Sizes smaller
+    /// than the smallest which the window allows are left out, and so are
+    /// windows as large as the desktop.
+    pub fn display_modes(&self) -> Vec<DisplayMode> {
+        let window = self.renderer.window();
+        let video = window.subsystem();
+        let display = window.display_index().unwrap_or(0);
+        let (min_w, min_h) = window.minimum_size();
+
+        let mut fullscreen: Vec<(u32, u32, i32)> = (0..video.num_display_modes(display).unwrap_or(0))
+            .filter_map(|i| video.display_mode(display, i).ok())
+            .map(|mode| (mode.w as u32, mode.h as u32, mode.refresh_rate))
+            .filter(|&(w, h, _)| w >= min_w && h >= min_h)
+            .collect();
+        fullscreen.sort_unstable();
+        fullscreen.dedup();
+
+        // Windows which are as large as the desktop would not fit on it once
+        // decorated.
+        let desktop = video.desktop_display_mode(display)
+            .map_or((u32::MAX, u32::MAX), |mode| (mode.w as u32, mode.h as u32));
+        let mut windowed: Vec<(u32, u32)> = fullscreen.iter()
+            .map(|&(w, h, _)| (w, h))
+            .filter(|&(w, h)| w < desktop.0 && h < desktop.1)
+            .collect();
+        windowed.dedup();
+
+        // Whatever the screen reports, the current size may always be kept.
+        if let DisplayMode::Windowed { w, h } = self.display_mode() {
+            if !windowed.contains(&(w, h)) {
+                windowed.push((w, h));
+                windowed.sort_unstable();
+            }
+        }
+
+        windowed.into_iter().map(|(w, h)| DisplayMode::Windowed { w, h })
+            .chain(fullscreen.into_iter().map(|(w, h, refresh_rate)| DisplayMode::Fullscreen { w, h, refresh_rate }))
+            .chain(Some(DisplayMode::Desktop))
+            .collect()
+    }
+
+    /// The mode in which the game is currently shown.
+    pub fn display_mode(&self) -> DisplayMode {
+        let window = self.renderer.window();
+
+        match window.fullscreen_state() {
+            FullscreenType::Off => {
+                let (w, h) = window.size();
+                DisplayMode::Windowed { w, h }
+            },
+            FullscreenType::True => match window.display_mode() {
+                Ok(mode) => DisplayMode::Fullscreen { w: mode.w as u32, h: mode.h as u32, refresh_rate: mode.refresh_rate },
+                Err(_) => DisplayMode::Desktop,
+            },
+            FullscreenType::Desktop => DisplayMode::Desktop,
+        }
+    }
+
+    /// Shows the game in `mode`. The logical area in which the game is drawn
+    /// keeps its size, and is scaled to fit.
+    pub fn set_display_mode(&mut self, mode: DisplayMode) -> Result<(), PhiError> {
+        let window = self.renderer.window_mut();
+
+        match mode {
+            DisplayMode::Windowed { w, h } => {
+                window.set_fullscreen(FullscreenType::Off).map_err(PhiError::Display)?;
+                window.set_size(w, h).map_err(|err| PhiError::Display(err.to_string()))?;
+                window.set_position(WindowPos::Centered, WindowPos::Centered);
+            },
+
+            DisplayMode::Fullscreen { w, h, refresh_rate } => {
+                //? SDL wants the pixel format along with the size, so we pick
+                //? that of the desktop, which every screen supports.
+                let display = window.display_index().map_err(PhiError::Display)?;
+                let desktop = window.subsystem().desktop_display_mode(display).map_err(PhiError::Display)?;
+                let mode = ::sdl2::video::DisplayMode::new(desktop.format, w as i32, h as i32, refresh_rate);
+
+                window.set_display_mode(mode).map_err(PhiError::Display)?;
+                window.set_fullscreen(FullscreenType::True).map_err(PhiError::Display)?;
+            },
+
+            DisplayMode::Desktop =>
+                window.set_fullscreen(FullscreenType::Desktop).map_err(PhiError::Display)?,
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+//! Settings which every visual effect must honor, centralized so that they can
+//! be changed in a single place, e.g. from the options menu.
+
+/// How demanding visual effects are allowed to be.
+#[derive(Clone, Copy, Default)]
+pub struct Effects {
+    /// Low effects, for photo-sensitive players: effects which flash quickly
+    /// or cover large portions of the screen are replaced by calmer
+    /// alternatives.
+    pub low: bool,
+}
+
+impl Effects {
+    /// The factor by which the speed of flashy animations, e.g. explosions,
+    /// is multiplied. Slowing them down reduces the rate at which they flash.
+    pub fn animation_speed(&self) -> f64 {
+        if self.low { 0.5 } else { 1.0 }
+    }
+
+    /// The factor by which the number of particles in bursts, e.g. debris, is
+    /// multiplied, so that they cover less of the screen.
+    pub fn particle_density(&self) -> f64 {
+        if self.low { 0.5 } else { 1.0 }
+    }
+
+    /// The factor by which the intensity of screen shakes is multiplied.
+    /// Shaking the whole screen is disabled altogether with low effects.
+    pub fn screen_shake(&self) -> f64 {
+        if self.low { 0.0 } else { 1.0 }
+    }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fmt;
+
+/// Everything which may go wrong while initializing the engine, loading the
+/// assets of the game, or running one of its views.
+#[derive(Debug)]
+pub enum PhiError {
+    /// SDL, or one of its subsystems, could not be initialized.
+    Init(String),
+
+    /// No audio device could be opened.
+    Audio(String),
+
+    /// An asset could not be loaded from the given path.
+    Asset { path: String, reason: String },
+
+    /// A view panicked, with the given message.
+    Crash(String),
+
+    /// The window could not be shown in the requested display mode.
+    Display(String),
+}
+
+impl PhiError {
+    /// Builds a closure which turns the reason why the asset at `path` failed
+    /// to load into a `PhiError`, for use with `map_err`.
+    pub fn asset<E: ToString>(path: &str) -> impl FnOnce(E) -> PhiError + '_ {
+        move |reason| PhiError::Asset {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PhiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PhiError::Init(ref reason) =>
+                write!(f, "could not initialize SDL: {}", reason),
+            PhiError::Audio(ref reason) =>
+                write!(f, "could not open an audio device: {}", reason),
+            PhiError::Asset { ref path, ref reason } =>
+                write!(f, "could not load `{}`: {}", path, reason),
+            PhiError::Crash(ref message) =>
+                write!(f, "the game crashed: {}", message),
+            PhiError::Display(ref reason) =>
+                write!(f, "could not change the display mode: {}", reason),
+        }
+    }
+}
+
+impl Error for PhiError {}
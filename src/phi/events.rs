@@ -1,13 +1,127 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
 macro_rules! struct_events {
     (
         keyboard: { $( $k_alias:ident : $k_sdl:ident ),* },
 
+        // Gamepad buttons which act as one of the keyboard aliases
+        gamepad: { $( $g_alias:ident : $g_sdl:ident ),* },
+
+        // Gamepad triggers which act as one of the keyboard aliases, pressed
+        // once they are pulled past halfway
+        triggers: { $( $t_alias:ident : $t_sdl:ident ),* },
+
         // Match against a pattern
         else: { $( $e_alias:ident : $e_sdl:pat ),* }
     )
     => {
         use sdl2::EventPump;
+        use sdl2::event::Event;
+        use std::collections::VecDeque;
+        use sdl2::GameControllerSubsystem;
+        use sdl2::controller::GameController;
+        use sdl2::keyboard::Keycode;
+
+        /// Names one of the logical key aliases, e.g. to ask `Events` how long
+        /// it has been held. Variants are named after the aliases themselves,
+        /// and need not all be used by a game.
+        #[allow(non_camel_case_types, dead_code)]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum Key {
+            $( $k_alias, )*
+        }
+
+        /// How long every key alias has been held, in seconds.
+        struct HeldTimes {
+            $( $k_alias: f64, )*
+        }
+
+        /// The keys which trigger a logical key alias: a primary one and
+        /// an alternate one, either of which may be left unbound.
+        #[derive(Clone, Copy, PartialEq)]
+        pub struct Binding {
+            pub primary: Option<Keycode>,
+            pub alternate: Option<Keycode>,
+        }
+
+        impl Binding {
+            pub fn new(key: Keycode) -> Binding {
+                Binding { primary: Some(key), alternate: None }
+            }
+
+            /// A binding which no key triggers.
+            pub fn none() -> Binding {
+                Binding { primary: None, alternate: None }
+            }
+
+            pub fn matches(&self, key: Keycode) -> bool {
+                self.primary == Some(key) || self.alternate == Some(key)
+            }
+
+            /// The name of the primary key, or `-` if there is none.
+            pub fn primary_name(&self) -> String {
+                self.primary.map_or("-".to_string(), |key| key.name())
+            }
+
+            /// The names of both keys, e.g. `Up / W`.
+            pub fn name(&self) -> String {
+                match self.alternate {
+                    Some(key) => format!("{} / {}", self.primary_name(), key.name()),
+                    None => self.primary_name(),
+                }
+            }
 
+            //? Keys are saved as numbers rather than names, since some of
+            //? their names, e.g. `,`, would get in the way of parsing.
+            fn save(&self) -> String {
+                let code = |key: Option<Keycode>| key.map_or("-".to_string(), |key| (key as i32).to_string());
+                format!("{} {}", code(self.primary), code(self.alternate))
+            }
+
+            fn load(value: &str) -> Option<Binding> {
+                let (primary, alternate) = value.split_once(' ')?;
+                let code = |code: &str| match code {
+                    "-" => Some(None),
+                    code => Keycode::from_i32(code.parse().ok()?).map(Some),
+                };
+
+                Some(Binding { primary: code(primary)?, alternate: code(alternate)? })
+            }
+        }
+
+        /// The keys associated to every logical key alias. These default to
+        /// the keys declared in `struct_events!`, but may be changed at
+        /// runtime, e.g. from the options menu.
+        #[derive(Clone, Copy)]
+        pub struct KeyBindings {
+            $( pub $k_alias: Binding, )*
+        }
+
+        impl KeyBindings {
+            pub fn new() -> KeyBindings {
+                use sdl2::keyboard::Keycode::*;
+
+                KeyBindings {
+                    $( $k_alias: Binding::new($k_sdl), )*
+                }
+            }
+
+            /// Reads the bindings saved in `storage` under the name of every
+            /// alias. Those which are missing or malformed keep their value.
+            pub fn load(&mut self, storage: &crate::phi::storage::Storage) {
+                $(
+                    if let Some(binding) = storage.get(stringify!($k_alias)).and_then(Binding::load) {
+                        self.$k_alias = binding;
+                    }
+                )*
+            }
+
+            pub fn save(&self, storage: &mut crate::phi::storage::Storage) {
+                $( storage.set(stringify!($k_alias), &self.$k_alias.save()); )*
+            }
+        }
 
         pub struct ImmediateEvents {
             // For every keyboard event, we have an Option<bool>
@@ -16,7 +130,43 @@ macro_rules! struct_events {
             // None         => Nothing happening _now_
             $( pub $k_alias: Option<bool>, )*
             $( pub $e_alias: bool, )*
-            resize: Option<(u32, u32)>
+
+            // The size to which the window was resized during this frame, if
+            // it was, in pixels.
+            pub resize: Option<(u32, u32)>,
+
+            // The last key which was pressed during this frame, bound or not.
+            // Used to let the player pick a new binding.
+            pub key_pressed: Option<Keycode>,
+
+            // The text which was typed during this frame, as reported by the
+            // system, e.g. with the keyboard layout and dead keys applied.
+            pub text_input: String,
+
+            // Where the left mouse button was clicked during this frame, if
+            // it was, in window coordinates.
+            pub mouse_click: Option<(i32, i32)>,
+
+            // Whether the left mouse button was released during this frame.
+            pub mouse_release: bool,
+
+            // Whether the mouse was moved during this frame.
+            pub mouse_moved: bool,
+
+            // How far the mouse wheel was turned during this frame, positive
+            // away from the player.
+            pub mouse_wheel: i32,
+
+            // Whether the window lost, or gained, the focus during this frame.
+            pub focus_lost: bool,
+            pub focus_gained: bool,
+
+            // Whether a gamepad was plugged in during this frame.
+            pub controller_added: bool,
+
+            // Whether the gamepad which the player was using was unplugged
+            // during this frame.
+            pub controller_lost: bool,
         }
 
         impl ImmediateEvents {
@@ -26,14 +176,51 @@ macro_rules! struct_events {
                     // set to None
                     $( $k_alias: None, )*
                     $( $e_alias: false, )*
-                    resize: None
+                    resize: None,
+                    key_pressed: None,
+                    text_input: String::new(),
+                    mouse_click: None,
+                    mouse_release: false,
+                    mouse_moved: false,
+                    mouse_wheel: 0,
+                    focus_lost: false,
+                    focus_gained: false,
+                    controller_added: false,
+                    controller_lost: false,
                 }
             }
         }
 
         pub struct Events {
             pump: EventPump,
+            controller_subsystem: GameControllerSubsystem,
+
+            // The gamepads which are currently plugged in. SDL only reports
+            // their input while they are kept open.
+            controllers: Vec<GameController>,
+
+            // The instance id of the gamepad which last sent some input.
+            active_controller: Option<u32>,
+
+            // The events which were received from SDL, but were not handled
+            // by a logic step yet.
+            queued: VecDeque<Event>,
+
             pub now: ImmediateEvents,
+            pub bindings: KeyBindings,
+
+            // The last known position of the mouse, in window coordinates.
+            pub mouse: (i32, i32),
+
+            // Whether the left mouse button is currently held down.
+            pub mouse_down: bool,
+
+            // Where the left stick of the gamepad is pushed, each axis
+            // ranging from -1 to 1, with y pointing down.
+            pub left_stick: (f64, f64),
+
+            // How long every key has been held, as of the last tick.
+            held_times: HeldTimes,
 
             // true   => pressed
             // false  => not pressed
@@ -41,59 +228,364 @@ macro_rules! struct_events {
         }
 
         impl Events {
-            pub fn new(pump: EventPump) -> Events {
+            /// Describes on a single line what the player is holding and what
+            /// happened during this tick, to be played back by `read_input`.
+            pub fn write_input(&self) -> String {
+                let flag = |on: bool| if on { '1' } else { '0' };
+
+                let mut held = String::new();
+                $( held.push(flag(self.$k_alias)); )*
+
+                let mut now = String::new();
+                $(
+                    now.push(match self.now.$k_alias {
+                        Some(true) => '+',
+                        Some(false) => '-',
+                        None => '.',
+                    });
+                )*
+
+                let mut flags = String::new();
+                $( flags.push(flag(self.now.$e_alias)); )*
+                flags.push(flag(self.now.mouse_moved));
+                flags.push(flag(self.now.controller_added));
+                flags.push(flag(self.now.controller_lost));
+
+                let click = self.now.mouse_click
+                    .map_or("-".to_string(), |(x, y)| format!("{},{}", x, y));
+                let key = self.now.key_pressed
+                    .map_or("-".to_string(), |key| (key as i32).to_string());
+
+                //? The text comes last, since it may hold spaces itself.
+                format!("{} {} {} {} {} {} {} {} {} {} {} {} {}",
+                    held, now, flags, self.mouse.0, self.mouse.1, flag(self.mouse_down),
+                    click, flag(self.now.mouse_release), self.now.mouse_wheel,
+                    self.left_stick.0, self.left_stick.1,
+                    key, self.now.text_input.replace('\n', " "))
+            }
+
+            /// Replaces the input of this tick with the one described by
+            /// `line`, as written by `write_input`. Returns `None`, leaving
+            /// the input in an unspecified state, if the line is malformed.
+            pub fn read_input(&mut self, line: &str) -> Option<()> {
+                let mut parts = line.splitn(13, ' ');
+                let mut next = || parts.next();
+
+                let mut held = next()?.chars();
+                $( self.$k_alias = held.next()? == '1'; )*
+
+                let mut now = next()?.chars();
+                $(
+                    self.now.$k_alias = match now.next()? {
+                        '+' => Some(true),
+                        '-' => Some(false),
+                        _ => None,
+                    };
+                )*
+
+                let mut flags = next()?.chars();
+                $( self.now.$e_alias = flags.next()? == '1'; )*
+                self.now.mouse_moved = flags.next()? == '1';
+                self.now.controller_added = flags.next()? == '1';
+                self.now.controller_lost = flags.next()? == '1';
+
+                self.mouse = (next()?.parse().ok()?, next()?.parse().ok()?);
+                self.mouse_down = next()? == "1";
+                self.now.mouse_click = match next()? {
+                    "-" => None,
+                    click => {
+                        let (x, y) = click.split_once(',')?;
+                        Some((x.parse().ok()?, y.parse().ok()?))
+                    },
+                };
+                self.now.mouse_release = next()? == "1";
+                self.now.mouse_wheel = next()?.parse().ok()?;
+                self.left_stick = (next()?.parse().ok()?, next()?.parse().ok()?);
+                self.now.key_pressed = match next()? {
+                    "-" => None,
+                    key => Some(Keycode::from_i32(key.parse().ok()?)?),
+                };
+                self.now.text_input = next().unwrap_or("").to_string();
+
+                Some(())
+            }
+
+            /// Whether `key` was pressed during this step.
+            pub fn pressed(&self, key: Key) -> bool {
+                match key {
+                    $( Key::$k_alias => self.now.$k_alias == Some(true), )*
+                }
+            }
+
+            /// Whether `key` was released during this step.
+            pub fn released(&self, key: Key) -> bool {
+                match key {
+                    $( Key::$k_alias => self.now.$k_alias == Some(false), )*
+                }
+            }
+
+            /// For how long `key` has been held down, in seconds, or 0 if it is
+            /// not. The count starts at 0 on the tick during which it is
+            /// pressed.
+            pub fn held_for(&self, key: Key) -> f64 {
+                match key {
+                    $( Key::$k_alias => if self.$k_alias { self.held_times.$k_alias } else { 0.0 }, )*
+                }
+            }
+
+            /// Counts `elapsed` seconds towards the keys which are held, once
+            /// a tick was simulated.
+            pub fn advance(&mut self, elapsed: f64) {
+                $(
+                    self.held_times.$k_alias =
+                        if self.$k_alias { self.held_times.$k_alias + elapsed } else { 0.0 };
+                )*
+            }
+
+            /// Forget about the events in `now`, once they were handled by an
+            /// update of the game.
+            pub fn consume_now(&mut self) {
+                self.now = ImmediateEvents::new();
+            }
+
+            pub fn new(pump: EventPump, controller_subsystem: GameControllerSubsystem) -> Events {
                 Events {
                     pump: pump,
+                    controller_subsystem,
+                    controllers: vec![],
+                    active_controller: None,
+                    queued: VecDeque::new(),
                     now : ImmediateEvents::new(),
+                    bindings: KeyBindings::new(),
+                    mouse: (0, 0),
+                    mouse_down: false,
+                    left_stick: (0.0, 0.0),
+                    held_times: HeldTimes { $( $k_alias: 0.0, )* },
 
                     // By default, initialize every key with _not pressed_
                     $( $k_alias: false ),*
                 }
             }
 
-            pub fn pump(&mut self, renderer: &mut WindowCanvas) {
-                self.now = ImmediateEvents::new();
+            /// Forget about every key, button and stick which is held down,
+            /// as if they were all released. Keys which were held are reported
+            /// as released in `now`.
+            pub fn reset_held(&mut self) {
+                $(
+                    if self.$k_alias {
+                        self.now.$k_alias = Some(false);
+                        self.$k_alias = false;
+                    }
+                )*
+
+                self.mouse_down = false;
+                self.left_stick = (0.0, 0.0);
+            }
+
+            /// Queues the events which SDL received since the last call. They
+            /// are handled by `step`, over as many logic steps as needed.
+            pub fn pump(&mut self) {
+                self.queued.extend(self.pump.poll_iter());
+            }
+
+            /// Whether handling `event` during the current step would overwrite
+            /// an edge which is already reported in `now`, e.g. a key which was
+            /// pressed and would be released.
+            fn conflicts(&self, event: &Event) -> bool {
+                use sdl2::event::Event::*;
+                use sdl2::mouse::MouseButton;
+                use sdl2::controller::{Axis, Button};
+
+                match *event {
+                    KeyDown { keycode: Some(keycode), .. } => {
+                        self.now.key_pressed.is_some()
+                            $( || (self.bindings.$k_alias.matches(keycode) && self.now.$k_alias.is_some()) )*
+                    },
+
+                    KeyUp { keycode: Some(keycode), .. } => {
+                        false $( || (self.bindings.$k_alias.matches(keycode) && self.now.$k_alias.is_some()) )*
+                    },
+
+                    MouseButtonDown { mouse_btn: MouseButton::Left, .. } =>
+                        self.now.mouse_click.is_some() || self.now.mouse_release,
+
+                    MouseButtonUp { mouse_btn: MouseButton::Left, .. } =>
+                        self.now.mouse_release,
+
+                    ControllerButtonDown { button, .. } | ControllerButtonUp { button, .. } => {
+                        false $( || (button == Button::$g_sdl && self.now.$g_alias.is_some()) )*
+                    },
+
+                    ControllerAxisMotion { axis, value, .. } => {
+                        let pulled = value as f64 / i16::MAX as f64 > 0.5;
+                        false $( || (axis == Axis::$t_sdl && pulled != self.$t_alias && self.now.$t_alias.is_some()) )*
+                    },
+
+                    _ => false,
+                }
+            }
+
+            /// Handles the queued events of one logic step, adding them to
+            /// `now` until it is cleared with `consume_now`.
+            ///
+            /// A step takes every queued event, up to the first one which would
+            /// overwrite an edge reported during the step. That event, and the
+            /// ones after it, wait for the next step. A key which is tapped
+            /// between two frames is thus reported as pressed, then released,
+            /// rather than only released, however low the frame rate.
+            pub fn step(&mut self, renderer: &mut WindowCanvas) {
+                let mut focus_lost = false;
+
+                while let Some(event) = self.queued.pop_front() {
+                    if self.conflicts(&event) {
+                        self.queued.push_front(event);
+                        break;
+                    }
 
-                for event in self.pump.poll_iter() {
                     use sdl2::event::Event::*;
-                    use sdl2::keyboard::Keycode::*;
-                    use sdl2::event::WindowEvent::Resized;
+                    use sdl2::event::WindowEvent::{FocusGained, FocusLost, Resized, Shown};
+                    use sdl2::mouse::MouseButton;
+                    use sdl2::controller::{Axis, Button};
 
                     match event {
                         Window { win_event: Resized( _, _ ), .. } => {
                             self.now.resize = Some(renderer.output_size().unwrap());
                         },
 
-                        KeyDown { keycode, .. } => match keycode {
-                            // $( ... ),* containing $k_sdl and $k_alias means:
-                            // "for every element ($k_alias : $k_sdl) pair,
-                            // check whether the keycode is Some($k_sdl). If
-                            // it is ,then set the $k_alias fields to true."
-                            
+                        //? Whatever is released while the window does not have
+                        //? the focus is never reported, so we would believe
+                        //? that it is held forever.
+                        Window { win_event: FocusLost, .. } => {
+                            self.now.focus_lost = true;
+                            focus_lost = true;
+                        },
+
+                        Window { win_event: Shown, .. } => {
+                            focus_lost = true;
+                        },
+
+                        Window { win_event: FocusGained, .. } => {
+                            self.now.focus_gained = true;
+                        },
+
+                        KeyDown { keycode: Some(keycode), .. } => {
+                            self.now.key_pressed = Some(keycode);
+
+                            // $( ... )* containing $k_alias means: "for every
+                            // alias, check whether the keycode is the one
+                            // currently bound to it. If it is, then set the
+                            // $k_alias fields to true."
                             $(
-                                Some($k_sdl) => {
+                                if self.bindings.$k_alias.matches(keycode) {
                                     // Prevent multiple presses when keeping a key down
                                     // Was previously not pressed?
                                     if  !self.$k_alias {
                                         // Key pressed
                                         self.now.$k_alias = Some(true);
                                     }
-        
+
                                     self.$k_alias = true;
                                 }
-                            ),*
-                            _ => {}
+                            )*
                         },
 
-                        KeyUp { keycode, .. } => match keycode {
+                        KeyUp { keycode: Some(keycode), .. } => {
                             $(
-                                Some($k_sdl) => {
+                                if self.bindings.$k_alias.matches(keycode) {
                                     // Key released
                                     self.now.$k_alias = Some(false);
                                     self.$k_alias = false;
                                 }
-                            ),*
-                            _ => {}
+                            )*
+                        },
+
+                        TextInput { text, .. } => {
+                            self.now.text_input.push_str(&text);
+                        },
+
+                        MouseMotion { x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.now.mouse_moved = true;
+                        },
+
+                        MouseWheel { y, .. } => {
+                            self.now.mouse_wheel += y;
+                        },
+
+                        MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.now.mouse_click = Some((x, y));
+                            self.mouse_down = true;
+                        },
+
+                        MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } => {
+                            self.mouse = (x, y);
+                            self.now.mouse_release = true;
+                            self.mouse_down = false;
+                        },
+
+                        ControllerDeviceAdded { which, .. } => {
+                            match self.controller_subsystem.open(which) {
+                                Ok(controller) => {
+                                    self.controllers.push(controller);
+                                    self.now.controller_added = true;
+                                },
+                                Err(err) =>
+                                    println!("Could not open gamepad {}: {}", which, err),
+                            }
+                        },
+
+                        ControllerDeviceRemoved { which, .. } => {
+                            self.controllers.retain(|c| c.instance_id() != which);
+
+                            if self.active_controller == Some(which) {
+                                self.active_controller = None;
+                                self.now.controller_lost = true;
+                            }
+                        },
+
+                        ControllerButtonDown { which, button, .. } => {
+                            self.active_controller = Some(which);
+
+                            $(
+                                if button == Button::$g_sdl {
+                                    if !self.$g_alias {
+                                        self.now.$g_alias = Some(true);
+                                    }
+
+                                    self.$g_alias = true;
+                                }
+                            )*
+                        },
+
+                        ControllerAxisMotion { which, axis, value, .. } => {
+                            let value = value as f64 / i16::MAX as f64;
+
+                            match axis {
+                                Axis::LeftX => self.left_stick.0 = value.max(-1.0),
+                                Axis::LeftY => self.left_stick.1 = value.max(-1.0),
+                                $(
+                                    Axis::$t_sdl => {
+                                        let pulled = value > 0.5;
+                                        if pulled != self.$t_alias {
+                                            self.now.$t_alias = Some(pulled);
+                                            self.$t_alias = pulled;
+                                        }
+                                    },
+                                )*
+                                _ => continue,
+                            }
+
+                            self.active_controller = Some(which);
+                        },
+
+                        ControllerButtonUp { button, .. } => {
+                            $(
+                                if button == Button::$g_sdl {
+                                    self.now.$g_alias = Some(false);
+                                    self.$g_alias = false;
+                                }
+                            )*
                         },
 
                         $(
@@ -105,8 +597,70 @@ macro_rules! struct_events {
                         _ => {}
                     }
                 }
+
+                if focus_lost {
+                    self.reset_held();
+                }
             }
         }
     };
 }
 
+
+
+/// Writes the input of every tick to a file, along with the seed of the random
+/// number generator, so that the session may be played back with
+/// `InputReplay`.
+pub struct InputRecorder {
+    file: BufWriter<File>,
+}
+
+impl InputRecorder {
+    pub fn create<P: AsRef<Path>>(path: P, seed: u64) -> io::Result<InputRecorder> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "seed={}", seed)?;
+        Ok(InputRecorder { file })
+    }
+
+    /// Logs the input of the tick which is about to be simulated.
+    pub fn record(&mut self, events: &super::Events) -> io::Result<()> {
+        writeln!(self.file, "{}", events.write_input())
+    }
+}
+
+/// Feeds the input saved by an `InputRecorder` back into the game, one tick at
+/// a time, in place of the player's.
+pub struct InputReplay {
+    pub seed: u64,
+    lines: ::std::vec::IntoIter<String>,
+}
+
+impl InputReplay {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<InputReplay> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+
+        let seed = lines.next()
+            .and_then(|line| line.strip_prefix("seed="))
+            .and_then(|seed| seed.trim().parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing seed"))?;
+
+        Ok(InputReplay {
+            seed,
+            lines: lines.map(|line| line.to_string()).collect::<Vec<_>>().into_iter(),
+        })
+    }
+
+    /// Replaces the input of the tick which is about to be simulated with the
+    /// recorded one. Returns `false` once every tick was played back.
+    pub fn play(&mut self, events: &mut super::Events) -> io::Result<bool> {
+        let line = match self.lines.next() {
+            Some(line) => line,
+            None => return Ok(false),
+        };
+
+        events.read_input(&line)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("bad input: {}", line)))?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,250 @@
+//! Arithmetic expressions over named variables, e.g. `80 * sin(time * 2)`,
+//! which data files may use wherever a number depends on the state of the
+//! game.
+//!
+//! Expressions support `+ - * / %`, `^` for powers, parentheses, the constant
+//! `pi`, and the functions `sin`, `cos`, `tan`, `atan2`, `sqrt`, `abs`,
+//! `floor`, `min`, `max` and `clamp`. They may only read the variables which
+//! they are given, so that they cannot do anything else than compute numbers.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+
+    /// The variable at the given index of those listed when parsing.
+    Var(usize),
+
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Atan2,
+    Sqrt,
+    Abs,
+    Floor,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Function> {
+        match name {
+            "sin" => Some(Function::Sin),
+            "cos" => Some(Function::Cos),
+            "tan" => Some(Function::Tan),
+            "atan2" => Some(Function::Atan2),
+            "sqrt" => Some(Function::Sqrt),
+            "abs" => Some(Function::Abs),
+            "floor" => Some(Function::Floor),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "clamp" => Some(Function::Clamp),
+            _ => None,
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Atan2 | Function::Min | Function::Max => 2,
+            Function::Clamp => 3,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Function::Sin => args[0].sin(),
+            Function::Cos => args[0].cos(),
+            Function::Tan => args[0].tan(),
+            Function::Atan2 => args[0].atan2(args[1]),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Abs => args[0].abs(),
+            Function::Floor => args[0].floor(),
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Clamp => args[0].max(args[1]).min(args[2]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let mut number = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_digit() || **ch == '.') {
+                number.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("{} is not a number", number))?));
+        } else if ch.is_ascii_alphabetic() || ch == '_' {
+            let mut name = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_alphanumeric() || **ch == '_') {
+                name.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/%^(),".contains(ch) {
+            tokens.push(Token::Symbol(ch));
+            chars.next();
+        } else {
+            return Err(format!("unexpected {}", ch));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Reads tokens from left to right, by precedence climbing.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    at: usize,
+    vars: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.at += 1;
+        self.tokens.get(self.at - 1).cloned()
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(ch)) if ch == symbol => Ok(()),
+            _ => Err(format!("expected {}", symbol)),
+        }
+    }
+
+    /// Parses operators which bind at least as tightly as `min_precedence`.
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+
+        while let Some(&Token::Symbol(op)) = self.peek() {
+            let precedence = match op {
+                '+' | '-' => 1,
+                '*' | '/' | '%' => 2,
+                '^' => 3,
+                _ => break,
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            self.next();
+
+            //? Powers are right-associative: `2 ^ 3 ^ 2` is `2 ^ 9`.
+            let rhs = self.binary(if op == '^' { precedence } else { precedence + 1 })?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Symbol('-')) {
+            self.next();
+            //? Powers bind more tightly than negation: `-x ^ 2` is `-(x ^ 2)`.
+            return Ok(Expr::Neg(Box::new(self.binary(3)?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+
+            Some(Token::Symbol('(')) => {
+                let expr = self.binary(1)?;
+                self.expect(')')?;
+                Ok(expr)
+            },
+
+            Some(Token::Name(name)) => {
+                if let Some(function) = Function::parse(&name) {
+                    self.expect('(')?;
+                    let mut args = vec![self.binary(1)?];
+                    while self.peek() == Some(&Token::Symbol(',')) {
+                        self.next();
+                        args.push(self.binary(1)?);
+                    }
+                    self.expect(')')?;
+
+                    if args.len() != function.arity() {
+                        return Err(format!("{} takes {} arguments", name, function.arity()));
+                    }
+                    return Ok(Expr::Call(function, args));
+                }
+
+                if name == "pi" {
+                    return Ok(Expr::Number(::std::f64::consts::PI));
+                }
+
+                self.vars.iter().position(|&var| var == name)
+                    .map(Expr::Var)
+                    .ok_or_else(|| format!("unknown name {}", name))
+            },
+
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of the expression".to_string()),
+        }
+    }
+}
+
+impl Expr {
+    /// Parses `text`, which may read the variables called `vars`. Their values
+    /// are then given to `eval` in the same order.
+    pub fn parse(text: &str, vars: &[&str]) -> Result<Expr, String> {
+        let mut parser = Parser { tokens: tokenize(text)?, at: 0, vars };
+        let expr = parser.binary(1)?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    pub fn eval(&self, values: &[f64]) -> f64 {
+        match *self {
+            Expr::Number(number) => number,
+            Expr::Var(index) => values.get(index).copied().unwrap_or(0.0),
+            Expr::Neg(ref expr) => -expr.eval(values),
+            Expr::Binary(op, ref lhs, ref rhs) => {
+                let (lhs, rhs) = (lhs.eval(values), rhs.eval(values));
+                match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    '%' => lhs.rem_euclid(rhs),
+                    _ => lhs.powf(rhs),
+                }
+            },
+            Expr::Call(function, ref args) => {
+                let args: Vec<f64> = args.iter().map(|arg| arg.eval(values)).collect();
+                function.apply(&args)
+            },
+        }
+    }
+}
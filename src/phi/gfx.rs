@@ -1,21 +1,102 @@
 use crate::phi::data::Rectangle;
-use crate::phi::Phi;
-use std::cell::RefCell;
+use crate::phi::{Phi, PhiError};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
-use sdl2::render::{WindowCanvas, Texture};
+use sdl2::pixels::Color;
+use sdl2::render::{BlendMode, WindowCanvas, Texture};
 use sdl2::image::LoadTexture;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+pub mod bitmap_font;
+pub mod draw;
+pub mod noise;
+
+/// A color by which the pixels of a sprite are multiplied when it is drawn,
+/// along with its opacity. This allows, say, to flash a sprite in red or to
+/// fade it in, without creating another texture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tint {
+    pub color: Color,
+    pub alpha: u8,
+
+    /// How much of the sprite is added on top of itself once tinted, which
+    /// washes it out towards white, e.g. to flash something which was hit.
+    pub glow: u8,
+}
+
+impl Tint {
+    /// Draws the sprite as it is.
+    pub const NONE: Tint = Tint { color: Color::RGB(255, 255, 255), alpha: 255, glow: 0 };
+
+    /// Draws the sprite with the given opacity, in `[0, 1]`.
+    pub fn alpha(alpha: f64) -> Tint {
+        Tint {
+            alpha: (alpha.clamp(0.0, 1.0) * 255.0) as u8,
+            ..Tint::NONE
+        }
+    }
+
+    /// Draws the sprite multiplied by the given color.
+    pub fn color(color: Color) -> Tint {
+        Tint { color, ..Tint::NONE }
+    }
+}
 
 /// Common interface for rendering a graphical component to some given region
 /// of the window.
 pub trait Renderable {
-    fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle);
+    fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle) {
+        self.render_tinted(renderer, dest, Tint::NONE);
+    }
+
+    fn render_tinted(&self, renderer: &mut WindowCanvas, dest: Rectangle, tint: Tint);
+}
+
+/// How the pixels of an image are changed when it is loaded as a sprite.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteOptions {
+    /// The color which is made fully transparent, for images which were
+    /// saved without an alpha channel.
+    pub color_key: Option<Color>,
+
+    /// Colors which are replaced by others, e.g. to give enemies the colors
+    /// of their team or to apply a cosmetic skin. The alpha of the pixels is
+    /// kept.
+    pub palette: Vec<(Color, Color)>,
+}
+
+impl SpriteOptions {
+    /// Whether the image is loaded as it is.
+    fn is_plain(&self) -> bool {
+        self.color_key.is_none() && self.palette.is_empty()
+    }
+
+    /// Changes a single pixel, given as its B, G, R, A bytes.
+    fn apply(&self, pixel: &mut [u8]) {
+        let rgb = (pixel[2], pixel[1], pixel[0]);
+        let matches = |color: Color| (color.r, color.g, color.b) == rgb;
+
+        if self.color_key.is_some_and(matches) {
+            pixel[3] = 0;
+        } else if let Some(&(_, to)) = self.palette.iter().find(|&&(from, _)| matches(from)) {
+            pixel[0] = to.b;
+            pixel[1] = to.g;
+            pixel[2] = to.r;
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Sprite {
     tex: Rc<RefCell<Texture>>,
     src: Rectangle,
+
+    // Whether the region is mirrored horizontally when drawn, and by how
+    // many degrees it is turned clockwise around its center.
+    flipped: bool,
+    angle: f64,
 }
 
 impl Sprite {
@@ -29,14 +110,91 @@ impl Sprite {
                 h: tex_query.height as f64,
                 x: 0.0,
                 y: 0.0,
-            }
+            },
+            flipped: false,
+            angle: 0.0,
         }
     }
 
+    /// Creates a disc `radius` pixels wide of the given color, opaque at its
+    /// rim and fading out towards its center, e.g. for a bubble around a ship.
+    pub fn bubble(renderer: &WindowCanvas, radius: u32, color: Color) -> Result<Sprite, PhiError> {
+        let side = radius * 2;
+        let center = radius as f64 - 0.5;
+
+        let pixels: Vec<Color> = (0..side * side)
+            .map(|i| {
+                let (x, y) = ((i % side) as f64 - center, (i / side) as f64 - center);
+                let t = (x * x + y * y).sqrt() / radius as f64;
+                let alpha = if t > 1.0 { 0.0 } else { t * t * t };
+                Color::RGBA(color.r, color.g, color.b, (alpha * 255.0) as u8)
+            })
+            .collect();
+
+        Sprite::from_pixels(renderer, (side, side), &pixels)
+    }
+
+    /// Creates a sprite `w` by `h` pixels large out of `pixels`, given row by
+    /// row from the top-left corner, which may be translucent.
+    pub fn from_pixels(renderer: &WindowCanvas, (w, h): (u32, u32), pixels: &[Color]) -> Result<Sprite, PhiError> {
+        use sdl2::pixels::PixelFormatEnum;
+
+        //? ARGB8888 pixels are stored as B, G, R, A bytes on little-endian
+        //? machines, which SDL's own pixel formats assume.
+        let bytes: Vec<u8> = pixels.iter()
+            .flat_map(|color| [color.b, color.g, color.r, color.a])
+            .collect();
+
+        let mut texture = renderer.texture_creator()
+            .create_texture_static(PixelFormatEnum::ARGB8888, w, h)
+            .map_err(|err| PhiError::Init(err.to_string()))?;
+        texture.update(None, &bytes, w as usize * 4)
+            .map_err(|err| PhiError::Init(err.to_string()))?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(Sprite::new(texture))
+    }
+
     /// Creates a new sprite form an image file located at the given path,
-    /// Returns `Some` if the file could be read, and `None` otherwise.
-    pub fn load(renderer: &WindowCanvas, path: &str) -> Option<Sprite> {
-        renderer.texture_creator().load_texture(Path::new(path)).ok().map(Sprite::new)
+    /// Returns an error if the file could not be read.
+    pub fn load(renderer: &WindowCanvas, path: &str) -> Result<Sprite, PhiError> {
+        Sprite::load_with(renderer, path, &SpriteOptions::default())
+    }
+
+    /// Like `load`, but the pixels of the image are changed according to
+    /// `options` before the texture is created, e.g. to recolor a ship.
+    pub fn load_with(renderer: &WindowCanvas, path: &str, options: &SpriteOptions) -> Result<Sprite, PhiError> {
+        use sdl2::image::LoadSurface;
+        use sdl2::pixels::PixelFormatEnum;
+        use sdl2::surface::Surface;
+
+        if options.is_plain() {
+            return renderer.texture_creator().load_texture(Path::new(path))
+                .map(Sprite::new)
+                .map_err(PhiError::asset(path));
+        }
+
+        //? Converting the surface lets us walk its pixels without caring about
+        //? the format in which the image was saved. ARGB8888 pixels are stored
+        //? as B, G, R, A bytes on little-endian machines.
+        let mut surface = Surface::from_file(path)
+            .and_then(|surface| surface.convert_format(PixelFormatEnum::ARGB8888))
+            .map_err(PhiError::asset(path))?;
+
+        let (width, pitch) = (surface.width() as usize, surface.pitch() as usize);
+        surface.with_lock_mut(|pixels| {
+            for row in pixels.chunks_mut(pitch) {
+                for pixel in row[..width * 4].chunks_mut(4) {
+                    options.apply(pixel);
+                }
+            }
+        });
+
+        let mut texture = renderer.texture_creator().create_texture_from_surface(&surface)
+            .map_err(|err| PhiError::Asset { path: path.to_string(), reason: err.to_string() })?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(Sprite::new(texture))
     }
 
     /// Returns a new `Sprite` representing a sub-region of the current one.
@@ -55,6 +213,8 @@ impl Sprite {
             Some(Sprite {
                 tex: self.tex.clone(),
                 src: new_src,
+                flipped: self.flipped,
+                angle: self.angle,
             })
         } else {
             None
@@ -65,19 +225,276 @@ impl Sprite {
     pub fn size(&self) -> (f64, f64) {
         (self.src.w, self.src.h)
     }
+
+    /// Returns the same sprite, mirrored horizontally, e.g. so that a ship
+    /// faces the other way.
+    pub fn flipped(&self) -> Sprite {
+        Sprite {
+            flipped: !self.flipped,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the same sprite, turned clockwise by `angle` radians around
+    /// its center, e.g. so that a ship faces where it is heading.
+    pub fn rotated(&self, angle: f64) -> Sprite {
+        Sprite {
+            angle: angle.to_degrees(),
+            ..self.clone()
+        }
+    }
+
+    fn copy_to(&self, renderer: &mut WindowCanvas, tex: &Texture, dest: Rectangle) {
+        renderer.copy_ex(tex, self.src.to_sdl(), dest.to_sdl(), self.angle, None, self.flipped, false).unwrap();
+    }
+}
+
+/// The silhouette of a frame, grown by a few pixels, which is drawn in some
+/// color behind the frame to highlight it, e.g. to show what a missile is
+/// going after.
+#[derive(Clone)]
+pub struct Outline {
+    sprite: Sprite,
+
+    // How far the silhouette reaches past the frame, in pixels of the frame.
+    thickness: f64,
+}
+
+impl Outline {
+    /// Creates the outlines of the frames of the spritesheet located at
+    /// `path`, which are `frame_w` by `frame_h` pixels, row by row, reaching
+    /// `thickness` pixels past them. Returns an error if the file could not
+    /// be read.
+    pub fn load_frames(renderer: &WindowCanvas, path: &str, (frame_w, frame_h): (u32, u32), thickness: u32) -> Result<Vec<Outline>, PhiError> {
+        use sdl2::image::LoadSurface;
+        use sdl2::pixels::PixelFormatEnum;
+        use sdl2::surface::Surface;
+
+        //? ARGB8888 pixels are stored as B, G, R, A bytes on little-endian
+        //? machines, so that the alpha of a pixel is its fourth byte.
+        let surface = Surface::from_file(path)
+            .and_then(|surface| surface.convert_format(PixelFormatEnum::ARGB8888))
+            .map_err(PhiError::asset(path))?;
+
+        let (cols, rows) = (surface.width() / frame_w, surface.height() / frame_h);
+        let (cell_w, cell_h) = (frame_w + 2 * thickness, frame_h + 2 * thickness);
+        let (sheet_w, sheet_h) = (cols * cell_w, rows * cell_h);
+        let pitch = surface.pitch() as usize;
+
+        // The offsets of the pixels which lie within `thickness` of another.
+        let t = thickness as i64;
+        let reach: Vec<(i64, i64)> = (-t..=t)
+            .flat_map(|dy| (-t..=t).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| dx * dx + dy * dy <= t * t)
+            .collect();
+
+        //? Every frame gets a cell of its own in the outlines' sheet, with
+        //? room around it, so that an outline never bleeds into its
+        //? neighbours. Every pixel of the frame is stamped onto the pixels
+        //? within reach, which keep the highest opacity. Pixels surrounded by
+        //? opaque ones would only stamp over what their neighbours do, so
+        //? they are skipped, which spares most of the work.
+        let mut pixels = vec![0u8; (sheet_w * sheet_h * 4) as usize];
+        surface.with_lock(|source| {
+            let (frame_w, frame_h) = (frame_w as i64, frame_h as i64);
+
+            for row in 0..rows as i64 {
+                for col in 0..cols as i64 {
+                    let alpha = |x: i64, y: i64| {
+                        if (0..frame_w).contains(&x) && (0..frame_h).contains(&y) {
+                            let (x, y) = ((col * frame_w + x) as usize, (row * frame_h + y) as usize);
+                            source[y * pitch + x * 4 + 3]
+                        } else {
+                            0
+                        }
+                    };
+
+                    for y in 0..frame_h {
+                        for x in 0..frame_w {
+                            let value = alpha(x, y);
+                            let inner = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                                .iter()
+                                .all(|&(x, y)| alpha(x, y) == 255);
+                            let offsets: &[(i64, i64)] = if inner { &[(0, 0)] } else { &reach };
+
+                            for &(dx, dy) in offsets {
+                                let (x, y) = (col * cell_w as i64 + t + x + dx, row * cell_h as i64 + t + y + dy);
+                                let i = (y * sheet_w as i64 + x) as usize * 4;
+                                pixels[i + 3] = pixels[i + 3].max(value);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        //? The silhouette is white, so that it takes whichever color it is
+        //? tinted with.
+        for pixel in pixels.chunks_mut(4) {
+            pixel[..3].copy_from_slice(&[255, 255, 255]);
+        }
+
+        let mut texture = renderer.texture_creator()
+            .create_texture_static(PixelFormatEnum::ARGB8888, sheet_w, sheet_h)
+            .map_err(|err| PhiError::Asset { path: path.to_string(), reason: err.to_string() })?;
+        texture.update(None, &pixels, sheet_w as usize * 4)
+            .map_err(|err| PhiError::Asset { path: path.to_string(), reason: err.to_string() })?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        let sheet = Sprite::new(texture);
+        let outlines = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .filter_map(|(col, row)| sheet.region(Rectangle {
+                x: (col * cell_w) as f64,
+                y: (row * cell_h) as f64,
+                w: cell_w as f64,
+                h: cell_h as f64,
+            }))
+            .map(|sprite| Outline { sprite, thickness: thickness as f64 })
+            .collect();
+
+        Ok(outlines)
+    }
+
+    /// Returns the same outline, mirrored horizontally, to match a flipped
+    /// sprite.
+    pub fn flipped(&self) -> Outline {
+        Outline {
+            sprite: self.sprite.flipped(),
+            ..self.clone()
+        }
+    }
+
+    /// Draws the outline in `color` around a frame drawn at `dest`. It should
+    /// be drawn before the frame, so that only its edge shows.
+    pub fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle, color: Color) {
+        let (w, h) = self.sprite.size();
+        let scale_x = dest.w / (w - 2.0 * self.thickness);
+        let scale_y = dest.h / (h - 2.0 * self.thickness);
+
+        self.sprite.render_tinted(renderer, Rectangle {
+            x: dest.x - self.thickness * scale_x,
+            y: dest.y - self.thickness * scale_y,
+            w: w * scale_x,
+            h: h * scale_y,
+        }, Tint::color(color));
+    }
+}
+
+/// What was drawn during a frame, to spot what makes rendering slow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// The number of sprites copied to the screen.
+    pub sprites: usize,
+
+    /// How many sprites were drawn from another texture than the one before.
+    /// Drawing from a same texture in a row is cheaper for the GPU.
+    pub texture_switches: usize,
+
+    /// The number of particles drawn, each of which takes a draw call.
+    pub particles: usize,
+
+    /// The number of pieces of text rendered to a texture. This is slow, and
+    /// should only happen when some text changes.
+    pub texts: usize,
+}
+
+thread_local! {
+    //? Counting in a thread-local variable spares us from passing some context
+    //? to every `Renderable`. SDL may only be used from the main thread anyway.
+    static DRAW_STATS: Cell<DrawStats> = const { Cell::new(DrawStats { sprites: 0, texture_switches: 0, particles: 0, texts: 0 }) };
+
+    // The address of the last texture drawn from.
+    static LAST_TEXTURE: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count(update: impl FnOnce(&mut DrawStats)) {
+    DRAW_STATS.with(|stats| {
+        let mut current = stats.get();
+        update(&mut current);
+        stats.set(current);
+    });
+}
+
+/// Records that some text was rendered to a texture.
+pub fn count_text() {
+    count(|stats| stats.texts += 1);
+}
+
+/// Returns what was drawn since the last call.
+pub fn take_draw_stats() -> DrawStats {
+    DRAW_STATS.with(|stats| stats.take())
 }
 
 impl Renderable for Sprite {
-    fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle) {
-        renderer.copy(&mut self.tex.borrow_mut(), self.src.to_sdl(), dest.to_sdl()).unwrap();
+    fn render_tinted(&self, renderer: &mut WindowCanvas, dest: Rectangle, tint: Tint) {
+        let texture = Rc::as_ptr(&self.tex) as usize;
+        let switched = LAST_TEXTURE.with(|last| last.replace(texture)) != texture;
+        count(|stats| {
+            stats.sprites += 1;
+            stats.texture_switches += switched as usize;
+        });
+
+        let mut tex = self.tex.borrow_mut();
+
+        if tint == Tint::NONE {
+            self.copy_to(renderer, &tex, dest);
+            return;
+        }
+
+        //? The texture is shared by every region of the spritesheet, so we
+        //? restore its modulation once we are done drawing.
+        let blend_mode = tex.blend_mode();
+        tex.set_color_mod(tint.color.r, tint.color.g, tint.color.b);
+        tex.set_alpha_mod(tint.alpha);
+        if tint.alpha < 255 {
+            tex.set_blend_mode(BlendMode::Blend);
+        }
+
+        self.copy_to(renderer, &tex, dest);
+
+        //? Adding the sprite on top of itself brightens its colors without
+        //? touching the transparent pixels around it.
+        if tint.glow > 0 {
+            tex.set_blend_mode(BlendMode::Add);
+            tex.set_color_mod(tint.glow, tint.glow, tint.glow);
+            self.copy_to(renderer, &tex, dest);
+        }
+
+        tex.set_color_mod(255, 255, 255);
+        tex.set_alpha_mod(255);
+        tex.set_blend_mode(blend_mode);
     }
 }
 
+/// How an `AnimatedSprite` goes through its frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Start over from the first frame once the last one was shown.
+    Loop,
+
+    /// Stop on the last frame.
+    Once,
+
+    /// Go back and forth between the first and the last frame.
+    PingPong,
+}
+
+/// A sprite which goes through a sequence of frames over time.
+///
+/// Rather than calling back whoever plays it, the animation reports what
+/// happened whenever it is moved forward: `add_time` returns the frame which
+/// it went to, and `is_finished` whether an animation played once is over. A
+/// callback kept in the sprite could not borrow the entity or the view which
+/// owns it, which is usually what reacts, e.g. by removing an explosion.
 #[derive(Clone)]
 pub struct AnimatedSprite {
     /// The frames that will be rendered, in order.
     sprites: Rc<Vec<Sprite>>,
 
+    /// How the animation goes through the frames.
+    mode: PlayMode,
+
     /// The time it takes to get from one frame to the next, in seconds.
     frame_delay: f64,
 
@@ -100,6 +517,7 @@ impl AnimatedSprite {
     pub fn new(sprites: Vec<Sprite>, frame_delay: f64) -> AnimatedSprite {
         AnimatedSprite {
             sprites: Rc::new(sprites),
+            mode: PlayMode::Loop,
             frame_delay: frame_delay,
             current_time: 0.0,
         }
@@ -123,6 +541,39 @@ impl AnimatedSprite {
         self.sprites.len()
     }
 
+    /// Set how the animation goes through its frames. Sprites loop by
+    /// default.
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+    }
+
+    /// The index of the frame which is currently shown.
+    pub fn current_frame(&self) -> usize {
+        let frames = self.frames();
+        let elapsed = (self.current_time / self.frame_delay) as usize;
+
+        match self.mode {
+            PlayMode::Loop => elapsed % frames,
+            PlayMode::Once => elapsed.min(frames - 1),
+            PlayMode::PingPong if frames < 2 => 0,
+            PlayMode::PingPong => {
+                //? A round trip shows the first and last frames only once,
+                //? e.g. 0 1 2 3 2 1, then 0 again.
+                let period = 2 * frames - 2;
+                let step = elapsed % period;
+                if step < frames { step } else { period - step }
+            },
+        }
+    }
+
+    /// Whether an animation played once went past its last frame, which is
+    /// how its completion is noticed, see `AnimatedSprite`. Looping animations
+    /// never finish.
+    pub fn is_finished(&self) -> bool {
+        self.mode == PlayMode::Once &&
+            self.current_time >= self.frames() as f64 * self.frame_delay
+    }
+
     /// Set the time it takes to get from one frame to the next, in seconds.
     /// If the value is negatice, then we "rewind" the animation.
     pub fn set_frame_delay(&mut self, frame_delay: f64) {
@@ -141,7 +592,10 @@ impl AnimatedSprite {
 
     /// Adds a certain amount of time, in seconds, to the `current_time` of the
     /// animated sprite, so that it knows when it must go to the next frame.
-    pub fn add_time(&mut self, dt: f64) {
+    /// Returns the new frame if it changed, so that the caller may react to
+    /// it, e.g. by playing a sound on some frame.
+    pub fn add_time(&mut self, dt: f64) -> Option<usize> {
+        let before = self.current_frame();
         self.current_time += dt;
 
         // Of we decode tp gp "back in time", this allows us to select the
@@ -149,13 +603,17 @@ impl AnimatedSprite {
         if self.current_time < 0.0 {
             self.current_time = (self.frames() - 1) as f64 * self.frame_delay;
         }
+
+        let after = self.current_frame();
+        if after != before { Some(after) } else { None }
     }
 
-    pub fn load_frames(phi: &mut Phi, descr: AnimatedSpriteDescr) -> Vec<Sprite> {
-        // Read the asteroid's iamge from the filesystem and construct an 
-        // animated sprite out of it.
+    pub fn load_frames(phi: &mut Phi, descr: AnimatedSpriteDescr) -> Result<Vec<Sprite>, PhiError> {
+        // Read the asteroid's iamge from the filesystem, unless it was
+        // already, and construct an animated sprite out of it. The frames
+        // share the texture of the spritesheet.
 
-        let spritesheet = Sprite::load(&mut phi.renderer, descr.image_path).unwrap();
+        let spritesheet = phi.sprites.get(&phi.renderer, descr.image_path)?;
         let mut frames = Vec::with_capacity(descr.total_frames);
 
         for yth in 0..descr.frames_high {
@@ -164,37 +622,384 @@ impl AnimatedSprite {
                     break;
                 }
 
-                frames.push(
-                    spritesheet.region(Rectangle {
-                        w: descr.frame_w,
-                        h: descr.frame_h,
-                        x: descr.frame_w * xth as f64,
-                        y: descr.frame_h * yth as f64,
-                    }).unwrap());
+                let frame = spritesheet.region(Rectangle {
+                    w: descr.frame_w,
+                    h: descr.frame_h,
+                    x: descr.frame_w * xth as f64,
+                    y: descr.frame_h * yth as f64,
+                }).ok_or_else(|| PhiError::Asset {
+                    path: descr.image_path.to_string(),
+                    reason: format!("frame {} lies outside of the spritesheet", frames.len()),
+                })?;
+
+                frames.push(frame);
             }
         }
 
-        frames
+        Ok(frames)
     }
 }
 
 impl Renderable for AnimatedSprite {
     /// Renders the current frame of the sprite.
-    fn render(&self, renderer: &mut WindowCanvas, dest: Rectangle) {
-        let current_frame = 
-            (self.current_time / self.frame_delay) as usize % self.frames();
+    fn render_tinted(&self, renderer: &mut WindowCanvas, dest: Rectangle, tint: Tint) {
+        let sprite = &self.sprites[self.current_frame()];
+        sprite.render_tinted(renderer, dest, tint);
+    }
+}
+
+/// Where the scene is viewed from. Entities are positioned in the scene, and
+/// the camera moves them to where they appear on the screen, so that it may
+/// pan, zoom or shake the whole scene at once.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    /// The point of the scene which appears at the center of the screen,
+    /// before shaking.
+    pub center: (f64, f64),
 
-        let sprite = &self.sprites[current_frame];
-        sprite.render(renderer, dest);
+    /// How much bigger the scene appears than it is.
+    pub zoom: f64,
+
+    // Where the center of the screen actually is on the screen, which
+    // differs from `center` while shaking.
+    screen_center: (f64, f64),
+
+    // The current offset caused by the shake, how far it may go in pixels,
+    // and for how long it keeps shaking, in seconds.
+    shake_offset: (f64, f64),
+    shake_intensity: f64,
+    shake_left: f64,
+}
+
+impl Camera {
+    /// A camera showing the scene as it is on a screen of size `(w, h)`.
+    pub fn new((w, h): (f64, f64)) -> Camera {
+        Camera {
+            center: (w / 2.0, h / 2.0),
+            zoom: 1.0,
+            screen_center: (w / 2.0, h / 2.0),
+            shake_offset: (0.0, 0.0),
+            shake_intensity: 0.0,
+            shake_left: 0.0,
+        }
+    }
+
+    /// Shakes the scene by up to `intensity` pixels during `duration`
+    /// seconds, weakening over time. A stronger shake overrides a weaker one.
+    pub fn shake(&mut self, intensity: f64, duration: f64) {
+        if intensity >= self.shake_intensity * self.shake_left.min(1.0) {
+            self.shake_intensity = intensity;
+            self.shake_left = duration;
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.shake_left = (self.shake_left - dt).max(0.0);
+
+        if self.shake_left > 0.0 {
+            let strength = self.shake_intensity * self.shake_left.min(1.0);
+            let random = || (::rand::random::<f64>() * 2.0 - 1.0) * strength;
+            self.shake_offset = (random(), random());
+        } else {
+            self.shake_offset = (0.0, 0.0);
+        }
+    }
+
+    /// Where `rect`, in the scene, appears on the screen.
+    pub fn view(&self, rect: Rectangle) -> Rectangle {
+        let (x, y) = self.view_point((rect.x, rect.y));
+        Rectangle {
+            x,
+            y,
+            w: rect.w * self.zoom,
+            h: rect.h * self.zoom,
+        }
+    }
+
+    /// Where `point`, in the scene, appears on the screen.
+    pub fn view_point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        ((x - self.center.0) * self.zoom + self.screen_center.0 + self.shake_offset.0,
+         (y - self.center.1) * self.zoom + self.screen_center.1 + self.shake_offset.1)
     }
 }
 
 pub trait CopySprite<T> {
     fn copy_sprite(&mut self, sprite: &T, dest: Rectangle);
+
+    /// Like `copy_sprite`, but modulates the sprite's colors by `tint`.
+    fn copy_sprite_tinted(&mut self, sprite: &T, dest: Rectangle, tint: Tint);
+
+    /// Like `copy_sprite`, but `dest` is in the scene viewed by `camera`.
+    fn copy_sprite_through(&mut self, sprite: &T, dest: Rectangle, camera: &Camera);
 }
 
 impl<T: Renderable> CopySprite<T> for WindowCanvas {
     fn copy_sprite(&mut self, renderable: &T, dest: Rectangle) {
         renderable.render(self, dest);
     }
-}
\ No newline at end of file
+
+    fn copy_sprite_tinted(&mut self, renderable: &T, dest: Rectangle, tint: Tint) {
+        renderable.render_tinted(self, dest, tint);
+    }
+
+    fn copy_sprite_through(&mut self, renderable: &T, dest: Rectangle, camera: &Camera) {
+        renderable.render(self, camera.view(dest));
+    }
+}
+
+/// The fonts in which characters missing from the chosen font are looked up,
+/// in order, e.g. for localized menus or the names of players. Those which are
+/// not installed are skipped.
+const FALLBACK_FONTS: &[&str] = &[
+    "assets/fallback.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// Keeps every font which was loaded so far, so that rendering some text does
+/// not require reading the font file from the disk every time.
+pub struct FontCache {
+    //? A font may not outlive the TTF context which loaded it. Because the
+    //? cache lives for the whole duration of the program anyway, we simply
+    //? leak the context so that it may be borrowed for `'static`.
+    ttf: &'static Sdl2TtfContext,
+    fonts: HashMap<(&'static str, u16), Font<'static, 'static>>,
+
+    // The fallback fonts which are installed, minus those which failed to
+    // load.
+    fallbacks: Vec<&'static str>,
+}
+
+impl FontCache {
+    pub fn new() -> Result<FontCache, PhiError> {
+        let ttf = ::sdl2::ttf::init().map_err(|err| PhiError::Init(err.to_string()))?;
+
+        Ok(FontCache {
+            ttf: Box::leak(Box::new(ttf)),
+            fonts: HashMap::new(),
+            fallbacks: FALLBACK_FONTS.iter().copied().filter(|path| Path::new(path).is_file()).collect(),
+        })
+    }
+
+    /// Returns the font located at `path` with the given point size, loading
+    /// it if it was never requested before. Returns an error if the font
+    /// could not be loaded.
+    pub fn get(&mut self, path: &'static str, size: u16) -> Result<&Font<'static, 'static>, PhiError> {
+        if !self.fonts.contains_key(&(path, size)) {
+            let font = self.ttf.load_font(Path::new(path), size).map_err(PhiError::asset(path))?;
+            self.fonts.insert((path, size), font);
+        }
+
+        Ok(&self.fonts[&(path, size)])
+    }
+
+    /// Returns the font located at `path`, followed by the fallback fonts,
+    /// all with the given point size, for text whose characters may be
+    /// missing from the first one. Fallbacks which cannot be loaded are left
+    /// out from then on.
+    pub fn chain(&mut self, path: &'static str, size: u16) -> Result<Vec<&Font<'static, 'static>>, PhiError> {
+        self.get(path, size)?;
+
+        let fallbacks = ::std::mem::take(&mut self.fallbacks);
+        self.fallbacks = fallbacks.into_iter()
+            .filter(|&fallback| match self.get(fallback, size) {
+                Ok(_) => true,
+                Err(err) => {
+                    println!("Not falling back to a font: {}", err);
+                    false
+                },
+            })
+            .collect();
+
+        Ok(::std::iter::once(path).chain(self.fallbacks.iter().copied())
+            .map(|path| &self.fonts[&(path, size)])
+            .collect())
+    }
+}
+
+/// Identifies the outlines of a spritesheet, by the path of the sheet, the
+/// size of its frames, and the thickness of the outlines.
+type OutlinesKey = (String, (u32, u32), u32);
+
+/// Keeps every spritesheet which was loaded through it, so that creating an
+/// entity whose sprite comes from a shared sheet, e.g. an asteroid, neither
+/// reads nor decodes the image again. Sprites share the texture of the sheet.
+#[derive(Default)]
+pub struct SpriteCache {
+    sheets: HashMap<String, Sprite>,
+
+    // The outlines of the frames of the sheets.
+    outlines: HashMap<OutlinesKey, Rc<Vec<Outline>>>,
+}
+
+impl SpriteCache {
+    /// Returns the image located at `path` as a sprite, loading it if it was
+    /// never requested before. Returns an error if the file could not be
+    /// read.
+    pub fn get(&mut self, renderer: &WindowCanvas, path: &str) -> Result<Sprite, PhiError> {
+        if !self.sheets.contains_key(path) {
+            self.sheets.insert(path.to_string(), Sprite::load(renderer, path)?);
+        }
+
+        Ok(self.sheets[path].clone())
+    }
+
+    /// Like `Outline::load_frames`, but the outlines of a spritesheet are only
+    /// created once, since growing every frame takes a while.
+    pub fn outlines(&mut self, renderer: &WindowCanvas, path: &str, frame_size: (u32, u32), thickness: u32) -> Result<Rc<Vec<Outline>>, PhiError> {
+        let key = (path.to_string(), frame_size, thickness);
+        if !self.outlines.contains_key(&key) {
+            let outlines = Outline::load_frames(renderer, path, frame_size, thickness)?;
+            self.outlines.insert(key.clone(), Rc::new(outlines));
+        }
+
+        Ok(self.outlines[&key].clone())
+    }
+}
+
+/// Describes the particles emitted by a `ParticleEmitter`.
+#[derive(Clone, Copy)]
+pub struct ParticleConfig {
+    /// How many particles are emitted every second while emitting.
+    pub rate: f64,
+
+    /// How long a particle lives, in seconds.
+    pub lifetime: f64,
+
+    /// The direction in which particles are emitted, in radians, and by how
+    /// much it may randomly vary in either direction.
+    pub angle: f64,
+    pub spread: f64,
+
+    /// The range from which the speed of a particle is picked, in pixels per
+    /// second.
+    pub speed: (f64, f64),
+
+    /// The length of the side of a particle, in pixels.
+    pub size: f64,
+
+    /// The color of a particle when it is emitted, and when it dies. It goes
+    /// linearly from one to the other, opacity included.
+    pub start_color: Color,
+    pub end_color: Color,
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    pos: (f64, f64),
+    vel: (f64, f64),
+    age: f64,
+}
+
+/// Emits small colored squares, e.g. for exhaust or debris. Dead particles
+/// are kept around and reused, so that a steady emitter does not allocate.
+#[derive(Clone)]
+pub struct ParticleEmitter {
+    config: ParticleConfig,
+    particles: Vec<Particle>,
+
+    // The number of particles which are alive. They are always the first
+    // ones in `particles`.
+    alive: usize,
+
+    // The fraction of a particle which should have been emitted already.
+    pending: f64,
+
+    /// Where particles are emitted from.
+    pub position: (f64, f64),
+
+    /// Whether particles are continuously emitted, at `config.rate`.
+    pub emitting: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(config: ParticleConfig) -> ParticleEmitter {
+        ParticleEmitter {
+            config,
+            particles: vec![],
+            alive: 0,
+            pending: 0.0,
+            position: (0.0, 0.0),
+            emitting: false,
+        }
+    }
+
+    /// Emits `count` particles at once from `position`.
+    pub fn burst(&mut self, count: usize, position: (f64, f64)) {
+        for _ in 0..count {
+            self.emit(position);
+        }
+    }
+
+    fn emit(&mut self, pos: (f64, f64)) {
+        let config = &self.config;
+        let angle = config.angle + (::rand::random::<f64>() * 2.0 - 1.0) * config.spread;
+        let speed = config.speed.0 + ::rand::random::<f64>() * (config.speed.1 - config.speed.0);
+
+        let particle = Particle {
+            pos,
+            vel: (angle.cos() * speed, angle.sin() * speed),
+            age: 0.0,
+        };
+
+        if self.alive < self.particles.len() {
+            self.particles[self.alive] = particle;
+        } else {
+            self.particles.push(particle);
+        }
+
+        self.alive += 1;
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        let lifetime = self.config.lifetime;
+        let mut i = 0;
+
+        while i < self.alive {
+            let particle = &mut self.particles[i];
+            particle.age += dt;
+            particle.pos.0 += particle.vel.0 * dt;
+            particle.pos.1 += particle.vel.1 * dt;
+
+            //? Swapping a dead particle with the last live one keeps the live
+            //? particles together without moving the others around.
+            if particle.age >= lifetime {
+                self.alive -= 1;
+                self.particles.swap(i, self.alive);
+            } else {
+                i += 1;
+            }
+        }
+
+        if self.emitting {
+            self.pending += self.config.rate * dt;
+
+            while self.pending >= 1.0 {
+                self.pending -= 1.0;
+                let position = self.position;
+                self.emit(position);
+            }
+        }
+    }
+
+    pub fn render(&self, renderer: &mut WindowCanvas, camera: &Camera) {
+        let config = &self.config;
+        let lerp = |from: u8, to: u8, t: f64| (from as f64 + (to as f64 - from as f64) * t) as u8;
+
+        count(|stats| stats.particles += self.alive);
+
+        for particle in &self.particles[..self.alive] {
+            let t = particle.age / config.lifetime;
+            let (from, to) = (config.start_color, config.end_color);
+            let color = Color::RGBA(
+                lerp(from.r, to.r, t), lerp(from.g, to.g, t),
+                lerp(from.b, to.b, t), lerp(from.a, to.a, t));
+
+            let rect = Rectangle::with_size(config.size, config.size).center_at(particle.pos);
+            draw::fill_rect(renderer, camera.view(rect), color);
+        }
+    }
+}
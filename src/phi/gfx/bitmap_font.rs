@@ -0,0 +1,124 @@
+//! Text drawn one glyph at a time from a sheet which is created once, for text
+//! which changes too often for every version of it to be rendered with SDL_ttf,
+//! e.g. a score or a timer.
+
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::phi::{Phi, PhiError};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect as SdlRect;
+use sdl2::render::{BlendMode, WindowCanvas};
+use sdl2::surface::Surface;
+use std::collections::HashMap;
+
+/// The printable ASCII characters, which is enough for most HUD elements.
+pub const ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// A font whose glyphs were rendered to a single texture, and which draws text
+/// by copying them one after the other. Kerning is not applied.
+pub struct BitmapFont {
+    glyphs: HashMap<char, Sprite>,
+    height: f64,
+
+    // The width of the widest digit.
+    digit_w: f64,
+
+    /// Whether every digit takes as much room as the widest one, so that a
+    /// number does not jitter around as it changes.
+    pub monospaced_digits: bool,
+}
+
+impl BitmapFont {
+    fn new(glyphs: HashMap<char, Sprite>, height: f64) -> BitmapFont {
+        let digit_w = ('0'..='9')
+            .filter_map(|digit| glyphs.get(&digit))
+            .map(|glyph| glyph.size().0)
+            .fold(0.0, f64::max);
+
+        BitmapFont {
+            glyphs,
+            height,
+            digit_w,
+            monospaced_digits: false,
+        }
+    }
+
+    /// Renders `chars` with the font located at `font_path`, in the given
+    /// size and color, to a glyph sheet. Characters which the font cannot
+    /// render are left out.
+    pub fn from_ttf(phi: &mut Phi, font_path: &'static str, size: u16, color: Color, chars: &str) -> Result<BitmapFont, PhiError> {
+        let font = phi.fonts.get(font_path, size)?;
+        let surfaces: Vec<(char, Surface)> = chars.chars()
+            .filter_map(|ch| font.render_char(ch).blended(color).ok().map(|surface| (ch, surface)))
+            .collect();
+
+        //? Glyphs are laid side by side on a single row. Each one is as wide as
+        //? the font advances past it, so that they can be drawn right after
+        //? one another.
+        let sheet_w: u32 = surfaces.iter().map(|(_, surface)| surface.width()).sum();
+        let sheet_h = surfaces.iter().map(|(_, surface)| surface.height()).max().unwrap_or(0);
+        let mut sheet = Surface::new(sheet_w.max(1), sheet_h.max(1), PixelFormatEnum::ARGB8888)
+            .map_err(PhiError::asset(font_path))?;
+
+        let mut x = 0;
+        let mut cells = vec![];
+        for (ch, mut surface) in surfaces {
+            let (w, h) = (surface.width(), surface.height());
+            surface.set_blend_mode(BlendMode::None)
+                .map_err(PhiError::asset(font_path))?;
+            surface.blit(None, &mut sheet, SdlRect::new(x as i32, 0, w, h))
+                .map_err(PhiError::asset(font_path))?;
+
+            cells.push((ch, Rectangle { x: x as f64, y: 0.0, w: w as f64, h: h as f64 }));
+            x += w;
+        }
+
+        let mut texture = phi.renderer.create_texture_from_surface(&sheet)
+            .map_err(PhiError::asset(font_path))?;
+        texture.set_blend_mode(BlendMode::Blend);
+        let sheet = Sprite::new(texture);
+
+        let glyphs = cells.into_iter()
+            .filter_map(|(ch, rect)| sheet.region(rect).map(|glyph| (ch, glyph)))
+            .collect();
+
+        Ok(BitmapFont::new(glyphs, sheet_h as f64))
+    }
+
+    /// How far the pen moves past `ch`, in pixels. Characters which the font
+    /// does not have take no room.
+    fn advance(&self, ch: char) -> f64 {
+        if self.monospaced_digits && ch.is_ascii_digit() {
+            self.digit_w
+        } else {
+            self.glyphs.get(&ch).map_or(0.0, |glyph| glyph.size().0)
+        }
+    }
+
+    /// The size of `text`, on a single line, in pixels.
+    pub fn size_of(&self, text: &str) -> (f64, f64) {
+        (text.chars().map(|ch| self.advance(ch)).sum(), self.height)
+    }
+
+    /// Draws `text`, scaled by `scale`, with its top-left corner at `(x, y)`.
+    /// Digits are centered in their room when they are monospaced.
+    pub fn render(&self, renderer: &mut WindowCanvas, text: &str, (x, y): (f64, f64), scale: f64) {
+        let mut pen = x;
+
+        for ch in text.chars() {
+            let advance = self.advance(ch) * scale;
+
+            if let Some(glyph) = self.glyphs.get(&ch) {
+                let (w, h) = glyph.size();
+                renderer.copy_sprite(glyph, Rectangle {
+                    x: pen + (advance - w * scale) / 2.0,
+                    y,
+                    w: w * scale,
+                    h: h * scale,
+                });
+            }
+
+            pen += advance;
+        }
+    }
+}
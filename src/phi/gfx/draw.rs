@@ -0,0 +1,158 @@
+//! Primitive shapes, drawn in a single color straight to the renderer, so that
+//! whatever is not a sprite goes through the same code path. Colors which are
+//! not opaque are blended with what was drawn before, and the blend mode is
+//! restored afterwards.
+//!
+//! Outlines may be smoothed, in which case their edges are anti-aliased.
+
+use crate::phi::data::Rectangle;
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::render::{BlendMode, WindowCanvas};
+use std::f64::consts::PI;
+
+/// Sets up the renderer to draw in `color`, then draws.
+fn with_color(renderer: &mut WindowCanvas, color: Color, smooth: bool, draw: impl FnOnce(&mut WindowCanvas)) {
+    let blend = color.a < 255 || smooth;
+    if blend {
+        renderer.set_blend_mode(BlendMode::Blend);
+    }
+
+    renderer.set_draw_color(color);
+    draw(renderer);
+
+    if blend {
+        renderer.set_blend_mode(BlendMode::None);
+    }
+}
+
+pub fn fill_rect(renderer: &mut WindowCanvas, rect: Rectangle, color: Color) {
+    with_color(renderer, color, false, |renderer| {
+        renderer.fill_rect(rect.to_sdl()).unwrap();
+    });
+}
+
+pub fn outline_rect(renderer: &mut WindowCanvas, rect: Rectangle, color: Color) {
+    with_color(renderer, color, false, |renderer| {
+        renderer.draw_rect(rect.to_sdl()).unwrap();
+    });
+}
+
+pub fn line(renderer: &mut WindowCanvas, from: (f64, f64), to: (f64, f64), color: Color, smooth: bool) {
+    with_color(renderer, color, smooth, |renderer| {
+        if smooth {
+            smooth_line(renderer, from, to, color);
+        } else {
+            let point = |(x, y): (f64, f64)| Point::new(x.round() as i32, y.round() as i32);
+            renderer.draw_line(point(from), point(to)).unwrap();
+        }
+    });
+}
+
+/// Draws the outline of the polygon going through `points`, in order, and
+/// back to the first one.
+pub fn outline_polygon(renderer: &mut WindowCanvas, points: &[(f64, f64)], color: Color, smooth: bool) {
+    for (i, &from) in points.iter().enumerate() {
+        line(renderer, from, points[(i + 1) % points.len()], color, smooth);
+    }
+}
+
+/// Fills the polygon going through `points`, which may be concave. Where it
+/// crosses itself, the even-odd rule decides what is inside.
+pub fn fill_polygon(renderer: &mut WindowCanvas, points: &[(f64, f64)], color: Color, smooth: bool) {
+    if points.len() < 3 {
+        return;
+    }
+
+    let top = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let bottom = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+    with_color(renderer, color, false, |renderer| {
+        //? Every row of pixels is filled between pairs of the edges which it
+        //? crosses, sorted from left to right.
+        for y in (top.floor() as i32)..(bottom.ceil() as i32) {
+            let scan = y as f64 + 0.5;
+            let mut crossings: Vec<f64> = points.iter().enumerate()
+                .map(|(i, &a)| (a, points[(i + 1) % points.len()]))
+                .filter(|&(a, b)| (a.1 <= scan) != (b.1 <= scan))
+                .map(|(a, b)| a.0 + (scan - a.1) * (b.0 - a.0) / (b.1 - a.1))
+                .collect();
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            for span in crossings.chunks_exact(2) {
+                let (left, right) = (span[0].round(), span[1].round());
+                renderer.fill_rect(Rectangle { x: left, y: y as f64, w: right - left, h: 1.0 }.to_sdl()).unwrap();
+            }
+        }
+    });
+
+    if smooth {
+        outline_polygon(renderer, points, color, true);
+    }
+}
+
+/// The points of a polygon close enough to a circle that the difference does
+/// not show.
+fn circle_points(center: (f64, f64), radius: f64) -> Vec<(f64, f64)> {
+    let segments = (radius * 1.5).clamp(12.0, 128.0) as usize;
+
+    (0..segments)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f64 / segments as f64;
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect()
+}
+
+pub fn outline_circle(renderer: &mut WindowCanvas, center: (f64, f64), radius: f64, color: Color, smooth: bool) {
+    outline_polygon(renderer, &circle_points(center, radius), color, smooth);
+}
+
+pub fn fill_circle(renderer: &mut WindowCanvas, center: (f64, f64), radius: f64, color: Color, smooth: bool) {
+    fill_polygon(renderer, &circle_points(center, radius), color, smooth);
+}
+
+/// Fills the slice of the circle which starts at `from_angle` and sweeps
+/// `sweep` radians clockwise, as the y axis points down.
+pub fn fill_pie(renderer: &mut WindowCanvas, center: (f64, f64), radius: f64, from_angle: f64, sweep: f64, color: Color, smooth: bool) {
+    let sweep = sweep.clamp(0.0, 2.0 * PI);
+    let segments = ((radius * 1.5).clamp(12.0, 128.0) * sweep / (2.0 * PI)).ceil().max(1.0) as usize;
+
+    let mut points = vec![center];
+    points.extend((0..=segments).map(|i| {
+        let angle = from_angle + sweep * i as f64 / segments as f64;
+        (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+    }));
+
+    fill_polygon(renderer, &points, color, smooth);
+}
+
+/// Draws a line with Xiaolin Wu's algorithm: each step along its main axis
+/// lights up the two pixels on either side of it, in proportion to how close
+/// they are.
+fn smooth_line(renderer: &mut WindowCanvas, from: (f64, f64), to: (f64, f64), color: Color) {
+    let steep = (to.1 - from.1).abs() > (to.0 - from.0).abs();
+    let swap = |(x, y): (f64, f64)| if steep { (y, x) } else { (x, y) };
+
+    let (mut a, mut b) = (swap(from), swap(to));
+    if a.0 > b.0 {
+        ::std::mem::swap(&mut a, &mut b);
+    }
+
+    let gradient = if b.0 == a.0 { 1.0 } else { (b.1 - a.1) / (b.0 - a.0) };
+
+    let mut plot = |x: f64, y: f64, coverage: f64| {
+        let (x, y) = swap((x, y));
+        renderer.set_draw_color(Color::RGBA(color.r, color.g, color.b, (color.a as f64 * coverage) as u8));
+        renderer.draw_point(Point::new(x as i32, y as i32)).unwrap();
+    };
+
+    let mut x = a.0.round();
+    while x <= b.0.round() {
+        let y = a.1 + gradient * (x - a.0);
+        let (row, fraction) = (y.floor(), y - y.floor());
+        plot(x, row, 1.0 - fraction);
+        plot(x, row + 1.0, fraction);
+        x += 1.0;
+    }
+}
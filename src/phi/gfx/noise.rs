@@ -0,0 +1,79 @@
+//! Value noise: random values on a grid, smoothly interpolated in between.
+//! Adding up several grids, each finer and fainter than the previous one,
+//! gives shapes which look like clouds or smoke.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Random values on a grid of `w` by `h` cells, which wraps around.
+struct Lattice {
+    w: usize,
+    h: usize,
+    values: Vec<f64>,
+}
+
+impl Lattice {
+    fn value(&self, x: usize, y: usize) -> f64 {
+        self.values[(y % self.h) * self.w + x % self.w]
+    }
+
+    /// The value at `(x, y)`, both in `[0, 1)`, blended between the four
+    /// closest points of the grid.
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        let (fx, fy) = (x.rem_euclid(1.0) * self.w as f64, y.rem_euclid(1.0) * self.h as f64);
+        let (x0, y0) = (fx.floor() as usize, fy.floor() as usize);
+        let (tx, ty) = (smooth(fx.fract()), smooth(fy.fract()));
+
+        let top = lerp(self.value(x0, y0), self.value(x0 + 1, y0), tx);
+        let bottom = lerp(self.value(x0, y0 + 1), self.value(x0 + 1, y0 + 1), tx);
+        lerp(top, bottom, ty)
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Eases `t` in and out, so that the seams between cells do not show.
+fn smooth(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Noise which wraps around on both axes, so that the images made from it
+/// tile seamlessly. The same seed always gives the same noise.
+pub struct ValueNoise {
+    // From the coarsest grid to the finest.
+    octaves: Vec<Lattice>,
+}
+
+impl ValueNoise {
+    /// Noise made of `octaves` grids, the first of which is `cells` wide and
+    /// high, and every next one twice as fine and half as strong.
+    pub fn new(seed: u64, (w, h): (usize, usize), octaves: usize) -> ValueNoise {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let octaves = (0..octaves)
+            .map(|i| {
+                let (w, h) = (w.max(1) << i, h.max(1) << i);
+                Lattice { w, h, values: (0..w * h).map(|_| rng.gen()).collect() }
+            })
+            .collect();
+
+        ValueNoise { octaves }
+    }
+
+    /// The noise at `(x, y)`, both in `[0, 1)`, which is in `[0, 1]`.
+    pub fn sample(&self, x: f64, y: f64) -> f64 {
+        let mut total = 0.0;
+        let mut weight = 1.0;
+        let mut weights = 0.0;
+
+        for lattice in &self.octaves {
+            total += lattice.sample(x, y) * weight;
+            weights += weight;
+            weight /= 2.0;
+        }
+
+        if weights > 0.0 { total / weights } else { 0.0 }
+    }
+}
@@ -4,13 +4,50 @@
 // the compilation timeline.
 #[macro_use]
 mod events;
+mod error;
+pub mod archive;
+pub mod assets;
+pub mod audio;
+pub mod config;
 pub mod data;
+pub mod debug;
+pub mod difficulty;
+pub mod display;
+pub mod effects;
+pub mod expr;
 pub mod gfx;
+pub mod signing;
+pub mod storage;
+pub mod text;
+pub mod trace;
+pub mod transition;
+pub mod tween;
+pub mod ui;
+#[cfg(target_os = "emscripten")]
+pub mod web;
+
+pub use self::config::{PhiConfig, ResizePolicy};
+pub use self::error::PhiError;
+pub use self::events::{InputRecorder, InputReplay};
+pub use self::transition::Transition;
 
 use sdl2::render::WindowCanvas;
-use self::gfx::Sprite;
+use self::audio::Fades;
+use self::data::IdAllocator;
+use self::debug::{DebugOverlay, FrameStats};
+use self::difficulty::Difficulty;
+use self::effects::Effects;
+use self::gfx::{FontCache, Sprite, SpriteCache};
+use self::trace::{Span, Tracer};
+use self::transition::ActiveTransition;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sdl2::image::Sdl2ImageContext;
 use sdl2::pixels::Color;
-use std::path::Path;
+use sdl2::surface::Surface;
+use sdl2::{Sdl, TimerSubsystem, VideoSubsystem};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 
 struct_events! {
     keyboard: {
@@ -21,43 +58,160 @@ struct_events! {
         key_right: Right,
         key_space: Space,
         key_enter: Return,
+        key_page_up: PageUp,
+        key_page_down: PageDown,
 
         key_1: Num1,
         key_2: Num2,
-        key_3: Num3
+        key_3: Num3,
+        key_4: Num4,
+        key_cannon: Tab,
+        key_bomb: B,
+
+        key_hitboxes: F1,
+        key_debug: F3,
+        key_record: F5,
+        key_step: F6,
+        key_bookmark: F8,
+        key_restart: R
+    },
+    gamepad: {
+        key_up: DPadUp,
+        key_down: DPadDown,
+        key_left: DPadLeft,
+        key_right: DPadRight,
+        key_space: A,
+        key_enter: Start,
+        key_escape: Back,
+        key_1: X,
+        key_2: Y,
+        key_3: B,
+        key_4: RightShoulder,
+        key_cannon: LeftShoulder,
+        key_bomb: RightStick,
+        key_restart: Back
+    },
+    triggers: {
+        key_page_up: TriggerLeft,
+        key_page_down: TriggerRight
     },
     else: {
         quit: Quit { .. }
     }
 }
 
+/// The duration of a tick of the simulation, in seconds.
+pub const TICK: f64 = 1.0 / 60.0;
+
+/// The longest time which is simulated in a single frame, in seconds. If a
+/// frame takes longer than this, e.g. because the window was being dragged,
+/// the game slows down rather than trying to catch up forever.
+const MAX_FRAME_TIME: f64 = 0.25;
+
 /// Bundles the Phi abstractions in a single structure witch
 /// can be passed easily between functions.
 pub struct Phi {
     pub events: Events,
     pub renderer: WindowCanvas,
+    pub fonts: FontCache,
+    pub sprites: SpriteCache,
+    pub effects: Effects,
+    pub difficulty: Difficulty,
+    pub stats: FrameStats,
+    pub ids: IdAllocator,
+    pub fades: Fades,
+
+    /// Whether views outline the bounding boxes of their entities, toggled
+    /// with a key, e.g. to tune collisions.
+    pub show_hitboxes: bool,
+
+    /// Where views report how long they spend on what, see `trace`.
+    pub trace: Tracer,
+
+    /// How fast the simulation runs relative to real time, e.g. 0.3 for slow
+    /// motion or 0 to freeze it, see `View::update`. It goes back to 1
+    /// whenever another view is shown.
+    pub time_scale: f64,
+
+    // The real time elapsed during the current tick, in seconds.
+    real_elapsed: f64,
+
+    // The random number generator which gameplay draws from, and the seed it
+    // was last given.
+    rng: StdRng,
+    seed: u64,
 }
 
 impl Phi{
-    fn new(events: Events, renderer: WindowCanvas) -> Phi {
-        Phi {
+    fn new(events: Events, renderer: WindowCanvas, seed: u64, trace: Tracer) -> Result<Phi, PhiError> {
+        Ok(Phi {
             events: events,
             renderer: renderer,
-        }
+            fonts: FontCache::new()?,
+            sprites: SpriteCache::default(),
+            effects: Effects::default(),
+            difficulty: Difficulty::default(),
+            stats: FrameStats::default(),
+            ids: IdAllocator::default(),
+            fades: Fades::default(),
+            show_hitboxes: false,
+            trace,
+            time_scale: 1.0,
+            real_elapsed: TICK,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+        })
+    }
+
+    /// The random number generator from which everything which affects the
+    /// gameplay should be drawn, so that a run may be reproduced from its
+    /// seed. Purely cosmetic effects, e.g. particles, may use `rand::random`.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// The time elapsed during the current tick, in seconds, regardless of
+    /// `time_scale`, for whatever should not slow down along with the game,
+    /// e.g. menus or fading music.
+    pub fn real_elapsed(&self) -> f64 {
+        self.real_elapsed
+    }
+
+    /// The time elapsed during the current tick, in seconds, scaled by
+    /// `time_scale` as it is now, i.e. along with whatever change the view
+    /// made to it since the tick started.
+    pub fn elapsed(&self) -> f64 {
+        self.real_elapsed * self.time_scale.max(0.0)
     }
 
+    /// The seed which the random number generator was last given.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts the random number generator over from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = seed;
+    }
+
+    /// The size of the logical area in which the game is drawn. This does not
+    /// depend on the actual size of the window: SDL scales the logical area so
+    /// that it fits the window, and letterboxes it if the aspect ratios differ.
     pub fn output_size(&self) -> (f64, f64) {
-        let (w, h) = self.renderer.output_size().unwrap();
+        let (w, h) = self.renderer.logical_size();
         (w as f64, h as f64)
     }
 
-    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &'static str, size: i32, color: Color) -> Option<Sprite> {
-        ::sdl2::ttf::init().unwrap().load_font(Path::new(font_path), size as u16).ok()
-            .and_then(|font| font
-                .render(text).blended(color).ok()
-                .and_then(|surface| self.renderer.create_texture_from_surface(&surface).ok())
-                .map(Sprite::new)
-        )
+    pub fn ttf_str_sprite(&mut self, text: &str, font_path: &'static str, size: i32, color: Color) -> Result<Sprite, PhiError> {
+        gfx::count_text();
+        let fonts = self.fonts.chain(font_path, size as u16)?;
+        let text = text::visual_order(text, text::TextDirection::Auto);
+        let surface = text::render_line(&fonts, font_path, &text, color)?;
+
+        self.renderer.create_texture_from_surface(&surface)
+            .map(Sprite::new)
+            .map_err(PhiError::asset(font_path))
     }
 }
 
@@ -66,116 +220,578 @@ impl Phi{
 /// should be executed before the next rendering.
 pub enum ViewAction {
     Quit,
+
+    /// Keep rendering the view which was updated. Going to another view goes
+    /// through `ChangeView` or `ChangeViewWith`, so that both are told.
     Render(Box<dyn View>),
+
+    /// Render `to` instead of `from` right away.
+    ChangeView {
+        from: Box<dyn View>,
+        to: Box<dyn View>,
+    },
+
+    /// Render `to` instead of `from`, going from one to the other through
+    /// `transition`.
+    ChangeViewWith {
+        transition: Transition,
+        from: Box<dyn View>,
+        to: Box<dyn View>,
+    },
+
+    /// Something went wrong which the view could not recover from. The game
+    /// loop replaces it with the view produced by its `recover` function.
+    Error(PhiError),
+}
+
+impl ViewAction {
+    /// Go from `from`, usually the current view, to the view produced by
+    /// `view`, or report why it could not be created.
+    pub fn switch_to<V: View + 'static>(from: Box<dyn View>, view: Result<V, PhiError>) -> ViewAction {
+        match view {
+            Ok(view) => ViewAction::ChangeView { from, to: Box::new(view) },
+            Err(err) => ViewAction::Error(err),
+        }
+    }
+
+    /// Like `switch_to`, but goes from `from`, usually the current view, to
+    /// `view` through `transition`.
+    pub fn change_view_with<V: View + 'static>(transition: Transition, from: Box<dyn View>, view: Result<V, PhiError>) -> ViewAction {
+        match view {
+            Ok(view) => ViewAction::ChangeViewWith { transition, from, to: Box::new(view) },
+            Err(err) => ViewAction::Error(err),
+        }
+    }
 }
 
 /// Interface through which Phi interacts with the possible states in which the
 /// application can be.
 pub trait View {
-    /// Called on every frame to take care of the logic of the program. From
+    /// Called on every tick to take care of the logic of the program. From
     /// user inputs and the instance's internal state, determine whether to
     /// render itself or another view, close the window, etc.
     ///
-    /// `elapsed` is expressed in seconds.
+    /// `elapsed` is expressed in seconds. It is always `TICK`, scaled by
+    /// `context.time_scale`, so that the simulation behaves the same
+    /// regardless of the frame rate. The unscaled time is given by
+    /// `context.real_elapsed()`. A view which changes `time_scale` during the
+    /// tick reads the time left to simulate from `context.elapsed()`, so that
+    /// the change applies right away rather than from the next tick.
     fn update(self: Box<Self>, context: &mut Phi, elapsed: f64) -> ViewAction;
 
     /// Called on every frame to take care rendering the current view. It
     /// disallows mutating the object by default, although you may still do it
     /// through a `RefCell` if you need to.
-    fn render(&self, context: &mut Phi);
+    ///
+    /// `alpha`, in `[0, 1)`, is how far we are between the last tick and the
+    /// next one, and may be used to interpolate the position of objects.
+    fn render(&self, context: &mut Phi, alpha: f64);
+
+    /// Called when the view starts being shown, e.g. to start its music.
+    fn on_enter(&mut self, _context: &mut Phi) {}
+
+    /// Called when the view is left for another one, once it was drawn for
+    /// the last time if it is left through a transition.
+    fn on_exit(&mut self, _context: &mut Phi) {}
+
+    /// Called when the window loses the focus, e.g. so that the game does not
+    /// carry on without the player.
+    fn on_pause(&mut self, _context: &mut Phi) {}
+
+    /// Called when the window gets the focus back.
+    fn on_resume(&mut self, _context: &mut Phi) {}
 }
 
-pub fn spawn<F>(title: &str, init: F)
-where 
-    F: Fn(&mut Phi) -> Box<dyn View>
-{
-    // Initialize sdl2
-    let sdl_context = sdl2::init().unwrap();
-    let video = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
-    let _image_context = ::sdl2::image::init(::sdl2::image::InitFlag::PNG).unwrap();
-    
-    // Initialize audio plugin
-    //? We will stick to the Ogg format throughout this article. However, you
-    //? can easily require other ones.
-    // let _mixer_context = ::sdl2::mixer::init(::sdl2::mixer::InitFlag::OGG).unwrap();
-    //? We configure our audio context so that:
-    //?   * The frequency is 44100;
-    //?   * Use signed 16 bits samples, in little-endian byte order;
-    //?   * It's also stereo (2 "channels");
-    //?   * Samples are 1024 bytes in size.
-    //? You don't really need to understand what all of this means. I myself just
-    //? copy-pasted this from andelf's demo. ;-)
-    ::sdl2::mixer::open_audio(44100, ::sdl2::mixer::AUDIO_S16LSB, 2, 1024).unwrap();
-    //? This function asks us how many channels we wish to allocate for our game.
-    //? That is, how many sounds do we wish to be able to play at the same time?
-    //? While testing, 16 channels seemed to be sufficient. Which means that we
-    //? should probably request 32 of 'em just in case. :-°
-    ::sdl2::mixer::allocate_channels(32);
-
-    // Create the window
-    let window = video.window(title, 800, 600)
+/// Leaves `from` for `to`, through `transition` if any, and lets both views
+/// know. Every change of view goes through here, except when a view failed,
+/// in which case there is nothing left of it to tell.
+fn change_view(
+    context: &mut Phi,
+    active: &mut Option<ActiveTransition>,
+    transition: Option<Transition>,
+    mut from: Box<dyn View>,
+    mut to: Box<dyn View>,
+) -> Result<Box<dyn View>, PhiError> {
+    if let Some(kind) = transition {
+        // Draw the previous view one last time, so that the transition
+        // starts from it.
+        from.render(context, 1.0);
+        *active = Some(ActiveTransition::start(context, kind)?);
+    }
+
+    from.on_exit(context);
+    context.time_scale = 1.0;
+    to.on_enter(context);
+    Ok(to)
+}
+
+/// The message carried by a panic, if it is some text.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "the game crashed".to_string(),
+        },
+    }
+}
+
+/// Creates the window described by `config`, along with a renderer which
+/// draws with the GPU if `accelerated` is set, and in software otherwise.
+/// Returns why the renderer cannot be used if it could not be created, or if
+/// it cannot hold textures as large as `config.largest_texture` or the window.
+fn build_canvas(video: &VideoSubsystem, config: &PhiConfig, icon: Option<&Surface>, accelerated: bool) -> Result<WindowCanvas, String> {
+    let mut window_builder = video.window(config.title, config.width, config.height);
+    window_builder
         .position_centered()
-        .opengl()
-        .resizable()
-        .build()
-        .unwrap();
-
-    // Create the context
-    let mut context = Phi::new(
-        Events::new(sdl_context.event_pump().unwrap()),
-        window.into_canvas()
-            .accelerated()
-            .build().unwrap(),
-    );
-    
-    // Create the default view
-    let mut current_view = init(&mut context);
-
-    // Frame timing
-
-    let interval = 1_000 / 60;
-    let mut before = timer.ticks();
-    let mut last_second = timer.ticks();
-    let mut fps = 0u16;
-
-    loop {
-        // Frame timing (bis)
-
-        let now = timer.ticks();
-        let dt = now - before;
-        let elapsed = dt as f64 / 1_000.0;
-
-        // If the time elapsed since the last frame is too small, wait out the
-        // difference and try again.
-        if dt < interval {
-            timer.delay(interval - dt);
-            continue;
-        }
+        .resizable();
+
+    if accelerated {
+        window_builder.opengl();
+    }
+
+    if config.fullscreen {
+        window_builder.fullscreen_desktop();
+    }
+
+    let mut window = window_builder.build()
+        .map_err(|err| err.to_string())?;
+
+    if config.resize_policy == ResizePolicy::Forbid {
+        window.set_minimum_size(config.width, config.height)
+            .map_err(|err| err.to_string())?;
+    }
+
+    if let Some(icon) = icon {
+        window.set_icon(icon);
+    }
+
+    let mut canvas_builder = window.into_canvas();
+    canvas_builder =
+        if accelerated { canvas_builder.accelerated() }
+        else { canvas_builder.software() };
+    if config.vsync {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+
+    let canvas = canvas_builder.build()
+        .map_err(|err| err.to_string())?;
+
+    //? A limit of 0 means that there is none, as with software renderers.
+    let info = canvas.info();
+    let required = config.largest_texture.max(config.width).max(config.height);
+    let too_small = |limit: u32| limit != 0 && limit < required;
+    if too_small(info.max_texture_width) || too_small(info.max_texture_height) {
+        return Err(format!("{} only holds textures of up to {}x{} pixels",
+            info.name, info.max_texture_width, info.max_texture_height));
+    }
+
+    Ok(canvas)
+}
+
+/// Builds the view which replaces one that failed, from the reason why it did.
+type Recover = Box<dyn Fn(&mut Phi, String) -> Result<Box<dyn View>, PhiError>>;
+
+/// Changes the input of a tick once it was read, see `PhiRunner::tick`.
+type Input = Box<dyn FnOnce(&mut Events)>;
+
+/// The game loop, driven from the outside one frame at a time rather than by
+/// `spawn`, e.g. so that the game may be embedded in an editor, a test harness
+/// or a platform which runs its own main loop:
+///
+///     let mut runner = PhiRunner::new(config, init, recover)?;
+///     while runner.step(dt)? {
+///         runner.render()?;
+///     }
+pub struct PhiRunner {
+    config: PhiConfig,
+    context: Phi,
 
-        before = now;
-        fps += 1;
+    // The view being shown, which is gone once a view quit.
+    current_view: Option<Box<dyn View>>,
+    recover: Recover,
+    overlay: DebugOverlay,
 
-        if now - last_second > 1_000 {
-            println!("FPS: {}", fps);
-            last_second = now;
-            fps = 0;
+    // The time which has passed but was not simulated yet, in seconds.
+    accumulator: f64,
+
+    // The transition between two views which is being played, if any.
+    transition: Option<ActiveTransition>,
+
+    replay: Option<InputReplay>,
+    recorder: Option<InputRecorder>,
+
+    // What is done to the input of the next tick, see `tick`.
+    input: Option<Input>,
+
+    // When the frame which is being stepped and rendered started.
+    frame: Option<Span>,
+
+    //? SDL shuts down once these are dropped, which must happen after
+    //? everything else was, hence their place at the end.
+    timer: TimerSubsystem,
+    _image_context: Sdl2ImageContext,
+    _sdl_context: Sdl,
+}
+
+impl PhiRunner {
+    /// Creates the window described by `config`, and the first view with
+    /// `init`. Whenever a view fails, either by returning `ViewAction::Error`
+    /// or by panicking, it is replaced with the view which `recover` builds
+    /// from the reason why it failed, so that the player may carry on.
+    pub fn new<F, R>(config: PhiConfig, init: F, recover: R) -> Result<PhiRunner, PhiError>
+    where
+        F: FnOnce(&mut Phi) -> Result<Box<dyn View>, PhiError>,
+        R: Fn(&mut Phi, String) -> Result<Box<dyn View>, PhiError> + 'static
+    {
+        //? SDL's dummy drivers accept everything which a real window or audio
+        //? device would, but show and play nothing. They must be picked before
+        //? SDL is initialized.
+        if config.headless {
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+            sdl2::hint::set("SDL_AUDIODRIVER", "dummy");
         }
 
+        // Initialize sdl2
+        let sdl_context = sdl2::init().map_err(PhiError::Init)?;
+        let video = sdl_context.video().map_err(PhiError::Init)?;
+        let timer = sdl_context.timer().map_err(PhiError::Init)?;
+        let image_context = ::sdl2::image::init(::sdl2::image::InitFlag::PNG).map_err(PhiError::Init)?;
 
-        // Logic & rendering
+        // Initialize audio plugin
+        //? We will stick to the Ogg format throughout this article. However, you
+        //? can easily require other ones.
+        // let _mixer_context = ::sdl2::mixer::init(::sdl2::mixer::InitFlag::OGG).unwrap();
+        //? Samples are signed 16 bits, in little-endian byte order.
+        let audio = &config.audio;
+        ::sdl2::mixer::open_audio(audio.frequency, ::sdl2::mixer::AUDIO_S16LSB,
+            audio.output_channels, audio.chunk_size).map_err(PhiError::Audio)?;
+        //? This function asks us how many channels we wish to allocate for our game.
+        //? That is, how many sounds do we wish to be able to play at the same time?
+        ::sdl2::mixer::allocate_channels(audio.mixing_channels);
+
+        let icon = match config.icon_path {
+            Some(path) => {
+                use sdl2::image::LoadSurface;
+                Some(Surface::from_file(path).map_err(PhiError::asset(path))?)
+            },
+            None => None,
+        };
 
-        context.events.pump(&mut context.renderer);
+        // Draw with the GPU if it can hold the largest textures, and in
+        // software otherwise, e.g. on a Raspberry Pi. There is no GPU to draw
+        // with when running headless.
+        let accelerated =
+            if config.headless { Err("running headless".to_string()) }
+            else { build_canvas(&video, &config, icon.as_ref(), true) };
 
-        match current_view.update(&mut context, elapsed) {
-            ViewAction::Render(view) => {
-                current_view = view;
-                current_view.render(&mut context);
-                context.renderer.present();
+        let (mut renderer, software) = match accelerated {
+            Ok(canvas) => (canvas, false),
+            Err(reason) => {
+                if !config.headless {
+                    println!("Drawing in software, with low effects: {}", reason);
+                }
+                (build_canvas(&video, &config, icon.as_ref(), false).map_err(PhiError::Init)?, !config.headless)
             },
+        };
 
-            ViewAction::Quit =>
-                break,
+        // Everything is drawn in logical coordinates, and scaled to the size
+        // of the window when presented.
+        renderer.set_logical_size(config.width, config.height)
+            .map_err(|err| PhiError::Init(err.to_string()))?;
+
+        // A replay must start from the seed with which it was recorded.
+        let replay = match config.replay_path {
+            Some(ref path) => Some(InputReplay::load(path).map_err(PhiError::asset(path))?),
+            None => None,
+        };
+        let seed = replay.as_ref().map(|replay| replay.seed)
+            .or(config.seed)
+            .unwrap_or_else(rand::random);
+
+        let recorder = match config.record_path {
+            Some(ref path) => Some(InputRecorder::create(path, seed).map_err(PhiError::asset(path))?),
+            None => None,
+        };
+
+        // Create the context
+        let mut context = Phi::new(
+            Events::new(
+                sdl_context.event_pump().map_err(PhiError::Init)?,
+                sdl_context.game_controller().map_err(PhiError::Init)?),
+            renderer,
+            seed,
+            Tracer::new(config.trace_path.clone()),
+        )?;
+
+        //? Drawing in software is slow enough without particles by the
+        //? hundred.
+        context.effects.low |= software;
+
+        // Create the default view
+        let mut current_view = init(&mut context)?;
+        current_view.on_enter(&mut context);
+
+        Ok(PhiRunner {
+            config,
+            context,
+            current_view: Some(current_view),
+            recover: Box::new(recover),
+            overlay: DebugOverlay::new(),
+            accumulator: 0.0,
+            transition: None,
+            replay,
+            recorder,
+            input: None,
+            frame: None,
+            timer,
+            _image_context: image_context,
+            _sdl_context: sdl_context,
+        })
+    }
+
+    /// Like `new`, but without showing a window or playing any sound, so that
+    /// views may be driven from tests, see `tick`.
+    //? Only the tests build runners this way, the game goes through `spawn`.
+    #[allow(dead_code)]
+    pub fn headless<F, R>(config: PhiConfig, init: F, recover: R) -> Result<PhiRunner, PhiError>
+    where
+        F: FnOnce(&mut Phi) -> Result<Box<dyn View>, PhiError>,
+        R: Fn(&mut Phi, String) -> Result<Box<dyn View>, PhiError> + 'static
+    {
+        PhiRunner::new(PhiConfig { headless: true, ..config }, init, recover)
+    }
+
+    /// Steps and renders a single tick, whose input is changed by `input` once
+    /// it was read, e.g. to press a key which nobody pressed. Returns whether
+    /// the game goes on, as `step` does.
+    #[allow(dead_code)]
+    pub fn tick<I>(&mut self, input: I) -> Result<bool, PhiError>
+    where
+        I: FnOnce(&mut Events) + 'static
+    {
+        self.input = Some(Box::new(input));
+        let goes_on = self.step(TICK)?;
+        self.input = None;
+
+        if goes_on {
+            self.render()?;
         }
+        Ok(goes_on)
     }
-}
\ No newline at end of file
+
+    /// Moves the game forward by `dt` seconds, in as many ticks as fit in that
+    /// time. What remains is carried over to the next step. Returns whether
+    /// the game goes on, i.e. `false` once a view quit or the replay is over.
+    pub fn step(&mut self, dt: f64) -> Result<bool, PhiError> {
+        let mut current_view = match self.current_view.take() {
+            Some(view) => view,
+            None => return Ok(false),
+        };
+        let context = &mut self.context;
+
+        self.overlay.frame(dt);
+        self.frame = Some(context.trace.start());
+
+        let pump = context.trace.start();
+        context.events.pump();
+        context.trace.end(pump, "events");
+        self.accumulator += dt.min(MAX_FRAME_TIME);
+
+        // The events received during the step are spread over its ticks, see
+        // `Events::step`.
+        while self.accumulator >= TICK {
+            let tick = context.trace.start();
+            context.stats.clear();
+            context.events.step(&mut context.renderer);
+
+            if context.events.now.key_debug == Some(true) {
+                self.overlay.visible = !self.overlay.visible;
+            }
+
+            if context.events.now.key_hitboxes == Some(true) {
+                context.show_hitboxes = !context.show_hitboxes;
+            }
+
+            if context.events.now.focus_lost {
+                current_view.on_pause(context);
+            }
+
+            if context.events.now.focus_gained {
+                current_view.on_resume(context);
+            }
+
+            let too_small = context.events.now.resize
+                .is_some_and(|(w, h)| w < self.config.width || h < self.config.height);
+            if too_small && self.config.resize_policy == ResizePolicy::Pause {
+                current_view.on_pause(context);
+            }
+
+            //? The window may still be closed during a replay, but whatever
+            //? else the player does is replaced by the recorded input.
+            if let Some(ref mut input) = self.replay {
+                let quit = context.events.now.quit;
+                match input.play(&mut context.events) {
+                    Ok(true) => context.events.now.quit |= quit,
+                    Ok(false) => {
+                        println!("The replay is over");
+                        return Ok(false);
+                    },
+                    Err(err) => {
+                        println!("Could not play the replay back: {}", err);
+                        return Ok(false);
+                    },
+                }
+            }
+
+            if let Some(input) = self.input.take() {
+                input(&mut context.events);
+            }
+
+            if let Some(ref mut input) = self.recorder {
+                if let Err(err) = input.record(&context.events) {
+                    println!("Could not record the input: {}", err);
+                    self.recorder = None;
+                }
+            }
+
+            //? Catching the panic drops whatever the view was doing, but the
+            //? context it was given is still usable, so we may go on with
+            //? another view.
+            context.real_elapsed = TICK;
+            let elapsed = context.elapsed();
+
+            let update = context.trace.start();
+            let action = panic::catch_unwind(AssertUnwindSafe(|| current_view.update(context, elapsed)))
+                .unwrap_or_else(|payload| ViewAction::Error(PhiError::Crash(panic_message(payload))));
+            context.trace.end(update, "update");
+
+            match action {
+                ViewAction::Render(view) =>
+                    current_view = view,
+
+                ViewAction::ChangeView { from, to } =>
+                    current_view = change_view(context, &mut self.transition, None, from, to)?,
+
+                ViewAction::ChangeViewWith { transition, from, to } =>
+                    current_view = change_view(context, &mut self.transition, Some(transition), from, to)?,
+
+                ViewAction::Quit =>
+                    return Ok(false),
+
+                ViewAction::Error(err) => {
+                    println!("A view failed: {}", err);
+                    self.transition = None;
+                    current_view = (self.recover)(context, err.to_string())?;
+                    context.time_scale = 1.0;
+                    current_view.on_enter(context);
+                },
+            }
+
+            context.fades.update(TICK);
+            context.events.advance(TICK);
+            context.events.consume_now();
+            self.accumulator -= TICK;
+
+            if self.transition.as_mut().is_some_and(|transition| transition.update(TICK)) {
+                self.transition = None;
+            }
+
+            context.trace.end(tick, "tick");
+            context.trace.counters("Entities", context.stats.counters());
+        }
+
+        self.current_view = Some(current_view);
+        Ok(true)
+    }
+
+    /// Draws the current view, between the last tick and the next one, along
+    /// with the transition and the debug overlay, and shows the result.
+    pub fn render(&mut self) -> Result<(), PhiError> {
+        let context = &mut self.context;
+        let current_view = match self.current_view {
+            Some(ref mut view) => view,
+            None => return Ok(()),
+        };
+
+        let alpha = self.accumulator / TICK;
+        let render = context.trace.start();
+        let rendered = panic::catch_unwind(AssertUnwindSafe(|| current_view.render(context, alpha)));
+        context.trace.end(render, "render");
+
+        if let Err(payload) = rendered {
+            let err = PhiError::Crash(panic_message(payload));
+            println!("A view failed: {}", err);
+            self.transition = None;
+            *current_view = (self.recover)(context, err.to_string())?;
+            context.time_scale = 1.0;
+            current_view.on_enter(context);
+            return Ok(());
+        }
+
+        if let Some(ref transition) = self.transition {
+            transition.render(context);
+        }
+        self.overlay.render(context)?;
+
+        let present = context.trace.start();
+        context.renderer.present();
+        context.trace.end(present, "present");
+
+        if let Some(frame) = self.frame.take() {
+            context.trace.end(frame, "frame");
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the window described by `config` and runs the game loop, starting
+/// with the view returned by `init`, until a view quits. See `PhiRunner` for
+/// how views which fail are handled by `recover`.
+///
+/// Returns an error if the engine could not be initialized or if `recover`
+/// failed as well.
+pub fn spawn<F, R>(config: PhiConfig, init: F, recover: R) -> Result<(), PhiError>
+where
+    F: FnOnce(&mut Phi) -> Result<Box<dyn View>, PhiError> + 'static,
+    R: Fn(&mut Phi, String) -> Result<Box<dyn View>, PhiError> + 'static
+{
+    //? Browsers run the loop themselves, see `web`.
+    #[cfg(target_os = "emscripten")]
+    web::run(move || PhiRunner::new(config, init, recover));
+
+    #[cfg(not(target_os = "emscripten"))]
+    {
+        // Frame timing
+        let headless = config.headless;
+        let interval =
+            if headless { 0 }
+            else { config.fps_cap.filter(|&fps| fps > 0).map_or(0, |fps| 1_000 / fps) };
+
+        let mut runner = PhiRunner::new(config, init, recover)?;
+        let mut before = runner.timer.ticks();
+
+        loop {
+            // Frame timing (bis)
+
+            let now = runner.timer.ticks();
+            let dt = now - before;
+            let elapsed = if headless { TICK } else { dt as f64 / 1_000.0 };
+
+            // If the time elapsed since the last frame is too small, wait out the
+            // difference and try again.
+            if dt < interval {
+                runner.timer.delay(interval - dt);
+                continue;
+            }
+
+            before = now;
+
+            if !runner.step(elapsed)? {
+                return Ok(());
+            }
+            runner.render()?;
+        }
+    }
+}
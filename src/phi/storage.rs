@@ -0,0 +1,135 @@
+use crate::phi::signing;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The key of the line holding the signature of a signed store.
+const SIGNATURE_KEY: &str = "signature";
+
+/// A small persistent key-value store, saved as a text file holding one
+/// `key=value` pair per line.
+pub struct Storage {
+    path: PathBuf,
+    values: BTreeMap<String, String>,
+
+    // The key with which the file is signed, if it is.
+    key: Option<Vec<u8>>,
+}
+
+fn parse(contents: &str) -> BTreeMap<String, String> {
+    contents.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// The contents of a signed store, without the line holding the signature,
+/// if that signature is the right one under `key`.
+pub fn verified<'a>(contents: &'a str, key: &[u8]) -> Option<&'a str> {
+    // The signature is on the last line.
+    let (data, last) = match contents.trim_end().rfind('\n') {
+        Some(at) => contents.split_at(at + 1),
+        None => ("", contents),
+    };
+
+    let prefix = format!("{}=", SIGNATURE_KEY);
+    last.trim().strip_prefix(&prefix)
+        .filter(|signature| signing::verify(key, data.as_bytes(), signature))
+        .map(|_| data)
+}
+
+/// Appends to the contents of a store the line holding their signature under
+/// `key`.
+pub fn append_signature(contents: &mut String, key: &[u8]) {
+    let signature = signing::sign(key, contents.as_bytes());
+    contents.push_str(&format!("{}={}\n", SIGNATURE_KEY, signature));
+}
+
+impl Storage {
+    /// Reads the store saved at `path`. If the file does not exist yet, or
+    /// could not be read, the store starts empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> Storage {
+        let values = fs::read_to_string(path.as_ref())
+            .map(|contents| parse(&contents))
+            .unwrap_or_default();
+
+        Storage {
+            path: path.as_ref().to_path_buf(),
+            values,
+            key: None,
+        }
+    }
+
+    /// Like `load`, but the file ends with a signature of its contents under
+    /// `key`. If the signature is missing or wrong, e.g. because the file was
+    /// edited by hand, its contents are ignored and the store starts empty.
+    pub fn load_signed<P: AsRef<Path>>(path: P, key: Vec<u8>) -> Storage {
+        let path = path.as_ref();
+        let mut values = BTreeMap::new();
+
+        if let Ok(contents) = fs::read_to_string(path) {
+            match verified(&contents, &key) {
+                Some(data) => values = parse(data),
+                None => println!("Ignoring {}: its signature does not match its contents", path.display()),
+            }
+        }
+
+        Storage {
+            path: path.to_path_buf(),
+            values,
+            key: Some(key),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.values.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    /// Writes the store back to the file it was loaded from.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut contents: String = self.values.iter()
+            .map(|(key, value)| format!("{}={}\n", key, value))
+            .collect();
+
+        if let Some(ref key) = self.key {
+            append_signature(&mut contents, key);
+        }
+
+        fs::write(&self.path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_round_trip() {
+        let key = b"install key";
+        let data = "kills=12\nruns=3\n";
+
+        let mut contents = data.to_string();
+        append_signature(&mut contents, key);
+        assert_eq!(verified(&contents, key), Some(data));
+
+        // Editing the values, or checking under another key, breaks it.
+        assert_eq!(verified(&contents.replace("12", "99"), key), None);
+        assert_eq!(verified(&contents, b"another key"), None);
+
+        // So does leaving the signature out.
+        assert_eq!(verified(data, key), None);
+    }
+}
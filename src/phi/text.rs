@@ -0,0 +1,423 @@
+//! Text which spans several lines: wrapped between words to fit a width, and
+//! aligned within the widest of its lines.
+//!
+//! Paragraphs may be written from right to left, e.g. in Arabic or Hebrew.
+//! They are then aligned the other way around, and their characters are put
+//! back in the order in which they are drawn, which SDL_ttf does not do.
+//!
+//! Characters which the chosen font lacks are drawn with the first fallback
+//! font which has them, and those which no font has are drawn as boxes, so
+//! that text in any language, e.g. the name of a player, may be rendered.
+
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{self, CopySprite, Sprite};
+use crate::phi::ui::Align;
+use crate::phi::{Phi, PhiError};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect as SdlRect;
+use sdl2::render::{BlendMode, WindowCanvas};
+use sdl2::surface::Surface;
+use sdl2::ttf::Font;
+
+/// The direction in which a paragraph is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDirection {
+    /// That of the first character which has one, e.g. a letter rather than
+    /// a digit or a space, or left to right if there is none.
+    Auto,
+
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Whether `ch` is written from right to left, i.e. belongs to one of the
+/// Hebrew, Arabic, Syriac, Thaana or N'Ko blocks, or to their presentation
+/// forms.
+fn is_rtl(ch: char) -> bool {
+    matches!(ch as u32, 0x0590..=0x08ff | 0xfb1d..=0xfdff | 0xfe70..=0xfeff)
+}
+
+/// The direction in which `ch` is written, if it has one of its own.
+fn strong_direction(ch: char) -> Option<TextDirection> {
+    if is_rtl(ch) {
+        Some(TextDirection::RightToLeft)
+    } else if ch.is_alphanumeric() {
+        Some(TextDirection::LeftToRight)
+    } else {
+        None
+    }
+}
+
+/// The character which replaces `ch` in text which is drawn from right to
+/// left, e.g. a closing parenthesis for an opening one.
+fn mirrored(ch: char) -> char {
+    match ch {
+        '(' => ')', ')' => '(',
+        '[' => ']', ']' => '[',
+        '{' => '}', '}' => '{',
+        '<' => '>', '>' => '<',
+        '«' => '»', '»' => '«',
+        _ => ch,
+    }
+}
+
+impl TextDirection {
+    /// The direction of `text`, which is never `Auto`.
+    pub fn of(self, text: &str) -> TextDirection {
+        match self {
+            TextDirection::Auto => text.chars()
+                .find_map(strong_direction)
+                .unwrap_or(TextDirection::LeftToRight),
+            direction => direction,
+        }
+    }
+
+    /// The alignment which stands for `align` in text written this way, e.g.
+    /// `Start` becomes `End` from right to left.
+    fn align(self, align: Align) -> Align {
+        match (self, align) {
+            (TextDirection::RightToLeft, Align::Start) => Align::End,
+            (TextDirection::RightToLeft, Align::End) => Align::Start,
+            _ => align,
+        }
+    }
+}
+
+/// Puts the characters of `line`, which are in the order in which they are
+/// read, in the order in which they are drawn from left to right, given the
+/// `direction` of its paragraph.
+///
+/// This is a small part of the Unicode bidirectional algorithm: runs of
+/// characters written from right to left are reversed, and so is the order of
+/// the runs of a paragraph written from right to left. Characters which have
+/// no direction, e.g. spaces, take that of the characters around them if both
+/// agree, and that of the paragraph otherwise.
+pub fn visual_order(line: &str, direction: TextDirection) -> String {
+    let direction = direction.of(line);
+    let chars: Vec<char> = line.chars().collect();
+    let strong: Vec<Option<TextDirection>> = chars.iter().map(|&ch| strong_direction(ch)).collect();
+
+    // The direction of every character, once those of the neutral ones are
+    // resolved.
+    let resolved: Vec<TextDirection> = (0..chars.len())
+        .map(|i| strong[i].unwrap_or_else(|| {
+            let before = strong[..i].iter().rev().find_map(|&dir| dir).unwrap_or(direction);
+            let after = strong[i + 1..].iter().find_map(|&dir| dir).unwrap_or(direction);
+            if before == after { before } else { direction }
+        }))
+        .collect();
+
+    let mut runs: Vec<(TextDirection, Vec<char>)> = vec![];
+    for (&ch, &dir) in chars.iter().zip(&resolved) {
+        match runs.last_mut() {
+            Some((last, run)) if *last == dir => run.push(ch),
+            _ => runs.push((dir, vec![ch])),
+        }
+    }
+
+    if direction == TextDirection::RightToLeft {
+        runs.reverse();
+    }
+
+    runs.into_iter()
+        .flat_map(|(dir, run)| match dir {
+            TextDirection::RightToLeft => run.into_iter().rev().map(mirrored).collect::<Vec<_>>(),
+            _ => run,
+        })
+        .collect()
+}
+
+/// A line rendered on its own, unless it is blank, and the direction of its
+/// paragraph.
+type RenderedLine = (Option<Surface<'static>>, TextDirection);
+
+/// How a piece of text is broken into lines and laid out. Fields other than
+/// the font and color may be changed with the struct update syntax, e.g.
+/// `TextLayout { max_width: Some(400.0), ..TextLayout::new(FONT_PATH, 20, color) }`.
+#[derive(Clone, Copy, Debug)]
+pub struct TextLayout {
+    pub font_path: &'static str,
+    pub size: i32,
+    pub color: Color,
+
+    /// The width past which lines are wrapped, in pixels. A word which is
+    /// wider on its own is left whole, on a line of its own.
+    pub max_width: Option<f64>,
+
+    /// How lines are aligned, relative to the widest one. `Start` is the
+    /// side on which the paragraph of the line begins, i.e. the right for
+    /// text written from right to left.
+    pub align: Align,
+
+    /// The direction in which paragraphs are written.
+    pub direction: TextDirection,
+
+    /// The distance between two lines, relative to the one recommended by the
+    /// font.
+    pub line_spacing: f64,
+}
+
+/// A piece of a line which is drawn in a single way.
+#[derive(Debug, PartialEq)]
+enum Run {
+    /// Characters drawn with the font at the given index of the chain.
+    Text(usize, String),
+
+    /// A character which no font of the chain has.
+    Missing,
+}
+
+/// Splits `line` into runs of characters which the same font has, the first
+/// one in `fonts` which has them. Control characters are left out.
+fn shape(fonts: &[&Font], line: &str) -> Vec<Run> {
+    let mut runs: Vec<Run> = vec![];
+
+    for ch in line.chars().filter(|ch| !ch.is_control()) {
+        //? SDL_ttf only knows of the characters of the basic multilingual
+        //? plane, and would mistake the others for whichever character their
+        //? lower 16 bits stand for.
+        let index = fonts.iter()
+            .position(|font| (ch as u32) <= 0xffff && font.find_glyph(ch).is_some());
+
+        match (index, runs.last_mut()) {
+            (Some(index), Some(Run::Text(last, text))) if *last == index => text.push(ch),
+            (Some(index), _) => runs.push(Run::Text(index, ch.to_string())),
+            (None, _) => runs.push(Run::Missing),
+        }
+    }
+
+    runs
+}
+
+/// The size of the box drawn for a missing character, in pixels.
+fn missing_size(font: &Font) -> (u32, u32) {
+    let h = font.ascent().max(1) as u32;
+    (h * 2 / 3 + 2, h)
+}
+
+/// The width of `line` once rendered with `render_line`, in pixels.
+pub fn line_width(fonts: &[&Font], line: &str) -> u32 {
+    shape(fonts, line).iter()
+        .map(|run| match run {
+            Run::Text(index, text) => fonts[*index].size_of(text).map_or(0, |(w, _)| w),
+            Run::Missing => missing_size(fonts[0]).0,
+        })
+        .sum()
+}
+
+/// Renders `line` in a single color, with the first font of `fonts`, falling
+/// back to the next ones for the characters which it lacks. Errors mention
+/// `font_path`, the path of the first font.
+pub fn render_line(fonts: &[&Font], font_path: &str, line: &str, color: Color) -> Result<Surface<'static>, PhiError> {
+    let runs = shape(fonts, line);
+
+    // Most text is entirely covered by the first font, and is rendered at
+    // once.
+    if let [Run::Text(0, text)] = &runs[..] {
+        return fonts[0].render(text).blended(color).map_err(PhiError::asset(font_path));
+    }
+
+    let surfaces = runs.iter()
+        .map(|run| match run {
+            Run::Text(index, text) =>
+                fonts[*index].render(text).blended(color)
+                    .map(|surface| Some((surface, fonts[0].ascent() - fonts[*index].ascent())))
+                    .map_err(PhiError::asset(font_path)),
+            Run::Missing => Ok(None),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (box_w, box_h) = missing_size(fonts[0]);
+    let width: u32 = surfaces.iter()
+        .map(|surface| surface.as_ref().map_or(box_w, |(surface, _)| surface.width()))
+        .sum();
+    let height = fonts[0].height().max(1) as u32;
+
+    let mut block = Surface::new(width.max(1), height, PixelFormatEnum::ARGB8888)
+        .map_err(PhiError::asset(font_path))?;
+
+    //? Runs are laid side by side, on the baseline of the first font.
+    //? Missing characters are outlined with a thin box, which leaves a pixel
+    //? of room on either side, like glyphs do.
+    let mut x = 0;
+    for surface in surfaces {
+        match surface {
+            Some((mut surface, dy)) => {
+                surface.set_blend_mode(BlendMode::None)
+                    .map_err(PhiError::asset(font_path))?;
+                surface.blit(None, &mut block, SdlRect::new(x, dy, surface.width(), surface.height()))
+                    .map_err(PhiError::asset(font_path))?;
+                x += surface.width() as i32;
+            },
+
+            None => {
+                let (w, h) = (box_w as i32 - 2, box_h as i32);
+                let top = fonts[0].ascent() - h;
+                let edges = [
+                    SdlRect::new(x + 1, top, w as u32, 1),
+                    SdlRect::new(x + 1, top + h - 1, w as u32, 1),
+                    SdlRect::new(x + 1, top, 1, h as u32),
+                    SdlRect::new(x + w, top, 1, h as u32),
+                ];
+                for edge in edges {
+                    block.fill_rect(edge, color).map_err(PhiError::asset(font_path))?;
+                }
+                x += box_w as i32;
+            },
+        }
+    }
+
+    Ok(block)
+}
+
+/// Text which was laid out and rendered one line at a time, ready to be drawn.
+pub struct TextBlock {
+    // Every line which is not blank, along with its position in the block.
+    lines: Vec<(Sprite, f64, f64)>,
+    size: (f64, f64),
+}
+
+impl TextLayout {
+    /// Lays text out on a single line per paragraph, aligned to the left.
+    pub fn new(font_path: &'static str, size: i32, color: Color) -> TextLayout {
+        TextLayout {
+            font_path,
+            size,
+            color,
+            max_width: None,
+            align: Align::Start,
+            direction: TextDirection::Auto,
+            line_spacing: 1.0,
+        }
+    }
+
+    /// Breaks `text` into lines, along with the direction of their
+    /// paragraph. Line breaks in the text are kept, and each paragraph is
+    /// wrapped to `max_width`.
+    fn wrap(&self, fonts: &[&Font], text: &str) -> Vec<(String, TextDirection)> {
+        let fits = |line: &str| self.max_width
+            .is_none_or(|max| line_width(fonts, line) as f64 <= max);
+
+        let mut lines = vec![];
+        for paragraph in text.lines() {
+            let direction = self.direction.of(paragraph);
+            let mut line = String::new();
+
+            for word in paragraph.split_whitespace() {
+                let candidate =
+                    if line.is_empty() { word.to_string() }
+                    else { format!("{} {}", line, word) };
+
+                if fits(&candidate) || line.is_empty() {
+                    line = candidate;
+                } else {
+                    lines.push((::std::mem::replace(&mut line, word.to_string()), direction));
+                }
+            }
+
+            lines.push((line, direction));
+        }
+
+        lines
+    }
+
+    /// Renders every line of `text` to a surface of its own, along with the
+    /// direction of its paragraph, and the height of a line. Blank lines have
+    /// no surface.
+    fn render_lines(&self, phi: &mut Phi, text: &str) -> Result<(Vec<RenderedLine>, f64), PhiError> {
+        let fonts = phi.fonts.chain(self.font_path, self.size as u16)?;
+        let line_h = fonts[0].recommended_line_spacing() as f64 * self.line_spacing;
+
+        let surfaces = self.wrap(&fonts, text).into_iter()
+            .map(|(line, direction)| {
+                //? SDL_ttf cannot render empty strings.
+                if line.chars().all(char::is_control) {
+                    return Ok((None, direction));
+                }
+
+                gfx::count_text();
+                let line = visual_order(&line, direction);
+                render_line(&fonts, self.font_path, &line, self.color).map(|surface| (Some(surface), direction))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok((surfaces, line_h))
+    }
+
+    /// Where every line goes in the block, given the width of the lines and
+    /// the direction of their paragraph, and the size of the whole block.
+    fn place(&self, widths: &[(f64, TextDirection)], line_h: f64) -> (Vec<(f64, f64)>, (f64, f64)) {
+        let block_w = widths.iter().map(|&(w, _)| w).fold(0.0, f64::max);
+        let positions = widths.iter().enumerate()
+            .map(|(i, &(w, direction))| (direction.align(self.align).position(block_w, w, 0.0), i as f64 * line_h))
+            .collect();
+
+        (positions, (block_w, widths.len() as f64 * line_h))
+    }
+
+    /// Lays `text` out, so that it may be drawn with `TextBlock::render`.
+    pub fn layout(&self, phi: &mut Phi, text: &str) -> Result<TextBlock, PhiError> {
+        let (surfaces, line_h) = self.render_lines(phi, text)?;
+        let widths: Vec<(f64, TextDirection)> = surfaces.iter()
+            .map(|(surface, direction)| (surface.as_ref().map_or(0.0, |surface| surface.width() as f64), *direction))
+            .collect();
+        let (positions, size) = self.place(&widths, line_h);
+
+        let mut lines = vec![];
+        for ((surface, _), (x, y)) in surfaces.into_iter().zip(positions) {
+            if let Some(surface) = surface {
+                let sprite = phi.renderer.create_texture_from_surface(&surface)
+                    .map(Sprite::new)
+                    .map_err(PhiError::asset(self.font_path))?;
+                lines.push((sprite, x, y));
+            }
+        }
+
+        Ok(TextBlock { lines, size })
+    }
+
+    /// Renders `text` to a single sprite, e.g. for text which is drawn often
+    /// but rarely changes.
+    pub fn sprite(&self, phi: &mut Phi, text: &str) -> Result<Sprite, PhiError> {
+        let (surfaces, line_h) = self.render_lines(phi, text)?;
+        let widths: Vec<(f64, TextDirection)> = surfaces.iter()
+            .map(|(surface, direction)| (surface.as_ref().map_or(0.0, |surface| surface.width() as f64), *direction))
+            .collect();
+        let (positions, (block_w, block_h)) = self.place(&widths, line_h);
+
+        let mut block = Surface::new((block_w as u32).max(1), (block_h as u32).max(1), PixelFormatEnum::ARGB8888)
+            .map_err(PhiError::asset(self.font_path))?;
+
+        for ((surface, _), (x, y)) in surfaces.into_iter().zip(positions) {
+            if let Some(mut surface) = surface {
+                //? Lines never overlap, so their pixels, alpha included, are
+                //? copied as they are onto the transparent block.
+                surface.set_blend_mode(BlendMode::None)
+                    .map_err(PhiError::asset(self.font_path))?;
+                surface.blit(None, &mut block, SdlRect::new(x as i32, y as i32, surface.width(), surface.height()))
+                    .map_err(PhiError::asset(self.font_path))?;
+            }
+        }
+
+        let mut texture = phi.renderer.create_texture_from_surface(&block)
+            .map_err(PhiError::asset(self.font_path))?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        Ok(Sprite::new(texture))
+    }
+}
+
+impl TextBlock {
+    /// The size of the whole block, in pixels.
+    pub fn size(&self) -> (f64, f64) {
+        self.size
+    }
+
+    /// Draws the block with its top-left corner at `(x, y)`.
+    pub fn render(&self, renderer: &mut WindowCanvas, (x, y): (f64, f64)) {
+        for (sprite, dx, dy) in &self.lines {
+            let (w, h) = sprite.size();
+            renderer.copy_sprite(sprite, Rectangle { x: x + dx, y: y + dy, w, h });
+        }
+    }
+}
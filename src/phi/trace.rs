@@ -0,0 +1,122 @@
+//! Records how long the engine and the views spend on what, e.g. updating or
+//! rendering, frame after frame, for investigations which the debug overlay is
+//! too coarse for.
+//!
+//! The trace is written in the trace event format of `chrome://tracing`,
+//! which Perfetto opens as is and Tracy imports with `import-chrome`. It is
+//! only recorded by builds with the `trace` feature: otherwise, spans and
+//! counters cost nothing.
+//!
+//!     let span = phi.trace.start();
+//!     // ...
+//!     phi.trace.end(span, "collisions");
+
+#[cfg(feature = "trace")]
+pub use self::recording::{Span, Tracer};
+
+#[cfg(not(feature = "trace"))]
+pub use self::disabled::{Span, Tracer};
+
+#[cfg(feature = "trace")]
+mod recording {
+    use std::fs;
+    use std::time::Instant;
+
+    /// When something which is being traced started.
+    #[derive(Clone, Copy)]
+    pub struct Span(Instant);
+
+    pub struct Tracer {
+        // Where the trace is written once the game exits, if it is recorded.
+        path: Option<String>,
+
+        // When the trace started, which the time of every event is relative
+        // to, and the events recorded so far, as JSON objects.
+        origin: Instant,
+        events: Vec<String>,
+    }
+
+    impl Tracer {
+        /// A tracer which writes to `path`, if any, once it is dropped.
+        pub fn new(path: Option<String>) -> Tracer {
+            Tracer {
+                path,
+                origin: Instant::now(),
+                events: vec![],
+            }
+        }
+
+        fn micros(&self, at: Instant) -> f64 {
+            at.duration_since(self.origin).as_secs_f64() * 1_000_000.0
+        }
+
+        pub fn start(&self) -> Span {
+            Span(Instant::now())
+        }
+
+        /// Records that whatever is called `name` lasted from `span` until
+        /// now. Spans may be nested.
+        pub fn end(&mut self, span: Span, name: &'static str) {
+            if self.path.is_none() {
+                return;
+            }
+
+            let (ts, now) = (self.micros(span.0), self.micros(Instant::now()));
+            self.events.push(format!(
+                r#"{{"name":"{}","ph":"X","ts":{:.3},"dur":{:.3},"pid":1,"tid":1}}"#,
+                name, ts, now - ts));
+        }
+
+        /// Records the current value of the counters, which are drawn as
+        /// graphs under the spans, in a track called `name`.
+        pub fn counters(&mut self, name: &'static str, values: &[(&'static str, usize)]) {
+            if self.path.is_none() || values.is_empty() {
+                return;
+            }
+
+            let args: Vec<String> = values.iter()
+                .map(|(counter, value)| format!(r#""{}":{}"#, counter, value))
+                .collect();
+            self.events.push(format!(
+                r#"{{"name":"{}","ph":"C","ts":{:.3},"pid":1,"args":{{{}}}}}"#,
+                name, self.micros(Instant::now()), args.join(",")));
+        }
+    }
+
+    impl Drop for Tracer {
+        fn drop(&mut self) {
+            if let Some(ref path) = self.path {
+                let json = format!("{{\"traceEvents\":[\n{}\n]}}\n", self.events.join(",\n"));
+                match fs::write(path, json) {
+                    Ok(()) => println!("Wrote {} trace events to {}", self.events.len(), path),
+                    Err(err) => println!("Could not write the trace: {}", err),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod disabled {
+    #[derive(Clone, Copy)]
+    pub struct Span;
+
+    pub struct Tracer;
+
+    impl Tracer {
+        pub fn new(path: Option<String>) -> Tracer {
+            if path.is_some() {
+                println!("This build cannot record traces, see the `trace` feature");
+            }
+            Tracer
+        }
+
+        pub fn start(&self) -> Span {
+            Span
+        }
+
+        pub fn end(&mut self, _: Span, _: &'static str) {}
+
+        pub fn counters(&mut self, _: &'static str, _: &[(&'static str, usize)]) {}
+    }
+}
@@ -0,0 +1,121 @@
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{draw, CopySprite, Sprite, Tint};
+use sdl2::pixels::{Color, PixelFormatEnum};
+
+/// How the game loop goes from one view to the next. Every transition lasts
+/// the given number of seconds.
+#[derive(Clone, Copy, Debug)]
+pub enum Transition {
+    /// Fade the previous view to black, then the next one from black.
+    Fade(f64),
+
+    /// Blend the previous view into the next one.
+    Crossfade(f64),
+
+    /// Uncover the next view from the left, as if the previous one was
+    /// wiped off the screen.
+    Wipe(f64),
+}
+
+impl Transition {
+    fn duration(self) -> f64 {
+        match self {
+            Transition::Fade(duration) |
+            Transition::Crossfade(duration) |
+            Transition::Wipe(duration) => duration,
+        }
+    }
+}
+
+/// A transition which is being played by the game loop.
+pub struct ActiveTransition {
+    transition: Transition,
+
+    // What the screen looked like when the transition started. Only the
+    // next view keeps running during the transition.
+    snapshot: Sprite,
+    elapsed: f64,
+}
+
+impl ActiveTransition {
+    /// Starts `transition` from whatever was last drawn on the screen, which
+    /// must not have been presented yet.
+    pub fn start(phi: &mut Phi, transition: Transition) -> Result<ActiveTransition, PhiError> {
+        //? We read back the pixels of the whole screen, which is slow, but
+        //? only happens once per transition.
+        //?
+        //? The viewport covers the logical area, which SDL scaled to the
+        //? window, so its size in actual pixels depends on the scale.
+        let format = PixelFormatEnum::ARGB8888;
+        let viewport = phi.renderer.viewport();
+        let (scale_x, scale_y) = phi.renderer.scale();
+        let w = (viewport.width() as f32 * scale_x) as u32;
+        let h = (viewport.height() as f32 * scale_y) as u32;
+        let pixels = phi.renderer.read_pixels(None, format).map_err(PhiError::Init)?;
+
+        let mut texture = phi.renderer.texture_creator()
+            .create_texture_static(format, w, h)
+            .map_err(|err| PhiError::Init(err.to_string()))?;
+        texture.update(None, &pixels, w as usize * 4)
+            .map_err(|err| PhiError::Init(err.to_string()))?;
+
+        Ok(ActiveTransition {
+            transition,
+            snapshot: Sprite::new(texture),
+            elapsed: 0.0,
+        })
+    }
+
+    /// Advances the transition. Returns whether it is finished.
+    pub fn update(&mut self, dt: f64) -> bool {
+        self.elapsed += dt;
+        self.elapsed >= self.transition.duration()
+    }
+
+    /// Draws the transition on top of the next view, which was just rendered.
+    pub fn render(&self, phi: &mut Phi) {
+        let progress = (self.elapsed / self.transition.duration()).clamp(0.0, 1.0);
+        let (w, h) = phi.output_size();
+        let screen = Rectangle::with_size(w, h);
+
+        match self.transition {
+            Transition::Fade(_) => {
+                // Show the previous view during the first half, the next one
+                // during the second half, and darken both around the middle.
+                let darkness =
+                    if progress < 0.5 {
+                        phi.renderer.copy_sprite(&self.snapshot, screen);
+                        progress * 2.0
+                    } else {
+                        (1.0 - progress) * 2.0
+                    };
+
+                draw::fill_rect(&mut phi.renderer, screen, Color::RGBA(0, 0, 0, (darkness * 255.0) as u8));
+            },
+
+            Transition::Crossfade(_) =>
+                phi.renderer.copy_sprite_tinted(&self.snapshot, screen, Tint::alpha(1.0 - progress)),
+
+            Transition::Wipe(_) => {
+                // Only draw what remains of the previous view, on the right.
+                let (snap_w, snap_h) = self.snapshot.size();
+                let region = self.snapshot.region(Rectangle {
+                    x: snap_w * progress,
+                    y: 0.0,
+                    w: snap_w * (1.0 - progress),
+                    h: snap_h,
+                });
+
+                if let Some(region) = region {
+                    phi.renderer.copy_sprite(&region, Rectangle {
+                        x: w * progress,
+                        y: 0.0,
+                        w: w * (1.0 - progress),
+                        h,
+                    });
+                }
+            },
+        }
+    }
+}
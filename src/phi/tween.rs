@@ -0,0 +1,61 @@
+//! Values which move from one to another over time, following an easing
+//! curve, e.g. to animate the interface or the camera.
+
+/// How a tween goes from its start to its end, given how far along it is, in
+/// `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ease {
+    /// Start slowly, then speed up.
+    In,
+
+    /// Start fast, then slow down, e.g. for numbers which count up.
+    Out,
+
+    /// Start and stop slowly, e.g. for the camera.
+    InOut,
+}
+
+impl Ease {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::In => t * t * t,
+            Ease::Out => 1.0 - (1.0 - t).powi(3),
+            Ease::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A value which goes from `from` to `to` over `duration` seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    pub from: f64,
+    pub to: f64,
+    pub duration: f64,
+    pub ease: Ease,
+
+    // The time since the tween started, in seconds.
+    time: f64,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, duration: f64, ease: Ease) -> Tween {
+        Tween { from, to, duration, ease, time: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.time = (self.time + dt).min(self.duration);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.time >= self.duration
+    }
+
+    pub fn value(&self) -> f64 {
+        if self.duration <= 0.0 {
+            return self.to;
+        }
+
+        self.from + (self.to - self.from) * self.ease.apply(self.time / self.duration)
+    }
+}
@@ -0,0 +1,772 @@
+//! A small set of widgets out of which menus are built.
+//!
+//! A `Menu` is a vertical list of widgets, drawn inside of a colored box at the
+//! center of the window. The player moves the focus between the widgets which
+//! accept it with the directional keys or the mouse, and interacts with them
+//! using the confirm and left/right keys or by clicking. Every interaction is
+//! reported back to the owner of the menu as a `UiEvent`.
+//!
+//! Widgets may be grouped under categories, and the menu may be filtered so
+//! that only the widgets whose text, or whose category's, contains some search
+//! string are shown. A menu which is given a maximum number of rows scrolls
+//! once it holds more widgets than that, following the focus, the page keys
+//! and the mouse wheel.
+
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{draw, CopySprite, Sprite};
+use sdl2::pixels::Color;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+// Definitions for the menu's layout
+const BORDER_WIDTH: f64 = 3.0;
+const MARGIN_H: f64 = 10.0;
+const SLIDER_BAR_H: f64 = 6.0;
+const SCROLL_BAR_W: f64 = 4.0;
+
+/// How an element is aligned along one axis of its container.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    /// The position of an element of length `size` inside of a container of
+    /// length `container`. For `Start` and `End`, `offset` is the distance
+    /// from the corresponding edge; for `Center`, it is the displacement from
+    /// the center.
+    pub fn position(self, container: f64, size: f64, offset: f64) -> f64 {
+        match self {
+            Align::Start => offset,
+            Align::Center => (container - size) / 2.0 + offset,
+            Align::End => container - size - offset,
+        }
+    }
+
+    /// The offset which places an element of length `size` at `position`,
+    /// i.e. the inverse of `Align::position`.
+    pub fn offset(self, container: f64, size: f64, position: f64) -> f64 {
+        match self {
+            Align::Start => position,
+            Align::Center => position - (container - size) / 2.0,
+            Align::End => container - size - position,
+        }
+    }
+
+    /// The alignment whose third of the container holds the point `at`.
+    pub fn nearest(container: f64, at: f64) -> Align {
+        if at < container / 3.0 { Align::Start }
+        else if at < container * 2.0 / 3.0 { Align::Center }
+        else { Align::End }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Align::Start => "start",
+            Align::Center => "center",
+            Align::End => "end",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Align> {
+        match name {
+            "start" => Some(Align::Start),
+            "center" => Some(Align::Center),
+            "end" => Some(Align::End),
+            _ => None,
+        }
+    }
+}
+
+/// The point of the screen relative to which an element is placed, e.g. the
+/// bottom-left corner or the center of the top edge.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Anchor {
+    pub horizontal: Align,
+    pub vertical: Align,
+}
+
+impl Anchor {
+    /// The region occupied by an element of the given `size`, placed at
+    /// `offset` from this anchor on a screen of size `screen`.
+    pub fn place(self, screen: (f64, f64), size: (f64, f64), offset: (f64, f64)) -> Rectangle {
+        Rectangle {
+            w: size.0,
+            h: size.1,
+            x: self.horizontal.position(screen.0, size.0, offset.0),
+            y: self.vertical.position(screen.1, size.1, offset.1),
+        }
+    }
+
+    /// The anchor closest to `rect`, and the offset at which `rect` is placed
+    /// relative to it.
+    pub fn nearest(screen: (f64, f64), rect: Rectangle) -> (Anchor, (f64, f64)) {
+        let (cx, cy) = rect.center();
+        let anchor = Anchor {
+            horizontal: Align::nearest(screen.0, cx),
+            vertical: Align::nearest(screen.1, cy),
+        };
+
+        let offset = (
+            anchor.horizontal.offset(screen.0, rect.w, rect.x),
+            anchor.vertical.offset(screen.1, rect.h, rect.y),
+        );
+
+        (anchor, offset)
+    }
+}
+
+/// Identifies a widget inside of the menu which holds it.
+pub type WidgetId = usize;
+
+/// The different kinds of widgets, along with the state they hold.
+#[derive(Clone, Copy, PartialEq)]
+pub enum WidgetKind {
+    /// Some text which cannot be interacted with.
+    Label,
+
+    /// Some text which can be activated.
+    Button,
+
+    /// A switch which is either on or off.
+    Toggle(bool),
+
+    /// A value in `[min, max]` which moves by `step` at a time.
+    Slider { value: f64, min: f64, max: f64, step: f64 },
+}
+
+impl WidgetKind {
+    /// Whether the player may move the focus onto a widget of this kind.
+    fn focusable(&self) -> bool {
+        !matches!(*self, WidgetKind::Label)
+    }
+}
+
+/// What happened to a menu's widgets during the last frame.
+#[derive(Clone, Copy, PartialEq)]
+pub enum UiEvent {
+    /// A button was activated.
+    Clicked(WidgetId),
+
+    /// A toggle was switched to the given state.
+    Toggled(WidgetId, bool),
+
+    /// A slider's value was changed to the given one.
+    Changed(WidgetId, f64),
+
+    /// The focus was moved to another widget.
+    Focused(WidgetId),
+}
+
+/// Some text pre-rendered in both of the states in which a widget may be drawn.
+struct Text {
+    /// The sprite which is rendered when the player does not focus on the
+    /// widget.
+    idle_sprite: Sprite,
+
+    /// The sprite which is rendered when the player "focuses" a widget with
+    /// the directional keys or the mouse.
+    hover_sprite: Sprite,
+}
+
+impl Text {
+    /// Renders some text which should fit in rows which are `row_h` pixels
+    /// high.
+    fn new(phi: &mut Phi, text: &str, row_h: f64) -> Result<Text, PhiError> {
+        let idle_size = (row_h * 0.64) as i32;
+        let hover_size = (row_h * 0.76) as i32;
+
+        Ok(Text {
+            idle_sprite: phi.ttf_str_sprite(text, FONT_PATH, idle_size, Color::RGB(220, 220, 220))?,
+            hover_sprite: phi.ttf_str_sprite(text, FONT_PATH, hover_size, Color::RGB(255, 255, 255))?,
+        })
+    }
+}
+
+struct Widget {
+    text: String,
+    kind: WidgetKind,
+
+    /// The header of the category in which the widget was added, if any.
+    category: Option<WidgetId>,
+
+    /// The text shown for every state of the widget. Toggles have one for
+    /// when they are off and one for when they are on, so that switching
+    /// them does not require rendering anything.
+    texts: Vec<Text>,
+}
+
+impl Widget {
+    fn new(phi: &mut Phi, text: &str, kind: WidgetKind, row_h: f64) -> Result<Widget, PhiError> {
+        let texts = match kind {
+            WidgetKind::Toggle(_) => vec![
+                Text::new(phi, &format!("{}: Off", text), row_h)?,
+                Text::new(phi, &format!("{}: On", text), row_h)?,
+            ],
+            _ => vec![Text::new(phi, text, row_h)?],
+        };
+
+        Ok(Widget {
+            text: text.to_string(),
+            kind,
+            category: None,
+            texts,
+        })
+    }
+
+    /// The text to show for the current state of the widget.
+    fn current_text(&self) -> &Text {
+        match self.kind {
+            WidgetKind::Toggle(on) => &self.texts[on as usize],
+            _ => &self.texts[0],
+        }
+    }
+}
+
+/// A vertical list of widgets, of which at most one has the focus.
+pub struct Menu {
+    widgets: Vec<Widget>,
+    focus: Option<WidgetId>,
+    box_w: f64,
+    row_h: f64,
+
+    /// The headers of the categories, in the order in which they were added.
+    categories: Vec<WidgetId>,
+
+    /// The category in which widgets are currently added, if any.
+    category: Option<WidgetId>,
+
+    /// The widgets which match the filter, from top to bottom.
+    visible: Vec<WidgetId>,
+
+    /// Lowercase, so that the search ignores the case.
+    filter: String,
+
+    /// How many rows are shown at once, if there is a limit.
+    max_rows: Option<usize>,
+
+    /// The index in `visible` of the topmost row which is shown.
+    scroll: usize,
+}
+
+impl Menu {
+    /// Creates an empty menu whose box is `box_w` pixels wide, and in which
+    /// every widget is given a row `row_h` pixels high.
+    pub fn new(box_w: f64, row_h: f64) -> Menu {
+        Menu {
+            widgets: vec![],
+            focus: None,
+            box_w,
+            row_h,
+            categories: vec![],
+            category: None,
+            visible: vec![],
+            filter: String::new(),
+            max_rows: None,
+            scroll: 0,
+        }
+    }
+
+    /// Like `new`, but shows at most `max_rows` rows at once, and lets the
+    /// player scroll through the others.
+    pub fn scrolling(box_w: f64, row_h: f64, max_rows: usize) -> Menu {
+        Menu {
+            max_rows: Some(max_rows.max(1)),
+            ..Menu::new(box_w, row_h)
+        }
+    }
+
+    /// Appends a widget at the bottom of the menu and returns its identifier.
+    /// The first focusable widget to be added receives the focus.
+    pub fn add(&mut self, phi: &mut Phi, text: &str, kind: WidgetKind) -> Result<WidgetId, PhiError> {
+        let id = self.widgets.len();
+        let mut widget = Widget::new(phi, text, kind, self.row_h)?;
+        widget.category = self.category;
+        self.widgets.push(widget);
+        self.apply_filter();
+
+        Ok(id)
+    }
+
+    /// Appends the header of a category, under which every widget added
+    /// afterwards is grouped, and returns its identifier.
+    pub fn add_category(&mut self, phi: &mut Phi, name: &str) -> Result<WidgetId, PhiError> {
+        let id = self.widgets.len();
+        self.widgets.push(Widget::new(phi, name, WidgetKind::Label, self.row_h)?);
+        self.categories.push(id);
+        self.category = Some(id);
+        self.apply_filter();
+
+        Ok(id)
+    }
+
+    /// Stops grouping the widgets which are added afterwards under the last
+    /// category. Such widgets, e.g. a "Back" button, are never filtered out.
+    pub fn end_category(&mut self) {
+        self.category = None;
+    }
+
+    /// The widget which has the focus, if any.
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    /// Changes the text of a widget, re-rendering it if necessary.
+    pub fn set_text(&mut self, phi: &mut Phi, id: WidgetId, text: &str) -> Result<(), PhiError> {
+        if self.widgets[id].text != text {
+            let kind = self.widgets[id].kind;
+            let category = self.widgets[id].category;
+            self.widgets[id] = Widget::new(phi, text, kind, self.row_h)?;
+            self.widgets[id].category = category;
+            self.apply_filter();
+        }
+
+        Ok(())
+    }
+
+    /// Only shows the widgets whose text, or the name of whose category,
+    /// contains `filter`, ignoring the case. Widgets which are not in a
+    /// category are always shown, and so are the headers of the categories
+    /// which have something to show.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_lowercase();
+        self.apply_filter();
+    }
+
+    fn apply_filter(&mut self) {
+        let matches = |widget: &Widget| widget.text.to_lowercase().contains(&self.filter);
+
+        let shown: Vec<bool> = self.widgets.iter()
+            .map(|widget| match widget.category {
+                Some(header) => matches(widget) || matches(&self.widgets[header]),
+                None => true,
+            })
+            .collect();
+
+        self.visible = (0..self.widgets.len())
+            .filter(|&id| if self.categories.contains(&id) {
+                (0..self.widgets.len()).any(|other| self.widgets[other].category == Some(id) && shown[other])
+            } else {
+                shown[id]
+            })
+            .collect();
+
+        // Give the focus to the first widget on display if it was filtered
+        // out, and back to it when the filter is relaxed.
+        if !self.focus.is_some_and(|id| self.visible.contains(&id)) {
+            self.focus = self.visible.iter().copied().find(|&id| self.widgets[id].kind.focusable());
+        }
+
+        self.scroll = self.scroll.min(self.max_scroll());
+        self.scroll_to_focus();
+    }
+
+    /// How many rows are shown at once.
+    fn page_len(&self) -> usize {
+        self.max_rows.map_or(self.visible.len(), |rows| rows.min(self.visible.len()))
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.visible.len() - self.page_len()
+    }
+
+    /// The widgets which are currently scrolled into view, from top to bottom.
+    fn shown(&self) -> &[WidgetId] {
+        &self.visible[self.scroll..self.scroll + self.page_len()]
+    }
+
+    /// Scrolls just enough for the focused widget to be shown.
+    fn scroll_to_focus(&mut self) {
+        let index = match self.focus.and_then(|id| self.visible.iter().position(|&other| other == id)) {
+            Some(index) => index,
+            None => return,
+        };
+
+        if index < self.scroll {
+            self.scroll = index;
+        } else if index >= self.scroll + self.page_len() {
+            self.scroll = index + 1 - self.page_len();
+        }
+    }
+
+    /// Scrolls by `rows`, downwards if positive, and keeps the focus on a
+    /// widget which is shown.
+    fn scroll_by(&mut self, rows: isize) -> Option<UiEvent> {
+        let scroll = (self.scroll as isize + rows).clamp(0, self.max_scroll() as isize) as usize;
+        if scroll == self.scroll {
+            return None;
+        }
+        self.scroll = scroll;
+
+        if self.focus.is_some_and(|id| self.shown().contains(&id)) {
+            return None;
+        }
+
+        // Focus the nearest widget which is shown, in the direction in which
+        // the menu was scrolled.
+        let mut shown = self.shown().to_vec();
+        if rows < 0 {
+            shown.reverse();
+        }
+
+        let id = shown.into_iter().find(|&id| self.widgets[id].kind.focusable())?;
+        self.focus = Some(id);
+        Some(UiEvent::Focused(id))
+    }
+
+    /// Handles the player's input for this frame, and returns what happened
+    /// to the widgets, if anything.
+    pub fn update(&mut self, phi: &mut Phi) -> Option<UiEvent> {
+        self.handle(phi, true)
+    }
+
+    /// Like `update`, but the keys which move the focus and change widgets
+    /// are left alone, e.g. while they are typed into a search.
+    pub fn update_pointer(&mut self, phi: &mut Phi) -> Option<UiEvent> {
+        self.handle(phi, false)
+    }
+
+    fn handle(&mut self, phi: &mut Phi, keyboard: bool) -> Option<UiEvent> {
+        // Mouse interaction: hovering a widget gives it the focus, and clicking
+        // on it activates it.
+        if phi.events.now.mouse_moved || phi.events.now.mouse_click.is_some() {
+            let (mx, my) = phi.events.mouse;
+            let hovered = self.widget_at(phi, mx as f64, my as f64);
+
+            if let Some(id) = hovered {
+                if phi.events.now.mouse_click.is_some() {
+                    self.focus = Some(id);
+                    return self.click(phi, id, mx as f64);
+                }
+
+                if self.focus != Some(id) {
+                    self.focus = Some(id);
+                    return Some(UiEvent::Focused(id));
+                }
+            }
+        }
+
+        // Scrolling, with the mouse wheel or a page at a time.
+        let page = self.page_len() as isize;
+        if phi.events.now.mouse_wheel != 0 {
+            return self.scroll_by(-phi.events.now.mouse_wheel as isize);
+        }
+
+        if phi.events.now.key_page_up == Some(true) {
+            return self.scroll_by(-page);
+        }
+
+        if phi.events.now.key_page_down == Some(true) {
+            return self.scroll_by(page);
+        }
+
+        if !keyboard {
+            return None;
+        }
+
+        let id = self.focus?;
+
+        // Keyboard interaction
+        if phi.events.now.key_up == Some(true) {
+            return self.move_focus(false);
+        }
+
+        if phi.events.now.key_down == Some(true) {
+            return self.move_focus(true);
+        }
+
+        let confirm =
+            phi.events.now.key_space == Some(true) ||
+            phi.events.now.key_enter == Some(true);
+        let left = phi.events.now.key_left == Some(true);
+        let right = phi.events.now.key_right == Some(true);
+
+        match self.widgets[id].kind {
+            WidgetKind::Button if confirm =>
+                Some(UiEvent::Clicked(id)),
+
+            WidgetKind::Toggle(on) if confirm || left || right => {
+                self.widgets[id].kind = WidgetKind::Toggle(!on);
+                Some(UiEvent::Toggled(id, !on))
+            },
+
+            WidgetKind::Slider { value, min, max, step } if left || right => {
+                let value = if left { value - step } else { value + step }.clamp(min, max);
+                self.set_slider(id, value)
+            },
+
+            _ => None,
+        }
+    }
+
+    /// Renders the menu at the center of the window.
+    pub fn render(&self, phi: &mut Phi) {
+        let (win_w, win_h) = phi.output_size();
+        let box_h = self.page_len() as f64 * self.row_h;
+
+        // Render the border of the colored box which holds the widgets
+        draw::fill_rect(&mut phi.renderer, Rectangle {
+            w: self.box_w + BORDER_WIDTH * 2.0,
+            h: box_h + BORDER_WIDTH * 2.0 + MARGIN_H * 2.0,
+            x: (win_w - self.box_w) / 2.0 - BORDER_WIDTH,
+            y: (win_h - box_h) / 2.0 - MARGIN_H - BORDER_WIDTH,
+        }, Color::RGB(70, 15, 70));
+
+        // Render the colored box which holds the widgets
+        draw::fill_rect(&mut phi.renderer, Rectangle {
+            w: self.box_w,
+            h: box_h + MARGIN_H * 2.0,
+            x: (win_w - self.box_w) / 2.0,
+            y: (win_h - box_h) / 2.0 - MARGIN_H,
+        }, Color::RGB(140, 30, 140));
+
+        // Render the widgets themselves, cutting off whatever overflows from
+        // the box.
+        let content = Rectangle {
+            w: self.box_w,
+            h: box_h,
+            x: (win_w - self.box_w) / 2.0,
+            y: (win_h - box_h) / 2.0,
+        };
+        phi.renderer.set_clip_rect(content.to_sdl());
+
+        for (index, &id) in self.shown().iter().enumerate() {
+            let widget = &self.widgets[id];
+            let row = self.row_at(phi, index);
+            let text = widget.current_text();
+            let sprite =
+                if self.focus == Some(id) { &text.hover_sprite }
+                else { &text.idle_sprite };
+
+            let (w, h) = sprite.size();
+
+            if let WidgetKind::Slider { value, min, max, .. } = widget.kind {
+                // Sliders show their text on the left, and a bar on the right
+                // which is filled proportionally to their value.
+                phi.renderer.copy_sprite(sprite, Rectangle {
+                    w,
+                    h,
+                    x: row.x + MARGIN_H,
+                    y: row.y + (self.row_h - h) / 2.0,
+                });
+
+                let bar = Menu::slider_bar(row);
+                let ratio = if max > min { (value - min) / (max - min) } else { 0.0 };
+
+                draw::fill_rect(&mut phi.renderer, bar, Color::RGB(70, 15, 70));
+                draw::fill_rect(&mut phi.renderer, Rectangle { w: bar.w * ratio, ..bar }, Color::RGB(220, 220, 220));
+            } else {
+                phi.renderer.copy_sprite(sprite, Rectangle {
+                    w,
+                    h,
+                    x: (win_w - w) / 2.0,
+                    y: row.y + (self.row_h - h) / 2.0,
+                });
+            }
+        }
+
+        phi.renderer.set_clip_rect(None);
+
+        // Show how far the menu is scrolled, if it may be
+        if self.max_scroll() > 0 {
+            let len = self.visible.len() as f64;
+            draw::fill_rect(&mut phi.renderer, Rectangle {
+                w: SCROLL_BAR_W,
+                h: content.h * self.page_len() as f64 / len,
+                x: content.x + content.w - SCROLL_BAR_W,
+                y: content.y + content.h * self.scroll as f64 / len,
+            }, Color::RGB(220, 220, 220));
+        }
+    }
+
+    /// The region of the window which the widget `id` occupies, if it is
+    /// shown.
+    fn row(&self, phi: &Phi, id: WidgetId) -> Option<Rectangle> {
+        let index = self.shown().iter().position(|&other| other == id)?;
+        Some(self.row_at(phi, index))
+    }
+
+    /// The region of the window which the row at `index` among those which
+    /// are shown occupies.
+    fn row_at(&self, phi: &Phi, index: usize) -> Rectangle {
+        let (win_w, win_h) = phi.output_size();
+        let box_h = self.page_len() as f64 * self.row_h;
+
+        Rectangle {
+            w: self.box_w,
+            h: self.row_h,
+            x: (win_w - self.box_w) / 2.0,
+            y: (win_h - box_h) / 2.0 + self.row_h * index as f64,
+        }
+    }
+
+    /// The region of a slider's row in which its bar is drawn.
+    fn slider_bar(row: Rectangle) -> Rectangle {
+        Rectangle {
+            w: row.w / 2.0 - MARGIN_H * 2.0,
+            h: SLIDER_BAR_H,
+            x: row.x + row.w / 2.0 + MARGIN_H,
+            y: row.y + (row.h - SLIDER_BAR_H) / 2.0,
+        }
+    }
+
+    /// The focusable widget located at the given point of the window, if any.
+    fn widget_at(&self, phi: &Phi, x: f64, y: f64) -> Option<WidgetId> {
+        let point = Rectangle { x, y, w: 0.0, h: 0.0 };
+
+        self.shown().iter().copied()
+            .find(|&id| self.row(phi, id).is_some_and(|row| row.contains(point)))
+            .filter(|&id| self.widgets[id].kind.focusable())
+    }
+
+    /// Activates the widget `id` as if it were clicked at the horizontal
+    /// position `x`.
+    fn click(&mut self, phi: &mut Phi, id: WidgetId, x: f64) -> Option<UiEvent> {
+        match self.widgets[id].kind {
+            WidgetKind::Label => None,
+
+            WidgetKind::Button => Some(UiEvent::Clicked(id)),
+
+            WidgetKind::Toggle(on) => {
+                self.widgets[id].kind = WidgetKind::Toggle(!on);
+                Some(UiEvent::Toggled(id, !on))
+            },
+
+            WidgetKind::Slider { min, max, step, .. } => {
+                // Jump to the value under the cursor, rounded to a step.
+                let bar = Menu::slider_bar(self.row(phi, id)?);
+                let ratio = ((x - bar.x) / bar.w).clamp(0.0, 1.0);
+                let mut value = min + ratio * (max - min);
+                if step > 0.0 {
+                    value = min + ((value - min) / step).round() * step;
+                }
+
+                self.set_slider(id, value.clamp(min, max))
+            },
+        }
+    }
+
+    fn set_slider(&mut self, id: WidgetId, value: f64) -> Option<UiEvent> {
+        if let WidgetKind::Slider { value: ref mut current, .. } = self.widgets[id].kind {
+            if *current != value {
+                *current = value;
+                return Some(UiEvent::Changed(id, value));
+            }
+        }
+
+        None
+    }
+
+    /// Moves the focus to the next (or previous) focusable widget which is
+    /// shown, wrapping around at both ends of the menu.
+    fn move_focus(&mut self, forward: bool) -> Option<UiEvent> {
+        let len = self.visible.len();
+        let focus = self.focus?;
+        let mut index = self.visible.iter().position(|&id| id == focus)?;
+
+        for _ in 0..len {
+            index = if forward { (index + 1) % len } else { (index + len - 1) % len };
+
+            let id = self.visible[index];
+            if self.widgets[id].kind.focusable() {
+                self.focus = Some(id);
+                self.scroll_to_focus();
+                return Some(UiEvent::Focused(id));
+            }
+        }
+
+        None
+    }
+}
+
+/// How far from its center a radial menu places its options.
+const RADIAL_RADIUS: f64 = 70.0;
+
+/// How far the stick must be pushed before a radial menu picks an option.
+const RADIAL_DEADZONE: f64 = 0.5;
+
+/// A ring of options around a point, out of which the player picks one by
+/// pushing the stick, or the directional keys, towards it. Unlike a `Menu`,
+/// it is meant to be opened while holding a button and confirmed by
+/// releasing it.
+pub struct RadialMenu {
+    options: Vec<Text>,
+    selected: Option<usize>,
+}
+
+impl RadialMenu {
+    /// Creates a radial menu whose options are spread evenly clockwise,
+    /// starting from the top.
+    pub fn new(phi: &mut Phi, labels: &[&str]) -> Result<RadialMenu, PhiError> {
+        Ok(RadialMenu {
+            options: labels.iter()
+                .map(|label| Text::new(phi, label, 30.0))
+                .collect::<Result<_, _>>()?,
+            selected: None,
+        })
+    }
+
+    /// The option which the player is currently pointing at, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// The direction of the option `i`, as a unit vector with y pointing
+    /// down.
+    fn direction(&self, i: usize) -> (f64, f64) {
+        let angle = i as f64 / self.options.len() as f64 * 2.0 * ::std::f64::consts::PI;
+        (angle.sin(), -angle.cos())
+    }
+
+    /// Selects the option closest to the direction in which the stick or
+    /// the directional keys are pushed. Letting go keeps the last selection.
+    pub fn update(&mut self, phi: &Phi) {
+        let events = &phi.events;
+        let keys = (
+            events.key_right as i32 as f64 - events.key_left as i32 as f64,
+            events.key_down as i32 as f64 - events.key_up as i32 as f64,
+        );
+
+        let (x, y) =
+            if keys != (0.0, 0.0) { keys }
+            else { events.left_stick };
+
+        if (x * x + y * y).sqrt() < RADIAL_DEADZONE {
+            return;
+        }
+
+        // The option whose direction is the closest, i.e. has the largest
+        // dot product with the stick's.
+        self.selected = (0..self.options.len())
+            .map(|i| {
+                let (dx, dy) = self.direction(i);
+                (i, dx * x + dy * y)
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(i, _)| i);
+    }
+
+    /// Renders the options around `center`, highlighting the selected one.
+    pub fn render(&self, phi: &mut Phi, center: (f64, f64)) {
+        // Render a disc behind the options, so that they read as a whole
+        draw::fill_circle(&mut phi.renderer, center, RADIAL_RADIUS, Color::RGBA(0, 0, 0, 120), true);
+        draw::outline_circle(&mut phi.renderer, center, RADIAL_RADIUS, Color::RGB(140, 30, 140), true);
+
+        for (i, text) in self.options.iter().enumerate() {
+            let (dx, dy) = self.direction(i);
+            let selected = self.selected == Some(i);
+            let sprite = if selected { &text.hover_sprite } else { &text.idle_sprite };
+            let (w, h) = sprite.size();
+            let rect = Rectangle::with_size(w + MARGIN_H * 2.0, h + MARGIN_H)
+                .center_at((center.0 + dx * RADIAL_RADIUS, center.1 + dy * RADIAL_RADIUS));
+
+            draw::fill_rect(&mut phi.renderer, rect,
+                if selected { Color::RGB(140, 30, 140) }
+                else { Color::RGB(70, 15, 70) });
+            phi.renderer.copy_sprite(sprite, Rectangle::with_size(w, h).center_at(rect.center()));
+        }
+    }
+}
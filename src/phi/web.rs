@@ -0,0 +1,134 @@
+//! Runs the game in a browser, in builds for `wasm32-unknown-emscripten`:
+//!
+//!     cargo build --release --target wasm32-unknown-emscripten
+//!
+//! The `.js`, `.wasm` and `.data` files which this produces are then served
+//! along with `web/index.html`.
+//!
+//! The browser never lets a page block, so rather than looping, the game asks
+//! it to call `PhiRunner::step` and `PhiRunner::render` once per frame. The
+//! assets are bundled next to the page by `build.rs` and fetched before the
+//! game starts, and the profiles are kept in the storage of the browser, to
+//! which they are written every few seconds.
+
+use crate::phi::{PhiError, PhiRunner};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+
+/// Where the profiles are kept, which must match the folder in which views
+/// save them, relative to `/`, where the game runs.
+const PROFILES_DIR: &str = "/profiles";
+
+/// The time between two writes of the profiles to the storage of the browser,
+/// in seconds.
+const PERSIST_DELAY: f64 = 5.0;
+
+extern "C" {
+    fn emscripten_set_main_loop_arg(func: extern "C" fn(*mut c_void), arg: *mut c_void, fps: c_int, simulate_infinite_loop: c_int);
+    fn emscripten_cancel_main_loop();
+    fn emscripten_run_script(script: *const c_char);
+    fn emscripten_run_script_int(script: *const c_char) -> c_int;
+}
+
+fn run_script(script: &str) -> c_int {
+    let script = CString::new(script).expect("scripts do not contain NUL bytes");
+    unsafe { emscripten_run_script_int(script.as_ptr()) }
+}
+
+/// Mounts the storage of the browser where the profiles are kept, and starts
+/// reading it. The game must not start before `profiles_ready`.
+fn mount_profiles() {
+    run_script(&format!(
+        "FS.mkdir('{dir}'); FS.mount(IDBFS, {{}}, '{dir}'); Module.profilesReady = 0; \
+         FS.syncfs(true, function () {{ Module.profilesReady = 1; }}); 0",
+        dir = PROFILES_DIR));
+}
+
+fn profiles_ready() -> bool {
+    run_script("Module.profilesReady | 0") != 0
+}
+
+/// Writes what was saved to the profiles since the last time to the storage of
+/// the browser, which would otherwise lose it once the page is closed.
+fn persist() {
+    let script = CString::new("FS.syncfs(false, function () {})").unwrap();
+    unsafe { emscripten_run_script(script.as_ptr()) };
+}
+
+/// The state of the game between two frames.
+enum Page {
+    /// The profiles are being read, after which the runner is created.
+    Loading(Option<Box<dyn FnOnce() -> Result<PhiRunner, PhiError>>>),
+
+    /// The game is running.
+    Running {
+        runner: Box<PhiRunner>,
+
+        // When the last frame started, in milliseconds, and the time since the
+        // profiles were last persisted, in seconds.
+        before: u32,
+        since_persist: f64,
+    },
+}
+
+extern "C" fn frame(arg: *mut c_void) {
+    //? The page lives as long as the main loop, which only ever runs on the
+    //? thread that registered it.
+    let page = unsafe { &mut *(arg as *mut Page) };
+    let result = match *page {
+        Page::Loading(ref mut start) => {
+            if !profiles_ready() {
+                return;
+            }
+            start.take().expect("the game starts once")().map(|runner| {
+                let before = runner.timer.ticks();
+                *page = Page::Running { runner: Box::new(runner), before, since_persist: 0.0 };
+            })
+        },
+
+        Page::Running { ref mut runner, ref mut before, ref mut since_persist } => {
+            let now = runner.timer.ticks();
+            let elapsed = (now - *before) as f64 / 1_000.0;
+            *before = now;
+
+            *since_persist += elapsed;
+            if *since_persist >= PERSIST_DELAY {
+                *since_persist = 0.0;
+                persist();
+            }
+
+            match runner.step(elapsed) {
+                Ok(true) => runner.render(),
+                Ok(false) => {
+                    persist();
+                    unsafe { emscripten_cancel_main_loop() };
+                    Ok(())
+                },
+                Err(err) => Err(err),
+            }
+        },
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        persist();
+        unsafe { emscripten_cancel_main_loop() };
+    }
+}
+
+/// Hands the game over to the browser, which runs it with the runner built by
+/// `start` once the profiles were read. Never returns.
+pub fn run<F>(start: F) -> !
+where
+    F: FnOnce() -> Result<PhiRunner, PhiError> + 'static
+{
+    mount_profiles();
+
+    let page = Box::new(Page::Loading(Some(Box::new(start))));
+
+    //? A frame rate of 0 lets the browser pick it, i.e. `requestAnimationFrame`.
+    //? Simulating an infinite loop throws out of this function, so that the
+    //? page is never dropped.
+    unsafe { emscripten_set_main_loop_arg(frame, Box::into_raw(page) as *mut c_void, 0, 1) };
+    unreachable!("the main loop never returns")
+}
@@ -0,0 +1,325 @@
+//! Goals which players unlock once, across every run, and the toasts which
+//! tell them when they did.
+//!
+//! The gameplay code reports what happens through `Achievements::notify`,
+//! which keeps track of whatever progress the goals need and unlocks them.
+
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{draw, CopySprite, Sprite, Tint};
+use crate::phi::storage::Storage;
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::shared::{install_key, profile_path, ParallaxBackground, ACHIEVEMENTS_FILE, STARFIELD_PATH};
+use sdl2::pixels::Color;
+use std::io;
+
+/// The number of asteroids to destroy, across every run, for `Centurion`.
+const CENTURION_ASTEROIDS: u64 = 100;
+
+/// The combo multiplier to reach for `ChainReaction`.
+const CHAIN_MULTIPLIER: u64 = 5;
+
+/// How many rows of the list are shown at once.
+const MAX_ROWS: usize = 11;
+
+const TOAST_FONT_PATH: &str = "assets/belligerent.ttf";
+const TOAST_FONT_SIZE: i32 = 20;
+
+/// How long a toast is shown, how long it takes to slide in, and how long to
+/// fade out, in seconds.
+const TOAST_TIME: f64 = 3.5;
+const TOAST_SLIDE: f64 = 0.3;
+const TOAST_FADE: f64 = 0.6;
+
+/// The space around the text of a toast, the space between toasts and the
+/// top right corner of the screen, and between stacked toasts, in pixels.
+const TOAST_PADDING: f64 = 10.0;
+const TOAST_MARGIN: (f64, f64) = (10.0, 80.0);
+const TOAST_SPACING: f64 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    FirstBlood,
+    Centurion,
+    Pacifist,
+    Untouchable,
+    ChainReaction,
+    GiantSlayer,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstBlood,
+        Achievement::Centurion,
+        Achievement::Pacifist,
+        Achievement::Untouchable,
+        Achievement::ChainReaction,
+        Achievement::GiantSlayer,
+    ];
+
+    /// The key under which the achievement is saved.
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "first_blood",
+            Achievement::Centurion => "centurion",
+            Achievement::Pacifist => "pacifist",
+            Achievement::Untouchable => "untouchable",
+            Achievement::ChainReaction => "chain_reaction",
+            Achievement::GiantSlayer => "giant_slayer",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "First Blood",
+            Achievement::Centurion => "Centurion",
+            Achievement::Pacifist => "Pacifist",
+            Achievement::Untouchable => "Untouchable",
+            Achievement::ChainReaction => "Chain Reaction",
+            Achievement::GiantSlayer => "Giant Slayer",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "Shoot something down",
+            Achievement::Centurion => "Destroy 100 asteroids",
+            Achievement::Pacifist => "Finish a wave without firing",
+            Achievement::Untouchable => "Finish a wave without being hit",
+            Achievement::ChainReaction => "Reach a combo of x5",
+            Achievement::GiantSlayer => "Defeat the boss",
+        }
+    }
+}
+
+/// Something which happened during a run, as far as achievements go.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AchievementEvent {
+    AsteroidDestroyed,
+    EnemyKilled,
+    BossDefeated,
+    ShotFired,
+    ShipHit,
+
+    /// Wave number `n`, counted from 1, started, which means that the one
+    /// before it, if any, was finished.
+    WaveStarted(u32),
+
+    /// The combo multiplier went up to the given value.
+    Combo(u64),
+}
+
+/// The achievements of a player, and their progress towards those which need
+/// some. The file is signed, like the lifetime stats, so that achievements
+/// may not be unlocked by editing it.
+pub struct Achievements {
+    storage: Storage,
+
+    // Whether the ship fired, or was hit, during the current wave, if a wave
+    // started since the run did.
+    fired: Option<bool>,
+    hit: Option<bool>,
+
+    // Those unlocked since `take_unlocked` was last called.
+    unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub fn load(profile: &str) -> Achievements {
+        Achievements {
+            storage: Storage::load_signed(profile_path(profile, ACHIEVEMENTS_FILE), install_key()),
+            fired: None,
+            hit: None,
+            unlocked: vec![],
+        }
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.storage.get(achievement.key()) == Some("unlocked")
+    }
+
+    fn unlock(&mut self, achievement: Achievement) {
+        if !self.is_unlocked(achievement) {
+            self.storage.set(achievement.key(), "unlocked");
+            self.unlocked.push(achievement);
+        }
+    }
+
+    fn asteroids(&self) -> u64 {
+        self.storage.get("asteroids").and_then(|value| value.parse().ok()).unwrap_or(0)
+    }
+
+    pub fn notify(&mut self, event: AchievementEvent) {
+        match event {
+            AchievementEvent::AsteroidDestroyed => {
+                let asteroids = self.asteroids() + 1;
+                self.storage.set("asteroids", &asteroids.to_string());
+
+                self.unlock(Achievement::FirstBlood);
+                if asteroids >= CENTURION_ASTEROIDS {
+                    self.unlock(Achievement::Centurion);
+                }
+            },
+
+            AchievementEvent::EnemyKilled => self.unlock(Achievement::FirstBlood),
+
+            AchievementEvent::BossDefeated => {
+                self.unlock(Achievement::FirstBlood);
+                self.unlock(Achievement::GiantSlayer);
+            },
+
+            AchievementEvent::ShotFired => self.fired = self.fired.map(|_| true),
+            AchievementEvent::ShipHit => self.hit = self.hit.map(|_| true),
+
+            AchievementEvent::WaveStarted(wave) => {
+                if wave > 1 {
+                    if self.fired == Some(false) {
+                        self.unlock(Achievement::Pacifist);
+                    }
+                    if self.hit == Some(false) {
+                        self.unlock(Achievement::Untouchable);
+                    }
+                }
+
+                self.fired = Some(false);
+                self.hit = Some(false);
+            },
+
+            AchievementEvent::Combo(multiplier) => {
+                if multiplier >= CHAIN_MULTIPLIER {
+                    self.unlock(Achievement::ChainReaction);
+                }
+            },
+        }
+    }
+
+    /// The achievements unlocked since this was last called.
+    pub fn take_unlocked(&mut self) -> Vec<Achievement> {
+        ::std::mem::take(&mut self.unlocked)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.storage.save()
+    }
+}
+
+struct Toast {
+    sprite: Sprite,
+
+    // For how long it was shown, in seconds.
+    age: f64,
+}
+
+/// Tells the player which achievements they just unlocked, stacked in the top
+/// right corner of the screen.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn new() -> Toasts {
+        Toasts::default()
+    }
+
+    pub fn push(&mut self, phi: &mut Phi, achievement: Achievement) -> Result<(), PhiError> {
+        let text = format!("Achievement: {}", achievement.title());
+        let sprite = phi.ttf_str_sprite(&text, TOAST_FONT_PATH, TOAST_FONT_SIZE, Color::RGB(255, 220, 90))?;
+        self.toasts.push(Toast { sprite, age: 0.0 });
+        Ok(())
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for toast in &mut self.toasts {
+            toast.age += dt;
+        }
+
+        self.toasts.retain(|toast| toast.age < TOAST_TIME);
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let (screen_w, _) = phi.output_size();
+        let mut y = TOAST_MARGIN.1;
+
+        for toast in &self.toasts {
+            let (w, h) = toast.sprite.size();
+            let (box_w, box_h) = (w + 2.0 * TOAST_PADDING, h + 2.0 * TOAST_PADDING);
+
+            // Slides in from the right edge, then fades out.
+            let slide = (toast.age / TOAST_SLIDE).min(1.0);
+            let alpha = ((TOAST_TIME - toast.age) / TOAST_FADE).clamp(0.0, 1.0);
+            let x = screen_w - (box_w + TOAST_MARGIN.0) * slide;
+
+            let frame = Rectangle { x, y, w: box_w, h: box_h };
+            draw::fill_rect(&mut phi.renderer, frame, Color::RGBA(20, 20, 40, (180.0 * alpha) as u8));
+            draw::outline_rect(&mut phi.renderer, frame, Color::RGBA(255, 220, 90, (255.0 * alpha) as u8));
+
+            let dest = Rectangle { x: x + TOAST_PADDING, y: y + TOAST_PADDING, w, h };
+            phi.renderer.copy_sprite_tinted(&toast.sprite, dest, Tint::alpha(alpha));
+
+            y += box_h + TOAST_SPACING;
+        }
+    }
+}
+
+/// Lists every achievement, and which of them a player unlocked.
+pub struct AchievementsView {
+    menu: Menu,
+    back: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl AchievementsView {
+    pub fn new(phi: &mut Phi, profile: &str) -> Result<AchievementsView, PhiError> {
+        let achievements = Achievements::load(profile);
+        let mut menu = Menu::scrolling(640.0, 36.0, MAX_ROWS);
+
+        let unlocked = Achievement::ALL.iter().filter(|&&a| achievements.is_unlocked(a)).count();
+        menu.add(phi, &format!("Achievements ({}/{})", unlocked, Achievement::ALL.len()), WidgetKind::Label)?;
+
+        for &achievement in &Achievement::ALL {
+            let mark = if achievements.is_unlocked(achievement) { "[x]" } else { "[ ]" };
+            menu.add(phi, &format!("{} {}", mark, achievement.title()), WidgetKind::Label)?;
+            menu.add(phi, &format!("    {}", achievement.description()), WidgetKind::Label)?;
+        }
+
+        Ok(AchievementsView {
+            back: menu.add(phi, "Back", WidgetKind::Button)?,
+            menu,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+}
+
+impl View for AchievementsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        let back = phi.events.now.key_escape == Some(true) ||
+            self.menu.update(phi) == Some(UiEvent::Clicked(self.back));
+        if back {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::phi::storage::Storage;
+use crate::phi::{Binding, KeyBindings};
+use crate::views::shared::profile_path;
+use sdl2::keyboard::Keycode;
+use std::io;
+
+/// A named set of bindings which the player may start from.
+pub struct BindingProfile {
+    /// The name under which the profile is saved.
+    pub id: &'static str,
+
+    /// The name under which the profile is shown.
+    pub name: &'static str,
+
+    pub bindings: fn() -> KeyBindings,
+}
+
+pub const PROFILES: &[BindingProfile] = &[
+    BindingProfile { id: "default", name: "Default", bindings: default },
+    BindingProfile { id: "lefty", name: "Lefty", bindings: lefty },
+    BindingProfile { id: "gamepad-only", name: "Gamepad only", bindings: gamepad_only },
+];
+
+fn both(primary: Keycode, alternate: Keycode) -> Binding {
+    Binding { primary: Some(primary), alternate: Some(alternate) }
+}
+
+/// Flies with the arrow keys, or WASD, and fires with the space bar.
+fn default() -> KeyBindings {
+    use sdl2::keyboard::Keycode::*;
+
+    KeyBindings {
+        key_up: both(Up, W),
+        key_down: both(Down, S),
+        key_left: both(Left, A),
+        key_right: both(Right, D),
+        ..KeyBindings::new()
+    }
+}
+
+/// Flies with the left hand, and fires and switches cannons with the right
+/// one.
+fn lefty() -> KeyBindings {
+    use sdl2::keyboard::Keycode::*;
+
+    KeyBindings {
+        key_up: both(W, Up),
+        key_down: both(S, Down),
+        key_left: both(A, Left),
+        key_right: both(D, Right),
+        key_space: both(RCtrl, Space),
+        key_1: both(Num7, Num1),
+        key_2: both(Num8, Num2),
+        key_3: both(Num9, Num3),
+        key_4: both(Num0, Num4),
+        key_cannon: both(RShift, Tab),
+        key_bomb: both(RAlt, B),
+        ..KeyBindings::new()
+    }
+}
+
+/// Leaves the keys which play the game unbound, so that nothing happens when
+/// they are hit by mistake. Menus may still be used with the mouse.
+fn gamepad_only() -> KeyBindings {
+    KeyBindings {
+        key_up: Binding::none(),
+        key_down: Binding::none(),
+        key_left: Binding::none(),
+        key_right: Binding::none(),
+        key_space: Binding::none(),
+        key_1: Binding::none(),
+        key_2: Binding::none(),
+        key_3: Binding::none(),
+        key_4: Binding::none(),
+        key_cannon: Binding::none(),
+        key_bomb: Binding::none(),
+        ..KeyBindings::new()
+    }
+}
+
+/// The bindings of a player: the profile they started from, along with
+/// whatever they changed since.
+pub struct PlayerBindings {
+    storage: Storage,
+
+    /// The index of the profile in `PROFILES`.
+    pub profile: usize,
+    pub bindings: KeyBindings,
+}
+
+impl PlayerBindings {
+    pub fn load(profile: &str) -> PlayerBindings {
+        let storage = Storage::load(profile_path(profile, "bindings.cfg"));
+
+        let profile = storage.get("profile")
+            .and_then(|id| PROFILES.iter().position(|p| p.id == id))
+            .unwrap_or(0);
+        let mut bindings = (PROFILES[profile].bindings)();
+        bindings.load(&storage);
+
+        PlayerBindings { storage, profile, bindings }
+    }
+
+    /// Starts over from the bindings of the profile at `index` in `PROFILES`.
+    pub fn switch_to(&mut self, index: usize) {
+        self.profile = index;
+        self.bindings = (PROFILES[index].bindings)();
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        self.storage.set("profile", PROFILES[self.profile].id);
+        self.bindings.save(&mut self.storage);
+        self.storage.save()
+    }
+}
@@ -0,0 +1,379 @@
+//! A large ship which comes once every few waves, and attacks differently as
+//! it loses health, until it breaks apart in a chain of explosions.
+
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::{EntityName, Rectangle, Vec2};
+use crate::phi::gfx::{draw, Camera, CopySprite, Sprite, Tint};
+use crate::views::bullets::{spawn_aimed_bullet, BulletPool};
+use crate::views::health::Health;
+use crate::views::patterns::{BulletPattern, BulletPatterns, PatternPlayer};
+use crate::views::scripts::{BehaviorScript, ScriptInputs};
+use rand::Rng;
+use sdl2::pixels::Color;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+const NAME: &str = "Dreadnought";
+
+/// The damage which the boss takes before it is destroyed, and the points
+/// which it is worth.
+const BOSS_HEALTH: f64 = 60.0;
+pub const BOSS_SCORE: u64 = 2_000;
+
+/// The mass of the boss, relative to that of the largest asteroids, so that
+/// the ship bounces off it rather than pushes it.
+const BOSS_MASS: f64 = 20.0;
+
+/// The time after running into the ship during which the boss may not hurt it
+/// again, so that staying in contact does not cost a life every tick.
+const RAM_COOLDOWN: f64 = 1.0;
+
+/// The parts which the boss is made of, as the offset of their center from
+/// that of the boss, in pixels, and the factor by which their sprite is
+/// scaled. The first one is the hull, which the others escort.
+const PARTS: [((f64, f64), f64); 3] = [
+    ((0.0, 0.0), 3.0),
+    ((50.0, -75.0), 1.5),
+    ((50.0, 75.0), 1.5),
+];
+
+/// How far from the right of the screen the boss hovers, and how fast it
+/// gets there, in pixels per second.
+const HOVER_X: f64 = 160.0;
+const ENTER_SPEED: f64 = 90.0;
+
+/// How fast the boss follows the ship up and down while it hovers.
+const FOLLOW_SPEED: f64 = 50.0;
+
+/// How fast the boss charges at the ship, and how fast it comes back.
+const CHARGE_SPEED: f64 = 420.0;
+const RETURN_SPEED: f64 = 180.0;
+
+/// How far in front of the ship a charge stops, in pixels.
+const CHARGE_STOP: f64 = 120.0;
+
+/// For how long the boss breaks apart once destroyed, in seconds.
+const DEATH_TIME: f64 = 2.5;
+const DEATH_EXPLOSION_DELAY: f64 = 0.15;
+
+const TINT: Color = Color::RGB(255, 110, 110);
+
+// The health bar, across the top of the screen.
+const BAR_MARGIN: f64 = 60.0;
+const BAR_Y: f64 = 50.0;
+const BAR_H: f64 = 10.0;
+
+/// How the boss attacks, which depends on how much health it has left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossPhase {
+    /// Fans of bullets aimed at the ship.
+    Spread,
+
+    /// Narrower fans, and charges at the ship now and then.
+    Charge,
+
+    /// Rings of bullets in every direction, and more frequent charges.
+    Frenzy,
+}
+
+impl BossPhase {
+    const ALL: [BossPhase; 3] = [BossPhase::Spread, BossPhase::Charge, BossPhase::Frenzy];
+
+    /// The name of the bullet pattern which the boss fires, see `patterns`.
+    fn pattern_name(self) -> &'static str {
+        match self {
+            BossPhase::Spread => "boss-spread",
+            BossPhase::Charge => "boss-charge",
+            BossPhase::Frenzy => "boss-frenzy",
+        }
+    }
+
+    /// The time between the starts of two patterns, in seconds.
+    fn fire_delay(self) -> f64 {
+        match self {
+            BossPhase::Spread => 1.4,
+            BossPhase::Charge => 1.8,
+            BossPhase::Frenzy => 1.2,
+        }
+    }
+
+    /// The time between two charges, in seconds, if the boss charges at all.
+    fn charge_delay(self) -> Option<f64> {
+        match self {
+            BossPhase::Spread => None,
+            BossPhase::Charge => Some(4.0),
+            BossPhase::Frenzy => Some(3.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BossState {
+    /// Flying in from the right of the screen.
+    Entering,
+    Hovering,
+
+    /// Rushing to the given distance from the left of the screen, then
+    /// coming back.
+    Charging(f64),
+    Returning,
+
+    /// Breaking apart, for the given time so far, in seconds.
+    Dying(f64),
+}
+
+/// What the boss asks the game to do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossEvent {
+    /// Something blew up at the given point while the boss breaks apart.
+    Explosion((f64, f64)),
+
+    /// The boss is gone.
+    Defeated,
+}
+
+#[derive(Clone)]
+pub struct Boss {
+    pub name: EntityName,
+    sprite: Sprite,
+    label: Sprite,
+
+    // The center of the hull, now and before the last tick.
+    pos: Vec2,
+    prev_pos: Vec2,
+
+    health: Health,
+    state: BossState,
+
+    // The time since the boss appeared, in seconds.
+    time: f64,
+
+    // The bullet pattern of every phase, in the order of `BossPhase::ALL`,
+    // and the one being fired, if any.
+    patterns: Vec<BulletPattern>,
+    volley: Option<PatternPlayer>,
+
+    // Time left before the next volley, the next charge, and the time at
+    // which the boss may hurt the ship again, in seconds.
+    fire_cooldown: f64,
+    charge_cooldown: f64,
+    ram_cooldown: f64,
+}
+
+impl Boss {
+    /// A boss made of copies of `sprite`, which enters from the right of a
+    /// screen of size `(w, h)`, and fires bullets in the `patterns` named
+    /// after its phases.
+    pub fn new(phi: &mut Phi, sprite: Sprite, (w, h): (f64, f64), patterns: &BulletPatterns) -> Result<Boss, PhiError> {
+        let pos = Vec2::new(w + sprite.size().0 * PARTS[0].1, h / 2.0);
+        let patterns = BossPhase::ALL.iter()
+            .map(|phase| patterns.get(phase.pattern_name()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Boss {
+            name: phi.ids.name("boss"),
+            label: phi.ttf_str_sprite(NAME, FONT_PATH, 20, Color::RGB(255, 255, 255))?,
+            sprite,
+            pos,
+            prev_pos: pos,
+            health: Health::new(BOSS_HEALTH),
+            state: BossState::Entering,
+            time: 0.0,
+            patterns,
+            volley: None,
+            fire_cooldown: 2.0,
+            charge_cooldown: 0.0,
+            ram_cooldown: 0.0,
+        })
+    }
+
+    pub fn phase(&self) -> BossPhase {
+        let left = self.health.fraction();
+        if left > 2.0 / 3.0 { BossPhase::Spread }
+        else if left > 1.0 / 3.0 { BossPhase::Charge }
+        else { BossPhase::Frenzy }
+    }
+
+    fn part_rects(&self, pos: Vec2) -> Vec<Rectangle> {
+        let (w, h) = self.sprite.size();
+        PARTS.iter()
+            .map(|&(offset, scale)| Rectangle::with_size(w * scale, h * scale).center_at((pos + offset.into()).into()))
+            .collect()
+    }
+
+    /// The parts which bullets may hit and the ship may collide with, which
+    /// is none of them once the boss is breaking apart.
+    pub fn parts(&self) -> Vec<Rectangle> {
+        match self.state {
+            BossState::Dying(_) => vec![],
+            _ => self.part_rects(self.pos),
+        }
+    }
+
+    /// The center, velocity and mass of the hull, e.g. for the ship to be
+    /// knocked back when it runs into it.
+    pub fn body(&self) -> (Vec2, Vec2, f64) {
+        let vel = match self.state {
+            BossState::Charging(_) => Vec2::new(-CHARGE_SPEED, 0.0),
+            _ => Vec2::ZERO,
+        };
+
+        (self.pos, vel, BOSS_MASS)
+    }
+
+    /// Whether running into the ship hurts it, which it only does once in a
+    /// while.
+    pub fn ram(&mut self) -> bool {
+        if self.ram_cooldown > 0.0 {
+            return false;
+        }
+
+        self.ram_cooldown = RAM_COOLDOWN;
+        true
+    }
+
+    /// Takes a hit which deals `damage`. Returns whether it destroyed the
+    /// boss, which then starts breaking apart.
+    pub fn hit(&mut self, damage: f64) -> bool {
+        if matches!(self.state, BossState::Dying(_)) {
+            return false;
+        }
+
+        if self.health.damage(damage) {
+            self.state = BossState::Dying(0.0);
+            return true;
+        }
+
+        false
+    }
+
+    /// Moves the boss by `dt` seconds, between the heights `top` and
+    /// `bottom`, firing at `target` into `pool` in the way of its phase, and
+    /// of its `script` while it hovers, if any.
+    pub fn update(&mut self, phi: &mut Phi, dt: f64, target: (f64, f64), (top, bottom): (f64, f64), pool: &mut BulletPool, script: Option<&BehaviorScript>) -> Option<BossEvent> {
+        self.prev_pos = self.pos;
+        self.time += dt;
+        self.health.update(dt);
+        self.ram_cooldown -= dt;
+
+        let (w, _) = phi.output_size();
+        let hover_x = w - HOVER_X;
+        let phase = self.phase();
+
+        // Fire from the front of the hull, wherever it goes.
+        let muzzle = (self.part_rects(self.pos)[0].x, self.pos.y);
+        if let Some(ref mut volley) = self.volley {
+            volley.update(dt, &mut phi.ids, muzzle, target, pool);
+        }
+        if self.volley.is_some_and(|volley| volley.is_over()) {
+            self.volley = None;
+        }
+
+        match self.state {
+            BossState::Entering => {
+                self.pos.x = (self.pos.x - ENTER_SPEED * dt).max(hover_x);
+                if self.pos.x <= hover_x {
+                    self.state = BossState::Hovering;
+                    self.charge_cooldown = phase.charge_delay().unwrap_or(0.0);
+                }
+                return None;
+            },
+
+            BossState::Hovering => {
+                // Follow the ship up and down, without leaving the screen.
+                let max_dy = FOLLOW_SPEED * dt;
+                let (_, hull_h) = self.sprite.size();
+                let margin = hull_h * PARTS[0].1 / 2.0 + 75.0;
+                self.pos.y = (self.pos.y + (target.1 - self.pos.y).clamp(-max_dy, max_dy))
+                    .clamp(top + margin, (bottom - margin).max(top + margin));
+
+                if let Some(delay) = phase.charge_delay() {
+                    self.charge_cooldown -= dt;
+                    if self.charge_cooldown <= 0.0 {
+                        self.charge_cooldown = delay;
+                        self.state = BossState::Charging(target.0 + CHARGE_STOP);
+                        return None;
+                    }
+                }
+
+                if let Some(script) = script {
+                    let inputs = ScriptInputs { pos: muzzle, player: target, time: self.time };
+                    for (angle, speed) in script.fire(inputs, dt) {
+                        let towards = (muzzle.0 + angle.cos(), muzzle.1 + angle.sin());
+                        spawn_aimed_bullet(&mut phi.ids, muzzle, towards, speed, pool);
+                    }
+                }
+
+                self.fire_cooldown -= dt;
+                if self.fire_cooldown <= 0.0 {
+                    self.fire_cooldown = phase.fire_delay();
+                    self.volley = Some(PatternPlayer::new(self.patterns[phase as usize]));
+                }
+            },
+
+            BossState::Charging(stop_x) => {
+                self.pos.x -= CHARGE_SPEED * dt;
+                if self.pos.x <= stop_x {
+                    self.state = BossState::Returning;
+                }
+            },
+
+            BossState::Returning => {
+                self.pos.x = (self.pos.x + RETURN_SPEED * dt).min(hover_x);
+                if self.pos.x >= hover_x {
+                    self.state = BossState::Hovering;
+                }
+            },
+
+            BossState::Dying(time) => {
+                let time = time + dt;
+                self.state = BossState::Dying(time);
+
+                self.volley = None;
+                if time >= DEATH_TIME {
+                    return Some(BossEvent::Defeated);
+                }
+
+                // Blow up somewhere on one of the parts every now and then.
+                let due = (time / DEATH_EXPLOSION_DELAY).floor() > ((time - dt) / DEATH_EXPLOSION_DELAY).floor();
+                if due {
+                    let parts = self.part_rects(self.pos);
+                    let part = parts[phi.rng().gen_range(0..parts.len())];
+                    let at = (part.x + phi.rng().gen::<f64>() * part.w, part.y + phi.rng().gen::<f64>() * part.h);
+                    return Some(BossEvent::Explosion(at));
+                }
+            },
+        }
+
+        None
+    }
+
+    pub fn render(&self, phi: &mut Phi, camera: &Camera, alpha: f64) {
+        let pos = self.prev_pos + (self.pos - self.prev_pos) * alpha;
+
+        // Flash white when hit, and flicker while breaking apart.
+        let tint = match self.state {
+            BossState::Dying(time) => Tint { alpha: if ((time * 20.0) as u32).is_multiple_of(2) { 255 } else { 120 }, ..Tint::color(TINT) },
+            _ => self.health.tinted(Tint::color(TINT)),
+        };
+
+        // The escorts are drawn first, behind the hull.
+        for rect in self.part_rects(pos).into_iter().rev() {
+            phi.renderer.copy_sprite_tinted(&self.sprite, camera.view(rect), tint);
+        }
+    }
+
+    /// Draws the name of the boss and how much health it has left across the
+    /// top of the screen.
+    pub fn render_health_bar(&self, phi: &mut Phi) {
+        let (win_w, _) = phi.output_size();
+        let bar = Rectangle { x: BAR_MARGIN, y: BAR_Y, w: win_w - 2.0 * BAR_MARGIN, h: BAR_H };
+        let left = self.health.fraction();
+
+        let (w, h) = self.label.size();
+        phi.renderer.copy_sprite(&self.label, Rectangle { x: bar.x, y: bar.y - h - 2.0, w, h });
+
+        draw::fill_rect(&mut phi.renderer, bar, Color::RGBA(0, 0, 0, 160));
+        draw::fill_rect(&mut phi.renderer, Rectangle { w: bar.w * left, ..bar }, Color::RGB(220, 50, 50));
+        draw::outline_rect(&mut phi.renderer, bar, Color::RGB(255, 255, 255));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::phi::Phi;
-use crate::phi::data::Rectangle;
+use crate::phi::data::{reflect, EntityName, IdAllocator, MaybeAlive, Rectangle, Segment, Shape, Vec2};
+use crate::phi::gfx::{draw, Camera, ParticleConfig, ParticleEmitter};
 use sdl2::pixels::Color;
+use std::f64::consts::PI;
 
 //? The velocity shared by all bullets, in pixels per second.
 const BULLET_SPEED: f64 = 240.0;
@@ -9,11 +11,38 @@ const BULLET_SPEED: f64 = 240.0;
 const BULLET_W: f64 = 8.0;
 const BULLET_H: f64 = 4.0;
 
-struct RectBullet {
+// Constants about the homing missiles
+const MISSILE_SPEED: f64 = 200.0;
+const MISSILE_TURN_RATE: f64 = 3.0;
+const MISSILE_LIFETIME: f64 = 4.0;
+const MISSILE_SIDE: f64 = 6.0;
+
+// The damage dealt by the bullets of the slower cannons, which make up for
+// firing less often. Other bullets deal 1.
+const DIVERGENT_DAMAGE: f64 = 1.5;
+const HOMING_DAMAGE: f64 = 3.0;
+
+const MISSILE_SMOKE: ParticleConfig = ParticleConfig {
+    rate: 40.0,
+    lifetime: 0.5,
+    angle: 0.0,
+    spread: PI,
+    speed: (5.0, 20.0),
+    size: 3.0,
+    start_color: Color::RGBA(200, 200, 200, 160),
+    end_color: Color::RGBA(120, 120, 120, 0),
+};
+
+#[derive(Clone)]
+pub struct RectBullet {
+    name: EntityName,
     rect: Rectangle,
 }
 
-struct SineBullet {
+#[derive(Clone)]
+pub struct SineBullet {
+    name: EntityName,
+
     //? Notice that the bounding box isn't stored directly. This means that
     //? we do not keep useless innformation. It also implies that we must compute
     //? the `sin` function every time we attempt to get the bounding box.
@@ -26,7 +55,9 @@ struct SineBullet {
 
 /// Bullet which follows a vertical trajectory given by:
 ///     a * ((t / b)^3 - (t / b)^2)
-struct DevergentBullet {
+#[derive(Clone)]
+pub struct DevergentBullet {
+    name: EntityName,
     pos_x : f64,
     origin_y : f64,
     a: f64,
@@ -34,61 +65,101 @@ struct DevergentBullet {
     total_time: f64,
 }
 
+/// Missile which steers towards the closest target, turning by at most
+/// `MISSILE_TURN_RATE` radians per second, and leaves a trail of smoke.
+#[derive(Clone)]
+pub struct HomingMissile {
+    name: EntityName,
+    pos: Vec2,
+
+    // The direction in which the missile is heading, in radians.
+    heading: f64,
+    total_time: f64,
+    smoke: ParticleEmitter,
+}
+
+/// Bullet fired by an enemy, which travels in a straight line towards
+/// wherever the player's ship was when it was fired.
+#[derive(Clone)]
+pub struct AimedBullet {
+    name: EntityName,
+    rect: Rectangle,
+    vel: Vec2,
+}
+
 pub trait Bullet: {
-    /// Update the bullet.
+    /// Update the bullet in place.
     /// If the bullet should be destroyed, e.g. because it has left the screen
-    /// then return `None`.
-    /// Otherwise, return `Some(update_bullet)`
-    /// 
-    /// Notice how we use `Box<Self> as the type of `self`. This means: keep
-    /// this data behind a pointer, but `move` the pointer. You should note that
-    /// we are not copying the value: we are only copying the _address_ at
-    /// which the value is stored in memory, which has a negligible cost. We can
-    /// do this because Rust will automatically free the memory once the `Box` that
-    /// contains it is itself destroyed.
-    fn update(self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>>;
+    /// then return `false`.
+    /// Otherwise, return `true`
+    ///
+    /// Notice how we take `&mut self`: bullets are kept in a `BulletPool`,
+    /// which reuses the slots of dead bullets rather than allocating some
+    /// memory for every shot, so they are updated where they are stored.
+    ///
+    /// `targets` are the bounding boxes of whatever the bullet may hit, for
+    /// those bullets which aim at something.
+    fn update(&mut self, phi: &mut Phi, dt: f64, targets: &[Rectangle]) -> bool;
+
+    /// Bounce the bullet off `wall`, which it just went through, by moving it
+    /// to where it would be had it bounced. Bullets which follow a curve are
+    /// mirrored across the wall, which is only right for horizontal walls.
+    fn ricochet(&mut self, wall: Segment);
 
     /// Render the bullet to the screen.
     /// Here, we take an immutable reference to the bullet, because we do not
     /// need to change its value to draw it, This is the same as before.
-    fn render(&self, phi: &mut Phi);
+    fn render(&self, phi: &mut Phi, camera: &Camera);
 
     /// Return the bullet's bounding box.
     fn rect(&self) -> Rectangle;
+
+    /// Return the name under which the bullet appears in debugging output.
+    fn name(&self) -> EntityName;
+
+    /// Return the damage which the bullet deals to whatever it hits. Heavier
+    /// cannons fire bullets which deal more.
+    fn damage(&self) -> f64 {
+        1.0
+    }
 }
 
 impl Bullet for RectBullet {
     /// Update the bullet.
     /// If the bullet should be destroyed, e.g. because it has left the screen
-    /// then return `None`.
-    /// Otherwise, return `Some(update_bullet)`
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+    /// then return `false`.
+    /// Otherwise, return `true`
+    fn update(&mut self, phi: &mut Phi, dt: f64, _: &[Rectangle]) -> bool {
         let (w, _) = phi.output_size();
         self.rect.x += BULLET_SPEED * dt;
 
         // If the bullet has left the screen then delete it.
-        if self.rect.x > w {
-            None
-        } else {
-            Some(self)
-        }
+        let off_screen = self.rect.x > w;
+        !off_screen
     }
 
     /// Render the bullet to the screen.
-    fn render(&self, phi: &mut Phi) {
+    fn ricochet(&mut self, wall: Segment) {
+        self.rect = self.rect.center_at(wall.mirror(self.rect.center()));
+    }
+
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
         // We will render this kind of bullet in yellow
-        phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect.to_sdl()).unwrap();
+        draw::fill_rect(&mut phi.renderer, camera.view(self.rect), Color::RGB(230, 230, 30));
     }
 
     /// Return the bullet's bounding box.
     fn rect(&self) -> Rectangle {
         self.rect
     }
+
+    fn name(&self) -> EntityName {
+        self.name
+    }
 }
 
 impl Bullet for SineBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+    fn update(&mut self, phi: &mut Phi, dt: f64, _: &[Rectangle]) -> bool {
         //? We store the total time...
         self.total_time += dt;
 
@@ -98,16 +169,17 @@ impl Bullet for SineBullet {
         // If the bullet has left the screen, then delete it.
         let (w, _) = phi.output_size();
 
-        if self.rect().x > w{
-            None
-        } else {
-            Some(self)
-        }
+        let off_screen = self.rect().x > w;
+        !off_screen
+    }
+
+    fn ricochet(&mut self, wall: Segment) {
+        self.origin_y = wall.mirror((self.pos_x, self.origin_y)).1;
+        self.amplitude = -self.amplitude;
     }
 
-    fn render(&self, phi: &mut Phi) {
-        phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl()).unwrap();
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
+        draw::fill_rect(&mut phi.renderer, camera.view(self.rect()), Color::RGB(230, 230, 30));
     }
 
     fn rect(&self) -> Rectangle {
@@ -120,10 +192,14 @@ impl Bullet for SineBullet {
             h: BULLET_H,
         }
     }
+
+    fn name(&self) -> EntityName {
+        self.name
+    }
 }
 
 impl Bullet for DevergentBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>>{
+    fn update(&mut self, phi: &mut Phi, dt: f64, _: &[Rectangle]) -> bool {
         self.total_time += dt;
         self.pos_x += BULLET_SPEED * dt;
 
@@ -131,18 +207,19 @@ impl Bullet for DevergentBullet {
         let (w, h) = phi.output_size();
         let rect = self.rect();
 
-        if rect.x > w || rect.x < 0.0 ||
-           rect.y > h || rect.y < 0.0 {
-            None
-        } else {
-            Some(self)
-        }
+        let off_screen = rect.x > w || rect.x < 0.0 ||
+            rect.y > h || rect.y < 0.0;
+        !off_screen
     }
 
-    fn render(&self, phi: &mut Phi) {
+    fn ricochet(&mut self, wall: Segment) {
+        self.origin_y = wall.mirror((self.pos_x, self.origin_y)).1;
+        self.a = -self.a;
+    }
+
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
         // We will render this kind of bullet in yellow.
-        phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl()).unwrap();
+        draw::fill_rect(&mut phi.renderer, camera.view(self.rect()), Color::RGB(230, 230, 30));
     }
 
     fn rect(&self) -> Rectangle {
@@ -156,6 +233,120 @@ impl Bullet for DevergentBullet {
             h: BULLET_H,
         }
     }
+
+    fn name(&self) -> EntityName {
+        self.name
+    }
+
+    fn damage(&self) -> f64 {
+        DIVERGENT_DAMAGE
+    }
+}
+
+impl HomingMissile {
+    /// The index of the target which the missile steers towards, i.e. the
+    /// closest one, if any.
+    pub fn target(&self, targets: &[Rectangle]) -> Option<usize> {
+        let dist = |target: &Rectangle| (Vec2::from(target.center()) - self.pos).length_squared();
+
+        (0..targets.len())
+            .min_by(|&a, &b| dist(&targets[a]).partial_cmp(&dist(&targets[b])).unwrap())
+    }
+}
+
+impl Bullet for HomingMissile {
+    fn update(&mut self, phi: &mut Phi, dt: f64, targets: &[Rectangle]) -> bool {
+        self.total_time += dt;
+
+        // Turn towards the closest target, if any.
+        let closest = self.target(targets).map(|index| Vec2::from(targets[index].center()));
+
+        if let Some(target) = closest {
+            // The difference between both angles, in [-PI, PI).
+            let wanted = (target - self.pos).angle();
+            let diff = (wanted - self.heading + PI).rem_euclid(2.0 * PI) - PI;
+            let max_turn = MISSILE_TURN_RATE * dt;
+            self.heading += diff.clamp(-max_turn, max_turn);
+        }
+
+        self.pos += Vec2::from_angle(self.heading) * (MISSILE_SPEED * dt);
+
+        // The smoke is emitted behind the missile.
+        self.smoke.position = self.pos.into();
+        self.smoke.emitting = true;
+        self.smoke.update(dt);
+
+        // If the missile has left the screen, or ran out of fuel, then delete
+        // it.
+        let (w, h) = phi.output_size();
+
+        let gone = self.pos.x > w || self.pos.x < 0.0 ||
+            self.pos.y > h || self.pos.y < 0.0 ||
+            self.total_time > MISSILE_LIFETIME;
+        !gone
+    }
+
+    fn ricochet(&mut self, wall: Segment) {
+        self.pos = wall.mirror(self.pos.into()).into();
+        self.heading = reflect(Vec2::from_angle(self.heading), wall.normal()).angle();
+    }
+
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
+        self.smoke.render(&mut phi.renderer, camera);
+
+        // The missile is a triangle pointing where it is heading.
+        let ahead = Vec2::from_angle(self.heading) * MISSILE_SIDE;
+        let back = self.pos - ahead / 2.0;
+        let points = [
+            self.pos + ahead,
+            back + ahead.perp() / 2.0,
+            back - ahead.perp() / 2.0,
+        ].map(|point| camera.view_point(point.into()));
+        draw::fill_polygon(&mut phi.renderer, &points, Color::RGB(230, 130, 30), true);
+    }
+
+    fn rect(&self) -> Rectangle {
+        Rectangle::with_size(MISSILE_SIDE, MISSILE_SIDE).center_at(self.pos.into())
+    }
+
+    fn name(&self) -> EntityName {
+        self.name
+    }
+
+    fn damage(&self) -> f64 {
+        HOMING_DAMAGE
+    }
+}
+
+impl Bullet for AimedBullet {
+    fn update(&mut self, phi: &mut Phi, dt: f64, _: &[Rectangle]) -> bool {
+        self.rect = self.rect.translated(self.vel * dt);
+
+        // If the bullet has left the screen, in any direction, then delete it.
+        let (w, h) = phi.output_size();
+
+        let off_screen = self.rect.x > w || self.rect.x < -self.rect.w ||
+            self.rect.y > h || self.rect.y < -self.rect.h;
+        !off_screen
+    }
+
+    fn ricochet(&mut self, wall: Segment) {
+        self.rect = self.rect.center_at(wall.mirror(self.rect.center()));
+        self.vel = reflect(self.vel, wall.normal());
+    }
+
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
+        // Enemy bullets are red, so that they stand out from the player's.
+        draw::fill_rect(&mut phi.renderer, camera.view(self.rect), Color::RGB(230, 60, 30));
+    }
+
+    fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    fn name(&self) -> EntityName {
+        self.name
+    }
 }
 
 #[derive(Clone,Copy)]
@@ -163,67 +354,293 @@ pub enum CannonType {
     RectBullet,
     SineBullet { amplitude: f64, angular_vel: f64 },
     DevergentBullet { a: f64, b: f64},
+    HomingMissile,
+}
+
+impl CannonType {
+    /// The name under which the cannon is shown to the player.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CannonType::RectBullet => "Rect",
+            CannonType::SineBullet { .. } => "Sine",
+            CannonType::DevergentBullet { .. } => "Divergent",
+            CannonType::HomingMissile => "Homing",
+        }
+    }
+
+    /// The time the cannon takes to fire again, in seconds.
+    pub fn fire_delay(&self) -> f64 {
+        match *self {
+            CannonType::RectBullet => 0.15,
+            CannonType::SineBullet { .. } => 0.2,
+            CannonType::DevergentBullet { .. } => 0.3,
+            CannonType::HomingMissile => 0.6,
+        }
+    }
 }
 
-pub fn spawn_bullets(cannon: CannonType, cannons_x: f64, cannons1_y: f64, cannons2_y: f64) -> Vec<Box<dyn Bullet>> {
+pub fn spawn_bullets(ids: &mut IdAllocator, cannon: CannonType, cannons_x: f64, cannons1_y: f64, cannons2_y: f64, pool: &mut BulletPool) {
     match cannon {
-        CannonType::RectBullet => 
-            vec![
-                Box::new(RectBullet {
-                    rect: Rectangle {
-                        x: cannons_x,
-                        y: cannons1_y,
-                        w: BULLET_W,
-                        h: BULLET_H,
-                    }
-                }),
-                Box::new(RectBullet {
-                    rect: Rectangle {
-                        x: cannons_x,
-                        y: cannons2_y,
-                        w: BULLET_W,
-                        h: BULLET_H,
-                    }
-                }),
-            ],
-
-        CannonType::SineBullet { amplitude, angular_vel } =>
-            vec![
-                Box::new(SineBullet {
-                    pos_x: cannons_x,
-                    origin_y: cannons1_y,
-                    amplitude: amplitude,
-                    angular_vel: angular_vel,
-                    total_time: 0.0,
-                }),
-                Box::new(SineBullet {
-                    pos_x: cannons_x,
-                    origin_y: cannons2_y,
-                    amplitude: amplitude,
-                    angular_vel: angular_vel,
-                    total_time: 0.0,
-                }),
-            ],
-            
-        CannonType::DevergentBullet { a, b } => 
-            vec![
-                // If a,b > 0, eventually goes upwards
-                Box::new(DevergentBullet {
-                    pos_x: cannons_x,
-                    origin_y: cannons1_y,
-                    a: -a,
-                    b: b, 
-                    total_time: 0.0,
-                }),
-
-                // If a,b > 0, eventually goes downwards
-                Box::new(DevergentBullet{
-                    pos_x: cannons_x,
-                    origin_y: cannons2_y,
-                    a: a,
-                    b: b,
+        CannonType::RectBullet => {
+            pool.push(AnyBullet::Rect(RectBullet {
+                name: ids.name("bullet"),
+                rect: Rectangle {
+                    x: cannons_x,
+                    y: cannons1_y,
+                    w: BULLET_W,
+                    h: BULLET_H,
+                }
+            }));
+            pool.push(AnyBullet::Rect(RectBullet {
+                name: ids.name("bullet"),
+                rect: Rectangle {
+                    x: cannons_x,
+                    y: cannons2_y,
+                    w: BULLET_W,
+                    h: BULLET_H,
+                }
+            }));
+        },
+
+        CannonType::SineBullet { amplitude, angular_vel } => {
+            pool.push(AnyBullet::Sine(SineBullet {
+                name: ids.name("bullet"),
+                pos_x: cannons_x,
+                origin_y: cannons1_y,
+                amplitude: amplitude,
+                angular_vel: angular_vel,
+                total_time: 0.0,
+            }));
+            pool.push(AnyBullet::Sine(SineBullet {
+                name: ids.name("bullet"),
+                pos_x: cannons_x,
+                origin_y: cannons2_y,
+                amplitude: amplitude,
+                angular_vel: angular_vel,
+                total_time: 0.0,
+            }));
+        },
+
+        CannonType::DevergentBullet { a, b } => {
+            // If a,b > 0, eventually goes upwards
+            pool.push(AnyBullet::Divergent(DevergentBullet {
+                name: ids.name("bullet"),
+                pos_x: cannons_x,
+                origin_y: cannons1_y,
+                a: -a,
+                b: b, 
+                total_time: 0.0,
+            }));
+
+            // If a,b > 0, eventually goes downwards
+            pool.push(AnyBullet::Divergent(DevergentBullet {
+                name: ids.name("bullet"),
+                pos_x: cannons_x,
+                origin_y: cannons2_y,
+                a: a,
+                b: b,
+                total_time: 0.0,
+            }));
+        },
+
+        CannonType::HomingMissile => {
+            for &y in &[cannons1_y, cannons2_y] {
+                pool.push(AnyBullet::Homing(HomingMissile {
+                    name: ids.name("missile"),
+                    pos: Vec2::new(cannons_x, y),
+                    heading: 0.0,
                     total_time: 0.0,
-                })
-            ]
+                    smoke: ParticleEmitter::new(MISSILE_SMOKE),
+                }));
+            }
+        },
+    }
+}
+
+/// Fires a bullet from `from` towards `target`, which travels `speed` pixels
+/// every second.
+pub fn spawn_aimed_bullet(ids: &mut IdAllocator, from: (f64, f64), target: (f64, f64), speed: f64, pool: &mut BulletPool) {
+    pool.push(AnyBullet::Aimed(AimedBullet {
+        name: ids.name("enemy_bullet"),
+        rect: Rectangle::with_size(BULLET_H, BULLET_H).center_at(from),
+        vel: (Vec2::from(target) - Vec2::from(from)).normalize() * speed,
+    }));
+}
+
+/// Any of the bullets above. Storing them by value, rather than behind a
+/// `Box<dyn Bullet>`, lets a `BulletPool` reuse their memory.
+#[derive(Clone)]
+pub enum AnyBullet {
+    Rect(RectBullet),
+    Sine(SineBullet),
+    Divergent(DevergentBullet),
+    Homing(HomingMissile),
+    Aimed(AimedBullet),
+}
+
+impl AnyBullet {
+    fn as_bullet(&self) -> &dyn Bullet {
+        match *self {
+            AnyBullet::Rect(ref bullet) => bullet,
+            AnyBullet::Sine(ref bullet) => bullet,
+            AnyBullet::Divergent(ref bullet) => bullet,
+            AnyBullet::Homing(ref bullet) => bullet,
+            AnyBullet::Aimed(ref bullet) => bullet,
+        }
+    }
+
+    fn as_bullet_mut(&mut self) -> &mut dyn Bullet {
+        match *self {
+            AnyBullet::Rect(ref mut bullet) => bullet,
+            AnyBullet::Sine(ref mut bullet) => bullet,
+            AnyBullet::Divergent(ref mut bullet) => bullet,
+            AnyBullet::Homing(ref mut bullet) => bullet,
+            AnyBullet::Aimed(ref mut bullet) => bullet,
+        }
+    }
+}
+
+impl Bullet for AnyBullet {
+    fn update(&mut self, phi: &mut Phi, dt: f64, targets: &[Rectangle]) -> bool {
+        self.as_bullet_mut().update(phi, dt, targets)
+    }
+
+    fn ricochet(&mut self, wall: Segment) {
+        self.as_bullet_mut().ricochet(wall)
+    }
+
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
+        self.as_bullet().render(phi, camera)
+    }
+
+    fn rect(&self) -> Rectangle {
+        self.as_bullet().rect()
+    }
+
+    fn name(&self) -> EntityName {
+        self.as_bullet().name()
+    }
+
+    fn damage(&self) -> f64 {
+        self.as_bullet().damage()
+    }
+}
+
+/// Holds bullets without allocating for every one of them. The slots of dead
+/// bullets are kept around and reused by the next ones.
+#[derive(Clone, Default)]
+pub struct BulletPool {
+    slots: Vec<MaybeAlive<AnyBullet>>,
+
+    // The number of slots which hold a bullet. They are always the first
+    // ones in `slots`.
+    len: usize,
+
+    // How far the bullet in every slot moved during the last update, so that
+    // fast bullets may not go through thin targets between two ticks.
+    moves: Vec<Vec2>,
+}
+
+impl BulletPool {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push(&mut self, bullet: AnyBullet) {
+        let slot = MaybeAlive { alive: true, value: bullet };
+
+        if self.len < self.slots.len() {
+            self.slots[self.len] = slot;
+            self.moves[self.len] = Vec2::ZERO;
+        } else {
+            self.slots.push(slot);
+            self.moves.push(Vec2::ZERO);
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes every bullet, keeping their slots for the next ones.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AnyBullet> {
+        self.slots[..self.len].iter().map(|slot| &slot.value)
+    }
+
+    /// The bullet at `index`, as given by the order of `iter`.
+    pub fn get_mut(&mut self, index: usize) -> &mut MaybeAlive<AnyBullet> {
+        &mut self.slots[..self.len][index]
+    }
+
+    /// Updates every bullet, removing those which are destroyed. Bullets
+    /// which go through one of the `walls` ricochet off it.
+    pub fn update(&mut self, phi: &mut Phi, dt: f64, targets: &[Rectangle], walls: &[Segment]) {
+        for (slot, motion) in self.slots[..self.len].iter_mut().zip(&mut self.moves) {
+            let from = slot.value.rect().center();
+            slot.alive = slot.value.update(phi, dt, targets);
+
+            let path = Segment { from, to: slot.value.rect().center() };
+            if let Some(&wall) = walls.iter().find(|&&wall| path.crossing(wall).is_some()) {
+                slot.value.ricochet(wall);
+            }
+
+            *motion = Vec2::from(slot.value.rect().center()) - Vec2::from(from);
+        }
+
+        self.sweep();
+    }
+
+    /// Whether the bullet at `index` touched `target` at any point of its last
+    /// move, rather than only where it ended up.
+    pub fn hits(&self, index: usize, target: Shape) -> bool {
+        let motion = self.moves[index];
+        let start = self.slots[index].value.rect().translated(-motion);
+        target.swept_by(start, motion)
+    }
+
+    /// The area through which the bullet at `index` went during its last
+    /// move.
+    pub fn swept_rect(&self, index: usize) -> Rectangle {
+        let end = self.slots[index].value.rect();
+        let start = end.translated(-self.moves[index]);
+        let (x, y) = (start.x.min(end.x), start.y.min(end.y));
+
+        Rectangle {
+            x,
+            y,
+            w: start.x.max(end.x) + end.w - x,
+            h: start.y.max(end.y) + end.h - y,
+        }
+    }
+
+    /// The indices of the `targets` which homing missiles steer towards, so
+    /// that they may be highlighted.
+    pub fn homing_targets(&self, targets: &[Rectangle]) -> Vec<usize> {
+        self.iter()
+            .filter_map(|bullet| match *bullet {
+                AnyBullet::Homing(ref missile) => missile.target(targets),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Removes the bullets which were marked as dead.
+    pub fn sweep(&mut self) {
+        let mut i = 0;
+
+        //? The last live bullet takes the slot of a dead one, along with its
+        //? move, and is checked in turn. Dead bullets end up past `len`, where
+        //? the next bullets fired reuse their slots rather than allocate.
+        while i < self.len {
+            if self.slots[i].alive {
+                i += 1;
+            } else {
+                self.len -= 1;
+                self.slots.swap(i, self.len);
+                self.moves.swap(i, self.len);
+            }
+        }
     }
 }
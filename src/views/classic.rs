@@ -0,0 +1,470 @@
+//! The classic mode, played as in the arcades of old: the ship turns and
+//! thrusts rather than strafing, asteroids drift in every direction and come
+//! back around the other side of the screen, and bullets carry the momentum
+//! of the ship which fired them.
+
+use crate::phi::data::{Circle, Rectangle, Vec2};
+use crate::phi::gfx::{draw, AnimatedSprite, CopySprite, Sprite};
+use crate::phi::{Phi, PhiError, Transition, View, ViewAction};
+use crate::views::game::{asteroid_sprite, explosion_sprite, AsteroidSize, ASTEROID_SIDE, EXPLOSION_FPS, EXPLOSION_SIDE};
+use crate::views::shared::{ParallaxBackground, STARFIELD_PATH};
+use crate::views::ships::SHIPS;
+use crate::views::summary::{Restart, RunSummary, RunSummaryView};
+use rand::Rng;
+use sdl2::pixels::Color;
+use std::f64::consts::PI;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+// The frames of the ship's spritesheet which are shown while it drifts and
+// while it thrusts, see `ShipSpec::frames`.
+const DRIFT_FRAME: usize = 3;
+const THRUST_FRAME: usize = 4;
+
+// How fast the ship turns, in radians per second, how fast it speeds up while
+// thrusting, in pixels per second squared, the fraction of its speed which it
+// loses every second, and its top speed, in pixels per second.
+const TURN_SPEED: f64 = 4.5;
+const THRUST: f64 = 320.0;
+const DRAG: f64 = 0.4;
+const MAX_SPEED: f64 = 360.0;
+
+/// The radius with which the ship collides, as a fraction of its height.
+const SHIP_RADIUS: f64 = 0.4;
+
+// How fast bullets leave the ship, in pixels per second, how long they fly,
+// and the time between two of them, in seconds.
+const BULLET_SPEED: f64 = 420.0;
+const BULLET_LIFE: f64 = 1.1;
+const BULLET_SIZE: f64 = 4.0;
+const BULLET_COLOR: Color = Color::RGB(255, 230, 120);
+const FIRE_DELAY: f64 = 0.18;
+
+/// The radius with which asteroids collide, as a fraction of their side.
+const ASTEROID_RADIUS: f64 = 0.4;
+
+/// The range from which the speed of new asteroids is picked, in pixels per
+/// second. Fragments are faster than the asteroid they broke from.
+const ASTEROID_SPEED: (f64, f64) = (30.0, 90.0);
+const FRAGMENT_BOOST: f64 = 1.4;
+
+/// The number of asteroids in the first wave. Every wave has one more.
+const FIRST_WAVE: usize = 4;
+
+/// How close to the ship asteroids may appear, in pixels.
+const SAFE_DISTANCE: f64 = 180.0;
+
+/// The number of ships which the player starts with.
+const LIVES: u32 = 3;
+
+/// How long the ship may not be hit after it appears, in seconds, during
+/// which it blinks at the given period.
+const SPAWN_SHIELD: f64 = 2.5;
+const BLINK_PERIOD: f64 = 0.15;
+
+/// How long the ship stays away once it was destroyed, and how long the run
+/// goes on after the last ship was, in seconds.
+const RESPAWN_DELAY: f64 = 1.5;
+const GAME_OVER_DELAY: f64 = 2.0;
+
+/// Moves `pos` to the other side of the screen once whatever is there, whose
+/// bounding circle is `radius` pixels wide, went past an edge. Returns whether
+/// it was moved.
+fn wrap(pos: &mut Vec2, radius: f64, (w, h): (f64, f64)) -> bool {
+    let wrapped = |at: &mut f64, size: f64| {
+        if *at < -radius {
+            *at += size + 2.0 * radius;
+            true
+        } else if *at > size + radius {
+            *at -= size + 2.0 * radius;
+            true
+        } else {
+            false
+        }
+    };
+
+    // Both axes are checked, even if the first one wrapped.
+    let x = wrapped(&mut pos.x, w);
+    let y = wrapped(&mut pos.y, h);
+    x || y
+}
+
+/// Something which drifts around the screen, and comes back around the other
+/// side.
+#[derive(Clone, Copy)]
+struct Body {
+    pos: Vec2,
+    vel: Vec2,
+
+    // Where the body was before the last tick, so that rendering may
+    // interpolate between both positions, unless it just wrapped around.
+    prev_pos: Vec2,
+}
+
+impl Body {
+    fn new(pos: Vec2, vel: Vec2) -> Body {
+        Body { pos, vel, prev_pos: pos }
+    }
+
+    fn update(&mut self, dt: f64, radius: f64, screen: (f64, f64)) {
+        self.prev_pos = self.pos;
+        self.pos += self.vel * dt;
+        if wrap(&mut self.pos, radius, screen) {
+            self.prev_pos = self.pos;
+        }
+    }
+
+    /// Where the body is drawn, between the last tick and the next one.
+    fn at(&self, alpha: f64) -> Vec2 {
+        self.prev_pos + (self.pos - self.prev_pos) * alpha
+    }
+}
+
+struct Ship {
+    body: Body,
+
+    /// Where the ship is heading, in radians, clockwise from the right.
+    angle: f64,
+    thrusting: bool,
+
+    // The time left before the ship may fire again, and during which it may
+    // not be hit, in seconds.
+    fire_cooldown: f64,
+    shield: f64,
+}
+
+impl Ship {
+    fn spawn(screen: (f64, f64)) -> Ship {
+        let center = Vec2::new(screen.0 / 2.0, screen.1 / 2.0);
+        Ship {
+            body: Body::new(center, Vec2::ZERO),
+            angle: -PI / 2.0,
+            thrusting: false,
+            fire_cooldown: 0.0,
+            shield: SPAWN_SHIELD,
+        }
+    }
+
+    fn heading(&self) -> Vec2 {
+        Vec2::from_angle(self.angle)
+    }
+}
+
+struct Asteroid {
+    body: Body,
+    size: AsteroidSize,
+    sprite: AnimatedSprite,
+}
+
+impl Asteroid {
+    fn radius(&self) -> f64 {
+        self.size.side() * ASTEROID_RADIUS
+    }
+
+    fn circle(&self) -> Circle {
+        Circle { center: self.body.pos, radius: self.radius() }
+    }
+}
+
+struct Bullet {
+    body: Body,
+    life: f64,
+}
+
+struct Explosion {
+    sprite: AnimatedSprite,
+    center: Vec2,
+    side: f64,
+}
+
+pub struct ClassicView {
+    ship: Option<Ship>,
+    ship_sprites: Vec<Sprite>,
+    ship_size: (f64, f64),
+
+    asteroids: Vec<Asteroid>,
+    bullets: Vec<Bullet>,
+    explosions: Vec<Explosion>,
+    asteroid_sprite: AnimatedSprite,
+    explosion_sprite: AnimatedSprite,
+
+    score: u64,
+    destroyed: u64,
+    lives: u32,
+    wave: u32,
+
+    // The time left before the ship comes back, or before the run ends if it
+    // was the last one, in seconds.
+    respawn: f64,
+
+    // The score and lives, drawn in the top-left corner, which are only
+    // rendered again when they change.
+    hud: Sprite,
+    hud_values: (u64, u32),
+
+    background: ParallaxBackground,
+}
+
+impl ClassicView {
+    pub fn new(phi: &mut Phi) -> Result<ClassicView, PhiError> {
+        let spec = SHIPS[0];
+        let mut view = ClassicView {
+            ship: Some(Ship::spawn(phi.output_size())),
+            ship_sprites: spec.frames(&phi.renderer)?,
+            ship_size: (spec.w * spec.scale, spec.h * spec.scale),
+            asteroids: vec![],
+            bullets: vec![],
+            explosions: vec![],
+            asteroid_sprite: asteroid_sprite(phi)?,
+            explosion_sprite: explosion_sprite(phi)?,
+            score: 0,
+            destroyed: 0,
+            lives: LIVES,
+            wave: 0,
+            respawn: 0.0,
+            hud: ClassicView::hud(phi, 0, LIVES)?,
+            hud_values: (0, LIVES),
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        };
+
+        view.next_wave(phi);
+        Ok(view)
+    }
+
+    fn hud(phi: &mut Phi, score: u64, lives: u32) -> Result<Sprite, PhiError> {
+        phi.ttf_str_sprite(&format!("Score: {}   Ships: {}", score, lives), FONT_PATH, 24, Color::RGB(255, 255, 255))
+    }
+
+    fn asteroid(&self, phi: &mut Phi, size: AsteroidSize, pos: Vec2, speed: f64) -> Asteroid {
+        let angle = phi.rng().gen::<f64>() * 2.0 * PI;
+        let mut sprite = self.asteroid_sprite.clone();
+        sprite.set_fps(phi.rng().gen::<f64>() * 20.0 + 10.0);
+
+        Asteroid {
+            body: Body::new(pos, Vec2::from_angle(angle) * speed),
+            size,
+            sprite,
+        }
+    }
+
+    /// Sends in one more large asteroid than in the last wave, away from the
+    /// ship.
+    fn next_wave(&mut self, phi: &mut Phi) {
+        self.wave += 1;
+        let (w, h) = phi.output_size();
+        let center = self.ship.as_ref().map_or(Vec2::new(w / 2.0, h / 2.0), |ship| ship.body.pos);
+
+        for _ in 0..FIRST_WAVE + self.wave as usize - 1 {
+            let pos = loop {
+                let pos = Vec2::new(phi.rng().gen::<f64>() * w, phi.rng().gen::<f64>() * h);
+                if (pos - center).length() >= SAFE_DISTANCE {
+                    break pos;
+                }
+            };
+
+            let (min, max) = ASTEROID_SPEED;
+            let speed = min + phi.rng().gen::<f64>() * (max - min);
+            let asteroid = self.asteroid(phi, AsteroidSize::Large, pos, speed);
+            self.asteroids.push(asteroid);
+        }
+    }
+
+    /// Blows something up at `center`, `side` pixels wide.
+    fn explode(&mut self, phi: &Phi, center: Vec2, side: f64) {
+        let mut sprite = self.explosion_sprite.clone();
+        sprite.set_fps(EXPLOSION_FPS * phi.effects.animation_speed());
+        self.explosions.push(Explosion { sprite, center, side });
+    }
+
+    /// Breaks the asteroid at `index` into two smaller ones, if it is not
+    /// already as small as they get.
+    fn shatter(&mut self, phi: &mut Phi, index: usize) {
+        let asteroid = self.asteroids.swap_remove(index);
+        self.score += asteroid.size.score();
+        self.destroyed += 1;
+        self.explode(phi, asteroid.body.pos, EXPLOSION_SIDE * asteroid.size.side() / ASTEROID_SIDE);
+
+        if let Some(size) = asteroid.size.fragments() {
+            let speed = asteroid.body.vel.length() * FRAGMENT_BOOST;
+            for _ in 0..2 {
+                let fragment = self.asteroid(phi, size, asteroid.body.pos, speed);
+                self.asteroids.push(fragment);
+            }
+        }
+    }
+
+    fn update_ship(&mut self, phi: &mut Phi, dt: f64) {
+        let screen = phi.output_size();
+        let radius = self.ship_size.1 * SHIP_RADIUS;
+        let ship = match self.ship {
+            Some(ref mut ship) => ship,
+            None => return,
+        };
+
+        // Left and Right turn the ship, and Up thrusts it forward.
+        let turn = phi.events.key_right as i32 - phi.events.key_left as i32;
+        ship.angle = (ship.angle + turn as f64 * TURN_SPEED * dt).rem_euclid(2.0 * PI);
+
+        ship.thrusting = phi.events.key_up;
+        if ship.thrusting {
+            ship.body.vel += ship.heading() * (THRUST * dt);
+        }
+
+        ship.body.vel *= (1.0 - DRAG * dt).max(0.0);
+        if ship.body.vel.length() > MAX_SPEED {
+            ship.body.vel = ship.body.vel.normalize() * MAX_SPEED;
+        }
+        ship.body.update(dt, radius, screen);
+
+        ship.shield = (ship.shield - dt).max(0.0);
+        ship.fire_cooldown = (ship.fire_cooldown - dt).max(0.0);
+
+        //? Bullets add up the velocity of the ship to their own, so that they
+        //? fly away from it even while it moves at its top speed.
+        if phi.events.key_space && ship.fire_cooldown <= 0.0 {
+            ship.fire_cooldown = FIRE_DELAY;
+            let nose = ship.body.pos + ship.heading() * (self.ship_size.0 / 2.0);
+            self.bullets.push(Bullet {
+                body: Body::new(nose, ship.body.vel + ship.heading() * BULLET_SPEED),
+                life: BULLET_LIFE,
+            });
+        }
+    }
+
+    /// The summary of the run, once it is over.
+    fn summary(&self) -> RunSummary {
+        RunSummary {
+            destroyed: self.destroyed,
+            grazes: 0,
+            waves: self.wave,
+            score: self.score,
+            survived: None,
+            stats: None,
+            modified: false,
+        }
+    }
+}
+
+impl View for ClassicView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::change_view_with(Transition::Crossfade(0.5), self,
+                crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        let screen = phi.output_size();
+        self.update_ship(phi, elapsed);
+
+        for asteroid in &mut self.asteroids {
+            let radius = asteroid.radius();
+            asteroid.body.update(elapsed, radius, screen);
+            asteroid.sprite.add_time(elapsed);
+        }
+
+        for bullet in &mut self.bullets {
+            bullet.body.update(elapsed, BULLET_SIZE, screen);
+            bullet.life -= elapsed;
+        }
+        self.bullets.retain(|bullet| bullet.life > 0.0);
+
+        for explosion in &mut self.explosions {
+            explosion.sprite.add_time(elapsed);
+        }
+        self.explosions.retain(|explosion| !explosion.sprite.is_finished());
+
+        // Bullets break the first asteroid which they hit.
+        let mut i = 0;
+        while i < self.bullets.len() {
+            let point = Circle { center: self.bullets[i].body.pos, radius: BULLET_SIZE / 2.0 };
+            match self.asteroids.iter().position(|asteroid| asteroid.circle().overlaps(point)) {
+                Some(hit) => {
+                    self.bullets.swap_remove(i);
+                    self.shatter(phi, hit);
+                },
+                None => i += 1,
+            }
+        }
+
+        // Asteroids destroy the ship, unless it just appeared.
+        let ship_circle = self.ship.as_ref()
+            .filter(|ship| ship.shield <= 0.0)
+            .map(|ship| Circle { center: ship.body.pos, radius: self.ship_size.1 * SHIP_RADIUS });
+        if let Some(circle) = ship_circle {
+            if let Some(hit) = self.asteroids.iter().position(|asteroid| asteroid.circle().overlaps(circle)) {
+                self.ship = None;
+                self.lives -= 1;
+                self.respawn = if self.lives > 0 { RESPAWN_DELAY } else { GAME_OVER_DELAY };
+                self.explode(phi, circle.center, EXPLOSION_SIDE);
+                self.shatter(phi, hit);
+            }
+        }
+
+        if self.ship.is_none() {
+            self.respawn -= elapsed;
+            if self.respawn <= 0.0 {
+                if self.lives == 0 {
+                    let summary = self.summary();
+                    let restart: Restart = Box::new(|phi| Ok(Box::new(ClassicView::new(phi)?)));
+                    return ViewAction::change_view_with(Transition::Fade(0.8), self,
+                        RunSummaryView::new(phi, summary, Some(restart)));
+                }
+                self.ship = Some(Ship::spawn(screen));
+            }
+        }
+
+        if self.asteroids.is_empty() {
+            self.next_wave(phi);
+        }
+
+        if self.hud_values != (self.score, self.lives) {
+            self.hud_values = (self.score, self.lives);
+            match ClassicView::hud(phi, self.score, self.lives) {
+                Ok(hud) => self.hud = hud,
+                Err(err) => return ViewAction::Error(err),
+            }
+        }
+
+        self.background.update(elapsed * 0.25);
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+        self.background.render(&mut phi.renderer);
+
+        for asteroid in &self.asteroids {
+            let side = asteroid.size.side();
+            let dest = Rectangle::with_size(side, side).center_at(asteroid.body.at(alpha).into());
+            phi.renderer.copy_sprite(&asteroid.sprite, dest);
+        }
+
+        for bullet in &self.bullets {
+            let dest = Rectangle::with_size(BULLET_SIZE, BULLET_SIZE).center_at(bullet.body.at(alpha).into());
+            draw::fill_rect(&mut phi.renderer, dest, BULLET_COLOR);
+        }
+
+        // The ship blinks while it may not be hit.
+        if let Some(ref ship) = self.ship {
+            let blinking = ship.shield > 0.0 && (ship.shield / BLINK_PERIOD) as u32 % 2 == 1;
+            if !blinking {
+                let frame = if ship.thrusting { THRUST_FRAME } else { DRIFT_FRAME };
+                let (w, h) = self.ship_size;
+                let dest = Rectangle::with_size(w, h).center_at(ship.body.at(alpha).into());
+                phi.renderer.copy_sprite(&self.ship_sprites[frame].rotated(ship.angle), dest);
+            }
+        }
+
+        for explosion in &self.explosions {
+            let dest = Rectangle::with_size(explosion.side, explosion.side).center_at(explosion.center.into());
+            phi.renderer.copy_sprite(&explosion.sprite, dest);
+        }
+
+        let (w, h) = self.hud.size();
+        phi.renderer.copy_sprite(&self.hud, Rectangle { x: 16.0, y: 16.0, w, h });
+    }
+}
@@ -0,0 +1,134 @@
+//! Kills which follow each other closely are worth more and more, and every
+//! one of them shows what it earned where it happened.
+
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::{Rectangle, Vec2};
+use crate::phi::gfx::{Camera, CopySprite, Sprite, Tint};
+use sdl2::pixels::Color;
+use std::collections::HashMap;
+
+/// The time within which the next kill must happen to keep the combo going,
+/// in seconds.
+const COMBO_WINDOW: f64 = 2.0;
+
+/// The number of kills in a row which raise the multiplier by one, and the
+/// highest it may go.
+const KILLS_PER_STEP: u32 = 3;
+const MAX_MULTIPLIER: u64 = 8;
+
+const POPUP_FONT_PATH: &str = "assets/belligerent.ttf";
+const POPUP_FONT_SIZE: i32 = 20;
+
+/// How long a popup is shown, in seconds, and how fast it rises, in pixels
+/// per second.
+const POPUP_TIME: f64 = 0.9;
+const POPUP_RISE: f64 = 50.0;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Combo {
+    // The number of kills in a row, and the time left for the next one
+    // before the combo is over, in seconds.
+    kills: u32,
+    time_left: f64,
+}
+
+impl Combo {
+    pub fn new() -> Combo {
+        Combo::default()
+    }
+
+    /// By how much points are multiplied for the next kill.
+    pub fn multiplier(&self) -> u64 {
+        (1 + (self.kills / KILLS_PER_STEP) as u64).min(MAX_MULTIPLIER)
+    }
+
+    /// Counts a kill worth `points` on its own. Returns the points which it
+    /// actually earned.
+    pub fn kill(&mut self, points: u64) -> u64 {
+        let earned = points * self.multiplier();
+        self.kills += 1;
+        self.time_left = COMBO_WINDOW;
+        earned
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.time_left -= dt;
+        if self.time_left <= 0.0 {
+            self.kills = 0;
+        }
+    }
+}
+
+struct Popup {
+    text: String,
+
+    // Created once the popup is first updated, see `ScorePopups::spawn`.
+    sprite: Option<Sprite>,
+
+    // Where it was spawned in the scene, and for how long it was shown.
+    at: Vec2,
+    age: f64,
+}
+
+/// The points earned by recent kills, which rise and fade where they
+/// happened.
+#[derive(Default)]
+pub struct ScorePopups {
+    popups: Vec<Popup>,
+
+    //? Kills earn the same few amounts over and over, so the text of each is
+    //? rendered once rather than with every kill.
+    sprites: HashMap<String, Sprite>,
+}
+
+impl ScorePopups {
+    pub fn new() -> ScorePopups {
+        ScorePopups::default()
+    }
+
+    /// Shows `points` rising from `at`. Kills are handled where no error may
+    /// be returned, so the text is only rendered by the next `update`.
+    pub fn spawn(&mut self, at: (f64, f64), points: u64) {
+        self.popups.push(Popup {
+            text: format!("+{}", points),
+            sprite: None,
+            at: at.into(),
+            age: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, phi: &mut Phi, dt: f64) -> Result<(), PhiError> {
+        for popup in &mut self.popups {
+            popup.age += dt;
+
+            if popup.sprite.is_none() {
+                let sprite = match self.sprites.get(&popup.text) {
+                    Some(sprite) => sprite.clone(),
+                    None => {
+                        let sprite = phi.ttf_str_sprite(&popup.text, POPUP_FONT_PATH, POPUP_FONT_SIZE, Color::RGB(255, 230, 120))?;
+                        self.sprites.insert(popup.text.clone(), sprite.clone());
+                        sprite
+                    },
+                };
+                popup.sprite = Some(sprite);
+            }
+        }
+
+        self.popups.retain(|popup| popup.age < POPUP_TIME);
+        Ok(())
+    }
+
+    pub fn render(&self, phi: &mut Phi, camera: &Camera) {
+        for popup in &self.popups {
+            if let Some(ref sprite) = popup.sprite {
+                let (w, h) = sprite.size();
+                let (x, y) = camera.view_point((popup.at.x, popup.at.y - popup.age * POPUP_RISE));
+
+                // Fully opaque for the first half, then fading out.
+                let alpha = (1.0 - popup.age / POPUP_TIME) * 2.0;
+                let dest = Rectangle::with_size(w, h).center_at((x, y));
+                phi.renderer.copy_sprite_tinted(sprite, dest, Tint::alpha(alpha));
+            }
+        }
+    }
+}
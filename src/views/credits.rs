@@ -0,0 +1,83 @@
+use crate::phi::assets::{self, AssetInfo};
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::shared::{ParallaxBackground, STARFIELD_PATH};
+use sdl2::pixels::Color;
+use std::path::Path;
+
+/// Where the author and license of every image, sound and font are listed.
+pub const CREDITS_PATH: &str = "assets/credits.cfg";
+
+/// How many rows of the list are shown at once.
+const MAX_ROWS: usize = 11;
+
+/// Credits the people who made the assets of the game, gathered from
+/// `CREDITS_PATH`, so that they stay right as files are added or removed.
+pub struct CreditsView {
+    menu: Menu,
+    back: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl CreditsView {
+    pub fn new(phi: &mut Phi) -> Result<CreditsView, PhiError> {
+        let mut menu = Menu::scrolling(640.0, 36.0, MAX_ROWS);
+
+        // Files which are not shipped anymore are not worth crediting.
+        let infos: Vec<AssetInfo> = assets::load_info(CREDITS_PATH)?.into_iter()
+            .filter(|info| Path::new(&info.path).exists())
+            .collect();
+
+        menu.add(phi, "Credits", WidgetKind::Label)?;
+        for credit in assets::credits(&infos) {
+            menu.add(phi, &format!("{} - {}", credit.author, credit.license), WidgetKind::Label)?;
+            if let Some(ref source) = credit.source {
+                menu.add(phi, &format!("from {}", source), WidgetKind::Label)?;
+            }
+
+            for file in &credit.files {
+                let name = file.strip_prefix("assets/").unwrap_or(file);
+                menu.add(phi, &format!("    {}", name), WidgetKind::Label)?;
+            }
+        }
+
+        Ok(CreditsView {
+            back: menu.add(phi, "Back", WidgetKind::Button)?,
+            menu,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+}
+
+impl View for CreditsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        let back = phi.events.now.key_escape == Some(true) ||
+            self.menu.update(phi) == Some(UiEvent::Clicked(self.back));
+        if back {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::phi::text::{TextBlock, TextLayout};
+use crate::phi::ui::{Align, Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use sdl2::pixels::Color;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+/// The width to which the error message is wrapped, in pixels, and its
+/// distance from the top of the screen.
+const MESSAGE_W: f64 = 640.0;
+const MESSAGE_Y: f64 = 40.0;
+
+/// Shown instead of a view which failed, so that the player may go back to
+/// the main menu rather than lose the whole game.
+pub struct ErrorView {
+    menu: Menu,
+    message: TextBlock,
+    main_menu: WidgetId,
+    quit: WidgetId,
+}
+
+impl ErrorView {
+    pub fn new(phi: &mut Phi, message: &str) -> Result<ErrorView, PhiError> {
+        let mut menu = Menu::new(640.0, 40.0);
+
+        menu.add(phi, "Something went wrong", WidgetKind::Label)?;
+        menu.add(phi, &crate::build_info::short(), WidgetKind::Label)?;
+
+        let message = TextLayout {
+            max_width: Some(MESSAGE_W),
+            align: Align::Center,
+            ..TextLayout::new(FONT_PATH, 18, Color::RGB(220, 220, 220))
+        }.layout(phi, message)?;
+
+        Ok(ErrorView {
+            main_menu: menu.add(phi, "Main Menu", WidgetKind::Button)?,
+            quit: menu.add(phi, "Quit", WidgetKind::Button)?,
+            menu,
+            message,
+        })
+    }
+}
+
+impl View for ErrorView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        match self.menu.update(phi) {
+            Some(UiEvent::Clicked(id)) if id == self.main_menu =>
+                ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi)),
+            Some(UiEvent::Clicked(id)) if id == self.quit =>
+                ViewAction::Quit,
+            _ => ViewAction::Render(self),
+        }
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen. Nothing else is loaded, in case the assets are
+        // what failed.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the whole message above the menu
+        let (win_w, _) = phi.output_size();
+        let (w, _) = self.message.size();
+        self.message.render(&mut phi.renderer, ((win_w - w) / 2.0, MESSAGE_Y));
+
+        self.menu.render(phi);
+    }
+}
@@ -1,41 +1,231 @@
-use crate::phi::{Phi, View, ViewAction};
-use crate::phi::data::{Rectangle, MaybeAlive};
-use crate::phi::gfx::{Sprite, CopySprite, AnimatedSprite, AnimatedSpriteDescr};
-use crate::views::shared::Background;
+use crate::phi::{Key, Phi, PhiError, Transition, View, ViewAction, TICK};
+use crate::phi::audio::{self, FadeTarget, MusicDucking};
+use crate::phi::difficulty::Difficulty;
+use crate::phi::data::{reflect, Circle, EntityName, IdAllocator, Rectangle, Segment, Shape, SpatialGrid, Vec2};
+use crate::phi::gfx::{Sprite, CopySprite, AnimatedSprite, AnimatedSpriteDescr, Outline, PlayMode, Tint};
+use crate::phi::gfx::{draw, Camera, ParticleConfig, ParticleEmitter};
+use crate::phi::tween::{Ease, Tween};
+use crate::phi::ui::{Menu, RadialMenu, UiEvent, WidgetId, WidgetKind};
+use crate::views::shared::{ParallaxBackground, RestartHold, DEFAULT_PROFILE, STARFIELD_PATH};
+use crate::views::achievements::{AchievementEvent, Achievements, Toasts};
+use crate::views::boss::{Boss, BossEvent, BOSS_SCORE};
 use crate::views::bullets::*;
+use crate::views::combo::{Combo, ScorePopups};
+use crate::views::health::Health;
+use crate::views::heatmap::Heatmap;
+use crate::views::high_scores::{HighScores, Leaderboard, NameEntryView};
+use crate::views::hud::{Hud, HudLayout};
+use crate::views::level::{EnemyPattern, Level, LevelDirector, SurvivalDirector, WaveEvent, WAVES_PATH};
+use crate::views::mutators::Mutators;
+use crate::views::pickups::Pickups;
+use crate::views::patterns::{BulletPattern, BulletPatterns, PatternPlayer, PATTERNS_PATH};
+use crate::views::save::{bookmark_path, SavedAsteroid, SavedEnemy, SavedGame};
+use crate::views::scripts::{BehaviorScript, BehaviorScripts, ScriptInputs};
+use crate::views::ships::{ShipSpec, SHIPS};
+use crate::views::spawn::{SpawnDistribution, Spawner};
+use crate::views::stats::{LifetimeStats, RunEvent, RunStats, RunTimeline, Target};
+use crate::views::summary::{Restart, RunSummary, RunSummaryView};
+use crate::views::trailer::{TrailerCue, TrailerScript};
+use rand::Rng;
+use rand::rngs::StdRng;
 use sdl2::pixels::Color;
-use sdl2::mixer::Music;
+use sdl2::mixer::{Channel, Chunk, Music, MAX_VOLUME};
+use std::collections::VecDeque;
 use std::path::Path;
+use std::rc::Rc;
 
 
 const ASTEROID_PATH: &'static str = "assets/asteroid.png";
 const ASTEROID_WIDE: usize = 21;
 const ASTEROID_HIGH: usize = 7;
 const ASTEROID_TOTAL: usize = ASTEROID_WIDE * ASTEROID_HIGH - 4;
-const ASTEROID_SIDE: f64 = 96.0;
+pub const ASTEROID_SIDE: f64 = 96.0;
+
+/// The radius with which asteroids collide, as a fraction of their side, which
+/// leaves out the empty corners of their sprite.
+const ASTEROID_RADIUS: f64 = 0.4;
 
 // Constants about the explosion
 const EXPLOSION_PATH: &'static str = "assets/explosion.png";
 const EXPLOSIONS_WIDE: usize = 5;
 const EXPLOSIONS_HIGH: usize = 4;
 const EXPLOSIONS_TOTAL: usize = 17;
-const EXPLOSION_SIDE: f64 = 96.0;
-const EXPLOSION_FPS: f64 = 16.0;
-const EXPLOSION_DURATION: f64 = 1.0 / EXPLOSION_FPS * EXPLOSIONS_TOTAL as f64;
-
-/// Pixels traveled by the player's ship every second, when it is moving
-const PLAYER_SPEED:f64 = 180.0;
-const PLAYER_PATH: &'static str = "assets/spaceship.png";
+pub const EXPLOSION_SIDE: f64 = 96.0;
+pub const EXPLOSION_FPS: f64 = 16.0;
 
 /// BGM path
 const MUSIC_PATH: &'static str = "assets/mdk_phoenix_orchestral.ogg";
 
-// Constants about the ship
-const PLAYER_W: f64 = 43.0;
-const PLAYER_H: f64 = 39.0;
+// How long the music takes to fade in when a run starts, and a chime to fade
+// out when another one is played, in seconds
+const MUSIC_FADE_IN: f64 = 1.5;
+const CHIME_FADE_OUT: f64 = 0.03;
+
+/// The ship which enemies fly, turned around to face the player.
+const ENEMY_SHIP: ShipSpec = SHIPS[0];
+
+// The outline around whatever a homing missile is going after, as its color
+// and how far it reaches past the sprite, in pixels of the spritesheet
+const TARGET_OUTLINE: Color = Color::RGB(255, 90, 60);
+const TARGET_OUTLINE_THICKNESS: u32 = 3;
+
+/// How long the ship flashes after being hit, in seconds.
+const PLAYER_FLASH: f64 = 0.2;
+
+/// How long the ship must go without being hit for its shield to come back,
+/// in seconds.
+const SHIELD_RECHARGE: f64 = 8.0;
+
+// The shield's bubble, as its size relative to the ship, its color and its
+// opacity
+const SHIELD_SIZE: f64 = 1.5;
+const SHIELD_COLOR: Color = Color::RGB(90, 200, 255);
+const SHIELD_ALPHA: f64 = 0.6;
+
+// Particles
+const EXHAUST: ParticleConfig = ParticleConfig {
+    rate: 60.0,
+    lifetime: 0.3,
+    angle: ::std::f64::consts::PI,
+    spread: 0.3,
+    speed: (60.0, 120.0),
+    size: 3.0,
+    start_color: Color::RGBA(255, 200, 80, 220),
+    end_color: Color::RGBA(200, 40, 0, 0),
+};
+
+const SPARKS: ParticleConfig = ParticleConfig {
+    rate: 0.0,
+    lifetime: 0.2,
+    angle: ::std::f64::consts::PI,
+    spread: 1.2,
+    speed: (80.0, 200.0),
+    size: 2.0,
+    start_color: Color::RGBA(255, 255, 200, 255),
+    end_color: Color::RGBA(255, 200, 50, 0),
+};
+
+const DEBRIS: ParticleConfig = ParticleConfig {
+    rate: 0.0,
+    lifetime: 0.8,
+    angle: 0.0,
+    spread: ::std::f64::consts::PI,
+    speed: (20.0, 120.0),
+    size: 4.0,
+    start_color: Color::RGBA(160, 140, 120, 255),
+    end_color: Color::RGBA(90, 80, 70, 0),
+};
+
+const SPARKS_PER_IMPACT: f64 = 6.0;
+const SPARKS_PER_GRAZE: f64 = 10.0;
+
+/// The points earned when an asteroid or an enemy bullet passes within
+/// `GRAZE_MARGIN` pixels of the ship's hitbox without hitting it.
+const GRAZE_SCORE: u64 = 10;
+const GRAZE_MARGIN: f64 = 12.0;
+
+// The chime played on a graze, as its pitch in Hz and length in seconds
+const GRAZE_CHIME: (f64, f64) = (1320.0, 0.08);
+
+// The engine hum, as the pitch in Hz of its low and high layers, how loud it
+// gets at full speed relative to the maximum volume, and how fast it follows
+// the ship's speed, in full volumes per second
+const HUM_PITCHES: (f64, f64) = (55.0, 110.0);
+const HUM_VOLUME: f64 = 0.35;
+const HUM_RESPONSE: f64 = 4.0;
+const DEBRIS_PER_ASTEROID: f64 = 16.0;
+
+// Screen shakes, as their intensity in pixels and their duration in seconds
+const EXPLOSION_SHAKE: (f64, f64) = (3.0, 0.15);
+const PLAYER_HIT_SHAKE: (f64, f64) = (10.0, 0.4);
+
+// Once the ship is out of lives, time slows down to this factor as it blows
+// up, then comes back to normal over the given number of seconds, and the
+// game fades to the summary of the run.
+const GAME_OVER_SLOW_MOTION: f64 = 0.2;
+const GAME_OVER_TIME: f64 = 2.5;
+const GAME_OVER_FADE: f64 = 1.2;
+const GAME_OVER_SHAKE: (f64, f64) = (16.0, 0.8);
+
+/// How long the fade to a run which starts over takes, in seconds.
+const RESTART_FADE: f64 = 0.3;
+
+// Collision response
+/// The fraction of their speed along the impact which colliding objects keep,
+/// from 0 (they stick together) to 1 (perfectly elastic).
+const RESTITUTION: f64 = 0.8;
+/// The mass of the ship, relative to that of the largest asteroids.
+const PLAYER_MASS: f64 = 0.4;
+/// How long the ship cannot be steered after being knocked back, in seconds.
+const KNOCKBACK_STUN: f64 = 0.35;
+/// How quickly the knockback wears off, as a fraction of it per second.
+const KNOCKBACK_DAMPING: f64 = 4.0;
+/// The slowest that asteroids may travel to the left after bouncing off each
+/// other, in pixels per second, so that they still leave the screen.
+const ASTEROID_MIN_VEL: f64 = 30.0;
+/// How close asteroids may get to each other before they start steering
+/// apart, in pixels between their edges, and how hard they steer, in pixels
+/// per second squared when they touch.
+const SEPARATION_MARGIN: f64 = 24.0;
+const SEPARATION_STRENGTH: f64 = 240.0;
+
+/// The size of the cells of the grids through which collisions are found, in
+/// pixels. About as large as the largest asteroids.
+const GRID_CELL: f64 = 128.0;
 
 const DEBUG: bool = false;
 
+// Constants about the demo bot
+const BOT_HOME_X: f64 = 100.0;
+const BOT_DANGER_DIST: f64 = 150.0;
+const BOT_MARGIN: f64 = 10.0;
+const BOT_AIM_TOLERANCE: f64 = 8.0;
+const BOT_FIRE_DELAY: f64 = 0.3;
+
+/// How far ahead of the ship the bot looks for asteroids to shoot, in pixels.
+const BOT_SIGHT: f64 = 500.0;
+
+// Constants about the enemy ships
+const ENEMY_SPEED: f64 = 110.0;
+const ENEMY_DIVE_SPEED: f64 = 70.0;
+const ENEMY_SINE_AMPLITUDE: f64 = 60.0;
+const ENEMY_SINE_FREQ: f64 = 2.5;
+const ENEMY_FIRE_DELAY: f64 = 1.8;
+const ENEMY_HEALTH: f64 = 2.0;
+const ENEMY_SCORE: u64 = 150;
+
+/// How many bombs a run starts with, how much damage one deals to the boss,
+/// and how hard the screen shakes when one goes off.
+const BOMBS: u32 = 3;
+const BOMB_BOSS_DAMAGE: f64 = 15.0;
+const BOMB_SHAKE: (f64, f64) = (14.0, 0.5);
+
+/// The number of gems dropped by a destroyed enemy, and by the boss.
+const ENEMY_GEMS: usize = 3;
+const BOSS_GEMS: usize = 24;
+
+/// How long the number of a new wave is shown, in seconds.
+const WAVE_BANNER_TIME: f64 = 2.0;
+
+/// How often, in ticks, dev builds report on a running demo.
+const DEMO_REPORT_TICKS: u64 = 60 * 60;
+
+// Constants about the snapshot recorder
+const SNAPSHOT_TICKS: u64 = 60;
+const MAX_SNAPSHOTS: usize = 30;
+
+/// The cannons which the player may switch between, in order.
+const CANNONS: [CannonType; 4] = [
+    CannonType::RectBullet,
+    CannonType::SineBullet { amplitude: 10.0, angular_vel: 15.0 },
+    CannonType::DevergentBullet { a: 100.0, b: 1.2 },
+    CannonType::HomingMissile,
+];
+
+/// How fast the game goes on while the player picks a cannon from the
+/// radial selector, relative to its normal speed.
+const CANNON_SELECT_TIME_SCALE: f64 = 0.3;
+
 /// The different states our ship might be in. In the image, they're ordered
 /// from left to right, then top to bottom.
 #[derive(Clone, Copy)]
@@ -51,47 +241,748 @@ enum PlayerFrame {
     DownSlow = 8
 }
 
+/// A looping engine sound which follows the ship's movement: a low hum which
+/// grows louder with its speed, over which a higher one comes in while it flies
+/// forward, so that the engine seems to rise in pitch.
+struct EngineHum {
+    low: Chunk,
+    high: Chunk,
+
+    // The channels on which both layers are looping, once they were started.
+    channels: Option<(Channel, Channel)>,
+
+    // The current volume of both layers, relative to `HUM_VOLUME`.
+    levels: (f64, f64),
+}
+
+impl EngineHum {
+    fn new() -> Result<EngineHum, PhiError> {
+        Ok(EngineHum {
+            low: EngineHum::layer(HUM_PITCHES.0)?,
+            high: EngineHum::layer(HUM_PITCHES.1)?,
+            channels: None,
+            levels: (0.0, 0.0),
+        })
+    }
+
+    /// Synthesizes one second of a buzzing tone in the mixer's format. Since
+    /// the pitch is a whole number of Hz, it loops without a click.
+    fn layer(pitch: f64) -> Result<Chunk, PhiError> {
+        let (frequency, _, channels) = ::sdl2::mixer::query_spec().map_err(PhiError::Audio)?;
+
+        let buffer: Vec<i16> = (0..frequency as usize)
+            .flat_map(|i| {
+                let phase = i as f64 / frequency as f64 * pitch * 2.0 * ::std::f64::consts::PI;
+                let value = (phase.sin() + 0.5 * (2.0 * phase).sin() + 0.25 * (3.0 * phase).sin()) * 4000.0;
+                ::std::iter::repeat_n(value as i16, channels as usize)
+            })
+            .collect();
+
+        Chunk::from_raw_buffer(buffer.into_boxed_slice()).map_err(PhiError::Audio)
+    }
+
+    /// Moves the volume of both layers towards what the ship is doing.
+    fn update(&mut self, player: &Player, elapsed: f64) {
+        if self.channels.is_none() {
+            //? Playing fails when every channel is busy, in which case we try
+            //? again on the next tick.
+            let low = Channel::all().play(&self.low, -1);
+            let high = Channel::all().play(&self.high, -1);
+            match (low, high) {
+                (Ok(low), Ok(high)) => self.channels = Some((low, high)),
+                (low, high) => {
+                    low.into_iter().chain(high).for_each(Channel::halt);
+                    return;
+                },
+            }
+        }
+
+        let throttle = (player.speed(elapsed) / player.spec.speed).min(1.0);
+        let forward = matches!(player.current,
+            PlayerFrame::UpFast | PlayerFrame::MidFast | PlayerFrame::DownFast);
+        let targets = (throttle, if forward { throttle } else { 0.0 });
+
+        let step = HUM_RESPONSE * elapsed;
+        let follow = |level: f64, target: f64| level + (target - level).clamp(-step, step);
+        self.levels = (follow(self.levels.0, targets.0), follow(self.levels.1, targets.1));
+        self.apply();
+    }
+
+    /// Silences the engine right away, e.g. while the game is paused.
+    fn silence(&mut self) {
+        self.levels = (0.0, 0.0);
+        self.apply();
+    }
+
+    fn apply(&self) {
+        if let Some((low, high)) = self.channels {
+            let volume = |level: f64| (level * HUM_VOLUME * MAX_VOLUME as f64) as i32;
+            low.set_volume(volume(self.levels.0));
+            high.set_volume(volume(self.levels.1));
+        }
+    }
+}
+
+impl Drop for EngineHum {
+    //? The channels would otherwise keep looping until the chunks are freed,
+    //? and then keep the low volume of the engine for whichever sound plays
+    //? on them next.
+    fn drop(&mut self) {
+        if let Some((low, high)) = self.channels.take() {
+            for channel in [low, high] {
+                channel.halt();
+                channel.set_volume(MAX_VOLUME);
+            }
+        }
+    }
+}
+
+/// What the player's ship is asked to do during a tick, either by the player
+/// or by the demo bot.
+#[derive(Clone, Copy, Default)]
+pub struct Controls {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    fire: bool,
+    bomb: bool,
+
+    // The cannon to switch to, if any.
+    cannon: Option<CannonType>,
+}
+
+impl Controls {
+    /// Read the controls from the keyboard.
+    pub fn from_events(phi: &Phi) -> Controls {
+        // Change the player's cannons
+        let cannon =
+            if phi.events.now.key_1 == Some(true) {
+                Some(CANNONS[0])
+            } else if phi.events.now.key_2 == Some(true) {
+                Some(CANNONS[1])
+            } else if phi.events.now.key_3 == Some(true) {
+                Some(CANNONS[2])
+            } else if phi.events.now.key_4 == Some(true) {
+                Some(CANNONS[3])
+            } else {
+                None
+            };
+
+        Controls {
+            up: phi.events.key_up,
+            down: phi.events.key_down,
+            left: phi.events.key_left,
+            right: phi.events.key_right,
+            // Keep firing for as long as the key is held.
+            fire: phi.events.key_space,
+            bomb: phi.events.now.key_bomb == Some(true),
+            cannon,
+        }
+    }
+}
+
+/// Plays the game on its own in demo mode. It shoots at the closest asteroid
+/// in front of the ship and dodges the ones which are about to hit it.
+struct Bot {
+    // Time left before the bot may fire again, in seconds.
+    fire_cooldown: f64,
+
+    // The asteroids, by where they are, and the path which the bot's bullets
+    // would follow, as of the last tick.
+    sight: SpatialGrid<usize>,
+    line_of_fire: Segment,
+}
+
+impl Bot {
+    fn new() -> Bot {
+        Bot {
+            fire_cooldown: 0.0,
+            sight: SpatialGrid::new(GRID_CELL),
+            line_of_fire: Segment { from: (0.0, 0.0), to: (0.0, 0.0) },
+        }
+    }
+
+    fn controls(&mut self, player: &Player, asteroids: &[Asteroid], dt: f64) -> Controls {
+        let mut controls = Controls::default();
+        let ship = player.rect;
+        let (ship_x, ship_y) = ship.center();
+
+        // Keep some distance from the left edge, so that there is room to
+        // dodge in every direction.
+        controls.left = ship.x > BOT_HOME_X + 20.0;
+        controls.right = ship.x < BOT_HOME_X - 20.0;
+
+        // The closest asteroid which is about to hit the ship, if any.
+        let threat = asteroids.iter()
+            .filter(|a| a.rect.x + a.rect.w > ship.x && a.rect.x < ship.x + ship.w + BOT_DANGER_DIST)
+            .filter(|a| a.rect.y < ship.y + ship.h + BOT_MARGIN && a.rect.y + a.rect.h > ship.y - BOT_MARGIN)
+            .min_by(|a, b| a.rect.x.partial_cmp(&b.rect.x).unwrap());
+
+        // The closest asteroid in front of the ship, which we aim at.
+        let target = asteroids.iter()
+            .filter(|a| a.rect.x > ship_x)
+            .min_by(|a, b| a.rect.x.partial_cmp(&b.rect.x).unwrap());
+
+        if let Some(threat) = threat {
+            // Move away from the center of the asteroid.
+            let (_, threat_y) = threat.rect.center();
+            controls.up = ship_y <= threat_y;
+            controls.down = ship_y > threat_y;
+        } else if let Some(target) = target {
+            let (_, target_y) = target.rect.center();
+            controls.up = ship_y > target_y + BOT_AIM_TOLERANCE;
+            controls.down = ship_y < target_y - BOT_AIM_TOLERANCE;
+        }
+
+        // Fire whenever an asteroid is lined up.
+        self.sight.clear();
+        for (i, asteroid) in asteroids.iter().enumerate() {
+            self.sight.insert(asteroid.rect, i);
+        }
+
+        let nose = ship.x + ship.w;
+        self.line_of_fire = Segment { from: (nose, ship_y), to: (nose + BOT_SIGHT, ship_y) };
+        self.fire_cooldown -= dt;
+        let aligned = self.sight.raycast(self.line_of_fire).is_some();
+
+        if aligned && self.fire_cooldown <= 0.0 {
+            controls.fire = true;
+            self.fire_cooldown = BOT_FIRE_DELAY;
+        }
+
+        controls
+    }
+
+    /// Shows where the bot's bullets would go, and outlines every asteroid in
+    /// their way.
+    fn render_sight(&self, phi: &mut Phi, camera: &Camera) {
+        let Segment { from, to } = self.line_of_fire;
+        draw::line(&mut phi.renderer, camera.view_point(from), camera.view_point(to), Color::RGB(200, 50, 200), false);
+
+        for (t, _) in self.sight.raycast_all(self.line_of_fire) {
+            let hit = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+            let mark = Rectangle::with_size(6.0, 6.0).center_at(hit);
+            draw::outline_rect(&mut phi.renderer, camera.view(mark), Color::RGB(200, 50, 200));
+        }
+    }
+}
+
+/// Everything which is specific to a game played by the bot.
+struct Demo {
+    bot: Bot,
+    watermark: Sprite,
+
+    // The number of ticks the demo has been running for.
+    ticks: u64,
+}
+
+/// Everything which is specific to a game directed by a trailer script.
+struct Trailer {
+    script: TrailerScript,
+
+    // Flies the ship until the script hands it over to the player, whose
+    // inputs may come from a replay.
+    bot: Option<Bot>,
+
+    // Whether the HUD is shown, and whether the waves of the level spawn
+    // alongside what the script spawns.
+    show_hud: bool,
+    waves: bool,
+}
+
+/// The state of the simulation at some tick, along with the controls which
+/// were used during the ticks which followed.
+struct Snapshot {
+    tick: u64,
+    player: Player,
+    bullets: BulletPool,
+    asteroids: Vec<Asteroid>,
+    enemies: Vec<Enemy>,
+    enemy_bullets: BulletPool,
+    explosions: Vec<Explosion>,
+    pickups: Pickups,
+    boss: Option<Boss>,
+    combo: Combo,
+    stats: RunStats,
+    score: u64,
+    lives: u32,
+    bombs: u32,
+    director: LevelDirector,
+    survival: Option<SurvivalDirector>,
+
+    // The state of the random number generator which gameplay draws from, so
+    // that stepping forward from the snapshot plays out as it did.
+    rng: StdRng,
+
+    inputs: Vec<Controls>,
+}
+
+/// A dev tool which takes a snapshot of the simulation every
+/// `SNAPSHOT_TICKS` ticks. Once a bug was observed, the game may be frozen on
+/// any recent snapshot, from which we step forward one tick at a time while
+/// watching the debug overlay.
+///
+/// F5 freezes and resumes the game, Left and Right pick an older or a newer
+/// snapshot, and F6 simulates a single tick using the recorded controls.
+struct Recorder {
+    snapshots: VecDeque<Snapshot>,
+
+    // The number of ticks which were simulated since the game started.
+    ticks: u64,
+
+    // While frozen, the snapshot which was loaded and the number of ticks
+    // which were simulated since.
+    frozen: Option<(usize, usize)>,
+}
+
+impl Recorder {
+    fn new() -> Recorder {
+        Recorder {
+            snapshots: VecDeque::with_capacity(MAX_SNAPSHOTS),
+            ticks: 0,
+            frozen: None,
+        }
+    }
+
+    /// Called before every tick which is simulated while the game is not
+    /// frozen.
+    fn record(&mut self, phi: &mut Phi, game: &GameView, controls: Controls) {
+        if self.ticks.is_multiple_of(SNAPSHOT_TICKS) {
+            if self.snapshots.len() == MAX_SNAPSHOTS {
+                self.snapshots.pop_front();
+            }
+
+            self.snapshots.push_back(Snapshot {
+                tick: self.ticks,
+                player: game.player.clone(),
+                bullets: game.bullets.clone(),
+                asteroids: game.asteroids.clone(),
+                enemies: game.enemies.clone(),
+                enemy_bullets: game.enemy_bullets.clone(),
+                explosions: game.explosions.clone(),
+                pickups: game.pickups.clone(),
+                boss: game.boss.clone(),
+                combo: game.combo,
+                stats: game.stats,
+                score: game.score,
+                lives: game.lives,
+                bombs: game.bombs,
+                director: game.director.clone(),
+                survival: game.survival,
+                rng: phi.rng().clone(),
+                inputs: vec![],
+            });
+        }
+
+        if let Some(snapshot) = self.snapshots.back_mut() {
+            snapshot.inputs.push(controls);
+        }
+
+        self.ticks += 1;
+    }
+
+    /// Restores the state of the game from the snapshot at `index`.
+    fn load(&mut self, index: usize, phi: &mut Phi, game: &mut GameView) {
+        let snapshot = &self.snapshots[index];
+        game.player = snapshot.player.clone();
+        game.bullets = snapshot.bullets.clone();
+        game.asteroids = snapshot.asteroids.clone();
+        game.enemies = snapshot.enemies.clone();
+        game.enemy_bullets = snapshot.enemy_bullets.clone();
+        game.explosions = snapshot.explosions.clone();
+        game.pickups = snapshot.pickups.clone();
+        game.boss = snapshot.boss.clone();
+        game.combo = snapshot.combo;
+        game.stats = snapshot.stats;
+        game.score = snapshot.score;
+        game.lives = snapshot.lives;
+        game.bombs = snapshot.bombs;
+        game.director = snapshot.director.clone();
+        game.survival = snapshot.survival;
+        *phi.rng() = snapshot.rng.clone();
+
+        self.frozen = Some((index, 0));
+        if DEBUG {
+            println!("Loaded the snapshot of tick {}", snapshot.tick);
+        }
+    }
+
+    /// Forgets whatever was recorded after the current tick, so that
+    /// recording may go on from there.
+    fn resume(&mut self) {
+        if let Some((index, stepped)) = self.frozen.take() {
+            self.snapshots.truncate(index + 1);
+            self.snapshots[index].inputs.truncate(stepped);
+            self.ticks = self.snapshots[index].tick + stepped as u64;
+        }
+    }
+
+    /// Handles the recorder's keys. Returns whether the game is frozen and,
+    /// if so, the controls with which to simulate a single tick, if the
+    /// player asked for one.
+    fn update(&mut self, phi: &mut Phi, game: &mut GameView) -> (bool, Option<Controls>) {
+        if phi.events.now.key_record == Some(true) {
+            match self.frozen {
+                Some(_) => self.resume(),
+                None if !self.snapshots.is_empty() => self.load(self.snapshots.len() - 1, phi, game),
+                None => {},
+            }
+        }
+
+        let (index, stepped) = match self.frozen {
+            Some(frozen) => frozen,
+            None => return (false, None),
+        };
+
+        if phi.events.now.key_left == Some(true) && index > 0 {
+            self.load(index - 1, phi, game);
+        } else if phi.events.now.key_right == Some(true) && index + 1 < self.snapshots.len() {
+            self.load(index + 1, phi, game);
+        }
+
+        let (index, stepped) = self.frozen.unwrap_or((index, stepped));
+        let snapshot = &self.snapshots[index];
+        phi.stats.set("Snapshot", index);
+        phi.stats.set("Tick", (snapshot.tick as usize) + stepped);
+
+        if phi.events.now.key_step != Some(true) {
+            return (true, None);
+        }
+
+        // Past the recorded inputs, the ship simply stops.
+        let controls = snapshot.inputs.get(stepped).cloned().unwrap_or_default();
+        self.frozen = Some((index, stepped + 1));
+        (true, Some(controls))
+    }
+}
+
+/// For how long the confirm key must be held on "Main Menu" in the pause menu,
+/// in seconds, since it throws the run away.
+const HOLD_TO_QUIT: f64 = 0.8;
+
+/// The keys which confirm the choice of a menu.
+const CONFIRM_KEYS: [Key; 2] = [Key::key_enter, Key::key_space];
+
+/// What the player chose to do from the pause menu.
+enum PauseAction {
+    Resume,
+    SaveAndQuit,
+    QuitToMenu,
+}
+
+/// The menu shown on top of the game while it is paused.
+struct Pause {
+    menu: Menu,
+    resume: WidgetId,
+    save: WidgetId,
+    quit: WidgetId,
+
+    // Whether the game was paused because the player's gamepad was
+    // unplugged, in which case it resumes as soon as one is plugged back in.
+    disconnected: bool,
+
+    // Whether the player started holding a confirm key on "Main Menu".
+    holding_quit: bool,
+}
+
+impl Pause {
+    fn new(phi: &mut Phi, disconnected: bool) -> Result<Pause, PhiError> {
+        let mut menu = Menu::new(420.0, 50.0);
+
+        if disconnected {
+            menu.add(phi, "Controller disconnected", WidgetKind::Label)?;
+            menu.add(phi, "Reconnect it to resume", WidgetKind::Label)?;
+        }
+
+        Ok(Pause {
+            resume: menu.add(phi, "Resume", WidgetKind::Button)?,
+            save: menu.add(phi, "Save & Quit", WidgetKind::Button)?,
+            quit: menu.add(phi, "Main Menu (hold)", WidgetKind::Button)?,
+            menu,
+            disconnected,
+            holding_quit: false,
+        })
+    }
+
+    fn update(&mut self, phi: &mut Phi) -> Option<PauseAction> {
+        if phi.events.now.key_escape == Some(true) ||
+           (self.disconnected && phi.events.now.controller_added) {
+            return Some(PauseAction::Resume);
+        }
+
+        let event = self.menu.update(phi);
+
+        //? Going back to the main menu with the keyboard requires holding the
+        //? confirm key, so that the run is not thrown away by mistake. The
+        //? hold only counts if it started on the button.
+        if self.menu.focused() == Some(self.quit) {
+            if CONFIRM_KEYS.iter().any(|&key| phi.events.pressed(key)) {
+                self.holding_quit = true;
+            }
+            if CONFIRM_KEYS.iter().any(|&key| phi.events.released(key)) {
+                self.holding_quit = false;
+            }
+        } else {
+            self.holding_quit = false;
+        }
+
+        let held = CONFIRM_KEYS.iter().any(|&key| phi.events.held_for(key) >= HOLD_TO_QUIT);
+        if self.holding_quit && held {
+            return Some(PauseAction::QuitToMenu);
+        }
+
+        match event {
+            Some(UiEvent::Clicked(id)) if id == self.resume => Some(PauseAction::Resume),
+            Some(UiEvent::Clicked(id)) if id == self.save => Some(PauseAction::SaveAndQuit),
+            Some(UiEvent::Clicked(id)) if id == self.quit && phi.events.now.mouse_click.is_some() =>
+                Some(PauseAction::QuitToMenu),
+            _ => None,
+        }
+    }
+}
+
+/// The spinning asteroid, which is `ASTEROID_SIDE` pixels wide, at one frame
+/// per second until its speed is set.
+pub fn asteroid_sprite(phi: &mut Phi) -> Result<AnimatedSprite, PhiError> {
+    Ok(AnimatedSprite::with_fps(
+        AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
+            image_path: ASTEROID_PATH,
+            total_frames: ASTEROID_TOTAL,
+            frames_high: ASTEROID_HIGH,
+            frames_wide: ASTEROID_WIDE,
+            frame_w: ASTEROID_SIDE,
+            frame_h: ASTEROID_SIDE,
+        })?, 1.0))
+}
+
+/// The explosion, which is `EXPLOSION_SIDE` pixels wide, and plays once.
+pub fn explosion_sprite(phi: &mut Phi) -> Result<AnimatedSprite, PhiError> {
+    let mut sprite = AnimatedSprite::with_fps(
+        AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
+            image_path: EXPLOSION_PATH,
+            total_frames: EXPLOSIONS_TOTAL,
+            frames_high: EXPLOSIONS_HIGH,
+            frames_wide: EXPLOSIONS_WIDE,
+            frame_w: EXPLOSION_SIDE,
+            frame_h: EXPLOSION_SIDE,
+        })?, EXPLOSION_FPS);
+
+    // The explosion is destroyed once its animation is finished.
+    sprite.set_mode(PlayMode::Once);
+    Ok(sprite)
+}
+
+/// Asteroids break into smaller ones when they are shot, until they are too
+/// small to break any further.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    /// Every size, from the largest to the smallest.
+    pub const ALL: [AsteroidSize; 3] = [AsteroidSize::Large, AsteroidSize::Medium, AsteroidSize::Small];
+
+    /// The length of the side of an asteroid's bounding box.
+    pub fn side(self) -> f64 {
+        match self {
+            AsteroidSize::Large => ASTEROID_SIDE,
+            AsteroidSize::Medium => ASTEROID_SIDE * 0.6,
+            AsteroidSize::Small => ASTEROID_SIDE * 0.35,
+        }
+    }
+
+    /// The points earned by shooting an asteroid down. Smaller ones are
+    /// harder to hit, and so are worth more.
+    pub fn score(self) -> u64 {
+        match self {
+            AsteroidSize::Large => 20,
+            AsteroidSize::Medium => 50,
+            AsteroidSize::Small => 100,
+        }
+    }
+
+    /// The damage which an asteroid takes before it breaks.
+    pub fn health(self) -> f64 {
+        match self {
+            AsteroidSize::Large => 3.0,
+            AsteroidSize::Medium => 2.0,
+            AsteroidSize::Small => 1.0,
+        }
+    }
+
+    /// The size of the fragments an asteroid breaks into, if any.
+    pub fn fragments(self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Small => None,
+        }
+    }
+}
+
+/// The velocities of two round bodies, at `a` and `b`, after they bumped into
+/// each other. Returns `None` if they are already moving apart.
+///
+/// Every body is given as its center, velocity and mass.
+fn bounce(a: (Vec2, Vec2, f64), b: (Vec2, Vec2, f64)) -> Option<(Vec2, Vec2)> {
+    let (a_pos, a_vel, am) = a;
+    let (b_pos, b_vel, bm) = b;
+
+    // The direction of the impact, from `a` to `b`.
+    let normal = match (b_pos - a_pos).normalize() {
+        Vec2::ZERO => Vec2::new(1.0, 0.0),
+        normal => normal,
+    };
+
+    //? Only the velocity along the direction of the impact changes. Both
+    //? bodies receive the same impulse, in opposite directions, so that the
+    //? heavier one is the least affected.
+    let closing = (b_vel - a_vel).dot(normal);
+    if closing >= 0.0 {
+        return None;
+    }
+
+    let impulse = -(1.0 + RESTITUTION) * closing / (1.0 / am + 1.0 / bm);
+    Some((a_vel - normal * (impulse / am), b_vel + normal * (impulse / bm)))
+}
+
 #[derive(Clone)]
 struct Asteroid {
+    name: EntityName,
     sprite: AnimatedSprite,
+    size: AsteroidSize,
     rect: Rectangle,
-    vel: f64,
+
+    // The velocity of the asteroid, in pixels per second, which always
+    // points to the left. Only the fragments of other asteroids drift
+    // vertically.
+    vel: Vec2,
+
+    // Where the asteroid was before the last tick, so that rendering may
+    // interpolate between both positions.
+    prev_rect: Rectangle,
+
+    // Whether the asteroid is within the graze zone of the ship, and whether
+    // it already grazed it, so that it only earns points once.
+    grazing: bool,
+    grazed: bool,
+
+    health: Health,
 }
 
 impl Asteroid {
-    fn factory(phi: &mut Phi) -> AsteroidFactory {
-        AsteroidFactory {
-            sprite: AnimatedSprite::with_fps(
-                AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
-                    image_path: ASTEROID_PATH,
-                    total_frames: ASTEROID_TOTAL,
-                    frames_high: ASTEROID_HIGH,
-                    frames_wide: ASTEROID_WIDE,
-                    frame_w: ASTEROID_SIDE,
-                    frame_h: ASTEROID_SIDE,
-                }), 1.0),
+    fn factory(phi: &mut Phi, distribution: SpawnDistribution) -> Result<AsteroidFactory, PhiError> {
+        Ok(AsteroidFactory {
+            spawner: Spawner::new(distribution),
+            sprite: asteroid_sprite(phi)?,
+            outlines: phi.sprites.outlines(&phi.renderer, ASTEROID_PATH,
+                (ASTEROID_SIDE as u32, ASTEROID_SIDE as u32), TARGET_OUTLINE_THICKNESS)?,
+        })
+    }
+
+    /// The mass of the asteroid, relative to that of the largest ones.
+    fn mass(&self) -> f64 {
+        (self.rect.w / ASTEROID_SIDE).powi(2)
+    }
+
+    /// The center, velocity and mass of the asteroid, as used by `bounce`.
+    fn body(&self) -> (Vec2, Vec2, f64) {
+        (self.rect.center().into(), self.vel, self.mass())
+    }
+
+    /// Sets the velocity of the asteroid, which keeps heading to the left at
+    /// `ASTEROID_MIN_VEL` or faster.
+    fn set_vel(&mut self, vel: Vec2) {
+        self.vel = Vec2::new(vel.x.min(-ASTEROID_MIN_VEL), vel.y);
+    }
+
+    /// Lets the asteroid bounce off `other` if they overlap.
+    fn collide(&mut self, other: &mut Asteroid) {
+        if !self.shape().overlaps(other.shape()) {
+            return;
+        }
+
+        if let Some((a_vel, b_vel)) = bounce(self.body(), other.body()) {
+            self.set_vel(a_vel);
+            other.set_vel(b_vel);
+        }
+    }
+
+    /// The asteroid's body, which is round.
+    fn shape(&self) -> Shape {
+        Shape::Circle(Circle {
+            center: self.rect.center().into(),
+            radius: self.rect.w * ASTEROID_RADIUS,
+        })
+    }
+
+    /// The area around the asteroid in which other asteroids are steered
+    /// away from it.
+    fn personal_space(&self) -> Rectangle {
+        Rectangle::with_size(self.rect.w + 2.0 * SEPARATION_MARGIN, self.rect.h + 2.0 * SEPARATION_MARGIN)
+            .center_at(self.rect.center())
+    }
+
+    /// Steers the asteroid and `other` apart if they are too close, the more
+    /// so the closer they are, so that they do not clump together.
+    fn separate(&mut self, other: &mut Asteroid, dt: f64) {
+        let apart = Vec2::from(other.rect.center()) - Vec2::from(self.rect.center());
+        let dist = apart.length();
+        let range = (self.rect.w + other.rect.w) / 2.0 + SEPARATION_MARGIN;
+        if dist >= range {
+            return;
+        }
+
+        let normal = if dist > 0.0 { apart / dist } else { Vec2::new(0.0, 1.0) };
+        let push = normal * (SEPARATION_STRENGTH * (1.0 - dist / range) * dt);
+
+        self.set_vel(self.vel - push);
+        other.set_vel(other.vel + push);
+    }
+
+    /// Keeps the asteroid between the walls at the heights `top` and
+    /// `bottom`, bouncing off whichever it ran into.
+    fn bounce_off_walls(&mut self, (top, bottom): (f64, f64)) {
+        let normal =
+            if self.rect.y < top { Vec2::new(0.0, 1.0) }
+            else if self.rect.y + self.rect.h > bottom { Vec2::new(0.0, -1.0) }
+            else { return };
+
+        self.rect.y = self.rect.y.clamp(top, bottom - self.rect.h);
+
+        // Only bounce if the asteroid is still heading into the wall.
+        if self.vel.dot(normal) < 0.0 {
+            self.vel = reflect(self.vel, normal);
         }
     }
 
     fn update(mut self, dt: f64) -> Option<Asteroid>{
-        self.rect.x -= dt * self.vel;
+        self.prev_rect = self.rect;
+        self.rect = self.rect.translated(self.vel * dt);
         self.sprite.add_time(dt);
+        self.health.update(dt);
 
-        if self.rect.x <= -ASTEROID_SIDE {
+        if self.rect.x <= -self.rect.w {
             None
         } else {
             Some(self)
         }
     }
 
-    fn render(&self, phi: &mut Phi) {
-        if DEBUG {
-            // Render the bounding box.
-            phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect().to_sdl()).unwrap();
-        }
+    fn render(&self, phi: &mut Phi, camera: &Camera, alpha: f64) {
+        let dest = camera.view(self.prev_rect.lerp(self.rect, alpha));
+        phi.renderer.copy_sprite_tinted(&self.sprite, dest, self.health.tinted(Tint::NONE));
+    }
 
-        phi.renderer.copy_sprite(&self.sprite, self.rect);
+    /// Renders the outline of the current frame, taken from `outlines`, to
+    /// highlight the asteroid. Must be called before `render`.
+    fn render_outline(&self, phi: &mut Phi, camera: &Camera, alpha: f64, outlines: &[Outline]) {
+        let dest = camera.view(self.prev_rect.lerp(self.rect, alpha));
+        outlines[self.sprite.current_frame()].render(&mut phi.renderer, dest, TARGET_OUTLINE);
     }
 
     fn rect(&self) -> Rectangle {
@@ -101,69 +992,140 @@ impl Asteroid {
 
 struct AsteroidFactory {
     sprite: AnimatedSprite,
+    spawner: Spawner,
+
+    // The outlines of the frames of `sprite`, in the same order.
+    outlines: Rc<Vec<Outline>>,
 }
 
 impl AsteroidFactory {
-    fn random(&self, phi: &mut Phi) -> Asteroid {
-        let (w, h) = phi.output_size();
+    /// Creates an asteroid to the right of the screen, at a height picked by
+    /// the spawner, whose velocity is multiplied by `speed`.
+    ///
+    /// An asteroid which would reach the `player` in less than
+    /// `reaction_time` seconds never spawns in line with it, since it could
+    /// not be avoided. Returns `None` if there is no room for it elsewhere.
+    fn random(&mut self, phi: &mut Phi, speed: f64, player: Rectangle, reaction_time: f64, (top, bottom): (f64, f64)) -> Option<Asteroid> {
+        let (w, _) = phi.output_size();
+        let vel = (phi.rng().gen::<f64>().abs() * 100.0 + 50.0) * speed;
 
         // FPS in [10.0, 30.0)
         let mut sprite = self.sprite.clone();
-        sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
+        sprite.set_fps(phi.rng().gen::<f64>().abs() * 20.0 + 10.0);
+
+        // Some asteroids rock back and forth rather than spin.
+        if phi.rng().gen::<f64>() < 0.25 {
+            sprite.set_mode(PlayMode::PingPong);
+        }
+
+        // If the asteroid is too fast, it may not spawn in line with the
+        // player.
+        let max_y = bottom - top - ASTEROID_SIDE;
+        let time_to_player = (w - (player.x + player.w)) / vel;
+        let lane = ((player.y - top - ASTEROID_SIDE).max(0.0), (player.y - top + player.h).min(max_y));
+        let y = top + self.spawner.pick(phi.rng(), max_y, lane, time_to_player < reaction_time)?;
+
+        // In the screen vertically, and over the right of the screen
+        // horizontally
+        let rect = Rectangle {
+            w: ASTEROID_SIDE,
+            h: ASTEROID_SIDE,
+            x: w,
+            y,
+        };
 
-        Asteroid {
+        Some(Asteroid {
+            name: phi.ids.name("asteroid"),
             sprite: sprite,
+            size: AsteroidSize::Large,
+            rect,
+            vel: Vec2::new(-vel, 0.0),
+            prev_rect: rect,
+            grazing: false,
+            grazed: false,
+            health: Health::new(AsteroidSize::Large.health()),
+        })
+    }
 
-            // In the screen vertically, and over the right of the screen
-            // horizontally
-            rect: Rectangle {
-                w: ASTEROID_SIDE,
-                h: ASTEROID_SIDE,
-                x: w,
-                y: ::rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
-            },
-            vel: ::rand::random::<f64>().abs() * 100.0 + 50.0,
-        }
+    /// Creates an asteroid from its `saved` form, or returns `None` if its
+    /// size is unknown.
+    fn restore(&self, phi: &mut Phi, saved: &SavedAsteroid) -> Option<Asteroid> {
+        let size = *AsteroidSize::ALL.get(saved.size)?;
+        let rect = Rectangle { x: saved.x, y: saved.y, w: size.side(), h: size.side() };
+
+        let mut sprite = self.sprite.clone();
+        sprite.set_fps(phi.rng().gen::<f64>().abs() * 20.0 + 10.0);
+
+        Some(Asteroid {
+            name: phi.ids.name("asteroid"),
+            sprite,
+            size,
+            rect,
+            vel: Vec2::new(-saved.vel, saved.drift),
+            prev_rect: rect,
+            grazing: false,
+            grazed: false,
+            health: Health::with_fraction(size.health(), saved.health),
+        })
+    }
+
+    /// Breaks `parent` into two or three smaller asteroids, which keep going
+    /// in about the same direction but spread apart vertically. Returns no
+    /// fragments if the asteroid is already as small as they get.
+    fn fragments(&self, phi: &mut Phi, parent: &Asteroid) -> Vec<Asteroid> {
+        let size = match parent.size.fragments() {
+            Some(size) => size,
+            None => return vec![],
+        };
+
+        let count = if phi.rng().gen::<bool>() { 2 } else { 3 };
+        (0..count).map(|_| {
+            let rect = Rectangle::with_size(size.side(), size.side())
+                .center_at(parent.rect.center());
+
+            let mut sprite = parent.sprite.clone();
+            sprite.set_fps(phi.rng().gen::<f64>().abs() * 20.0 + 10.0);
+
+            Asteroid {
+                name: phi.ids.name("asteroid"),
+                sprite,
+                size,
+                rect,
+                vel: Vec2::new(
+                    parent.vel.x * (1.0 + phi.rng().gen::<f64>() * 0.5),
+                    parent.vel.y + (phi.rng().gen::<f64>() * 2.0 - 1.0) * 80.0),
+                prev_rect: rect,
+                grazing: false,
+                grazed: false,
+                health: Health::new(size.health()),
+            }
+        }).collect()
     }
 }
 
+#[derive(Clone)]
 struct Explosion {
     sprite: AnimatedSprite,
     rect: Rectangle,
-
-    // Keep how long its been arived, so that we destroy the explosion once
-    // its animation is finished.
-    alive_since: f64,
 }
 
 impl Explosion {
-    fn factory(phi: &mut Phi) -> ExplosionFactory {
-        ExplosionFactory {
-            sprite: AnimatedSprite::with_fps(
-                AnimatedSprite::load_frames(phi, AnimatedSpriteDescr {
-                    image_path: EXPLOSION_PATH,
-                    total_frames: EXPLOSIONS_TOTAL,
-                    frames_high: EXPLOSIONS_HIGH,
-                    frames_wide: EXPLOSIONS_WIDE,
-                    frame_w: EXPLOSION_SIDE,
-                    frame_h: EXPLOSION_SIDE,
-                }), EXPLOSION_FPS),
-        }
+    fn factory(phi: &mut Phi) -> Result<ExplosionFactory, PhiError> {
+        Ok(ExplosionFactory { sprite: explosion_sprite(phi)? })
     }
 
     fn update(mut self, dt: f64) -> Option<Explosion> {
-        self.alive_since += dt;
         self.sprite.add_time(dt);
 
-        if self.alive_since >= EXPLOSION_DURATION {
+        if self.sprite.is_finished() {
             None
         } else {
             Some(self)
         }
     }
 
-    fn render(&self, phi: &mut Phi) {
-        phi.renderer.copy_sprite(&self.sprite, self.rect);
+    fn render(&self, phi: &mut Phi, camera: &Camera) {
+        phi.renderer.copy_sprite_through(&self.sprite, self.rect, camera);
     }
 }
 
@@ -172,9 +1134,9 @@ struct ExplosionFactory {
 }
 
 impl ExplosionFactory {
-    fn at_center(&self, center: (f64, f64)) -> Explosion {
-        // FPS in [10.0, 30.0)
-        let sprite = self.sprite.clone();
+    fn at_center(&self, phi: &Phi, center: (f64, f64)) -> Explosion {
+        let mut sprite = self.sprite.clone();
+        sprite.set_fps(EXPLOSION_FPS * phi.effects.animation_speed());
 
         Explosion {
             sprite: sprite,
@@ -182,110 +1144,372 @@ impl ExplosionFactory {
             // In the screen vertically, and over the right of the screen
             // horizontally
             rect: Rectangle::with_size(EXPLOSION_SIDE, EXPLOSION_SIDE).center_at(center),
-
-            alive_since: 0.0,
         }
     }
 }
 
+impl EnemyPattern {
+    /// The color of the ships which follow the pattern, so that the player
+    /// may tell them apart.
+    fn tint(self) -> Tint {
+        Tint::color(match self {
+            EnemyPattern::Straight => Color::RGB(255, 140, 140),
+            EnemyPattern::Sine => Color::RGB(140, 255, 140),
+            EnemyPattern::Dive => Color::RGB(200, 140, 255),
+        })
+    }
+}
+
 #[derive(Clone)]
-struct Player {
+struct Enemy {
+    name: EntityName,
+    pattern: EnemyPattern,
     rect: Rectangle,
-    sprites: Vec<Sprite>,
-    current: PlayerFrame,
-    cannon: CannonType,
+    prev_rect: Rectangle,
+
+    // The sprites of the ship going up, straight and down, in that order.
+    sprites: Rc<Vec<Sprite>>,
+    current: usize,
+
+    // The height around which a `Sine` enemy waves, and for how long the
+    // enemy has been alive, in seconds.
+    origin_y: f64,
+    time: f64,
+
+    // Time left before the enemy fires at the player, in seconds, the
+    // pattern in which it fires, and the one being fired, if any.
+    fire_cooldown: f64,
+    bullets: BulletPattern,
+    volley: Option<PatternPlayer>,
+
+    health: Health,
 }
 
-impl Player {
-    pub fn new(phi: &mut Phi) -> Player {
-        // Get the spaceship's sprites.
-        let spritesheet = Sprite::load(&mut phi.renderer, PLAYER_PATH).unwrap();
-        let mut sprites = Vec::with_capacity(9);
-
-        for y in 0..3 {
-            for x in 0..3 {
-                sprites.push(spritesheet.region(Rectangle {
-                    w: PLAYER_W,
-                    h: PLAYER_H,
-                    x: PLAYER_W * x as f64,
-                    y: PLAYER_H * y as f64,
-                }).unwrap());
-            }
-        }
-
-        Player {
-            // Spawn the player at the center of the screen, vertically.
-            rect: Rectangle {
-                x: 64.0,
-                y: (phi.output_size().1 - PLAYER_H) / 2.0,
-                w: PLAYER_W,
-                h: PLAYER_H,
+impl Enemy {
+    fn factory(phi: &mut Phi) -> Result<EnemyFactory, PhiError> {
+        // Enemies use the player's ship, turned around to face it.
+        let frames = ENEMY_SHIP.frames(&phi.renderer)?;
+        let outlines = phi.sprites.outlines(&phi.renderer, ENEMY_SHIP.path,
+            (ENEMY_SHIP.w as u32, ENEMY_SHIP.h as u32), TARGET_OUTLINE_THICKNESS)?;
+        let shown = [PlayerFrame::UpNorm, PlayerFrame::MidNorm, PlayerFrame::DownNorm];
+
+        // Enemies fire the bullet pattern named after the way they move.
+        let patterns = BulletPatterns::load(PATTERNS_PATH)?;
+        let bullets = EnemyPattern::ALL.iter()
+            .map(|pattern| patterns.get(&format!("enemy-{}", pattern.name())))
+            .collect::<Result<_, _>>()?;
+
+        let mut names: Vec<_> = EnemyPattern::ALL.iter().map(|pattern| pattern.name()).collect();
+        names.push("boss");
+
+        Ok(EnemyFactory {
+            sprites: Rc::new(shown.iter().map(|&frame| frames[frame as usize].flipped()).collect()),
+            outlines: Rc::new(shown.iter().map(|&frame| outlines[frame as usize].flipped()).collect()),
+            patterns,
+            bullets,
+            scripts: BehaviorScripts::load(&names)?,
+        })
+    }
+
+    /// Moves the enemy along its pattern, `speed` times as fast as usual.
+    /// Returns `None` once it has left the screen.
+    fn update(mut self, dt: f64, target: (f64, f64), speed: f64, script: Option<&BehaviorScript>) -> Option<Enemy> {
+        self.prev_rect = self.rect;
+        self.time += dt;
+        self.fire_cooldown -= dt;
+        self.health.update(dt);
+
+        // A script may take over the way the enemy moves, along either axis.
+        let inputs = ScriptInputs { pos: self.rect.center(), player: target, time: self.time };
+        let (scripted_x, scripted_y) = script.map_or((false, false), BehaviorScript::moves);
+        let (vx, vy) = script.map_or((0.0, 0.0), |script| script.velocity(inputs, (0.0, 0.0)));
+
+        self.rect.x += if scripted_x { vx } else { -ENEMY_SPEED } * speed * dt;
+
+        let (_, center_y) = self.rect.center();
+        let y = match self.pattern {
+            _ if scripted_y => self.rect.y + vy * speed * dt,
+            EnemyPattern::Straight => self.rect.y,
+            EnemyPattern::Sine =>
+                self.origin_y + ENEMY_SINE_AMPLITUDE * (ENEMY_SINE_FREQ * self.time).sin(),
+            EnemyPattern::Dive => {
+                let max_dy = ENEMY_DIVE_SPEED * speed * dt;
+                let dy = (target.1 - center_y).clamp(-max_dy, max_dy);
+                self.rect.y + dy
             },
-            sprites: sprites,
-            current: PlayerFrame::MidNorm,
-            cannon: CannonType::RectBullet,
+        };
+
+        // Tilt the ship in the direction in which it is moving.
+        let dy = y - self.rect.y;
+        self.current =
+            if dy < -0.1 { 0 }
+            else if dy > 0.1 { 2 }
+            else { 1 };
+        self.rect.y = y;
+
+        if self.rect.x <= -self.rect.w {
+            None
+        } else {
+            Some(self)
         }
     }
 
-    pub fn update(&mut self, phi: &mut Phi, elapsed: f64) {
-        // Change the player's cannons
-        if phi.events.now.key_1 == Some(true) {
-            self.cannon = CannonType::RectBullet;
+    /// Starts firing at `target` whenever the enemy is ready to, and only
+    /// while it is still in front of it, then keeps firing for `dt` seconds.
+    /// Enemies whose `script` fires follow it instead of their pattern.
+    fn fire(&mut self, dt: f64, ids: &mut IdAllocator, target: (f64, f64), pool: &mut BulletPool, script: Option<&BehaviorScript>) {
+        let (x, y) = self.rect.center();
+
+        if let Some(script) = script.filter(|script| script.fires()) {
+            if x >= target.0 {
+                let inputs = ScriptInputs { pos: (x, y), player: target, time: self.time };
+                for (angle, speed) in script.fire(inputs, dt) {
+                    let towards = (self.rect.x + angle.cos(), y + angle.sin());
+                    spawn_aimed_bullet(ids, (self.rect.x, y), towards, speed, pool);
+                }
+            }
+            return;
         }
 
-        if phi.events.now.key_2 == Some(true) {
-            self.cannon = CannonType::SineBullet { 
-                amplitude: 10.0,
-                angular_vel: 15.0,
-            }
+        if self.fire_cooldown <= 0.0 && x >= target.0 {
+            self.fire_cooldown = ENEMY_FIRE_DELAY;
+            self.volley = Some(PatternPlayer::new(self.bullets));
         }
 
-        if phi.events.now.key_3 == Some(true) {
-            self.cannon = CannonType::DevergentBullet {
-                a: 100.0,
-                b: 1.2,
-            }
+        if let Some(ref mut volley) = self.volley {
+            volley.update(dt, ids, (self.rect.x, y), target, pool);
+        }
+        if self.volley.is_some_and(|volley| volley.is_over()) {
+            self.volley = None;
         }
+    }
 
-        // Moving logic
-        let diagonal = 
-            (phi.events.key_up ^ phi.events.key_down) &&
-            (phi.events.key_left ^ phi.events.key_right);
+    fn render(&self, phi: &mut Phi, camera: &Camera, alpha: f64) {
+        phi.renderer.copy_sprite_tinted(
+            &self.sprites[self.current],
+            camera.view(self.prev_rect.lerp(self.rect, alpha)),
+            self.health.tinted(self.pattern.tint())
+        );
+    }
 
-        let moved = 
-            if diagonal { 1.0 / 2.0f64.sqrt()}
-            else { 1.0 } * PLAYER_SPEED * elapsed;
-        
-        let dx = match (phi.events.key_left, phi.events.key_right) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
+    /// Renders the outline of the current sprite, taken from `outlines`, to
+    /// highlight the enemy. Must be called before `render`.
+    fn render_outline(&self, phi: &mut Phi, camera: &Camera, alpha: f64, outlines: &[Outline]) {
+        let dest = camera.view(self.prev_rect.lerp(self.rect, alpha));
+        outlines[self.current].render(&mut phi.renderer, dest, TARGET_OUTLINE);
+    }
+}
 
-        let dy = match (phi.events.key_up, phi.events.key_down) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
+struct EnemyFactory {
+    sprites: Rc<Vec<Sprite>>,
 
-        self.rect.x += dx;
-        self.rect.y += dy;
-
-        // The movable region spans the entire height of the window and 70% of its
-        // width. This way, the player cannot get to the far right of the screen, where
-        // we will spawn the asnewteroids, and get immediately eliminated.
-        //
-        // We restrain the width because most screens are wider than they are high.
-        let movable_region = Rectangle { 
-            x: 0.0,
-            y: 0.0,
-            w: phi.output_size().0 * 0.70,
-            h: phi.output_size().1,
-        };
+    // The outlines of `sprites`, in the same order.
+    outlines: Rc<Vec<Outline>>,
 
-        // If the player cannot fit in the screen, then there is a problem and
-        // the game should be promptly aborted.
-        self.rect = self.rect.move_inside(movable_region).unwrap();
+    // Every bullet pattern, and the one fired by the enemies which follow
+    // each movement pattern, in the order of `EnemyPattern::ALL`.
+    patterns: BulletPatterns,
+    bullets: Vec<BulletPattern>,
+
+    // The scripts which drive the enemies of some movement pattern, or the
+    // boss, by name.
+    scripts: BehaviorScripts,
+}
+
+impl EnemyFactory {
+    /// Creates an enemy following `pattern`, at a random height to the right
+    /// of the screen.
+    fn random(&self, phi: &mut Phi, pattern: EnemyPattern, (top, bottom): (f64, f64)) -> Enemy {
+        let (w, _) = phi.output_size();
+
+        // Leave room for `Sine` enemies to wave without leaving the screen.
+        let margin = ENEMY_SINE_AMPLITUDE;
+        let y = top + margin + phi.rng().gen::<f64>() * (bottom - top - ENEMY_SHIP.h - 2.0 * margin).max(0.0);
+        let rect = Rectangle { x: w, y, w: ENEMY_SHIP.w, h: ENEMY_SHIP.h };
+
+        Enemy {
+            name: phi.ids.name("enemy"),
+            pattern,
+            rect,
+            prev_rect: rect,
+            sprites: self.sprites.clone(),
+            current: 1,
+            origin_y: y,
+            time: 0.0,
+            // Give the player a moment before the first shot.
+            fire_cooldown: ENEMY_FIRE_DELAY / 2.0,
+            bullets: self.bullets[pattern as usize],
+            volley: None,
+            health: Health::new(ENEMY_HEALTH),
+        }
+    }
+
+    /// Creates an enemy from its `saved` form.
+    fn restore(&self, phi: &mut Phi, saved: &SavedEnemy) -> Enemy {
+        let rect = Rectangle { x: saved.x, y: saved.y, w: ENEMY_SHIP.w, h: ENEMY_SHIP.h };
+
+        Enemy {
+            name: phi.ids.name("enemy"),
+            pattern: saved.pattern,
+            rect,
+            prev_rect: rect,
+            sprites: self.sprites.clone(),
+            current: 1,
+            origin_y: saved.origin_y,
+            time: saved.time,
+            fire_cooldown: ENEMY_FIRE_DELAY / 2.0,
+            bullets: self.bullets[saved.pattern as usize],
+            volley: None,
+            health: Health::with_fraction(ENEMY_HEALTH, saved.health),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Player {
+    name: EntityName,
+    rect: Rectangle,
+    // Where the ship was before the last tick.
+    prev_rect: Rectangle,
+    sprites: Vec<Sprite>,
+    current: PlayerFrame,
+    cannon: CannonType,
+
+    // The ship which the player chose.
+    spec: ShipSpec,
+
+    // The factor by which the ship is scaled relative to its sprite.
+    scale: f64,
+
+    // The size of the part of the ship which can be hit, relative to the
+    // whole ship.
+    hitbox_scale: f64,
+
+    // How long the ship keeps flashing after being hit, in seconds.
+    flash: f64,
+
+    // Time left before the cannon may fire again, in seconds.
+    fire_cooldown: f64,
+
+    // The velocity at which the ship was knocked back by a collision, in
+    // pixels per second, and for how long it cannot be steered anymore.
+    knockback: Vec2,
+    stun: f64,
+
+    // Whether the shield is up, in which case it absorbs the next hit, and
+    // how long until it comes back if it is down, in seconds.
+    shield: bool,
+    shield_recharge: f64,
+    shield_sprite: Sprite,
+
+    // The hits which the ship took so far, absorbed or not.
+    hits_taken: u64,
+}
+
+impl Player {
+    pub fn new(phi: &mut Phi, spec: ShipSpec, scale: f64, hitbox_scale: f64) -> Result<Player, PhiError> {
+        // Get the spaceship's sprites.
+        let sprites = spec.frames(&phi.renderer)?;
+        let scale = scale * spec.scale;
+
+        // Spawn the player at the center of the screen, vertically.
+        let rect = Rectangle {
+            x: 64.0,
+            y: (phi.output_size().1 - spec.h * scale) / 2.0,
+            w: spec.w * scale,
+            h: spec.h * scale,
+        };
+
+        Ok(Player {
+            name: phi.ids.name("player"),
+            rect,
+            prev_rect: rect,
+            sprites: sprites,
+            current: PlayerFrame::MidNorm,
+            cannon: spec.cannon,
+            spec,
+            scale,
+            hitbox_scale,
+            flash: 0.0,
+            fire_cooldown: 0.0,
+            knockback: Vec2::ZERO,
+            stun: 0.0,
+            shield: true,
+            shield_recharge: 0.0,
+            shield_sprite: Sprite::bubble(&phi.renderer, (spec.w * scale * SHIELD_SIZE / 2.0) as u32, SHIELD_COLOR)?,
+            hits_taken: 0,
+        })
+    }
+
+    /// Takes a hit. Returns whether the shield absorbed it, in which case it
+    /// is gone until the ship goes `SHIELD_RECHARGE` seconds without being hit.
+    pub fn absorb_hit(&mut self) -> bool {
+        let absorbed = self.shield;
+        self.hits_taken += 1;
+        self.shield = false;
+        self.shield_recharge = SHIELD_RECHARGE;
+        absorbed
+    }
+
+    pub fn cannon(&self) -> CannonType {
+        self.cannon
+    }
+
+    /// How far the shield is recharged, in `[0, 1]`.
+    pub fn shield_charge(&self) -> f64 {
+        if self.shield { 1.0 } else { 1.0 - self.shield_recharge / SHIELD_RECHARGE }
+    }
+
+    /// Moves the ship according to `controls`, keeping it inside of
+    /// `movable_region`.
+    pub fn update(&mut self, movable_region: Rectangle, controls: Controls, elapsed: f64) {
+        self.prev_rect = self.rect;
+        self.flash = (self.flash - elapsed).max(0.0);
+        self.fire_cooldown = (self.fire_cooldown - elapsed).max(0.0);
+        self.stun = (self.stun - elapsed).max(0.0);
+
+        if !self.shield {
+            self.shield_recharge = (self.shield_recharge - elapsed).max(0.0);
+            self.shield = self.shield_recharge == 0.0;
+        }
+
+        // Change the player's cannons
+        if let Some(cannon) = controls.cannon {
+            self.cannon = cannon;
+        }
+
+        // The ship does not answer the controls while it is stunned.
+        let controls =
+            if self.stun > 0.0 { Controls::default() }
+            else { controls };
+
+        // Moving logic
+        let diagonal = 
+            (controls.up ^ controls.down) &&
+            (controls.left ^ controls.right);
+
+        let moved = 
+            if diagonal { 1.0 / 2.0f64.sqrt()}
+            else { 1.0 } * self.spec.speed * elapsed;
+        
+        let dx = match (controls.left, controls.right) {
+            (true, true) | (false, false) => 0.0,
+            (true, false) => -moved,
+            (false, true) => moved,
+        };
+
+        let dy = match (controls.up, controls.down) {
+            (true, true) | (false, false) => 0.0,
+            (true, false) => -moved,
+            (false, true) => moved,
+        };
+
+        self.rect = self.rect.translated(Vec2::new(dx, dy) + self.knockback * elapsed);
+        self.knockback *= (1.0 - KNOCKBACK_DAMPING * elapsed).max(0.0);
+
+        // If the player cannot fit in the screen, then there is a problem and
+        // the game should be promptly aborted.
+        self.rect = self.rect.move_inside(movable_region).unwrap();
 
         // Select the appropriate sprite of the ship to show.
         self.current = 
@@ -301,79 +1525,757 @@ impl Player {
             else { unreachable!() };
     }
 
-    pub fn render(&self, phi: &mut Phi) {
-        // Render the bounding box(for debugging purposes)
-        if DEBUG {
-            phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect.to_sdl()).unwrap();
-        }
+    /// How fast the ship moved during the last tick, which lasted `elapsed`
+    /// seconds, in pixels per second.
+    pub fn speed(&self, elapsed: f64) -> f64 {
+        let (dx, dy) = (self.rect.x - self.prev_rect.x, self.rect.y - self.prev_rect.y);
+        (dx * dx + dy * dy).sqrt() / elapsed
+    }
+
+    pub fn render(&self, phi: &mut Phi, camera: &Camera, alpha: f64) {
+        // Render the ship's current sprite, in red if it was just hit.
+        let tint =
+            if self.flash > 0.0 { Tint::color(Color::RGB(255, 80, 80)) }
+            else { Tint::NONE };
 
-        // Render the ship's current sprite.
-        phi.renderer.copy_sprite (
+        let rect = self.prev_rect.lerp(self.rect, alpha);
+        phi.renderer.copy_sprite_tinted(
             &self.sprites[self.current as usize],
-            self.rect
+            camera.view(rect),
+            tint
         );
+
+        // Render the shield around the ship, if it is up.
+        if self.shield {
+            let (w, h) = self.shield_sprite.size();
+            phi.renderer.copy_sprite_tinted(
+                &self.shield_sprite,
+                camera.view(Rectangle::with_size(w, h).center_at(rect.center())),
+                Tint::alpha(SHIELD_ALPHA)
+            );
+        }
     }
 
-    pub fn spawn_bullets(&self) -> Vec<Box<dyn Bullet>> {
-        let cannons_x = self.rect.x + 30.0;
-        let cannons1_y = self.rect.y + 6.0;
-        let cannons2_y = self.rect.y + PLAYER_H - 10.0;
+    /// The part of the ship which asteroids may hit, around its center.
+    pub fn hitbox(&self) -> Rectangle {
+        Rectangle::with_size(self.rect.w * self.hitbox_scale, self.rect.h * self.hitbox_scale)
+            .center_at(self.rect.center())
+    }
+
+    /// Knocks the ship back after it collided with a body, given as its
+    /// center, velocity and mass. The ship cannot be steered for a moment.
+    pub fn knock_back(&mut self, body: (Vec2, Vec2, f64)) {
+        let ship = (self.rect.center().into(), self.knockback, PLAYER_MASS);
+
+        if let Some((_, knockback)) = bounce(body, ship) {
+            self.knockback = knockback;
+            self.stun = KNOCKBACK_STUN;
+        }
+    }
+
+    /// The area around the hitbox in which whatever does not hit the ship
+    /// grazes it.
+    pub fn graze_zone(&self) -> Rectangle {
+        let hitbox = self.hitbox();
+        Rectangle::with_size(hitbox.w + 2.0 * GRAZE_MARGIN, hitbox.h + 2.0 * GRAZE_MARGIN)
+            .center_at(hitbox.center())
+    }
+
+    /// Fires the current cannon, unless it is still cooling down.
+    pub fn fire(&mut self, ids: &mut IdAllocator, pool: &mut BulletPool) {
+        if self.fire_cooldown > 0.0 {
+            return;
+        }
 
-        spawn_bullets(self.cannon, cannons_x, cannons1_y, cannons2_y)
+        self.fire_cooldown = self.cannon.fire_delay();
+        self.spawn_bullets(ids, pool);
+    }
+
+    pub fn spawn_bullets(&self, ids: &mut IdAllocator, pool: &mut BulletPool) {
+        let cannons_x = self.rect.x + 30.0 * self.scale;
+        let cannons1_y = self.rect.y + 6.0 * self.scale;
+        let cannons2_y = self.rect.y + (self.spec.h - 10.0) * self.scale;
+
+        spawn_bullets(ids, self.cannon, cannons_x, cannons1_y, cannons2_y, pool);
     }
 }
 
+/// What a run was started with, so that it may be started over the same way.
+#[derive(Clone, Copy)]
+pub struct RunSetup {
+    pub ship: usize,
+    pub mutators: Mutators,
+    pub arena: bool,
+    pub survival: bool,
+    pub seed: u64,
+}
+
 pub struct GameView {
+    setup: RunSetup,
+
+    // Whether the run was resumed from the save of the player, which it then
+    // replaces, so that the save goes once the run is over.
+    from_save: bool,
+
     player: Player,
-    bullets: Vec<Box<dyn Bullet>>,
+    bullets: BulletPool,
     asteroids: Vec<Asteroid>,
     asteroid_factory: AsteroidFactory,
+    enemies: Vec<Enemy>,
+    enemy_factory: EnemyFactory,
+    enemy_bullets: BulletPool,
     explosions: Vec<Explosion>,
     explosion_factory: ExplosionFactory,
+
+    // The gems and magnets which destroyed enemies left behind.
+    pickups: Pickups,
+
+    // How many kills followed each other closely, and what they earned.
+    combo: Combo,
+    popups: ScorePopups,
+
+    // Set while a boss is on screen, every few waves.
+    boss: Option<Boss>,
+
+    exhaust: ParticleEmitter,
+    sparks: ParticleEmitter,
+    debris: ParticleEmitter,
+
+    // Through which the entities are viewed, but not the HUD.
+    camera: Camera,
+
     music: Music<'static>,
+    ducking: MusicDucking,
+    mutators: Mutators,
+    difficulty: Difficulty,
+    level: Level,
+    director: LevelDirector,
+
+    // Set in runs of survival, which spawn without waves instead of
+    // following the director.
+    survival: Option<SurvivalDirector>,
+
+    // The number of the wave which just started, and for how long it is
+    // still shown, in seconds.
+    banner: Option<(Sprite, f64)>,
+    hud: Hud,
+
+    // What happened during this run, and how many points it earned.
+    timeline: RunTimeline,
+    stats: RunStats,
+    score: u64,
+
+    // The hits which the ship may still take once its shield is down.
+    lives: u32,
+
+    // The bombs which the player may still use, unless they are infinite.
+    bombs: u32,
+
+    // The enemy bullets which are within the graze zone of the ship, those
+    // which already grazed it, and the sound played when something grazes it.
+    grazing_bullets: Vec<EntityName>,
+    grazed_bullets: Vec<EntityName>,
+    graze_chime: Chunk,
+
+    // The channel on which the last chime was played, if any.
+    graze_channel: Option<Channel>,
+
+    engine_hum: EngineHum,
+
+    // The player's bullets and the asteroids, by where they are, so that
+    // collisions only need to be checked between neighbours.
+    bullet_grid: SpatialGrid<usize>,
+    asteroid_grid: SpatialGrid<usize>,
+
+    // Where the ship was hit, across every run.
+    heatmap: Heatmap,
+
+    // What the player achieved across every run, and the toasts telling them
+    // about what they just unlocked.
+    achievements: Achievements,
+    toasts: Toasts,
+
+    // Set when the game is played by the bot rather than by the player.
+    demo: Option<Demo>,
 
-    bg_back: Background,
-    bg_middle: Background,
-    bg_front: Background,
+    // Set when the game follows a trailer script.
+    trailer: Option<Trailer>,
+
+    // Set once the ship is out of lives: how fast time goes as it blows up.
+    game_over: Option<Tween>,
+
+    // Set while the game is paused.
+    pause: Option<Pause>,
+
+    // Set while the player holds the button which opens the radial cannon
+    // selector.
+    cannon_select: Option<RadialMenu>,
+
+    // Set in debug builds of the game, see `DEBUG`.
+    recorder: Option<Recorder>,
+
+    restart: RestartHold,
+
+    background: ParallaxBackground,
 }
 
 impl GameView {
-    pub fn new(phi: &mut Phi) -> GameView {
-        let music = Music::from_file(Path::new(MUSIC_PATH)).unwrap();
-        music.play(-1).unwrap();
-        
-        GameView {
-            player: Player::new(phi),
-            /// We start with no bullets. Because the size of the vector will
-            /// change drastically throughout the program, there is not much
-            /// point in giving it a capacity.
-            bullets: vec![],
+    /// Starts a run with the ship at index `ship` in `SHIPS`.
+    pub fn new(phi: &mut Phi, ship: usize) -> Result<GameView, PhiError> {
+        GameView::with_mutators(phi, ship, Mutators::default())
+    }
+
+    /// Starts a game played by the bot, which ends as soon as the player
+    /// presses a key.
+    pub fn demo(phi: &mut Phi) -> Result<GameView, PhiError> {
+        let mut game = GameView::new(phi, 0)?;
+        game.demo = Some(Demo {
+            bot: Bot::new(),
+            watermark: phi.ttf_str_sprite("DEMO", "assets/belligerent.ttf", 48, Color::RGB(255, 255, 255))?,
+            ticks: 0,
+        });
+
+        Ok(game)
+    }
+
+    /// Starts a run directed by the trailer script at `path`, see
+    /// `views::trailer`. It plays on until the script ends, whatever happens
+    /// to the ship.
+    pub fn trailer(phi: &mut Phi, path: &str) -> Result<GameView, PhiError> {
+        let mut game = GameView::new(phi, 0)?;
+        game.trailer = Some(Trailer {
+            script: TrailerScript::load(path)?,
+            bot: Some(Bot::new()),
+            show_hud: true,
+            waves: true,
+        });
+
+        Ok(game)
+    }
+
+    /// Starts a run in the arena, which is closed at the top and bottom.
+    pub fn arena(phi: &mut Phi, ship: usize) -> Result<GameView, PhiError> {
+        let mut game = GameView::new(phi, ship)?;
+        game.level = Level::arena();
+        game.setup.arena = true;
+        Ok(game)
+    }
+
+    /// Starts a run of survival, where asteroids and enemies come ever
+    /// faster, without waves, for as long as the ship holds on.
+    pub fn survival(phi: &mut Phi, ship: usize) -> Result<GameView, PhiError> {
+        let mut game = GameView::new(phi, ship)?;
+        game.survival = Some(SurvivalDirector::new());
+        game.setup.survival = true;
+        Ok(game)
+    }
+
+    /// Starts the run described by `setup` over, from the same seed.
+    pub fn restart(phi: &mut Phi, setup: RunSetup) -> Result<GameView, PhiError> {
+        let mut game = GameView::start(phi, setup.ship, setup.mutators, Some(setup.seed))?;
+        if setup.arena {
+            game.level = Level::arena();
+            game.setup.arena = true;
+        }
+        if setup.survival {
+            game.survival = Some(SurvivalDirector::new());
+            game.setup.survival = true;
+        }
+
+        Ok(game)
+    }
+
+    /// Resumes the run which the player saved before leaving the game.
+    pub fn resume(phi: &mut Phi) -> Result<GameView, PhiError> {
+        let saved = SavedGame::load(DEFAULT_PROFILE).ok_or_else(|| PhiError::Asset {
+            path: "save.cfg".to_string(),
+            reason: "the saved game could not be read".to_string(),
+        })?;
+
+        let mut game = GameView::restore(phi, saved)?;
+        game.from_save = true;
+        Ok(game)
+    }
+
+    /// Starts over from the bookmark called `name`, exactly as it was when it
+    /// was taken, see `take_bookmark`.
+    pub fn bookmark(phi: &mut Phi, name: &str) -> Result<GameView, PhiError> {
+        let path = bookmark_path(DEFAULT_PROFILE, name);
+        let saved = SavedGame::load_from(&path).ok_or_else(|| PhiError::Asset {
+            path: path.display().to_string(),
+            reason: "the bookmark could not be read".to_string(),
+        })?;
+
+        GameView::restore(phi, saved)
+    }
+
+    fn restore(phi: &mut Phi, saved: SavedGame) -> Result<GameView, PhiError> {
+        let mut game = GameView::with_mutators(phi, saved.ship, saved.mutators)?;
+        if saved.arena {
+            game.level = Level::arena();
+            game.setup.arena = true;
+        }
+
+        game.difficulty = saved.difficulty;
+        game.player.hitbox_scale = saved.mutators.player_hitbox(saved.difficulty);
+        game.lives = saved.lives;
+        if let Some(bombs) = saved.bombs {
+            game.bombs = bombs;
+        }
+        game.score = saved.score;
+        game.player.rect.x = saved.player.0;
+        game.player.rect.y = saved.player.1;
+        game.player.prev_rect = game.player.rect;
+        game.player.cannon = CANNONS.get(saved.cannon).copied().unwrap_or(CannonType::RectBullet);
+        game.director.resume(saved.director);
+        if let Some(time) = saved.survival {
+            game.survival = Some(SurvivalDirector::resume(time));
+            game.setup.survival = true;
+        }
+
+        for asteroid in &saved.asteroids {
+            game.asteroids.extend(game.asteroid_factory.restore(phi, asteroid));
+        }
+
+        for enemy in &saved.enemies {
+            game.enemies.push(game.enemy_factory.restore(phi, enemy));
+        }
+
+        if let Some(seed) = saved.seed {
+            phi.reseed(seed);
+        }
+
+        Ok(game)
+    }
+
+    /// The state of the run, as plain data which may be saved to a file.
+    fn saved(&self) -> SavedGame {
+        // A boss is not saved along with the rest of the run: it comes back
+        // from the start once the run is resumed.
+        let mut director = self.director.saved();
+        if self.boss.is_some() {
+            director.boss_wave = 0;
+        }
+
+        SavedGame {
+            arena: self.level.walls.is_some(),
+            mutators: self.mutators,
+            ship: SHIPS.iter().position(|ship| ship.name == self.player.spec.name).unwrap_or(0),
+            difficulty: self.difficulty,
+            lives: self.lives,
+            bombs: Some(self.bombs),
+            score: self.score,
+            player: (self.player.rect.x, self.player.rect.y),
+            cannon: CANNONS.iter().position(|cannon| cannon.name() == self.player.cannon.name()).unwrap_or(0),
+            director,
+            survival: self.survival.map(|survival| survival.time()),
+            seed: None,
+            asteroids: self.asteroids.iter().map(|asteroid| SavedAsteroid {
+                size: AsteroidSize::ALL.iter().position(|&size| size == asteroid.size).unwrap_or(0),
+                x: asteroid.rect.x,
+                y: asteroid.rect.y,
+                vel: -asteroid.vel.x,
+                drift: asteroid.vel.y,
+                health: asteroid.health.fraction(),
+            }).collect(),
+            enemies: self.enemies.iter().map(|enemy| SavedEnemy {
+                pattern: enemy.pattern,
+                x: enemy.rect.x,
+                y: enemy.rect.y,
+                origin_y: enemy.origin_y,
+                time: enemy.time,
+                health: enemy.health.fraction(),
+            }).collect(),
+        }
+    }
+
+    /// Starts a run with the ship at index `ship` in `SHIPS`, whose balance
+    /// is adjusted by the given `mutators`.
+    pub fn with_mutators(phi: &mut Phi, ship: usize, mutators: Mutators) -> Result<GameView, PhiError> {
+        GameView::start(phi, ship, mutators, None)
+    }
+
+    fn start(phi: &mut Phi, ship: usize, mutators: Mutators, seed: Option<u64>) -> Result<GameView, PhiError> {
+        let music = Music::from_file(Path::new(MUSIC_PATH)).map_err(PhiError::asset(MUSIC_PATH))?;
+        let director = LevelDirector::load(WAVES_PATH)?;
+
+        // Every run gets a seed of its own, drawn from the previous one, so
+        // that it may be replayed on its own.
+        let seed = seed.unwrap_or_else(|| phi.rng().gen());
+        phi.reseed(seed);
+
+        let game = GameView {
+            setup: RunSetup { ship, mutators, arena: false, survival: false, seed },
+            from_save: false,
+            player: Player::new(phi, SHIPS.get(ship).copied().unwrap_or(SHIPS[0]), mutators.player_scale(), mutators.player_hitbox(phi.difficulty))?,
+            /// We start with no bullets. The pool grows as more of them are
+            /// fired at once, and then reuses the same slots.
+            bullets: BulletPool::default(),
             asteroids: vec![],
-            asteroid_factory: Asteroid::factory(phi),
+            asteroid_factory: Asteroid::factory(phi, director.distribution)?,
+            enemies: vec![],
+            enemy_factory: Enemy::factory(phi)?,
+            enemy_bullets: BulletPool::default(),
             explosions: vec![],
-            explosion_factory: Explosion::factory(phi),
+            pickups: Pickups::new(),
+            combo: Combo::new(),
+            popups: ScorePopups::new(),
+            explosion_factory: Explosion::factory(phi)?,
+            boss: None,
+            exhaust: ParticleEmitter::new(EXHAUST),
+            sparks: ParticleEmitter::new(SPARKS),
+            debris: ParticleEmitter::new(DEBRIS),
+            camera: Camera::new(phi.output_size()),
             // Audio
             music: music,
+            ducking: MusicDucking::new(),
+            mutators,
+            difficulty: phi.difficulty,
+            level: Level::standard(),
+            director,
+            survival: None,
+            banner: None,
+            hud: Hud::new(phi, HudLayout::load(DEFAULT_PROFILE))?,
+            timeline: RunTimeline::new(),
+            stats: RunStats::default(),
+            score: 0,
+            lives: phi.difficulty.lives(),
+            bombs: BOMBS,
+            grazing_bullets: vec![],
+            grazed_bullets: vec![],
+            bullet_grid: SpatialGrid::new(GRID_CELL),
+            asteroid_grid: SpatialGrid::new(GRID_CELL),
+            graze_chime: audio::tone(GRAZE_CHIME.0, GRAZE_CHIME.1)?,
+            graze_channel: None,
+            engine_hum: EngineHum::new()?,
+            heatmap: Heatmap::load(DEFAULT_PROFILE),
+            achievements: Achievements::load(DEFAULT_PROFILE),
+            toasts: Toasts::new(),
+            demo: None,
+            trailer: None,
+            game_over: None,
+            pause: None,
+            cannon_select: None,
+            recorder: if DEBUG { Some(Recorder::new()) } else { None },
+            restart: RestartHold::new(phi),
+
+            background: ParallaxBackground::load_seeded(&phi.renderer, STARFIELD_PATH, seed)?,
+        };
 
-            bg_back: Background {
-                pos: 0.0,
-                vel: 20.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starBG.png").unwrap(),
-            },
+        Ok(game)
+    }
+}
 
-            bg_middle: Background {
-                pos: 0.0,
-                vel: 40.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starMG.png").unwrap(),
-            },
+impl GameView {
+    /// Rewards the player for something which came close to the ship at
+    /// `pos` without hitting it.
+    fn graze(&mut self, phi: &mut Phi, pos: (f64, f64)) {
+        self.score += GRAZE_SCORE;
+        self.stats.graze_points += GRAZE_SCORE;
+        self.timeline.record(RunEvent::Graze);
+
+        let sparks = SPARKS_PER_GRAZE * phi.effects.particle_density();
+        self.sparks.burst(sparks as usize, pos);
+
+        // Quickly fade the previous chime out, rather than stacking them
+        // when the player grazes several things in a row.
+        if let Some(channel) = self.graze_channel.filter(|channel| channel.is_playing()) {
+            phi.fades.fade_out(FadeTarget::Channel(channel), CHIME_FADE_OUT);
+        }
+
+        //? Playing fails when every channel is busy, in which case skipping
+        //? the chime is fine.
+        self.graze_channel = Channel::all().play(&self.graze_chime, 0).ok();
+    }
+
+    /// Outlines the shape with which every entity collides, in a color which
+    /// depends on what it is, along with the hitbox and graze zone of the
+    /// ship.
+    fn render_hitboxes(&self, phi: &mut Phi) {
+        let camera = &self.camera;
+        let mut outline = |shape: Shape, color: Color| match shape {
+            Shape::Rect(rect) =>
+                draw::outline_rect(&mut phi.renderer, camera.view(rect), color),
+            Shape::Circle(Circle { center, radius }) =>
+                draw::outline_circle(&mut phi.renderer, camera.view_point(center.into()), radius * camera.zoom, color, false),
+        };
+
+        outline(Shape::Rect(self.player.rect), Color::RGB(200, 200, 50));
+        outline(Shape::Rect(self.player.hitbox()), Color::RGB(200, 50, 50));
+        outline(Shape::Rect(self.player.graze_zone()), Color::RGB(50, 50, 200));
+
+        for bullet in self.bullets.iter() {
+            outline(Shape::Rect(bullet.rect()), Color::RGB(50, 200, 200));
+        }
+
+        for asteroid in &self.asteroids {
+            outline(asteroid.shape(), Color::RGB(200, 200, 50));
+        }
+
+        for enemy in &self.enemies {
+            outline(Shape::Rect(enemy.rect), Color::RGB(200, 50, 200));
+        }
 
-            bg_front: Background {
-                pos: 0.0,
-                vel: 80.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starFG.png").unwrap(),
+        for part in self.boss.iter().flat_map(Boss::parts) {
+            outline(Shape::Rect(part), Color::RGB(200, 50, 200));
+        }
+
+        for bullet in self.enemy_bullets.iter() {
+            outline(Shape::Rect(bullet.rect()), Color::RGB(230, 130, 30));
+        }
+    }
+
+    /// Opens the radial cannon selector while its button is held, and
+    /// switches to the chosen cannon once it is released. While the selector
+    /// is open, the directional controls point at a cannon rather than move
+    /// the ship.
+    fn select_cannon(&mut self, phi: &mut Phi, mut controls: Controls) -> Result<Controls, PhiError> {
+        if self.cannon_select.is_none() && phi.events.now.key_cannon == Some(true) {
+            let names: Vec<_> = CANNONS.iter().map(|cannon| cannon.name()).collect();
+            self.cannon_select = Some(RadialMenu::new(phi, &names)?);
+        }
+
+        if let Some(ref mut select) = self.cannon_select {
+            select.update(phi);
+            controls.up = false;
+            controls.down = false;
+            controls.left = false;
+            controls.right = false;
+
+            if !phi.events.key_cannon {
+                controls.cannon = select.selected().map(|i| CANNONS[i]).or(controls.cannon);
+                self.cannon_select = None;
             }
         }
+
+        Ok(controls)
+    }
+
+    /// Opens the pause menu. `disconnected` is set if the game is paused
+    /// because the player's gamepad was unplugged.
+    fn pause(&mut self, phi: &mut Phi, disconnected: bool) -> Result<(), PhiError> {
+        self.pause = Some(Pause::new(phi, disconnected)?);
+        self.engine_hum.silence();
+        phi.time_scale = 0.0;
+
+        // Keep the music going, but in the background.
+        self.ducking.duck();
+        Ok(())
+    }
+
+    /// Saves the seed, wave, score and loadout of the run under the first
+    /// free name, so that `--bookmark NAME` may start over from here.
+    fn take_bookmark(&self, phi: &mut Phi) {
+        // Go on from a fresh seed, so that what follows the bookmark plays
+        // out the same way once it is restored.
+        let seed = phi.rng().gen();
+        phi.reseed(seed);
+
+        let mut saved = self.saved();
+        saved.seed = Some(seed);
+
+        let name = (1..)
+            .map(|i| format!("bookmark-{}", i))
+            .find(|name| !bookmark_path(DEFAULT_PROFILE, name).exists())
+            .unwrap_or_default();
+
+        match saved.save_to(bookmark_path(DEFAULT_PROFILE, &name)) {
+            Ok(()) => println!("Bookmarked the run as {} (wave {}, score {})",
+                name, self.director.wave_number(), self.score),
+            Err(err) => println!("Could not save the bookmark: {}", err),
+        }
+    }
+
+    /// Spawns an asteroid, whose velocity is multiplied by `speed` before the
+    /// mutators and the difficulty apply.
+    fn spawn_asteroid(&mut self, phi: &mut Phi, speed: f64) {
+        let speed = speed * self.mutators.asteroid_speed() * self.difficulty.asteroid_speed();
+        let reaction_time = self.level.spawn_reaction_time;
+        let bounds = self.level.bounds(phi.output_size());
+        self.asteroids.extend(
+            self.asteroid_factory.random(phi, speed, self.player.rect, reaction_time, bounds));
+    }
+
+    fn spawn_enemy(&mut self, phi: &mut Phi, pattern: EnemyPattern) {
+        self.enemies.push(self.enemy_factory.random(phi, pattern, self.level.bounds(phi.output_size())));
+    }
+
+    /// Destroys every asteroid and enemy at once, along with the bullets
+    /// which the enemies fired, and damages the boss, if any. Asteroids do
+    /// not break into fragments. Nothing happens once the player is out of
+    /// bombs.
+    fn detonate_bomb(&mut self, phi: &mut Phi) {
+        if !self.mutators.infinite_bombs {
+            if self.bombs == 0 {
+                return;
+            }
+            self.bombs -= 1;
+        }
+
+        for asteroid in ::std::mem::take(&mut self.asteroids) {
+            let center = asteroid.rect().center();
+            self.timeline.record(RunEvent::Kill);
+            let points = self.combo.kill(asteroid.size.score());
+            self.stats.record_kill(Target::Asteroid, asteroid.size.score(), points);
+            self.achievements.notify(AchievementEvent::AsteroidDestroyed);
+            self.popups.spawn(center, points);
+            self.score += points;
+            self.explosions.push(self.explosion_factory.at_center(phi, center));
+        }
+
+        for enemy in ::std::mem::take(&mut self.enemies) {
+            let center = enemy.rect.center();
+            self.timeline.record(RunEvent::Kill);
+            let points = self.combo.kill(ENEMY_SCORE);
+            self.stats.record_kill(Target::Enemy, ENEMY_SCORE, points);
+            self.achievements.notify(AchievementEvent::EnemyKilled);
+            self.popups.spawn(center, points);
+            self.score += points;
+            self.pickups.drop_loot(phi, center, ENEMY_GEMS);
+            self.explosions.push(self.explosion_factory.at_center(phi, center));
+        }
+
+        if let Some(ref mut boss) = self.boss {
+            if boss.hit(BOMB_BOSS_DAMAGE) {
+                self.timeline.record(RunEvent::Kill);
+                let points = self.combo.kill(BOSS_SCORE);
+                self.stats.record_kill(Target::Boss, BOSS_SCORE, points);
+                self.achievements.notify(AchievementEvent::BossDefeated);
+                let (center, _, _) = boss.body();
+                self.popups.spawn(center.into(), points);
+                self.score += points;
+            }
+        }
+
+        self.achievements.notify(AchievementEvent::Combo(self.combo.multiplier()));
+        self.enemy_bullets.clear();
+
+        let (intensity, duration) = BOMB_SHAKE;
+        self.camera.shake(intensity * phi.effects.screen_shake(), duration);
+    }
+
+    /// Whether the run is only there to be watched, because the bot plays it
+    /// or a trailer script directs it, so that it may not be paused and does
+    /// not count.
+    fn is_watched(&self) -> bool {
+        self.demo.is_some() || self.trailer.is_some()
+    }
+
+    /// Adds the run to the player's lifetime stats, and exports its
+    /// timeline. Runs played by the bot, or by a script, do not count.
+    fn end_run(&self) {
+        if self.is_watched() {
+            return;
+        }
+
+        // A run which is over may not be resumed anymore. Other runs, e.g. in
+        // the arena, leave the save alone.
+        if self.from_save {
+            SavedGame::delete(DEFAULT_PROFILE);
+        }
+
+        let mut stats = LifetimeStats::load(DEFAULT_PROFILE);
+        stats.record_run(self.timeline.count(RunEvent::Kill));
+
+        if let Err(err) = stats.save() {
+            println!("Could not save the stats: {}", err);
+        }
+
+        if let Err(err) = self.heatmap.save(DEFAULT_PROFILE) {
+            println!("Could not save the heatmap: {}", err);
+        }
+
+        match self.timeline.export(DEFAULT_PROFILE) {
+            Ok(path) => println!("Exported the run to {}.{{json,csv}}", path.display()),
+            Err(err) => println!("Could not export the run: {}", err),
+        }
+    }
+
+    /// Lets everything play out in slow motion once the ship was destroyed
+    /// for good, without colliding anymore, then fades to the summary of the
+    /// run.
+    fn update_game_over(mut self: Box<Self>, phi: &mut Phi) -> ViewAction {
+        let slow_motion = match self.game_over {
+            Some(ref mut slow_motion) => {
+                slow_motion.update(phi.real_elapsed());
+                *slow_motion
+            },
+            None => return ViewAction::Render(self),
+        };
+        phi.time_scale = slow_motion.value();
+        let elapsed = phi.elapsed();
+
+        if slow_motion.is_done() {
+            let summary = RunSummary {
+                destroyed: self.timeline.count(RunEvent::Kill),
+                grazes: self.timeline.count(RunEvent::Graze),
+                waves: self.director.wave_number(),
+                score: self.score,
+                survived: self.survival.map(|survival| survival.time() as u64),
+                stats: Some(RunStats {
+                    hits_taken: self.player.hits_taken,
+                    time: self.timeline.time(),
+                    ..self.stats
+                }),
+                modified: self.mutators.is_modified(),
+            };
+
+            let setup = self.setup;
+            let restart: Restart = Box::new(move |phi| Ok(Box::new(GameView::restart(phi, setup)?)));
+
+            return ViewAction::change_view_with(Transition::Fade(GAME_OVER_FADE), self,
+                RunSummaryView::new(phi, summary, Some(restart)));
+        }
+
+        let game = &mut *self;
+        let target = game.player.rect.center();
+
+        game.asteroids = ::std::mem::take(&mut game.asteroids).into_iter()
+            .filter_map(|asteroid| asteroid.update(elapsed))
+            .collect();
+        game.enemies = ::std::mem::take(&mut game.enemies).into_iter()
+            .filter_map(|enemy| {
+                let script = game.enemy_factory.scripts.get(enemy.pattern.name());
+                enemy.update(elapsed, target, game.difficulty.enemy_speed(), script)
+            })
+            .collect();
+        game.explosions = ::std::mem::take(&mut game.explosions).into_iter()
+            .filter_map(|explosion| explosion.update(elapsed))
+            .collect();
+
+        game.bullets.update(phi, elapsed, &[], &[]);
+        game.bullets.sweep();
+        game.enemy_bullets.update(phi, elapsed, &[], &[]);
+        game.enemy_bullets.sweep();
+
+        game.exhaust.emitting = false;
+        game.exhaust.update(elapsed);
+        game.pickups.update(elapsed, None);
+        game.toasts.update(elapsed);
+        if let Err(err) = game.popups.update(phi, elapsed) {
+            return ViewAction::Error(err);
+        }
+        game.sparks.update(elapsed);
+        game.debris.update(elapsed);
+        game.camera.update(elapsed);
+        game.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    /// The view shown once the run is over: the name entry screen if its
+    /// score made it into the high scores, and the main menu otherwise.
+    fn after_run(&self, phi: &mut Phi) -> Result<Box<dyn View>, PhiError> {
+        let (board, value) = match self.survival {
+            Some(survival) => (Leaderboard::Survival, survival.time() as u64),
+            None => (Leaderboard::Waves, self.score),
+        };
+
+        if !self.is_watched() && HighScores::load(DEFAULT_PROFILE, board).qualifies(value) {
+            Ok(Box::new(NameEntryView::new(phi, DEFAULT_PROFILE, board, value, self.mutators.is_modified())?))
+        } else {
+            Ok(Box::new(crate::views::main_menu::MainMenuView::new(phi)?))
+        }
     }
 }
 
@@ -383,19 +2285,162 @@ impl View for GameView {
             return ViewAction::Quit;
         }
 
+        // Start over from the same seed, right away, when the player holds
+        // the restart key. A run which is over was already counted.
+        if self.restart.update(phi) && !self.is_watched() {
+            if self.game_over.is_none() {
+                self.end_run();
+            }
+
+            let setup = self.setup;
+            return ViewAction::change_view_with(Transition::Fade(RESTART_FADE), self,
+                GameView::restart(phi, setup));
+        }
+
+        // Pause the game when the player asks for it, or when the gamepad
+        // they were using gets unplugged.
+        let pause_requested =
+            phi.events.now.key_escape == Some(true) ||
+            phi.events.now.controller_lost;
+
+        // Runs which are over may not be paused anymore.
+        let pausable = self.pause.is_none() && self.game_over.is_none() && !self.is_watched();
+        if pausable && pause_requested {
+            if let Err(err) = self.pause(phi, phi.events.now.controller_lost) {
+                return ViewAction::Error(err);
+            }
+            return ViewAction::Render(self);
+        }
+
+        self.ducking.update(phi.real_elapsed());
+
+        if self.pause.is_some() {
+            match self.pause.as_mut().and_then(|pause| pause.update(phi)) {
+                Some(PauseAction::Resume) => {
+                    self.pause = None;
+                    self.ducking.restore();
+                    phi.time_scale = 1.0;
+                },
+
+                Some(PauseAction::SaveAndQuit) => {
+                    if let Err(err) = self.saved().save(DEFAULT_PROFILE) {
+                        println!("Could not save the game: {}", err);
+                    }
+
+                    return ViewAction::change_view_with(Transition::Crossfade(0.5), self,
+                        crate::views::main_menu::MainMenuView::new(phi));
+                },
+
+                Some(PauseAction::QuitToMenu) => {
+                    self.end_run();
+                    return match self.after_run(phi) {
+                        Ok(to) => ViewAction::ChangeViewWith { transition: Transition::Crossfade(0.5), from: self, to },
+                        Err(err) => ViewAction::Error(err),
+                    };
+                },
+
+                None => {},
+            }
+
+            return ViewAction::Render(self);
+        }
+
+        // While the recorder is frozen, only simulate the ticks which the
+        // player steps through.
+        let mut replayed = None;
+        if let Some(mut recorder) = self.recorder.take() {
+            let (frozen, controls) = recorder.update(phi, &mut self);
+            self.recorder = Some(recorder);
+
+            if frozen && controls.is_none() {
+                return ViewAction::Render(self);
+            }
+
+            replayed = controls;
+        }
+
+        if self.game_over.is_some() {
+            return self.update_game_over(phi);
+        }
+
+        if phi.events.now.key_bookmark == Some(true) && !self.is_watched() {
+            self.take_bookmark(phi);
+        }
+
         {
             let game = &mut *self;
 
-            game.player.update(phi, elapsed);
+            let controls = match game.demo {
+                Some(ref mut demo) => {
+                    // Any input ends the demo.
+                    if phi.events.now.key_pressed.is_some() || phi.events.now.mouse_click.is_some() {
+                        return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+                    }
+
+                    // The demo doubles as a stability test: dev builds report
+                    // on it regularly, so that it may be left running.
+                    demo.ticks += 1;
+                    if cfg!(debug_assertions) && demo.ticks.is_multiple_of(DEMO_REPORT_TICKS) {
+                        println!("Demo: {} ticks, {} asteroids, {} bullets, {} explosions",
+                            demo.ticks, game.asteroids.len(), game.bullets.len(), game.explosions.len());
+                    }
 
-            game.music.play(-1).unwrap();
+                    demo.bot.controls(&game.player, &game.asteroids, elapsed)
+                },
 
-            // Update the bullets
-            game.bullets = 
-                ::std::mem::replace(&mut game.bullets, vec![])
-                .into_iter()
-                .filter_map(|bullet| bullet.update(phi, elapsed))
+                None => match game.trailer.as_mut().and_then(|trailer| trailer.bot.as_mut()) {
+                    Some(bot) => bot.controls(&game.player, &game.asteroids, elapsed),
+                    None => {
+                        let controls = Controls::from_events(phi);
+                        match game.select_cannon(phi, controls) {
+                            Ok(controls) => controls,
+                            Err(err) => return ViewAction::Error(err),
+                        }
+                    },
+                },
+            };
+
+            // The game slows down, without stopping, while a cannon is being
+            // picked.
+            phi.time_scale =
+                if game.cannon_select.is_some() { CANNON_SELECT_TIME_SCALE }
+                else { 1.0 };
+            let elapsed = phi.elapsed();
+
+            let controls = match replayed {
+                Some(controls) => controls,
+                None => {
+                    if let Some(mut recorder) = game.recorder.take() {
+                        recorder.record(phi, game, controls);
+                        game.recorder = Some(recorder);
+                    }
+
+                    controls
+                },
+            };
+
+            let movable_region = game.level.movable_region.in_screen(phi.output_size());
+            game.player.update(movable_region, controls, elapsed);
+            game.engine_hum.update(&game.player, elapsed);
+            game.timeline.add_time(elapsed);
+
+            if let Err(err) = game.music.play(-1) {
+                return ViewAction::Error(PhiError::Audio(err));
+            }
+
+            // Update the bullets, which may steer towards the asteroids and
+            // the enemies
+            let targets: Vec<_> = game.asteroids.iter().map(Asteroid::rect)
+                .chain(game.enemies.iter().map(|enemy| enemy.rect))
+                .chain(game.boss.iter().flat_map(Boss::parts))
+                .collect();
+
+            // Bullets ricochet off the walls of the arena, if any.
+            let walls: Vec<Segment> = game.level.walls.iter()
+                .flat_map(|walls| walls.segments(phi.output_size()))
                 .collect();
+
+            game.bullets.update(phi, elapsed, &targets, &walls);
     
             // Update the asteroids
             game.asteroids =
@@ -403,6 +2448,89 @@ impl View for GameView {
                 .into_iter()
                 .filter_map(|asteroid| asteroid.update(elapsed))
                 .collect();
+
+            if let Some(walls) = game.level.walls {
+                let bounds = walls.in_screen(phi.output_size());
+                for asteroid in &mut game.asteroids {
+                    asteroid.bounce_off_walls(bounds);
+                }
+            }
+
+            // Asteroids which get too close steer apart, and those which run
+            // into each other bounce apart, unless the level lets them go
+            // through each other.
+            if game.level.asteroid_collisions {
+                game.asteroid_grid.clear();
+                for (i, asteroid) in game.asteroids.iter().enumerate() {
+                    game.asteroid_grid.insert(asteroid.personal_space(), i);
+                }
+
+                for i in 1..game.asteroids.len() {
+                    let (before, after) = game.asteroids.split_at_mut(i);
+                    for j in game.asteroid_grid.query(after[0].personal_space()) {
+                        if j < i {
+                            after[0].separate(&mut before[j], elapsed);
+                            after[0].collide(&mut before[j]);
+                        }
+                    }
+                }
+            }
+
+            // Dev builds pick up the scripts of the enemies as they are
+            // edited.
+            if cfg!(debug_assertions) {
+                game.enemy_factory.scripts.update(phi.real_elapsed());
+            }
+
+            // Update the enemies, which fire at the player's ship
+            let target = game.player.rect.center();
+            game.enemies =
+                ::std::mem::take(&mut game.enemies)
+                .into_iter()
+                .filter_map(|enemy| {
+                    let script = game.enemy_factory.scripts.get(enemy.pattern.name());
+                    enemy.update(elapsed, target, game.difficulty.enemy_speed(), script)
+                })
+                .collect();
+
+            for enemy in &mut game.enemies {
+                let script = game.enemy_factory.scripts.get(enemy.pattern.name());
+                enemy.fire(elapsed, &mut phi.ids, target, &mut game.enemy_bullets, script);
+            }
+
+            // The boss fires at it as well, and breaks apart once destroyed.
+            if let Some(ref mut boss) = game.boss {
+                let movable_region = game.level.movable_region.in_screen(phi.output_size());
+                let heights = (movable_region.y, movable_region.y + movable_region.h);
+
+                let script = game.enemy_factory.scripts.get("boss");
+                match boss.update(phi, elapsed, target, heights, &mut game.enemy_bullets, script) {
+                    Some(BossEvent::Explosion(at)) => {
+                        game.explosions.push(game.explosion_factory.at_center(phi, at));
+                        let (intensity, duration) = EXPLOSION_SHAKE;
+                        game.camera.shake(intensity * phi.effects.screen_shake(), duration);
+                    },
+
+                    Some(BossEvent::Defeated) => {
+                        let (center, _, _) = boss.body();
+                        game.pickups.drop_loot(phi, center.into(), BOSS_GEMS);
+                        game.boss = None;
+                        game.enemy_bullets.clear();
+
+                        let (intensity, duration) = PLAYER_HIT_SHAKE;
+                        game.camera.shake(intensity * phi.effects.screen_shake(), duration);
+
+                        match phi.ttf_str_sprite("Boss defeated", "assets/belligerent.ttf", 64, Color::RGB(255, 255, 255)) {
+                            Ok(sprite) => game.banner = Some((sprite, WAVE_BANNER_TIME)),
+                            Err(err) => return ViewAction::Error(err),
+                        }
+                    },
+
+                    None => {},
+                }
+            }
+
+            game.enemy_bullets.update(phi, elapsed, &[], &walls);
     
             // Update the explosions
             game.explosions =
@@ -410,109 +2538,623 @@ impl View for GameView {
                 .into_iter()
                 .filter_map(|explosion| explosion.update(elapsed))
                 .collect();
+
+            // Update the particles, the exhaust following the ship
+            let ship = game.player.rect;
+            game.exhaust.position = (ship.x, ship.y + ship.h / 2.0);
+            game.exhaust.emitting = true;
+            game.exhaust.update(elapsed);
+            let gem_points = game.pickups.update(elapsed, Some(ship));
+            game.stats.gem_points += gem_points;
+            game.score += gem_points;
+            game.combo.update(elapsed);
+            if let Err(err) = game.popups.update(phi, elapsed) {
+                return ViewAction::Error(err);
+            }
+            game.sparks.update(elapsed);
+            game.debris.update(elapsed);
+            game.camera.update(elapsed);
             
             // Collision detection
     
+            let collisions = phi.trace.start();
             let mut player_alive = true;
+            let hits_taken = game.player.hits_taken;
     
-            let mut transition_bullets: Vec<_> =
-                ::std::mem::replace(&mut game.bullets, vec![])
-                .into_iter()
-                .map(|bullet| MaybeAlive { alive: true, value: bullet })
-                .collect();
-    
+            // The fragments of the asteroids which were shot down.
+            let mut fragments = vec![];
+
+            game.bullet_grid.clear();
+            for i in 0..game.bullets.len() {
+                game.bullet_grid.insert(game.bullets.swept_rect(i), i);
+            }
+
             game.asteroids =
                 ::std::mem::replace(&mut game.asteroids, vec![])
                 .into_iter()
-                .filter_map(|asteroid| {
+                .filter_map(|mut asteroid| {
                     // By default, the asteroid has not been in a collision.
                     let mut asteroid_alive = true;
 
-                    for bullet in &mut transition_bullets {
-                        if asteroid.rect().overlaps(bullet.value.rect()) {
-                            asteroid_alive = false;
+                    // Large asteroids take several bullets, and flash with
+                    // every one of them until they break.
+                    for i in game.bullet_grid.query(asteroid.rect()) {
+                        let hit = game.bullets.hits(i, asteroid.shape());
+                        let bullet = game.bullets.get_mut(i);
+                        if bullet.alive && hit {
+                            let sparks = SPARKS_PER_IMPACT * phi.effects.particle_density();
+                            game.sparks.burst(sparks as usize, bullet.value.rect().center());
+
+                            if DEBUG {
+                                println!("{} hit {}", bullet.value.name(), asteroid.name);
+                            }
+
+                            if asteroid.health.damage(bullet.value.damage()) {
+                                asteroid_alive = false;
+                            }
                             bullet.alive = false;
+                            game.stats.shots_hit += 1;
                         }
                     }
 
+                    if !asteroid_alive {
+                        game.timeline.record(RunEvent::Kill);
+                        let points = game.combo.kill(asteroid.size.score());
+                        game.stats.record_kill(Target::Asteroid, asteroid.size.score(), points);
+                        game.achievements.notify(AchievementEvent::AsteroidDestroyed);
+                        game.achievements.notify(AchievementEvent::Combo(game.combo.multiplier()));
+                        game.popups.spawn(asteroid.rect().center(), points);
+                        game.score += points;
+                        fragments.append(&mut game.asteroid_factory.fragments(phi, &asteroid));
+                    }
+
                     // The player's ship is destroyed if it is hit by an asteroid.
                     // In which case, the asteroid is also destroyed.
-                    if asteroid.rect().overlaps(game.player.rect) {
+                    if asteroid.shape().overlaps(Shape::Rect(game.player.hitbox())) {
+                        if DEBUG {
+                            println!("{} hit {}", asteroid.name, game.player.name);
+                        }
+
                         asteroid_alive = false;
-                        player_alive = false;
+                        player_alive &= game.player.absorb_hit();
+                        game.player.knock_back(asteroid.body());
+                    } else if asteroid.shape().overlaps(Shape::Rect(game.player.graze_zone())) {
+                        // The asteroid may still hit the ship, so it only
+                        // earns points once it leaves the zone.
+                        asteroid.grazing = true;
+                    } else if asteroid.grazing {
+                        // The asteroid brushed past the ship without touching
+                        // its hitbox, which earns some points.
+                        asteroid.grazing = false;
+                        if asteroid_alive && !asteroid.grazed {
+                            asteroid.grazed = true;
+                            game.graze(phi, game.player.rect.center());
+                        }
                     }
 
                     if asteroid_alive {
                         Some(asteroid)
                     } else {
-                        // Spawn an explosive wherever an asteroid was destroyed.
+                        // Spawn an explosive wherever an asteroid was destroyed,
+                        // along with some debris.
+                        let debris = DEBRIS_PER_ASTEROID * phi.effects.particle_density();
+                        game.debris.burst(debris as usize, asteroid.rect().center());
                         game.explosions.push(
                             game.explosion_factory.at_center(
-                                asteroid.rect().center()));
+                                phi, asteroid.rect().center()));
+
+                        // Shake the screen a little with every explosion.
+                        let (intensity, duration) = EXPLOSION_SHAKE;
+                        game.camera.shake(intensity * phi.effects.screen_shake(), duration);
                         None
                     }
                 })
                 .collect();
     
-            game.bullets = transition_bullets.into_iter()
-                .filter_map(MaybeAlive::as_option)
+            game.enemies =
+                ::std::mem::take(&mut game.enemies)
+                .into_iter()
+                .filter_map(|mut enemy| {
+                    let mut enemy_alive = true;
+
+                    for i in game.bullet_grid.query(enemy.rect) {
+                        let hit = game.bullets.hits(i, Shape::Rect(enemy.rect));
+                        let bullet = game.bullets.get_mut(i);
+                        if bullet.alive && hit {
+                            if DEBUG {
+                                println!("{} hit {}", bullet.value.name(), enemy.name);
+                            }
+
+                            if enemy.health.damage(bullet.value.damage()) {
+                                enemy_alive = false;
+                            }
+                            bullet.alive = false;
+                            game.stats.shots_hit += 1;
+                        }
+                    }
+
+                    if !enemy_alive {
+                        game.timeline.record(RunEvent::Kill);
+                        let points = game.combo.kill(ENEMY_SCORE);
+                        game.stats.record_kill(Target::Enemy, ENEMY_SCORE, points);
+                        game.achievements.notify(AchievementEvent::EnemyKilled);
+                        game.achievements.notify(AchievementEvent::Combo(game.combo.multiplier()));
+                        game.popups.spawn(enemy.rect.center(), points);
+                        game.score += points;
+                    }
+
+                    // Ramming the player's ship destroys the enemy as well.
+                    if enemy.rect.overlaps(game.player.hitbox()) {
+                        if DEBUG {
+                            println!("{} hit {}", enemy.name, game.player.name);
+                        }
+
+                        enemy_alive = false;
+                        player_alive &= game.player.absorb_hit();
+                        game.player.knock_back((enemy.rect.center().into(), Vec2::new(-ENEMY_SPEED, 0.0), PLAYER_MASS));
+                    }
+
+                    if enemy_alive {
+                        Some(enemy)
+                    } else {
+                        game.pickups.drop_loot(phi, enemy.rect.center(), ENEMY_GEMS);
+
+                        let debris = DEBRIS_PER_ASTEROID * phi.effects.particle_density();
+                        game.debris.burst(debris as usize, enemy.rect.center());
+                        game.explosions.push(
+                            game.explosion_factory.at_center(phi, enemy.rect.center()));
+
+                        let (intensity, duration) = EXPLOSION_SHAKE;
+                        game.camera.shake(intensity * phi.effects.screen_shake(), duration);
+                        None
+                    }
+                })
                 .collect();
 
-            // TODO:
-            // For the moment, we won'tdo anything about the player dying. This will be
-            // the subject of a future episode.
+            if let Some(ref mut boss) = game.boss {
+                for part in boss.parts() {
+                    for i in game.bullet_grid.query(part) {
+                        let hit = game.bullets.hits(i, Shape::Rect(part));
+                        let bullet = game.bullets.get_mut(i);
+                        if bullet.alive && hit {
+                            if DEBUG {
+                                println!("{} hit {}", bullet.value.name(), boss.name);
+                            }
+
+                            bullet.alive = false;
+                            game.stats.shots_hit += 1;
+                            let sparks = SPARKS_PER_IMPACT * phi.effects.particle_density();
+                            game.sparks.burst(sparks as usize, bullet.value.rect().center());
+
+                            if boss.hit(bullet.value.damage()) {
+                                game.timeline.record(RunEvent::Kill);
+                                let points = game.combo.kill(BOSS_SCORE);
+                                game.stats.record_kill(Target::Boss, BOSS_SCORE, points);
+                                game.achievements.notify(AchievementEvent::BossDefeated);
+                                game.achievements.notify(AchievementEvent::Combo(game.combo.multiplier()));
+                                let (center, _, _) = boss.body();
+                                game.popups.spawn(center.into(), points);
+                                game.score += points;
+                            }
+                        }
+                    }
+                }
+
+                // Unlike the enemies, the boss survives ramming the ship, and
+                // throws it back.
+                let hitbox = game.player.hitbox();
+                if boss.parts().iter().any(|part| part.overlaps(hitbox)) {
+                    game.player.knock_back(boss.body());
+                    if boss.ram() {
+                        if DEBUG {
+                            println!("{} hit {}", boss.name, game.player.name);
+                        }
+
+                        player_alive &= game.player.absorb_hit();
+                    }
+                }
+            }
+
+            game.bullets.sweep();
+
+            // The enemies' bullets only ever hit the player's ship.
+            let hitbox = game.player.hitbox();
+            let hit_by = (0..game.enemy_bullets.len()).find(|&i| game.enemy_bullets.hits(i, Shape::Rect(hitbox)));
+            if let Some(i) = hit_by {
+                let bullet = game.enemy_bullets.get_mut(i);
+                if DEBUG {
+                    println!("{} hit {}", bullet.value.name(), game.player.name);
+                }
+
+                bullet.alive = false;
+                player_alive &= game.player.absorb_hit();
+            }
+            game.enemy_bullets.sweep();
+
+            // The other ones may graze it, once each, when they leave its
+            // graze zone. Those which hit the ship instead are gone by now,
+            // so that a hit never also counts as a near miss.
+            let graze_zone = game.player.graze_zone();
+            let grazing: Vec<_> = game.enemy_bullets.iter()
+                .filter(|bullet| bullet.rect().overlaps(graze_zone))
+                .map(|bullet| bullet.name())
+                .collect();
+            let grazed: Vec<_> = game.enemy_bullets.iter()
+                .filter(|bullet| game.grazing_bullets.contains(&bullet.name()) && !grazing.contains(&bullet.name()))
+                .map(|bullet| (bullet.name(), bullet.rect().center()))
+                .collect();
+
+            for (name, pos) in grazed {
+                if !game.grazed_bullets.contains(&name) {
+                    game.grazed_bullets.push(name);
+                    game.graze(phi, pos);
+                }
+            }
+            game.grazing_bullets = grazing;
+
+            let enemy_bullets = &game.enemy_bullets;
+            game.grazed_bullets.retain(|name| enemy_bullets.iter().any(|bullet| bullet.name() == *name));
+
+            game.asteroids.append(&mut fragments);
+            phi.trace.end(collisions, "collisions");
+
+            if game.player.hits_taken > hits_taken {
+                game.achievements.notify(AchievementEvent::ShipHit);
+            }
+
             if !player_alive {
-                println!("The player's ship has been destroyed.");
+                game.player.flash = PLAYER_FLASH;
+
+                let (intensity, duration) = PLAYER_HIT_SHAKE;
+                game.camera.shake(intensity * phi.effects.screen_shake(), duration);
+                game.timeline.record(RunEvent::Hit);
+                if !game.is_watched() {
+                    game.heatmap.record(game.player.rect.center(), phi.output_size());
+                }
+
+                if game.mutators.is_modified() {
+                    println!("The player's ship has been destroyed. (modified run)");
+                } else {
+                    println!("The player's ship has been destroyed.");
+                }
+
+                // The run ends once the ship is out of lives, or right away
+                // if it was started with the one-hit death mutator. Watched
+                // runs keep going regardless.
+                game.lives =
+                    if game.mutators.one_hit_death { 0 }
+                    else { game.lives.saturating_sub(1) };
+
+                // The ship blows up in slow motion, as the music fades out.
+                if game.lives == 0 && !game.is_watched() {
+                    game.end_run();
+
+                    let center = game.player.rect.center();
+                    game.explosions.push(game.explosion_factory.at_center(phi, center));
+                    let debris = DEBRIS_PER_ASTEROID * 3.0 * phi.effects.particle_density();
+                    game.debris.burst(debris as usize, center);
+
+                    let (intensity, duration) = GAME_OVER_SHAKE;
+                    game.camera.shake(intensity * phi.effects.screen_shake(), duration);
+
+                    game.ducking.reset();
+                    phi.fades.fade_out(FadeTarget::Music, GAME_OVER_TIME + GAME_OVER_FADE);
+                    game.game_over = Some(Tween::new(GAME_OVER_SLOW_MOTION, 1.0, GAME_OVER_TIME, Ease::In));
+                    return ViewAction::Render(self);
+                }
             }
     
             // Allow the player to shoot after the bullets are updated, so that,
             // when rendered for the first time, they are drawn wherever they
             // spawned.
-            if phi.events.now.key_space == Some(true) {
-                game.bullets.append(&mut game.player.spawn_bullets());
+            if controls.fire {
+                let before = game.bullets.len();
+                game.player.fire(&mut phi.ids, &mut game.bullets);
+                game.stats.shots_fired += (game.bullets.len() - before) as u64;
+                if game.bullets.len() > before {
+                    game.achievements.notify(AchievementEvent::ShotFired);
+                }
+            }
+
+            if controls.bomb {
+                game.detonate_bomb(phi);
             }
     
-            // Randomly create an asteroid about once every 100 frames, that is,
-            // a bit more often than once every two seconds.
-            if ::rand::random::<usize>() % 100  == 0 {
-                game.asteroids.push(game.asteroid_factory.random(phi));
+            // Follow the trailer script, if any, which moves the camera on
+            // its own.
+            if let Some(mut trailer) = game.trailer.take() {
+                for cue in trailer.script.update(elapsed, &mut game.camera, phi.output_size()) {
+                    match cue {
+                        TrailerCue::Asteroid { speed } => game.spawn_asteroid(phi, speed),
+                        TrailerCue::Enemy(pattern) => game.spawn_enemy(phi, pattern),
+                        TrailerCue::Shake { intensity, duration } => game.camera.shake(intensity, duration),
+                        TrailerCue::Hud(show) => trailer.show_hud = show,
+                        TrailerCue::Waves(on) => trailer.waves = on,
+                        TrailerCue::Bot(on) => trailer.bot = if on { Some(Bot::new()) } else { None },
+                        TrailerCue::End => return ViewAction::Quit,
+                    }
+                }
+
+                game.trailer = Some(trailer);
+            }
+
+            // Spawn whatever the current wave is made of, unless a trailer
+            // script holds the waves back. Runs of survival have no waves.
+            let remaining = game.asteroids.len() + game.enemies.len() + game.boss.is_some() as usize;
+            let spawn_rate = game.difficulty.spawn_rate();
+            let waves = game.trailer.as_ref().is_none_or(|trailer| trailer.waves);
+            let event = match game.survival {
+                Some(ref mut survival) => survival.update(elapsed, spawn_rate, remaining, phi.rng()),
+                None if waves => game.director.update(elapsed * spawn_rate, remaining, phi.rng()),
+                None => None,
+            };
+            match event {
+                Some(WaveEvent::Started(wave)) => {
+                    game.achievements.notify(AchievementEvent::WaveStarted(wave));
+
+                    let text = format!("Wave {}", wave);
+                    match phi.ttf_str_sprite(&text, "assets/belligerent.ttf", 64, Color::RGB(255, 255, 255)) {
+                        Ok(sprite) => game.banner = Some((sprite, WAVE_BANNER_TIME)),
+                        Err(err) => return ViewAction::Error(err),
+                    }
+                },
+
+                Some(WaveEvent::Asteroid { speed }) => game.spawn_asteroid(phi, speed),
+                Some(WaveEvent::Enemy(pattern)) => game.spawn_enemy(phi, pattern),
+
+                Some(WaveEvent::Boss) => {
+                    let sprite = game.enemy_factory.sprites[1].clone();
+                    match Boss::new(phi, sprite, phi.output_size(), &game.enemy_factory.patterns) {
+                        Ok(boss) => game.boss = Some(boss),
+                        Err(err) => return ViewAction::Error(err),
+                    }
+                },
+
+                None => {},
+            }
+
+            if let Some((_, ref mut time_left)) = game.banner {
+                *time_left -= elapsed;
+            }
+
+            // Tell the player about whatever they just unlocked. Runs played
+            // by the bot, or by a script, unlock nothing.
+            let unlocked = game.achievements.take_unlocked();
+            if !unlocked.is_empty() && !game.is_watched() {
+                for achievement in unlocked {
+                    if let Err(err) = game.toasts.push(phi, achievement) {
+                        return ViewAction::Error(err);
+                    }
+                }
+
+                if let Err(err) = game.achievements.save() {
+                    println!("Could not save the achievements: {}", err);
+                }
+            }
+            game.toasts.update(elapsed);
+            if game.banner.as_ref().is_some_and(|&(_, time_left)| time_left <= 0.0) {
+                game.banner = None;
             }
     
             // Update the backgrounds
-            game.bg_back.update(elapsed);
-            game.bg_middle.update(elapsed);
-            game.bg_front.update(elapsed);
+            game.background.update(elapsed);
+
+            // Report on the entities, for the debug overlay
+            phi.stats.set("Bullets", game.bullets.len());
+            phi.stats.set("Asteroids", game.asteroids.len());
+            phi.stats.set("Enemies", game.enemies.len());
+            phi.stats.set("Wave", game.director.wave_number() as usize);
+            phi.stats.set("Explosions", game.explosions.len());
+
+            // Update the HUD
+            let cannon = format!("Cannon: {}", game.player.cannon.name());
+            game.hud.set_text("cannon", &cannon);
+
+            let score = format!("Score: {}", game.score);
+            game.hud.set_text("score", &score);
+
+            match game.combo.multiplier() {
+                1 => game.hud.clear_text("combo"),
+                multiplier => game.hud.set_text("combo", &format!("Combo x{}", multiplier)),
+            }
+
+            let charge = game.player.shield_charge();
+            let shield =
+                if charge >= 1.0 { "Shield: Up".to_string() }
+                else { format!("Shield: {}%", (charge * 100.0) as u32) };
+            game.hud.set_text("shield", &shield);
+
+            let lives = format!("Lives: {}", game.lives);
+            game.hud.set_text("lives", &lives);
+
+            let bombs =
+                if game.mutators.infinite_bombs { "Bombs: Infinite".to_string() }
+                else { format!("Bombs: {}", game.bombs) };
+            game.hud.set_text("bombs", &bombs);
+
+            if let Some(ref survival) = game.survival {
+                let time = format!("Time: {}", Leaderboard::Survival.format(survival.time() as u64));
+                game.hud.set_text("time", &time);
+            }
         }
         // Update the player
         ViewAction::Render(self)
     }
 
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, alpha: f64) {
         // Clear the scene
         phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
         phi.renderer.clear();
 
         // Render the Backgrounds
-        self.bg_back.render(&mut phi.renderer);
-        self.bg_middle.render(&mut phi.renderer);
+        self.background.render_back(&mut phi.renderer);
+
+        // Render the walls of the arena, if any.
+        let camera = &self.camera;
+        if let Some(walls) = self.level.walls {
+            let (w, h) = phi.output_size();
+            let (top, bottom) = walls.in_screen((w, h));
+
+            for wall in &[Rectangle { x: 0.0, y: 0.0, w, h: top }, Rectangle { x: 0.0, y: bottom, w, h: h - bottom }] {
+                draw::fill_rect(&mut phi.renderer, camera.view(*wall), Color::RGB(90, 90, 110));
+            }
+        }
 
         // Render the entities
 
-        self.player.render(phi);
+        self.exhaust.render(&mut phi.renderer, camera);
+        if self.game_over.is_none() {
+            self.player.render(phi, camera, alpha);
+        }
 
-        for bullet in &self.bullets {
-            bullet.render(phi);
+        for bullet in self.bullets.iter() {
+            bullet.render(phi, camera);
         }
 
-        for asteroid in &self.asteroids {
-            asteroid.render(phi);
+        // Highlight whatever the homing missiles are going after. Targets are
+        // listed as they are when the bullets are updated.
+        let targets: Vec<_> = self.asteroids.iter().map(Asteroid::rect)
+            .chain(self.enemies.iter().map(|enemy| enemy.rect))
+            .chain(self.boss.iter().flat_map(Boss::parts))
+            .collect();
+        let targeted = self.bullets.homing_targets(&targets);
+
+        for (i, asteroid) in self.asteroids.iter().enumerate() {
+            if targeted.contains(&i) {
+                asteroid.render_outline(phi, camera, alpha, &self.asteroid_factory.outlines);
+            }
+            asteroid.render(phi, camera, alpha);
+        }
+
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            if targeted.contains(&(self.asteroids.len() + i)) {
+                enemy.render_outline(phi, camera, alpha, &self.enemy_factory.outlines);
+            }
+            enemy.render(phi, camera, alpha);
+        }
+
+        self.pickups.render(phi, camera, alpha, self.player.rect);
+
+        if let Some(ref boss) = self.boss {
+            boss.render(phi, camera, alpha);
+        }
+
+        for bullet in self.enemy_bullets.iter() {
+            bullet.render(phi, camera);
         }
 
         for explosion in &self.explosions {
-            explosion.render(phi);
+            explosion.render(phi, camera);
         }
 
+        self.debris.render(&mut phi.renderer, camera);
+        self.sparks.render(&mut phi.renderer, camera);
+        self.popups.render(phi, camera);
+
         // Render the foreground
-        self.bg_front.render(&mut phi.renderer);
+        self.background.render_front(&mut phi.renderer);
+
+        // Outline what may collide with what, for debugging purposes
+        if DEBUG || phi.show_hitboxes {
+            self.render_hitboxes(phi);
+        }
+
+        // Show where the ship tends to be hit, and where it may go
+        if DEBUG {
+            self.heatmap.render(phi);
+
+            let movable_region = self.level.movable_region.in_screen(phi.output_size());
+            draw::outline_rect(&mut phi.renderer, self.camera.view(movable_region), Color::RGB(50, 200, 50));
+
+            if let Some(ref demo) = self.demo {
+                demo.bot.render_sight(phi, &self.camera);
+            }
+        }
+
+        // Announce new waves, fading out, unless a trailer script hides the
+        // HUD.
+        let show_hud = self.trailer.as_ref().is_none_or(|trailer| trailer.show_hud);
+        if let Some((ref banner, time_left)) = self.banner.as_ref().filter(|_| show_hud) {
+            let (win_w, win_h) = phi.output_size();
+            let (w, h) = banner.size();
+            phi.renderer.copy_sprite_tinted(banner, Rectangle {
+                w,
+                h,
+                x: (win_w - w) / 2.0,
+                y: (win_h - h) / 3.0,
+            }, Tint::alpha(time_left / WAVE_BANNER_TIME * 2.0));
+        }
+
+        // Render the HUD on top of everything else, or the watermark if the
+        // bot is playing.
+        match self.demo {
+            Some(ref demo) => {
+                let (win_w, _) = phi.output_size();
+                let (w, h) = demo.watermark.size();
+
+                // Slowly pulse the watermark, so that it does not get in
+                // the way too much.
+                let time = demo.ticks as f64 * TICK;
+                let alpha = 0.6 + 0.4 * (time * ::std::f64::consts::PI).cos();
+                phi.renderer.copy_sprite_tinted(&demo.watermark, Rectangle {
+                    w,
+                    h,
+                    x: (win_w - w) / 2.0,
+                    y: 20.0,
+                }, Tint::alpha(alpha));
+            },
+
+            None if show_hud => self.hud.render(phi),
+            None => {},
+        }
+
+        if let Some(boss) = self.boss.as_ref().filter(|_| show_hud) {
+            boss.render_health_bar(phi);
+        }
+
+        self.toasts.render(phi);
+
+        // Render the cannon selector around the ship
+        if let Some(ref select) = self.cannon_select {
+            select.render(phi, self.camera.view(self.player.rect).center());
+        }
+
+        // Dim the game and render the pause menu on top of it
+        if let Some(ref pause) = self.pause {
+            let (win_w, win_h) = phi.output_size();
+            draw::fill_rect(&mut phi.renderer, Rectangle::with_size(win_w, win_h), Color::RGBA(0, 0, 0, 160));
+
+            pause.menu.render(phi);
+        }
+
+        if !self.is_watched() {
+            self.restart.render(phi);
+        }
+    }
+
+    fn on_enter(&mut self, phi: &mut Phi) {
+        //? The view which was left gave the music its full volume back by
+        //? now, whereas it may still have been ducked when this one was made.
+        self.ducking = MusicDucking::new();
+        if let Err(err) = self.music.play(-1) {
+            println!("Could not play the music: {}", err);
+        }
+
+        // Bring the music in gently rather than all at once.
+        phi.fades.fade_in(FadeTarget::Music, MUSIC_FADE_IN);
+    }
+
+    fn on_exit(&mut self, _phi: &mut Phi) {
+        // However the run was left, the music should not stay ducked.
+        self.ducking.reset();
+    }
+
+    fn on_pause(&mut self, phi: &mut Phi) {
+        // Do not let the game carry on while the player is away.
+        if self.pause.is_none() && self.game_over.is_none() && !self.is_watched() {
+            if let Err(err) = self.pause(phi, false) {
+                println!("Could not pause the game: {}", err);
+            }
+        }
     }
 }
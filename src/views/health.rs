@@ -0,0 +1,63 @@
+//! How much damage the things which the player shoots at may take before they
+//! are destroyed, and the flash which shows that they were hit.
+
+use crate::phi::gfx::Tint;
+
+/// For how long something flashes white once hit, in seconds.
+const HIT_FLASH: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Health {
+    current: f64,
+    max: f64,
+
+    // Time left before the flash of the last hit is over, in seconds.
+    flash: f64,
+}
+
+impl Health {
+    pub fn new(max: f64) -> Health {
+        Health { current: max, max, flash: 0.0 }
+    }
+
+    /// Health of which only `fraction` of `max` is left, e.g. when a saved
+    /// run is restored.
+    pub fn with_fraction(max: f64, fraction: f64) -> Health {
+        Health { current: max * fraction.clamp(0.0, 1.0), max, flash: 0.0 }
+    }
+
+    /// Takes `damage`. Returns whether it was the blow which destroyed the
+    /// entity, which is only ever the case once.
+    pub fn damage(&mut self, damage: f64) -> bool {
+        if self.is_depleted() {
+            return false;
+        }
+
+        self.current -= damage;
+        self.flash = HIT_FLASH;
+        self.is_depleted()
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// The part of the health which is left, in `[0, 1]`.
+    pub fn fraction(&self) -> f64 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+
+    /// Lets the flash of the last hit fade for `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        self.flash = (self.flash - dt).max(0.0);
+    }
+
+    /// The tint of something usually drawn with `tint`, which flashes white
+    /// for a moment after every hit.
+    pub fn tinted(&self, tint: Tint) -> Tint {
+        Tint {
+            glow: (self.flash / HIT_FLASH * 255.0) as u8,
+            ..tint
+        }
+    }
+}
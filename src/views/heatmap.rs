@@ -0,0 +1,85 @@
+use crate::phi::Phi;
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::draw;
+use crate::views::shared::profile_path;
+use sdl2::pixels::Color;
+use std::fs;
+use std::path::PathBuf;
+
+/// The number of cells in which the screen is divided, horizontally and
+/// vertically.
+const COLS: usize = 32;
+const ROWS: usize = 24;
+
+/// How many times the player's ship was hit in every region of the screen,
+/// across every run, to help tune where asteroids spawn and where the ship
+/// may go.
+pub struct Heatmap {
+    counts: Vec<u32>,
+}
+
+impl Heatmap {
+    fn path(profile: &str) -> PathBuf {
+        profile_path(profile, "heatmap.cfg")
+    }
+
+    /// Reads the heatmap saved for `profile`. If there is none, or if it
+    /// could not be parsed, the heatmap starts empty.
+    pub fn load(profile: &str) -> Heatmap {
+        let counts: Vec<u32> = fs::read_to_string(Heatmap::path(profile))
+            .map(|contents| contents.split_whitespace()
+                .filter_map(|count| count.parse().ok())
+                .collect())
+            .unwrap_or_default();
+
+        Heatmap {
+            counts: if counts.len() == COLS * ROWS { counts } else { vec![0; COLS * ROWS] },
+        }
+    }
+
+    /// Writes the heatmap for `profile`, one row of cells per line.
+    pub fn save(&self, profile: &str) -> std::io::Result<()> {
+        let path = Heatmap::path(profile);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents: String = self.counts.chunks(COLS)
+            .map(|row| {
+                let row: Vec<String> = row.iter().map(|count| count.to_string()).collect();
+                row.join(" ") + "\n"
+            })
+            .collect();
+
+        fs::write(path, contents)
+    }
+
+    /// Counts a hit at `pos`, on a screen of size `screen`.
+    pub fn record(&mut self, pos: (f64, f64), screen: (f64, f64)) {
+        let col = ((pos.0 / screen.0 * COLS as f64).max(0.0) as usize).min(COLS - 1);
+        let row = ((pos.1 / screen.1 * ROWS as f64).max(0.0) as usize).min(ROWS - 1);
+        self.counts[row * COLS + col] += 1;
+    }
+
+    /// Draws every cell in which the ship was hit as a translucent red
+    /// square, which is more opaque where it was hit more often.
+    pub fn render(&self, phi: &mut Phi) {
+        let max = match self.counts.iter().max() {
+            Some(&max) if max > 0 => max as f64,
+            _ => return,
+        };
+
+        let (w, h) = phi.output_size();
+        let (cell_w, cell_h) = (w / COLS as f64, h / ROWS as f64);
+
+        for (i, &count) in self.counts.iter().enumerate().filter(|&(_, &count)| count > 0) {
+            let alpha = 40.0 + 160.0 * count as f64 / max;
+            draw::fill_rect(&mut phi.renderer, Rectangle {
+                x: (i % COLS) as f64 * cell_w,
+                y: (i / COLS) as f64 * cell_h,
+                w: cell_w,
+                h: cell_h,
+            }, Color::RGBA(255, 0, 0, alpha as u8));
+        }
+    }
+}
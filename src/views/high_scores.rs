@@ -0,0 +1,324 @@
+use crate::phi::storage::Storage;
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::shared::{install_key, profile_path, ParallaxBackground, STARFIELD_PATH};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::cmp::Reverse;
+use std::io;
+
+/// How many scores are kept.
+pub const MAX_SCORES: usize = 10;
+
+/// The longest name which may be entered, in characters.
+const MAX_NAME_LEN: usize = 12;
+
+/// The lists in which the best runs are kept apart, by how they are played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Leaderboard {
+    /// Runs of waves, ranked by score.
+    Waves,
+
+    /// Runs of survival, ranked by the number of seconds survived.
+    Survival,
+}
+
+impl Leaderboard {
+    pub const ALL: [Leaderboard; 2] = [Leaderboard::Waves, Leaderboard::Survival];
+
+    /// The file of a profile in which the list is kept.
+    pub fn file(self) -> &'static str {
+        match self {
+            Leaderboard::Waves => "scores.cfg",
+            Leaderboard::Survival => "survival-scores.cfg",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Leaderboard::Waves => "High Scores",
+            Leaderboard::Survival => "Longest Survivals",
+        }
+    }
+
+    /// The list shown after this one.
+    fn next(self) -> Leaderboard {
+        match self {
+            Leaderboard::Waves => Leaderboard::Survival,
+            Leaderboard::Survival => Leaderboard::Waves,
+        }
+    }
+
+    /// How an entry of the list is shown, e.g. `2:05` for survivals.
+    pub fn format(self, value: u64) -> String {
+        match self {
+            Leaderboard::Waves => value.to_string(),
+            Leaderboard::Survival => format!("{}:{:02}", value / 60, value % 60),
+        }
+    }
+}
+
+/// A run which made it into a leaderboard.
+#[derive(Clone, Debug)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: u64,
+
+    /// Whether the run was played with some mutators, see `Mutators`.
+    pub modified: bool,
+}
+
+/// The best scores of a player, along with the name entered for each of them.
+/// The file is signed, so that scores which were edited by hand are discarded.
+pub struct HighScores {
+    storage: Storage,
+
+    // Sorted from the best score to the worst.
+    entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    pub fn load(profile: &str, board: Leaderboard) -> HighScores {
+        let storage = Storage::load_signed(profile_path(profile, board.file()), install_key());
+
+        //? Every entry is saved as `N=score name`, where N is its rank. The
+        //? score of a modified run is followed by `*`.
+        let mut entries: Vec<ScoreEntry> = (1..=MAX_SCORES)
+            .filter_map(|rank| storage.get(&rank.to_string()))
+            .filter_map(|entry| entry.split_once(' '))
+            .filter_map(|(score, name)| {
+                let (score, modified) = match score.strip_suffix('*') {
+                    Some(score) => (score, true),
+                    None => (score, false),
+                };
+                Some(ScoreEntry { name: name.to_string(), score: score.parse().ok()?, modified })
+            })
+            .collect();
+        entries.sort_by_key(|entry| Reverse(entry.score));
+
+        HighScores { storage, entries }
+    }
+
+    /// The scores, from the best to the worst.
+    pub fn entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+
+    /// Whether `score` would make it into the list.
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0 && (self.entries.len() < MAX_SCORES || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Adds `score` to the list, under `name`, dropping the worst score if
+    /// there are too many.
+    pub fn insert(&mut self, name: &str, score: u64, modified: bool) {
+        let rank = self.entries.iter().position(|entry| score > entry.score).unwrap_or(self.entries.len());
+        self.entries.insert(rank, ScoreEntry { name: name.to_string(), score, modified });
+        self.entries.truncate(MAX_SCORES);
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let mark = if entry.modified { "*" } else { "" };
+            self.storage.set(&(i + 1).to_string(), &format!("{}{} {}", entry.score, mark, entry.name));
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.storage.save()
+    }
+}
+
+/// Lists the best scores of the player, one leaderboard at a time.
+pub struct HighScoresView {
+    profile: String,
+    board: Leaderboard,
+
+    menu: Menu,
+    switch: WidgetId,
+    back: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl HighScoresView {
+    pub fn new(phi: &mut Phi, profile: &str, board: Leaderboard) -> Result<HighScoresView, PhiError> {
+        let mut menu = Menu::new(480.0, 36.0);
+        let scores = HighScores::load(profile, board);
+
+        menu.add(phi, board.title(), WidgetKind::Label)?;
+        if scores.entries().is_empty() {
+            menu.add(phi, "No scores yet", WidgetKind::Label)?;
+        }
+
+        for (i, entry) in scores.entries().iter().enumerate() {
+            let mark = if entry.modified { " (modified)" } else { "" };
+            let text = format!("{}. {} - {}{}", i + 1, entry.name, board.format(entry.score), mark);
+            menu.add(phi, &text, WidgetKind::Label)?;
+        }
+
+        Ok(HighScoresView {
+            profile: profile.to_string(),
+            board,
+            switch: menu.add(phi, board.next().title(), WidgetKind::Button)?,
+            back: menu.add(phi, "Back", WidgetKind::Button)?,
+            menu,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+}
+
+impl View for HighScoresView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        let clicked = self.menu.update(phi);
+        if clicked == Some(UiEvent::Clicked(self.switch)) {
+            let view = HighScoresView::new(phi, &self.profile, self.board.next());
+            return ViewAction::switch_to(self, view);
+        }
+
+        let back = phi.events.now.key_escape == Some(true) ||
+            clicked == Some(UiEvent::Clicked(self.back));
+        if back {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
+
+/// Asks for the name under which a new high score is saved, once a run is
+/// over.
+pub struct NameEntryView {
+    profile: String,
+    board: Leaderboard,
+    score: u64,
+    modified: bool,
+    name: String,
+
+    menu: Menu,
+    name_label: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl NameEntryView {
+    /// Asks for the name under which `score` is saved in `board`, marked as
+    /// `modified` if the run was played with some mutators.
+    pub fn new(phi: &mut Phi, profile: &str, board: Leaderboard, score: u64, modified: bool) -> Result<NameEntryView, PhiError> {
+        let mut menu = Menu::new(480.0, 50.0);
+
+        //? Only labels are added, so that typing a space does not activate
+        //? some button.
+        menu.add(phi, "New high score!", WidgetKind::Label)?;
+        menu.add(phi, &board.format(score), WidgetKind::Label)?;
+        if modified {
+            menu.add(phi, "Modified run", WidgetKind::Label)?;
+        }
+        let name_label = menu.add(phi, "Name: _", WidgetKind::Label)?;
+        menu.add(phi, "Press Enter to save", WidgetKind::Label)?;
+
+        Ok(NameEntryView {
+            profile: profile.to_string(),
+            board,
+            score,
+            modified,
+            name: String::new(),
+            menu,
+            name_label,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    fn save(&self) {
+        let name = match self.name.trim() {
+            "" => "Anonymous",
+            name => name,
+        };
+
+        let mut scores = HighScores::load(&self.profile, self.board);
+        scores.insert(name, self.score, self.modified);
+
+        if let Err(err) = scores.save() {
+            println!("Could not save the high scores: {}", err);
+        }
+    }
+}
+
+impl View for NameEntryView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            self.save();
+            return ViewAction::Quit;
+        }
+
+        let skip = phi.events.now.key_escape == Some(true);
+        if phi.events.now.key_enter == Some(true) || skip {
+            // Skipping the name entry saves the score anonymously.
+            if skip {
+                self.name.clear();
+            }
+
+            self.save();
+            let view = HighScoresView::new(phi, &self.profile, self.board);
+            return ViewAction::switch_to(self, view);
+        }
+
+        let mut name = self.name.clone();
+        if phi.events.now.key_pressed == Some(Keycode::Backspace) {
+            name.pop();
+        }
+
+        // Spaces in names are kept, but not other whitespace.
+        let typed = phi.events.now.text_input.chars()
+            .filter(|c| *c == ' ' || (!c.is_whitespace() && !c.is_control()));
+        for c in typed {
+            if name.chars().count() < MAX_NAME_LEN {
+                name.push(c);
+            }
+        }
+
+        if name != self.name {
+            self.name = name;
+            let text = format!("Name: {}_", self.name);
+            if let Err(err) = self.menu.set_text(phi, self.name_label, &text) {
+                return ViewAction::Error(err);
+            }
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::phi::{Phi, PhiError};
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::bitmap_font::{BitmapFont, ASCII};
+use crate::phi::ui::{Align, Anchor};
+use sdl2::pixels::Color;
+use crate::views::shared::profile_path;
+use std::fs;
+use std::path::PathBuf;
+
+const HUD_FONT_PATH: &str = "assets/belligerent.ttf";
+const HUD_FONT_SIZE: u16 = 24;
+
+/// Where and how large a single HUD element is drawn.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HudPlacement {
+    pub name: String,
+    pub anchor: Anchor,
+    pub offset: (f64, f64),
+    pub scale: f64,
+}
+
+/// The placement of every element of the HUD.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HudLayout {
+    pub elements: Vec<HudPlacement>,
+}
+
+impl Default for HudLayout {
+    fn default() -> HudLayout {
+        HudLayout {
+            elements: vec![
+                HudPlacement {
+                    name: "cannon".to_string(),
+                    anchor: Anchor { horizontal: Align::Start, vertical: Align::End },
+                    offset: (10.0, 10.0),
+                    scale: 1.0,
+                },
+                HudPlacement {
+                    name: "score".to_string(),
+                    anchor: Anchor { horizontal: Align::End, vertical: Align::Start },
+                    offset: (10.0, 10.0),
+                    scale: 1.0,
+                },
+                HudPlacement {
+                    name: "shield".to_string(),
+                    anchor: Anchor { horizontal: Align::End, vertical: Align::End },
+                    offset: (10.0, 10.0),
+                    scale: 1.0,
+                },
+                HudPlacement {
+                    name: "lives".to_string(),
+                    anchor: Anchor { horizontal: Align::Start, vertical: Align::Start },
+                    offset: (10.0, 10.0),
+                    scale: 1.0,
+                },
+                HudPlacement {
+                    name: "bombs".to_string(),
+                    anchor: Anchor { horizontal: Align::Start, vertical: Align::Start },
+                    offset: (10.0, 40.0),
+                    scale: 0.8,
+                },
+                HudPlacement {
+                    name: "combo".to_string(),
+                    anchor: Anchor { horizontal: Align::End, vertical: Align::Start },
+                    offset: (10.0, 40.0),
+                    scale: 0.8,
+                },
+                HudPlacement {
+                    name: "time".to_string(),
+                    anchor: Anchor { horizontal: Align::Center, vertical: Align::Start },
+                    offset: (0.0, 10.0),
+                    scale: 1.0,
+                },
+            ],
+        }
+    }
+}
+
+impl HudLayout {
+    fn path(profile: &str) -> PathBuf {
+        profile_path(profile, "hud.cfg")
+    }
+
+    /// Reads the layout saved for `profile`. Elements which are missing from
+    /// the file, or which could not be parsed, keep their default placement.
+    pub fn load(profile: &str) -> HudLayout {
+        let mut layout = HudLayout::default();
+
+        if let Ok(contents) = fs::read_to_string(HudLayout::path(profile)) {
+            for placement in contents.lines().filter_map(HudLayout::parse_line) {
+                if let Some(element) = layout.elements.iter_mut().find(|e| e.name == placement.name) {
+                    *element = placement;
+                }
+            }
+        }
+
+        layout
+    }
+
+    /// Writes the layout for `profile`, one element per line, in the form:
+    ///     name horizontal vertical offset_x offset_y scale
+    pub fn save(&self, profile: &str) -> std::io::Result<()> {
+        let path = HudLayout::path(profile);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let contents: String = self.elements.iter()
+            .map(|e| format!("{} {} {} {} {} {}\n",
+                e.name, e.anchor.horizontal.name(), e.anchor.vertical.name(),
+                e.offset.0, e.offset.1, e.scale))
+            .collect();
+
+        fs::write(path, contents)
+    }
+
+    fn parse_line(line: &str) -> Option<HudPlacement> {
+        let mut words = line.split_whitespace();
+
+        Some(HudPlacement {
+            name: words.next()?.to_string(),
+            anchor: Anchor {
+                horizontal: Align::from_name(words.next()?)?,
+                vertical: Align::from_name(words.next()?)?,
+            },
+            offset: (words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+            scale: words.next()?.parse().ok()?,
+        })
+    }
+}
+
+/// Pieces of text drawn over the game, placed according to a `HudLayout`.
+pub struct Hud {
+    pub layout: HudLayout,
+
+    /// The text currently shown by every element.
+    texts: Vec<Option<String>>,
+
+    //? The score changes on most frames, so the HUD is drawn from a glyph
+    //? sheet rather than rendering a texture for every new text.
+    font: BitmapFont,
+}
+
+impl Hud {
+    pub fn new(phi: &mut Phi, layout: HudLayout) -> Result<Hud, PhiError> {
+        let mut font = BitmapFont::from_ttf(phi, HUD_FONT_PATH, HUD_FONT_SIZE, Color::RGB(255, 255, 255), ASCII)?;
+        font.monospaced_digits = true;
+
+        Ok(Hud {
+            texts: vec![None; layout.elements.len()],
+            layout,
+            font,
+        })
+    }
+
+    /// Changes the text shown by the element called `name`.
+    pub fn set_text(&mut self, name: &str, text: &str) {
+        if let Some(i) = self.layout.elements.iter().position(|e| e.name == name) {
+            if self.texts[i].as_deref() != Some(text) {
+                self.texts[i] = Some(text.to_string());
+            }
+        }
+    }
+
+    /// Hides the element called `name` until it is given some text again.
+    pub fn clear_text(&mut self, name: &str) {
+        if let Some(i) = self.layout.elements.iter().position(|e| e.name == name) {
+            self.texts[i] = None;
+        }
+    }
+
+    /// The region of the screen occupied by the element at index `i`, if it
+    /// has some text to show.
+    pub fn element_rect(&self, phi: &Phi, i: usize) -> Option<Rectangle> {
+        let placement = &self.layout.elements[i];
+
+        self.texts[i].as_ref().map(|text| {
+            let (w, h) = self.font.size_of(text);
+            placement.anchor.place(
+                phi.output_size(),
+                (w * placement.scale, h * placement.scale),
+                placement.offset)
+        })
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        for i in 0..self.layout.elements.len() {
+            if let (Some(rect), Some(text)) = (self.element_rect(phi, i), &self.texts[i]) {
+                let scale = self.layout.elements[i].scale;
+                self.font.render(&mut phi.renderer, text, (rect.x, rect.y), scale);
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::phi::gfx::{draw, CopySprite, Sprite};
+use crate::phi::data::Rectangle;
+use crate::phi::ui::Anchor;
+use crate::views::hud::{Hud, HudLayout};
+use crate::views::shared::DEFAULT_PROFILE;
+use sdl2::pixels::Color;
+
+const SCALE_STEP: f64 = 0.1;
+const MIN_SCALE: f64 = 0.5;
+const MAX_SCALE: f64 = 3.0;
+
+/// Lets the player drag the elements of the HUD around with the mouse and
+/// scale them with the directional keys. The layout is saved when leaving.
+pub struct HudEditorView {
+    hud: Hud,
+    help: Sprite,
+
+    /// The element which is scaled by the directional keys.
+    selected: usize,
+
+    /// The element being dragged, and where the cursor grabbed it, relative
+    /// to its top-left corner.
+    dragging: Option<(usize, (f64, f64))>,
+}
+
+impl HudEditorView {
+    pub fn new(phi: &mut Phi) -> Result<HudEditorView, PhiError> {
+        let mut hud = Hud::new(phi, HudLayout::load(DEFAULT_PROFILE))?;
+        HudEditorView::fill_samples(&mut hud);
+
+        Ok(HudEditorView {
+            hud,
+            help: phi.ttf_str_sprite(
+                "Drag to move, left/right to scale, space to reset, escape to save",
+                "assets/belligerent.ttf", 16, Color::RGB(220, 220, 220))?,
+            selected: 0,
+            dragging: None,
+        })
+    }
+
+    /// Gives every element of the HUD some sample text, so that it can be
+    /// seen and grabbed.
+    fn fill_samples(hud: &mut Hud) {
+        hud.set_text("cannon", "Cannon: Rect");
+        hud.set_text("score", "Score: 12345");
+        hud.set_text("combo", "Combo x3");
+        hud.set_text("shield", "Shield: Up");
+        hud.set_text("lives", "Lives: 3");
+        hud.set_text("bombs", "Bombs: 2");
+        hud.set_text("time", "Time: 2:05");
+    }
+}
+
+impl View for HudEditorView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            if let Err(err) = self.hud.layout.save(DEFAULT_PROFILE) {
+                println!("Could not save the HUD layout: {}", err);
+            }
+
+            return ViewAction::switch_to(self, crate::views::options::OptionsView::new(phi));
+        }
+
+        let (mx, my) = (phi.events.mouse.0 as f64, phi.events.mouse.1 as f64);
+
+        // Grab the element under the cursor
+        if phi.events.now.mouse_click.is_some() {
+            let cursor = Rectangle { x: mx, y: my, w: 0.0, h: 0.0 };
+            self.dragging = (0..self.hud.layout.elements.len())
+                .find_map(|i| self.hud.element_rect(phi, i)
+                    .filter(|rect| rect.contains(cursor))
+                    .map(|rect| (i, (mx - rect.x, my - rect.y))));
+
+            if let Some((i, _)) = self.dragging {
+                self.selected = i;
+            }
+        }
+
+        // Move the grabbed element, anchoring it to the closest point of the
+        // screen so that it stays in place if the resolution changes.
+        if let Some((i, (grab_x, grab_y))) = self.dragging {
+            if let Some(rect) = self.hud.element_rect(phi, i) {
+                let moved = Rectangle { x: mx - grab_x, y: my - grab_y, ..rect };
+                let (anchor, offset) = Anchor::nearest(phi.output_size(), moved);
+
+                let placement = &mut self.hud.layout.elements[i];
+                placement.anchor = anchor;
+                placement.offset = offset;
+            }
+
+            if phi.events.now.mouse_release {
+                self.dragging = None;
+            }
+        }
+
+        // Scale the selected element
+        let placement = &mut self.hud.layout.elements[self.selected];
+
+        if phi.events.now.key_left == Some(true) {
+            placement.scale = (placement.scale - SCALE_STEP).max(MIN_SCALE);
+        }
+
+        if phi.events.now.key_right == Some(true) {
+            placement.scale = (placement.scale + SCALE_STEP).min(MAX_SCALE);
+        }
+
+        // Reset to the default layout
+        if phi.events.now.key_space == Some(true) {
+            self.hud.layout = HudLayout::default();
+        }
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the instructions
+        let (win_w, _) = phi.output_size();
+        let (w, h) = self.help.size();
+        phi.renderer.copy_sprite(&self.help, Rectangle {
+            w,
+            h,
+            x: (win_w - w) / 2.0,
+            y: 10.0,
+        });
+
+        // Outline every element, highlighting the selected one
+        for i in 0..self.hud.layout.elements.len() {
+            if let Some(rect) = self.hud.element_rect(phi, i) {
+                draw::outline_rect(&mut phi.renderer, rect,
+                    if i == self.selected { Color::RGB(230, 230, 30) }
+                    else { Color::RGB(140, 30, 140) });
+            }
+        }
+
+        self.hud.render(phi);
+    }
+}
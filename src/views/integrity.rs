@@ -0,0 +1,86 @@
+use crate::phi::assets::AssetProblem;
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use sdl2::pixels::Color;
+
+/// Where the digests of the files shipped with the game are listed.
+pub const MANIFEST_PATH: &str = "assets/manifest.sha256";
+
+/// How many rows of the list are shown at once.
+const MAX_ROWS: usize = 10;
+
+/// Lists the files which are missing or damaged, as found at startup, so that
+/// the player knows to reinstall the game rather than run into an error in the
+/// middle of it.
+pub struct IntegrityView {
+    menu: Menu,
+    continue_anyway: WidgetId,
+    quit: WidgetId,
+}
+
+impl IntegrityView {
+    pub fn new(phi: &mut Phi, problems: &[AssetProblem]) -> Result<IntegrityView, PhiError> {
+        let mut menu = Menu::scrolling(640.0, 36.0, MAX_ROWS);
+
+        menu.add(phi, "Some game files are missing or damaged", WidgetKind::Label)?;
+        menu.add(phi, "Reinstalling the game should fix it", WidgetKind::Label)?;
+        for problem in problems {
+            menu.add(phi, &problem.to_string(), WidgetKind::Label)?;
+        }
+
+        Ok(IntegrityView {
+            continue_anyway: menu.add(phi, "Continue anyway", WidgetKind::Button)?,
+            quit: menu.add(phi, "Quit", WidgetKind::Button)?,
+            menu,
+        })
+    }
+}
+
+impl View for IntegrityView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit || phi.events.now.key_escape == Some(true) {
+            return ViewAction::Quit;
+        }
+
+        match self.menu.update(phi) {
+            Some(UiEvent::Clicked(id)) if id == self.continue_anyway =>
+                ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi)),
+            Some(UiEvent::Clicked(id)) if id == self.quit =>
+                ViewAction::Quit,
+            _ => ViewAction::Render(self),
+        }
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen. Nothing else is loaded, since it may be damaged.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        self.menu.render(phi);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phi::{PhiConfig, PhiRunner};
+
+    #[test]
+    fn escape_quits() {
+        //? Assets are found relative to `src`, from which the game is run.
+        std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src")).unwrap();
+
+        let problems = [AssetProblem::Missing("assets/belligerent.ttf".to_string())];
+        let mut runner = PhiRunner::headless(PhiConfig::default(),
+            |phi| Ok(Box::new(IntegrityView::new(phi, &problems)?)),
+            |_, reason| panic!("the view failed: {}", reason)).unwrap();
+
+        // The list stays up for as long as nothing is pressed...
+        for _ in 0..3 {
+            assert!(runner.tick(|_| {}).unwrap());
+        }
+
+        // ...and goes away, along with the game, once escape is.
+        assert!(!runner.tick(|events| events.now.key_escape = Some(true)).unwrap());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::phi::PhiError;
+use crate::phi::data::{Rectangle, Segment};
+use crate::views::save::SavedDirector;
+use crate::views::spawn::SpawnDistribution;
+use rand::Rng;
+use std::fs;
+
+/// A region of the screen, given as fractions of its size, so that it does
+/// not depend on the size of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenRegion {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl ScreenRegion {
+    /// The region, in pixels, on a screen of size `(w, h)`.
+    pub fn in_screen(&self, (w, h): (f64, f64)) -> Rectangle {
+        Rectangle {
+            x: self.x * w,
+            y: self.y * h,
+            w: self.w * w,
+            h: self.h * h,
+        }
+    }
+}
+
+/// Solid walls along the top and bottom of the screen, at the given fractions
+/// of its height, off which bullets ricochet and asteroids bounce.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Walls {
+    pub top: f64,
+    pub bottom: f64,
+}
+
+impl Walls {
+    /// The height of both walls, in pixels, on a screen of size `(w, h)`.
+    pub fn in_screen(&self, (_, h): (f64, f64)) -> (f64, f64) {
+        (self.top * h, self.bottom * h)
+    }
+
+    /// Both walls, as segments spanning the width of the screen.
+    pub fn segments(&self, screen: (f64, f64)) -> [Segment; 2] {
+        let (top, bottom) = self.in_screen(screen);
+        let (w, _) = screen;
+
+        [
+            Segment { from: (0.0, top), to: (w, top) },
+            Segment { from: (0.0, bottom), to: (w, bottom) },
+        ]
+    }
+}
+
+/// The parameters of a level, which differ from one game mode to another.
+#[derive(Clone, Copy, Debug)]
+pub struct Level {
+    /// Where the player's ship may go.
+    pub movable_region: ScreenRegion,
+
+    /// The time, in seconds, which the player is given to dodge a new
+    /// asteroid. Faster ones never spawn in line with the ship.
+    pub spawn_reaction_time: f64,
+
+    /// Whether asteroids bounce off each other, and steer away from those
+    /// which are too close, rather than going through them.
+    pub asteroid_collisions: bool,
+
+    /// The walls which bound the level, if any. Without walls, whatever
+    /// leaves the screen is gone.
+    pub walls: Option<Walls>,
+}
+
+impl Level {
+    /// The level of a regular run.
+    pub fn standard() -> Level {
+        Level {
+            // The movable region spans the entire height of the window and
+            // 70% of its width. This way, the player cannot get to the far
+            // right of the screen, where we spawn the asteroids, and get
+            // immediately eliminated.
+            //
+            // We restrain the width because most screens are wider than they
+            // are high.
+            movable_region: ScreenRegion { x: 0.0, y: 0.0, w: 0.70, h: 1.0 },
+            spawn_reaction_time: 0.75,
+            asteroid_collisions: true,
+            walls: None,
+        }
+    }
+
+    /// The level of the arena mode, in which the screen is closed at the top
+    /// and at the bottom, so that whatever goes there comes back.
+    pub fn arena() -> Level {
+        Level {
+            // The ship stays between the walls.
+            movable_region: ScreenRegion { x: 0.0, y: 0.1, w: 0.70, h: 0.8 },
+            walls: Some(Walls { top: 0.1, bottom: 0.9 }),
+            ..Level::standard()
+        }
+    }
+
+    /// The part of the screen, as the height of its top and bottom in pixels,
+    /// in which things may spawn and move around.
+    pub fn bounds(&self, screen: (f64, f64)) -> (f64, f64) {
+        match self.walls {
+            Some(walls) => walls.in_screen(screen),
+            None => (0.0, screen.1),
+        }
+    }
+}
+
+/// How an enemy ship moves once it entered the screen from the right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyPattern {
+    /// Keep going left at the same height.
+    Straight,
+
+    /// Wave up and down while going left.
+    Sine,
+
+    /// Steer towards the height at which the player's ship is.
+    Dive,
+}
+
+impl EnemyPattern {
+    pub const ALL: [EnemyPattern; 3] = [EnemyPattern::Straight, EnemyPattern::Sine, EnemyPattern::Dive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EnemyPattern::Straight => "straight",
+            EnemyPattern::Sine => "sine",
+            EnemyPattern::Dive => "dive",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<EnemyPattern> {
+        match name {
+            "straight" => Some(EnemyPattern::Straight),
+            "sine" => Some(EnemyPattern::Sine),
+            "dive" => Some(EnemyPattern::Dive),
+            _ => None,
+        }
+    }
+}
+
+/// A group of asteroids and enemies which must all be dealt with before the
+/// next one comes.
+#[derive(Clone, Debug)]
+pub struct Wave {
+    pub asteroids: u32,
+    pub enemies: u32,
+
+    /// The factor by which the velocity of the asteroids is multiplied.
+    pub speed: f64,
+
+    /// The time between two spawns, in seconds.
+    pub spacing: f64,
+
+    /// The patterns which the enemies may follow.
+    pub patterns: Vec<EnemyPattern>,
+}
+
+/// What the `LevelDirector` asks the game to do.
+#[derive(Clone, Copy, Debug)]
+pub enum WaveEvent {
+    /// A new wave, numbered from 1, is about to start.
+    Started(u32),
+
+    /// Spawn an asteroid, whose velocity is multiplied by `speed`.
+    Asteroid { speed: f64 },
+
+    /// Spawn an enemy following the given pattern.
+    Enemy(EnemyPattern),
+
+    /// Send in the boss, which must be defeated before the next wave starts.
+    Boss,
+}
+
+/// The waves of a regular run.
+pub const WAVES_PATH: &str = "assets/waves.cfg";
+
+/// The time between the start of a wave and its first spawn, during which its
+/// number is shown, in seconds.
+const WAVE_BREAK: f64 = 2.5;
+
+/// By how much the asteroids get faster every time the waves start over.
+const LOOP_SPEEDUP: f64 = 0.25;
+
+/// Spawns the waves of a level one after the other. Once the last one is
+/// cleared, they start over, faster.
+#[derive(Clone)]
+pub struct LevelDirector {
+    waves: Vec<Wave>,
+
+    /// How the height at which asteroids enter the screen is picked.
+    pub distribution: SpawnDistribution,
+
+    /// How many waves are cleared between two bosses, if any ever comes.
+    boss_every: Option<u32>,
+
+    // The number of the wave after which the last boss came.
+    boss_wave: u32,
+
+    // The number of waves which were started so far.
+    started: u32,
+
+    // What is left to spawn of the current wave.
+    asteroids_left: u32,
+    enemies_left: u32,
+
+    // Time left before the next spawn, or before the next wave starts.
+    timer: f64,
+}
+
+impl LevelDirector {
+    pub fn new(waves: Vec<Wave>, distribution: SpawnDistribution, boss_every: Option<u32>) -> LevelDirector {
+        LevelDirector {
+            waves,
+            distribution,
+            boss_every,
+            boss_wave: 0,
+            started: 0,
+            asteroids_left: 0,
+            enemies_left: 0,
+            timer: 0.0,
+        }
+    }
+
+    /// Reads the waves at `path`, one per line:
+    ///     asteroids enemies speed spacing [pattern,pattern,...]
+    /// Enemies may follow any pattern unless some are listed. A line
+    ///     spawn <distribution>
+    /// picks how asteroids are spread over the height of the screen, which is
+    /// uniformly by default, and a line
+    ///     boss <waves>
+    /// sends a boss in every time that many waves were cleared. Lines which
+    /// start with `#` are ignored.
+    pub fn load(path: &str) -> Result<LevelDirector, PhiError> {
+        let contents = fs::read_to_string(path).map_err(PhiError::asset(path))?;
+        let mut distribution = SpawnDistribution::Uniform;
+        let mut boss_every = None;
+        let mut waves = vec![];
+
+        for (i, line) in contents.lines().map(|line| line.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || PhiError::Asset {
+                path: path.to_string(),
+                reason: format!("line {} does not describe a wave", i + 1),
+            };
+
+            if let Some(name) = line.strip_prefix("spawn ") {
+                distribution = SpawnDistribution::parse(name.trim()).ok_or_else(invalid)?;
+            } else if let Some(every) = line.strip_prefix("boss ") {
+                boss_every = Some(every.trim().parse().ok().filter(|&every| every > 0).ok_or_else(invalid)?);
+            } else {
+                waves.push(LevelDirector::parse_line(line).ok_or_else(invalid)?);
+            }
+        }
+
+        if waves.is_empty() {
+            return Err(PhiError::Asset {
+                path: path.to_string(),
+                reason: "there are no waves".to_string(),
+            });
+        }
+
+        Ok(LevelDirector::new(waves, distribution, boss_every))
+    }
+
+    fn parse_line(line: &str) -> Option<Wave> {
+        let mut words = line.split_whitespace();
+
+        let wave = Wave {
+            asteroids: words.next()?.parse().ok()?,
+            enemies: words.next()?.parse().ok()?,
+            speed: words.next()?.parse().ok()?,
+            spacing: words.next()?.parse().ok()?,
+            patterns: match words.next() {
+                None => EnemyPattern::ALL.to_vec(),
+                Some(names) => names.split(',')
+                    .map(EnemyPattern::parse)
+                    .collect::<Option<_>>()?,
+            },
+        };
+
+        if words.next().is_some() || wave.spacing <= 0.0 {
+            return None;
+        }
+
+        Some(wave)
+    }
+
+    /// The number of the current wave, starting from 1, or 0 before the
+    /// first one has started.
+    pub fn wave_number(&self) -> u32 {
+        self.started
+    }
+
+    /// How far the player got into the waves, so that the run may be resumed
+    /// later.
+    pub fn saved(&self) -> SavedDirector {
+        SavedDirector {
+            started: self.started,
+            asteroids_left: self.asteroids_left,
+            enemies_left: self.enemies_left,
+            timer: self.timer,
+            boss_wave: self.boss_wave,
+        }
+    }
+
+    /// Goes back to where the player was when `saved` was taken.
+    pub fn resume(&mut self, saved: SavedDirector) {
+        self.started = saved.started;
+        self.asteroids_left = saved.asteroids_left;
+        self.enemies_left = saved.enemies_left;
+        self.timer = saved.timer;
+        self.boss_wave = saved.boss_wave;
+    }
+
+    /// Whether the wave which was just cleared is followed by a boss, which
+    /// did not come yet.
+    fn boss_due(&self) -> bool {
+        self.boss_every.is_some_and(|every| {
+            self.started > 0 && self.started.is_multiple_of(every) && self.boss_wave < self.started
+        })
+    }
+
+    /// Moves the level forward by `dt` seconds, given the number of asteroids
+    /// and enemies which are still around. What spawns is drawn from `rng`.
+    pub fn update(&mut self, dt: f64, remaining: usize, rng: &mut impl Rng) -> Option<WaveEvent> {
+        self.timer -= dt;
+
+        // Once the current wave was entirely spawned, wait for it to be
+        // cleared before starting the next one. Its first spawn comes after
+        // a break, during which its number is shown.
+        if self.asteroids_left == 0 && self.enemies_left == 0 {
+            if remaining > 0 {
+                return None;
+            }
+
+            if self.boss_due() {
+                self.boss_wave = self.started;
+                return Some(WaveEvent::Boss);
+            }
+
+            self.started += 1;
+            let wave = &self.waves[(self.started as usize - 1) % self.waves.len()];
+            self.asteroids_left = wave.asteroids;
+            self.enemies_left = wave.enemies;
+            self.timer = WAVE_BREAK;
+            return Some(WaveEvent::Started(self.started));
+        }
+
+        if self.timer > 0.0 {
+            return None;
+        }
+
+        let wave = &self.waves[(self.started as usize - 1) % self.waves.len()];
+        self.timer = wave.spacing;
+
+        // Mix asteroids and enemies in proportion to what is left of each.
+        let total = self.asteroids_left + self.enemies_left;
+        let loops = (self.started - 1) / self.waves.len() as u32;
+
+        if rng.gen_range(0..total) < self.asteroids_left {
+            let speed = wave.speed * (1.0 + LOOP_SPEEDUP * loops as f64);
+            self.asteroids_left -= 1;
+            Some(WaveEvent::Asteroid { speed })
+        } else {
+            let pattern = wave.patterns[rng.gen_range(0..wave.patterns.len())];
+            self.enemies_left -= 1;
+            Some(WaveEvent::Enemy(pattern))
+        }
+    }
+}
+
+/// The time after which a survival run is twice as intense as when it
+/// started, in seconds.
+const SURVIVAL_RAMP: f64 = 60.0;
+
+/// The time between two spawns at the start of a survival run, in seconds.
+const SURVIVAL_SPACING: f64 = 1.6;
+
+/// How much faster asteroids get, and how much likelier enemies become, for
+/// every point of intensity. Enemies never make up more than half of what
+/// spawns.
+const SURVIVAL_SPEEDUP: f64 = 0.3;
+const SURVIVAL_ENEMIES: (f64, f64) = (0.1, 0.15);
+const SURVIVAL_MAX_ENEMIES: f64 = 0.5;
+
+/// How many asteroids and enemies may be around at once at the start of a
+/// survival run, and how many more for every point of intensity.
+const SURVIVAL_CROWD: (f64, f64) = (10.0, 5.0);
+
+/// How hard a survival run is once it lasted `time` seconds: 0 when it
+/// starts, 1 after `SURVIVAL_RAMP`, then growing ever more slowly, but
+/// without end.
+pub fn survival_intensity(time: f64) -> f64 {
+    (1.0 + time.max(0.0) / SURVIVAL_RAMP).log2()
+}
+
+/// Spawns asteroids and enemies without any wave, ever more often, faster and
+/// in more patterns the longer the player survives.
+#[derive(Clone, Copy, Debug)]
+pub struct SurvivalDirector {
+    // The time which the player survived so far, and the time left before
+    // the next spawn, in seconds.
+    time: f64,
+    timer: f64,
+}
+
+impl SurvivalDirector {
+    pub fn new() -> SurvivalDirector {
+        SurvivalDirector::resume(0.0)
+    }
+
+    /// Goes on with a run which the player already survived for `time`
+    /// seconds.
+    pub fn resume(time: f64) -> SurvivalDirector {
+        SurvivalDirector { time, timer: SURVIVAL_SPACING }
+    }
+
+    /// The time which the player survived so far, in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Moves the run forward by `dt` seconds, during which spawns come
+    /// `spawn_rate` times as often, given the number of asteroids and enemies
+    /// which are still around. What spawns is drawn from `rng`.
+    pub fn update(&mut self, dt: f64, spawn_rate: f64, remaining: usize, rng: &mut impl Rng) -> Option<WaveEvent> {
+        self.time += dt;
+        self.timer -= dt * spawn_rate;
+
+        let intensity = survival_intensity(self.time);
+        let crowd = SURVIVAL_CROWD.0 + SURVIVAL_CROWD.1 * intensity;
+        if self.timer > 0.0 || remaining as f64 >= crowd {
+            return None;
+        }
+
+        self.timer = SURVIVAL_SPACING / (1.0 + intensity);
+
+        // A new pattern is unlocked every time the intensity goes up by one.
+        let enemies = (SURVIVAL_ENEMIES.0 + SURVIVAL_ENEMIES.1 * intensity).min(SURVIVAL_MAX_ENEMIES);
+        if rng.gen_bool(enemies) {
+            let unlocked = (1 + intensity as usize).min(EnemyPattern::ALL.len());
+            Some(WaveEvent::Enemy(EnemyPattern::ALL[rng.gen_range(0..unlocked)]))
+        } else {
+            Some(WaveEvent::Asteroid { speed: 1.0 + SURVIVAL_SPEEDUP * intensity })
+        }
+    }
+}
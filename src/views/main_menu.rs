@@ -1,74 +1,194 @@
-use crate::phi::gfx::Sprite;
-use crate::phi::{data::Rectangle, gfx::CopySprite, Phi, View, ViewAction};
-use crate::views::shared::Background;
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{AnimatedSprite, CopySprite, Sprite, Tint};
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, Transition, View, ViewAction};
+use crate::views::game::{asteroid_sprite, ASTEROID_SIDE};
+use crate::views::high_scores::Leaderboard;
+use crate::views::save::SavedGame;
+use crate::views::ships::ShipSelectView;
+use crate::views::shared::{ParallaxBackground, DEFAULT_PROFILE, STARFIELD_PATH};
+use rand::Rng;
 use sdl2::pixels::Color;
 
+/// How long it takes to fade to the view chosen from the menu, in seconds.
+const MENU_FADE: f64 = 0.4;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+/// How many entries of the menu are shown at once.
+const MAX_ROWS: usize = 12;
+
+/// The shortest and longest time between two asteroids drifting behind the
+/// menu, in seconds, their size relative to those of a run, their speed, in
+/// pixels per second, and their opacity, so that they stay in the background.
+const DRIFT_DELAY: (f64, f64) = (2.0, 6.0);
+const DRIFT_SCALE: (f64, f64) = (0.3, 0.7);
+const DRIFT_SPEED: (f64, f64) = (20.0, 60.0);
+const DRIFT_ALPHA: f64 = 0.6;
+
+struct DriftingAsteroid {
+    sprite: AnimatedSprite,
+    rect: Rectangle,
+    speed: f64,
+}
+
+/// Asteroids which now and then drift across the screen, behind the menu,
+/// spinning as they do in a run.
+struct DriftingAsteroids {
+    sprite: AnimatedSprite,
+    asteroids: Vec<DriftingAsteroid>,
+
+    // Time left before the next asteroid shows up, in seconds.
+    delay: f64,
+}
+
+impl DriftingAsteroids {
+    fn new(phi: &mut Phi) -> Result<DriftingAsteroids, PhiError> {
+        Ok(DriftingAsteroids {
+            sprite: asteroid_sprite(phi)?,
+            asteroids: vec![],
+            delay: DRIFT_DELAY.0,
+        })
+    }
+
+    fn update(&mut self, phi: &Phi, dt: f64) {
+        self.delay -= dt;
+        if self.delay <= 0.0 {
+            //? The asteroids are purely cosmetic, so they do not draw from
+            //? `phi.rng()`, which the seed of the next run is drawn from.
+            let (w, h) = phi.output_size();
+            let mut rng = ::rand::thread_rng();
+
+            let side = ASTEROID_SIDE * rng.gen_range(DRIFT_SCALE.0..DRIFT_SCALE.1);
+            let mut sprite = self.sprite.clone();
+            sprite.set_fps(rng.gen_range(5.0..15.0));
+
+            self.asteroids.push(DriftingAsteroid {
+                sprite,
+                rect: Rectangle { x: w, y: rng.gen_range(0.0..h - side), w: side, h: side },
+                speed: rng.gen_range(DRIFT_SPEED.0..DRIFT_SPEED.1),
+            });
+            self.delay = rng.gen_range(DRIFT_DELAY.0..DRIFT_DELAY.1);
+        }
+
+        for asteroid in &mut self.asteroids {
+            asteroid.rect.x -= asteroid.speed * dt;
+            asteroid.sprite.add_time(dt);
+        }
+
+        self.asteroids.retain(|asteroid| asteroid.rect.x + asteroid.rect.w > 0.0);
+    }
+
+    fn render(&self, phi: &mut Phi) {
+        for asteroid in &self.asteroids {
+            phi.renderer.copy_sprite_tinted(&asteroid.sprite, asteroid.rect, Tint::alpha(DRIFT_ALPHA));
+        }
+    }
+}
+
+
+/// Makes the view to which an action of the menu leads.
+type MakeView = Box<dyn Fn(&mut Phi) -> Result<Box<dyn View>, PhiError>>;
 
 struct Action {
-    /// The function which should be executed if the action is chosen
+    /// The function which makes the view to which the action leads
     ///? Westore it in a Box because, as we saw previously, 'Fn' is a trait
     //? and we may only interact with unsized data through a pointer
-    func: Box< dyn Fn(&mut Phi) -> ViewAction>,
-    
-    /// The sprite which is rendered when the player does not focus on this
-    /// action's label.
-    idle_sprite: Sprite,
-    
-    /// The sprite which is rendered when the player "focuses" a label with the
-    /// directional keys
-    hover_sprite: Sprite,
+    func: MakeView,
+
+    /// The button which triggers the action in the menu.
+    button: WidgetId,
 }
 
 impl Action {
-    fn new(phi: &mut Phi, label: &'static str, func: Box<dyn Fn(&mut Phi) -> ViewAction>) -> Action {
-        Action {
+    fn new(phi: &mut Phi, menu: &mut Menu, label: &'static str, func: MakeView) -> Result<Action, PhiError> {
+        Ok(Action {
             func: func,
-            idle_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 32, Color::RGB(220, 220, 220)).unwrap(),
-            hover_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 38, Color::RGB(255, 255, 255)).unwrap(),
-        }
+            button: menu.add(phi, label, WidgetKind::Button)?,
+        })
     }
 }
 
 pub struct MainMenuView {
     actions: Vec<Action>,
-    selected: i8,
+    quit: WidgetId,
+    menu: Menu,
+
+    background: ParallaxBackground,
+    asteroids: DriftingAsteroids,
 
-    bg_back: Background,
-    bg_middle: Background,
-    bg_front: Background,
+    /// The version of the game, shown in the bottom-right corner.
+    version: Sprite,
 }
 
 impl MainMenuView {
-    pub fn new(phi: &mut Phi) -> MainMenuView {
-        MainMenuView {
-            actions: vec![
-                Action::new(phi, "New Game", Box::new(|phi| {
-                    ViewAction::Render(Box::new(crate::views::game::GameView::new(phi)))
-                })),
-                Action::new(phi, "Quit", Box::new(|_| {
-                    ViewAction::Quit
-                })),
-            ],
-            selected: 0,
-
-            bg_back: Background {
-                pos: 0.0,
-                vel: 20.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starBG.png").unwrap(),
-            },
-
-            bg_middle: Background {
-                pos: 0.0,
-                vel: 40.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starMG.png").unwrap(),
-            },
-
-            bg_front: Background {
-                pos: 0.0,
-                vel: 80.0,
-                sprite: Sprite::load(&mut phi.renderer, "assets/starFG.png").unwrap(),
-            },
+    pub fn new(phi: &mut Phi) -> Result<MainMenuView, PhiError> {
+        let mut menu = Menu::scrolling(360.0, 44.0, MAX_ROWS);
+        let mut actions = vec![];
+
+        // Only offer to resume a run if one was saved.
+        if SavedGame::exists(DEFAULT_PROFILE) {
+            actions.push(Action::new(phi, &mut menu, "Continue", Box::new(|phi| {
+                Ok(Box::new(crate::views::game::GameView::resume(phi)?))
+            }))?);
         }
+
+        actions.extend(vec![
+            Action::new(phi, &mut menu, "New Game", Box::new(|phi| {
+                Ok(Box::new(ShipSelectView::new(phi, Box::new(|phi, ship| {
+                    Ok(Box::new(crate::views::game::GameView::new(phi, ship)?))
+                }))?))
+            }))?,
+            Action::new(phi, &mut menu, "Arena", Box::new(|phi| {
+                Ok(Box::new(ShipSelectView::new(phi, Box::new(|phi, ship| {
+                    Ok(Box::new(crate::views::game::GameView::arena(phi, ship)?))
+                }))?))
+            }))?,
+            Action::new(phi, &mut menu, "Survival", Box::new(|phi| {
+                Ok(Box::new(ShipSelectView::new(phi, Box::new(|phi, ship| {
+                    Ok(Box::new(crate::views::game::GameView::survival(phi, ship)?))
+                }))?))
+            }))?,
+            Action::new(phi, &mut menu, "Tutorial", Box::new(|phi| {
+                Ok(Box::new(ShipSelectView::new(phi, Box::new(|phi, ship| {
+                    Ok(Box::new(crate::views::tutorial::TutorialView::new(phi, ship)?))
+                }))?))
+            }))?,
+            Action::new(phi, &mut menu, "Classic", Box::new(|phi| {
+                Ok(Box::new(crate::views::classic::ClassicView::new(phi)?))
+            }))?,
+            Action::new(phi, &mut menu, "Watch Demo", Box::new(|phi| {
+                Ok(Box::new(crate::views::game::GameView::demo(phi)?))
+            }))?,
+            Action::new(phi, &mut menu, "Mutators", Box::new(|phi| {
+                Ok(Box::new(crate::views::mutators::MutatorsView::new(phi)?))
+            }))?,
+            Action::new(phi, &mut menu, "High Scores", Box::new(|phi| {
+                Ok(Box::new(crate::views::high_scores::HighScoresView::new(phi, DEFAULT_PROFILE, Leaderboard::Waves)?))
+            }))?,
+            Action::new(phi, &mut menu, "Achievements", Box::new(|phi| {
+                Ok(Box::new(crate::views::achievements::AchievementsView::new(phi, DEFAULT_PROFILE)?))
+            }))?,
+            Action::new(phi, &mut menu, "What's New", Box::new(|phi| {
+                Ok(Box::new(crate::views::news::NewsView::new(phi)?))
+            }))?,
+            Action::new(phi, &mut menu, "Settings", Box::new(|phi| {
+                Ok(Box::new(crate::views::options::OptionsView::new(phi)?))
+            }))?,
+            Action::new(phi, &mut menu, "Credits", Box::new(|phi| {
+                Ok(Box::new(crate::views::credits::CreditsView::new(phi)?))
+            }))?,
+        ]);
+
+        Ok(MainMenuView {
+            actions,
+            quit: menu.add(phi, "Quit", WidgetKind::Button)?,
+            menu,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+            asteroids: DriftingAsteroids::new(phi)?,
+            version: phi.ttf_str_sprite(&crate::build_info::short(), FONT_PATH, 14, Color::RGB(160, 160, 160))?,
+        })
     }
 }
 
@@ -78,90 +198,52 @@ impl View for MainMenuView {
             return ViewAction::Quit;
         }
 
-        // Execute the currently selected action if requested
-        if phi.events.now.key_space == Some(true) || 
-           phi.events.now.key_enter == Some(true) {
-               return (self.actions[self.selected as usize].func)(phi);
-        }
-
-        // Change the selected action using the keyboard
-        if phi.events.now.key_up == Some(true) {
-            self.selected -= 1;
-            if self.selected < 0 {
-                self.selected = self.actions.len() as i8 - 1;
-            }
-        }
-
-        if phi.events.now.key_down == Some(true) {
-            self.selected += 1;
-            if self.selected >= self.actions.len() as i8 {
-                self.selected = 0;
+        // Execute the currently selected action if requested, fading to the
+        // view it leads to.
+        if let Some(UiEvent::Clicked(id)) = self.menu.update(phi) {
+            let result = self.actions.iter()
+                .find(|action| action.button == id)
+                .map(|action| (action.func)(phi));
+
+            match result {
+                Some(Ok(to)) =>
+                    return ViewAction::ChangeViewWith { transition: Transition::Fade(MENU_FADE), from: self, to },
+                Some(Err(err)) =>
+                    return ViewAction::Error(err),
+                None if id == self.quit =>
+                    return ViewAction::Quit,
+                None => {},
             }
         }
 
         // Update the backgrounds
-        self.bg_back.update(elapsed);
-        self.bg_middle.update(elapsed);
-        self.bg_front.update(elapsed);
+        self.background.update(elapsed);
+        self.asteroids.update(phi, elapsed);
 
         ViewAction::Render(self)
     }
 
-    fn render(&self, phi: &mut Phi) {
+    fn render(&self, phi: &mut Phi, _: f64) {
         // Clear the screen.
         phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
         phi.renderer.clear();
 
-        // Render the backgrounds
-        self.bg_back.render(&mut phi.renderer);
-        self.bg_middle.render(&mut phi.renderer);
-        self.bg_front.render(&mut phi.renderer);
-        
-        // Definitions for the menu's layout
+        // Render the backgrounds, with the asteroids drifting in between
+        self.background.render_back(&mut phi.renderer);
+        self.asteroids.render(phi);
+        self.background.render_front(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+
+        // Render the version in the bottom-right corner
         let (win_w, win_h) = phi.output_size();
-        let label_h = 50.0;
-        let border_width = 3.0;
-        let box_w = 360.0;
-        let box_h = self.actions.len() as f64 * label_h;
-        let margin_h = 10.0;
-
-         // Render the border of the colored box which holds the labels
-         phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
-         phi.renderer.fill_rect(Rectangle {
-             w: box_w + border_width * 2.0,
-             h: box_h + border_width * 2.0 + margin_h * 2.0,
-             x: (win_w - box_w) / 2.0 - border_width,
-             y: (win_h - box_h) / 2.0 - margin_h - border_width,
-         }.to_sdl()).unwrap();
-
-         // Render the colored box which holds the labels
-        phi.renderer.set_draw_color(Color::RGB(140, 30, 140));
-        phi.renderer.fill_rect(Rectangle {
-            w: box_w,
-            h: box_h + margin_h * 2.0,
-            x: (win_w - box_w) / 2.0,
-            y: (win_h - box_h) / 2.0 - margin_h,
-        }.to_sdl()).unwrap();
-
-        // Render the labels in the menu
-        for (i, action) in self.actions.iter().enumerate() {
-            if self.selected as usize == i {
-                let (w, h) = action.hover_sprite.size();
-                phi.renderer.copy_sprite(&action.hover_sprite, Rectangle {
-                    w: w,
-                    h: h,
-                    x: (win_w - w) / 2.0,
-                    y: (win_h - box_h + label_h - h) / 2.0 + label_h * i as f64,
-                });
-            } else {
-                let (w, h) = action.idle_sprite.size();
-                phi.renderer.copy_sprite(&action.idle_sprite, Rectangle {
-                    w: w,
-                    h: h,
-                    x: (win_w - w) / 2.0,
-                    y: (win_h - box_h + label_h - h) / 2.0 + label_h * i as f64,
-                });
-            }
-        }
+        let (w, h) = self.version.size();
+        phi.renderer.copy_sprite(&self.version, Rectangle {
+            w,
+            h,
+            x: win_w - w - 6.0,
+            y: win_h - h - 6.0,
+        });
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,33 @@
+pub mod achievements;
+pub mod bindings;
+pub mod boss;
+pub mod classic;
+pub mod combo;
+pub mod credits;
+pub mod error;
 pub mod game;
+pub mod health;
+pub mod heatmap;
+pub mod high_scores;
+pub mod hud;
+pub mod integrity;
+pub mod hud_editor;
+pub mod level;
 pub mod main_menu;
+pub mod mutators;
+pub mod news;
+pub mod options;
+pub mod patterns;
+pub mod pickups;
+pub mod results;
+pub mod save;
+pub mod scripts;
 pub mod shared;
-pub mod bullets;
\ No newline at end of file
+pub mod spawn;
+pub mod stats;
+pub mod summary;
+pub mod trailer;
+pub mod transfer;
+pub mod tutorial;
+pub mod bullets;
+pub mod ships;
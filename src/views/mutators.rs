@@ -0,0 +1,149 @@
+use crate::phi::difficulty::Difficulty;
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::ships::ShipSelectView;
+use crate::views::shared::{ParallaxBackground, STARFIELD_PATH};
+use sdl2::pixels::Color;
+
+/// Optional rules which change the balance of a run. A run played with any of
+/// them enabled is tagged as modified.
+#[derive(Clone, Copy, Default)]
+pub struct Mutators {
+    /// Asteroids travel twice as fast.
+    pub double_speed_asteroids: bool,
+
+    /// The run ends as soon as the player's ship is hit.
+    pub one_hit_death: bool,
+
+    /// The player's ship is half its usual size.
+    pub tiny_ship: bool,
+
+    /// Bombs may be used as often as the player likes.
+    pub infinite_bombs: bool,
+
+    /// Only a small core of the player's ship can be hit. Asteroids which
+    /// brush past it graze the ship instead, for bonus points.
+    pub core_hitbox: bool,
+}
+
+impl Mutators {
+    /// Whether the run deviates from the standard rules.
+    pub fn is_modified(&self) -> bool {
+        self.double_speed_asteroids || self.one_hit_death || self.tiny_ship ||
+            self.infinite_bombs || self.core_hitbox
+    }
+
+    /// The factor by which the velocity of every asteroid is multiplied.
+    pub fn asteroid_speed(&self) -> f64 {
+        if self.double_speed_asteroids { 2.0 } else { 1.0 }
+    }
+
+    /// The factor by which the size of the player's ship is multiplied.
+    pub fn player_scale(&self) -> f64 {
+        if self.tiny_ship { 0.5 } else { 1.0 }
+    }
+
+    /// The size of the part of the player's ship which can be hit, relative
+    /// to the whole ship, on the given `difficulty`.
+    pub fn player_hitbox(&self, difficulty: Difficulty) -> f64 {
+        if self.core_hitbox { difficulty.core_hitbox() } else { 1.0 }
+    }
+}
+
+/// Lets the player pick a ship, then starts a run with `mutators`.
+fn choose_ship(phi: &mut Phi, mutators: Mutators) -> Result<ShipSelectView, PhiError> {
+    ShipSelectView::new(phi, Box::new(move |phi, ship| {
+        Ok(Box::new(crate::views::game::GameView::with_mutators(phi, ship, mutators)?))
+    }))
+}
+
+/// Lets the player pick the mutators with which to play before starting a run.
+pub struct MutatorsView {
+    mutators: Mutators,
+    menu: Menu,
+    double_speed_asteroids: WidgetId,
+    one_hit_death: WidgetId,
+    tiny_ship: WidgetId,
+    infinite_bombs: WidgetId,
+    core_hitbox: WidgetId,
+    start: WidgetId,
+    back: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl MutatorsView {
+    pub fn new(phi: &mut Phi) -> Result<MutatorsView, PhiError> {
+        let mut menu = Menu::new(480.0, 50.0);
+
+        Ok(MutatorsView {
+            mutators: Mutators::default(),
+            double_speed_asteroids: menu.add(phi, "Fast asteroids", WidgetKind::Toggle(false))?,
+            one_hit_death: menu.add(phi, "One-hit death", WidgetKind::Toggle(false))?,
+            tiny_ship: menu.add(phi, "Tiny ship", WidgetKind::Toggle(false))?,
+            infinite_bombs: menu.add(phi, "Infinite bombs", WidgetKind::Toggle(false))?,
+            core_hitbox: menu.add(phi, "Core hitbox", WidgetKind::Toggle(false))?,
+            start: menu.add(phi, "Start", WidgetKind::Button)?,
+            back: menu.add(phi, "Back", WidgetKind::Button)?,
+            menu,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+}
+
+impl View for MutatorsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        match self.menu.update(phi) {
+            Some(UiEvent::Toggled(id, on)) if id == self.double_speed_asteroids =>
+                self.mutators.double_speed_asteroids = on,
+
+            Some(UiEvent::Toggled(id, on)) if id == self.one_hit_death =>
+                self.mutators.one_hit_death = on,
+
+            Some(UiEvent::Toggled(id, on)) if id == self.tiny_ship =>
+                self.mutators.tiny_ship = on,
+
+            Some(UiEvent::Toggled(id, on)) if id == self.infinite_bombs =>
+                self.mutators.infinite_bombs = on,
+
+            Some(UiEvent::Toggled(id, on)) if id == self.core_hitbox =>
+                self.mutators.core_hitbox = on,
+
+            Some(UiEvent::Clicked(id)) if id == self.start => {
+                let view = choose_ship(phi, self.mutators);
+                return ViewAction::switch_to(self, view);
+            },
+
+            Some(UiEvent::Clicked(id)) if id == self.back =>
+                return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi)),
+
+            _ => {},
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+    }
+}
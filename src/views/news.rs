@@ -0,0 +1,162 @@
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::phi::storage::Storage;
+use crate::phi::text::TextLayout;
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::shared::{profile_path, DEFAULT_PROFILE};
+use sdl2::pixels::Color;
+use std::fs;
+
+const CHANGELOG_PATH: &str = "assets/changelog.md";
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+/// The storage key under which the last version whose changelog was shown is
+/// remembered.
+const SEEN_VERSION_KEY: &str = "news.seen_version";
+
+// Definitions for the page's layout
+const MARGIN: f64 = 40.0;
+const BULLET_INDENT: f64 = 24.0;
+const BLANK_LINE_H: f64 = 12.0;
+
+/// Pixels scrolled every second while a directional key is held.
+const SCROLL_SPEED: f64 = 300.0;
+
+/// A line of the changelog, rendered according to its Markdown-lite style.
+struct Line {
+    sprite: Option<Sprite>,
+    indent: f64,
+    height: f64,
+}
+
+/// Shows the changelog bundled with the game. The changelog is written in a
+/// small subset of Markdown: `#` and `##` headings, `-` or `*` bullets, and
+/// plain paragraphs, one per line.
+pub struct NewsView {
+    lines: Vec<Line>,
+
+    /// How far the page was scrolled down, in pixels.
+    scroll: f64,
+}
+
+impl NewsView {
+    pub fn new(phi: &mut Phi) -> Result<NewsView, PhiError> {
+        let changelog = fs::read_to_string(CHANGELOG_PATH)
+            .map_err(PhiError::asset(CHANGELOG_PATH))?;
+
+        let lines = changelog.lines()
+            .map(|line| NewsView::render_line(phi, line))
+            .collect::<Result<_, _>>()?;
+
+        // Remember that the changelog of this version was seen, so that it is
+        // not shown automatically again.
+        let mut storage = Storage::load(profile_path(DEFAULT_PROFILE, "storage.cfg"));
+        storage.set(SEEN_VERSION_KEY, env!("CARGO_PKG_VERSION"));
+        if let Err(err) = storage.save() {
+            println!("Could not save the storage: {}", err);
+        }
+
+        Ok(NewsView {
+            lines,
+            scroll: 0.0,
+        })
+    }
+
+    /// Whether the changelog of the current version was never shown yet, in
+    /// which case it should be shown when the game starts.
+    pub fn has_unseen_news() -> bool {
+        let storage = Storage::load(profile_path(DEFAULT_PROFILE, "storage.cfg"));
+        storage.get(SEEN_VERSION_KEY) != Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn render_line(phi: &mut Phi, line: &str) -> Result<Line, PhiError> {
+        let line = line.trim_end();
+
+        let (text, size, indent, color) =
+            if let Some(text) = line.strip_prefix("## ") {
+                (text.to_string(), 28, 0.0, Color::RGB(230, 230, 30))
+            } else if let Some(text) = line.strip_prefix("# ") {
+                (text.to_string(), 40, 0.0, Color::RGB(255, 255, 255))
+            } else if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+                (format!("- {}", text), 20, BULLET_INDENT, Color::RGB(220, 220, 220))
+            } else {
+                (line.to_string(), 20, 0.0, Color::RGB(220, 220, 220))
+            };
+
+        // Blank lines are only used for spacing, and SDL_ttf cannot render
+        // empty strings anyway.
+        if text.trim().is_empty() {
+            return Ok(Line { sprite: None, indent, height: BLANK_LINE_H });
+        }
+
+        // Long paragraphs are wrapped to the width of the page.
+        let (win_w, _) = phi.output_size();
+        let sprite = TextLayout {
+            max_width: Some(win_w - MARGIN * 2.0 - indent),
+            ..TextLayout::new(FONT_PATH, size, color)
+        }.sprite(phi, &text)?;
+        let height = sprite.size().1;
+
+        Ok(Line { sprite: Some(sprite), indent, height })
+    }
+
+    /// The total height of the page, in pixels.
+    fn page_height(&self) -> f64 {
+        self.lines.iter().map(|line| line.height).sum()
+    }
+}
+
+impl View for NewsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) ||
+           phi.events.now.key_enter == Some(true) ||
+           phi.events.now.key_space == Some(true) {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        // Scroll while the directional keys are held
+        if phi.events.key_up {
+            self.scroll -= SCROLL_SPEED * elapsed;
+        }
+
+        if phi.events.key_down {
+            self.scroll += SCROLL_SPEED * elapsed;
+        }
+
+        let (_, win_h) = phi.output_size();
+        let max_scroll = (self.page_height() - (win_h - MARGIN * 2.0)).max(0.0);
+        self.scroll = self.scroll.clamp(0.0, max_scroll);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        let (_, win_h) = phi.output_size();
+        let mut y = MARGIN - self.scroll;
+
+        for line in &self.lines {
+            // Only render the lines which are within the margins.
+            if let Some(ref sprite) = line.sprite {
+                if y >= MARGIN && y + line.height <= win_h - MARGIN {
+                    let (w, h) = sprite.size();
+                    phi.renderer.copy_sprite(sprite, Rectangle {
+                        w,
+                        h,
+                        x: MARGIN + line.indent,
+                        y,
+                    });
+                }
+            }
+
+            y += line.height;
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{CopySprite, Sprite};
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::display::DisplayMode;
+use crate::phi::storage::Storage;
+use crate::phi::{Binding, KeyBindings, Phi, PhiError, Transition, View, ViewAction};
+use crate::views::bindings::{PlayerBindings, PROFILES};
+use crate::views::shared::{profile_path, ParallaxBackground, DEFAULT_PROFILE, STARFIELD_PATH};
+use crate::views::transfer::{export_data, import_data, EXPORT_PATH};
+use sdl2::keyboard::Keycode;
+use sdl2::mixer::{Music, MAX_VOLUME};
+use sdl2::pixels::Color;
+use std::io;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+/// How many rows of options are shown at once.
+const MAX_ROWS: usize = 13;
+
+/// The longest search which may be typed, in characters.
+const MAX_SEARCH_LEN: usize = 16;
+
+/// By how much the volume changes every time the player presses left/right.
+const VOLUME_STEP: i32 = MAX_VOLUME / 8;
+
+/// Gives access to one of the fields of `KeyBindings`.
+type BindingField = fn(&mut KeyBindings) -> &mut Binding;
+
+/// The key bindings which can be changed from the options menu, along with
+/// the name under which they are shown.
+const BINDINGS: &[(&str, BindingField)] = &[
+    ("Up", |b| &mut b.key_up),
+    ("Down", |b| &mut b.key_down),
+    ("Left", |b| &mut b.key_left),
+    ("Right", |b| &mut b.key_right),
+    ("Fire", |b| &mut b.key_space),
+    ("Cannon 1", |b| &mut b.key_1),
+    ("Cannon 2", |b| &mut b.key_2),
+    ("Cannon 3", |b| &mut b.key_3),
+    ("Cannon 4", |b| &mut b.key_4),
+    ("Pick cannon", |b| &mut b.key_cannon),
+    ("Bomb", |b| &mut b.key_bomb),
+];
+
+/// The display mode which the player picked, if any, and which is still
+/// readable.
+pub fn load_display_mode(profile: &str) -> Option<DisplayMode> {
+    let storage = Storage::load(profile_path(profile, "settings.cfg"));
+    storage.get("display_mode").and_then(DisplayMode::parse)
+}
+
+fn save_display_mode(profile: &str, mode: DisplayMode) -> io::Result<()> {
+    let mut storage = Storage::load(profile_path(profile, "settings.cfg"));
+    storage.set("display_mode", &mode.to_line());
+    storage.save()
+}
+
+pub struct OptionsView {
+    menu: Menu,
+    search: String,
+    search_label: WidgetId,
+    difficulty: WidgetId,
+    display: WidgetId,
+    low_effects: WidgetId,
+    volume: WidgetId,
+    binding_profile: WidgetId,
+    bindings: Vec<WidgetId>,
+    edit_hud: WidgetId,
+    export: WidgetId,
+    import: WidgetId,
+    back: WidgetId,
+
+    player_bindings: PlayerBindings,
+
+    /// The index in `BINDINGS` of the key for which the player is currently
+    /// choosing a new binding, if any, and whether it is the alternate one.
+    rebinding: Option<(usize, bool)>,
+
+    /// Reminds the player of the keys with which the menu is used.
+    footer: Sprite,
+
+    background: ParallaxBackground,
+}
+
+impl OptionsView {
+    pub fn new(phi: &mut Phi) -> Result<OptionsView, PhiError> {
+        let mut menu = Menu::scrolling(480.0, 34.0, MAX_ROWS);
+
+        let search_label = menu.add(phi, "Type to search", WidgetKind::Label)?;
+
+        menu.add_category(phi, "Gameplay")?;
+        let difficulty_text = OptionsView::difficulty_text(phi);
+        let difficulty = menu.add(phi, &difficulty_text, WidgetKind::Button)?;
+
+        menu.add_category(phi, "Display")?;
+        let display_text = OptionsView::display_text(phi);
+        let display = menu.add(phi, &display_text, WidgetKind::Button)?;
+
+        let low_effects = menu.add(phi, "Low effects", WidgetKind::Toggle(phi.effects.low))?;
+
+        menu.add_category(phi, "Audio")?;
+        let volume = menu.add(phi, "Volume", WidgetKind::Slider {
+            value: Music::get_volume() as f64,
+            min: 0.0,
+            max: MAX_VOLUME as f64,
+            step: VOLUME_STEP as f64,
+        })?;
+
+        menu.add_category(phi, "Controls")?;
+        let player_bindings = PlayerBindings::load(DEFAULT_PROFILE);
+        let profile_text = OptionsView::profile_text(&player_bindings);
+        let binding_profile = menu.add(phi, &profile_text, WidgetKind::Button)?;
+        let bindings = (0..BINDINGS.len())
+            .map(|i| {
+                let text = OptionsView::binding_text(phi, i);
+                menu.add(phi, &text, WidgetKind::Button)
+            })
+            .collect::<Result<_, _>>()?;
+
+        menu.add_category(phi, "Interface")?;
+        let edit_hud = menu.add(phi, "Edit HUD", WidgetKind::Button)?;
+
+        menu.add_category(phi, "Data")?;
+        let export = menu.add(phi, "Export data", WidgetKind::Button)?;
+        let import = menu.add(phi, "Import data", WidgetKind::Button)?;
+
+        menu.end_category();
+        let back = menu.add(phi, "Back", WidgetKind::Button)?;
+
+        Ok(OptionsView {
+            menu,
+            search: String::new(),
+            search_label,
+            difficulty,
+            display,
+            low_effects,
+            volume,
+            binding_profile,
+            bindings,
+            edit_hud,
+            export,
+            import,
+            back,
+            player_bindings,
+            rebinding: None,
+            footer: OptionsView::footer(phi)?,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    fn difficulty_text(phi: &Phi) -> String {
+        format!("Difficulty: {}", phi.difficulty.name())
+    }
+
+    fn display_text(phi: &Phi) -> String {
+        format!("Display: {}", phi.display_mode().name())
+    }
+
+    fn binding_text(phi: &mut Phi, i: usize) -> String {
+        let (name, binding) = BINDINGS[i];
+        format!("{}: {}", name, binding(&mut phi.events.bindings).name())
+    }
+
+    fn profile_text(player_bindings: &PlayerBindings) -> String {
+        format!("Key profile: {}", PROFILES[player_bindings.profile].name)
+    }
+
+    fn footer(phi: &mut Phi) -> Result<Sprite, PhiError> {
+        let b = phi.events.bindings;
+        let text = format!("{}/{}: Move   {}: Select   {}/{}: Adjust   {}: Back",
+            b.key_up.primary_name(), b.key_down.primary_name(), b.key_enter.primary_name(),
+            b.key_left.primary_name(), b.key_right.primary_name(), b.key_escape.primary_name());
+
+        phi.ttf_str_sprite(&text, FONT_PATH, 16, Color::RGB(220, 220, 220))
+    }
+
+    /// Shows the current bindings, and saves them for the player.
+    fn update_bindings(&mut self, phi: &mut Phi) -> Result<(), PhiError> {
+        for i in 0..BINDINGS.len() {
+            let text = OptionsView::binding_text(phi, i);
+            self.menu.set_text(phi, self.bindings[i], &text)?;
+        }
+
+        let text = OptionsView::profile_text(&self.player_bindings);
+        self.menu.set_text(phi, self.binding_profile, &text)?;
+        self.footer = OptionsView::footer(phi)?;
+
+        self.player_bindings.bindings = phi.events.bindings;
+        if let Err(err) = self.player_bindings.save() {
+            println!("Could not save the key bindings: {}", err);
+        }
+
+        Ok(())
+    }
+
+    /// Adds what was typed during this frame to the search, and filters the
+    /// menu accordingly. Returns whether anything was typed, in which case
+    /// the keys are not meant for the menu.
+    fn update_search(&mut self, phi: &mut Phi) -> Result<bool, PhiError> {
+        let mut search = self.search.clone();
+        if phi.events.now.key_pressed == Some(Keycode::Backspace) {
+            search.pop();
+        }
+
+        //? Spaces are left out, since they activate the focused widget.
+        let typed: Vec<char> = phi.events.now.text_input.chars().filter(|c| c.is_alphanumeric()).collect();
+        for &c in &typed {
+            if search.chars().count() < MAX_SEARCH_LEN {
+                search.push(c);
+            }
+        }
+
+        if search != self.search {
+            self.search = search;
+            self.menu.set_filter(&self.search);
+
+            let text =
+                if self.search.is_empty() { "Type to search".to_string() }
+                else { format!("Search: {}_", self.search) };
+            self.menu.set_text(phi, self.search_label, &text)?;
+        }
+
+        Ok(!typed.is_empty())
+    }
+
+    /// Switches to the mode which follows the current one among those which
+    /// the screen supports, and remembers it for the next launch.
+    fn cycle_display_mode(phi: &mut Phi) {
+        let modes = phi.display_modes();
+        let current = phi.display_mode();
+        let next = modes.iter()
+            .position(|&mode| mode == current)
+            .map_or(0, |i| (i + 1) % modes.len());
+
+        if let Err(err) = phi.set_display_mode(modes[next]) {
+            println!("Warning: {}", err);
+            return;
+        }
+
+        if let Err(err) = save_display_mode(DEFAULT_PROFILE, phi.display_mode()) {
+            println!("Could not save the display mode: {}", err);
+        }
+    }
+}
+
+impl View for OptionsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if let Some((i, alternate)) = self.rebinding {
+            // Wait for the player to press the key which they wish to use, then
+            // the alternate one. The escape key keeps the current binding, and
+            // backspace leaves the alternate one unbound.
+            if let Some(keycode) = phi.events.now.key_pressed {
+                let binding = (BINDINGS[i].1)(&mut phi.events.bindings);
+                match keycode {
+                    Keycode::Escape => {},
+                    Keycode::Backspace if alternate => binding.alternate = None,
+                    key if alternate => binding.alternate = Some(key),
+                    key => binding.primary = Some(key),
+                }
+
+                self.rebinding =
+                    if alternate || keycode == Keycode::Escape { None }
+                    else { Some((i, true)) };
+
+                if let Err(err) = self.update_bindings(phi) {
+                    return ViewAction::Error(err);
+                }
+
+                if self.rebinding.is_some() {
+                    let text = format!("{}: {} / <press a key>", BINDINGS[i].0,
+                        (BINDINGS[i].1)(&mut phi.events.bindings).primary_name());
+                    if let Err(err) = self.menu.set_text(phi, self.bindings[i], &text) {
+                        return ViewAction::Error(err);
+                    }
+                }
+            }
+        } else {
+            // The escape key clears the search before leaving the menu.
+            if phi.events.now.key_escape == Some(true) {
+                if self.search.is_empty() {
+                    return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+                }
+
+                self.search.clear();
+                self.menu.set_filter("");
+                if let Err(err) = self.menu.set_text(phi, self.search_label, "Type to search") {
+                    return ViewAction::Error(err);
+                }
+            }
+
+            //? Letters such as W, A, S and D also move around the menu, which
+            //? they should not do while they are typed into the search.
+            let typing = match self.update_search(phi) {
+                Ok(typing) => typing,
+                Err(err) => return ViewAction::Error(err),
+            };
+
+            let event = if typing { self.menu.update_pointer(phi) } else { self.menu.update(phi) };
+            match event {
+                Some(UiEvent::Clicked(id)) if id == self.difficulty => {
+                    phi.difficulty = phi.difficulty.next();
+                    let text = OptionsView::difficulty_text(phi);
+                    if let Err(err) = self.menu.set_text(phi, self.difficulty, &text) {
+                        return ViewAction::Error(err);
+                    }
+                },
+
+                Some(UiEvent::Clicked(id)) if id == self.display => {
+                    OptionsView::cycle_display_mode(phi);
+                    let text = OptionsView::display_text(phi);
+                    if let Err(err) = self.menu.set_text(phi, self.display, &text) {
+                        return ViewAction::Error(err);
+                    }
+                },
+
+                Some(UiEvent::Toggled(id, on)) if id == self.low_effects =>
+                    phi.effects.low = on,
+
+                Some(UiEvent::Changed(id, value)) if id == self.volume =>
+                    Music::set_volume(value as i32),
+
+                Some(UiEvent::Clicked(id)) if id == self.binding_profile => {
+                    let next = (self.player_bindings.profile + 1) % PROFILES.len();
+                    self.player_bindings.switch_to(next);
+                    phi.events.bindings = self.player_bindings.bindings;
+
+                    if let Err(err) = self.update_bindings(phi) {
+                        return ViewAction::Error(err);
+                    }
+                },
+
+                Some(UiEvent::Clicked(id)) if id == self.edit_hud =>
+                    return ViewAction::change_view_with(Transition::Wipe(0.3), self,
+                        crate::views::hud_editor::HudEditorView::new(phi)),
+
+                Some(UiEvent::Clicked(id)) if id == self.export => {
+                    let text = match export_data(EXPORT_PATH) {
+                        Ok(count) => format!("Exported {} files to {}", count, EXPORT_PATH),
+                        Err(err) => {
+                            println!("Could not export the data: {}", err);
+                            "Export data: failed".to_string()
+                        },
+                    };
+
+                    if let Err(err) = self.menu.set_text(phi, self.export, &text) {
+                        return ViewAction::Error(err);
+                    }
+                },
+
+                Some(UiEvent::Clicked(id)) if id == self.import => {
+                    let text = match import_data(EXPORT_PATH) {
+                        Ok(count) => {
+                            // The key bindings are the only imported settings
+                            // which are held in memory.
+                            self.player_bindings = PlayerBindings::load(DEFAULT_PROFILE);
+                            phi.events.bindings = self.player_bindings.bindings;
+                            if let Err(err) = self.update_bindings(phi) {
+                                return ViewAction::Error(err);
+                            }
+
+                            format!("Imported {} files", count)
+                        },
+                        Err(err) => {
+                            println!("Could not import the data: {}", err);
+                            "Import data: failed".to_string()
+                        },
+                    };
+
+                    if let Err(err) = self.menu.set_text(phi, self.import, &text) {
+                        return ViewAction::Error(err);
+                    }
+                },
+
+                Some(UiEvent::Clicked(id)) if id == self.back =>
+                    return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi)),
+
+                Some(UiEvent::Clicked(id)) => {
+                    if let Some(i) = self.bindings.iter().position(|&b| b == id) {
+                        let text = format!("{}: <press a key>", BINDINGS[i].0);
+                        if let Err(err) = self.menu.set_text(phi, id, &text) {
+                            return ViewAction::Error(err);
+                        }
+                        self.rebinding = Some((i, false));
+                    }
+                },
+
+                _ => {},
+            }
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+
+        // Render the footer at the bottom of the screen
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.footer.size();
+        phi.renderer.copy_sprite(&self.footer, Rectangle {
+            w,
+            h,
+            x: (win_w - w) / 2.0,
+            y: win_h - h - 6.0,
+        });
+    }
+}
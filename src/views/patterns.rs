@@ -0,0 +1,204 @@
+//! The patterns in which enemies and bosses fire their bullets, e.g. rings,
+//! spirals, aimed spreads and delayed bursts, as described in a file so that
+//! new ones do not require any code.
+//!
+//! A pattern is made of volleys, which all have the same shape, and which are
+//! rotated from one to the next to draw spirals:
+//!
+//!     boss-frenzy ring count=12 volleys=3 every=0.2 turn=15
+
+use crate::phi::PhiError;
+use crate::phi::data::{IdAllocator, Vec2};
+use crate::views::bullets::{spawn_aimed_bullet, BulletPool};
+use std::collections::HashMap;
+use std::fs;
+
+pub const PATTERNS_PATH: &str = "assets/patterns.cfg";
+
+/// How the bullets of a volley are spread around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternShape {
+    /// Evenly around a full circle.
+    Ring,
+
+    /// Side by side, in a fan centered on the aim.
+    Spread,
+}
+
+/// A pattern, as described by a line of the patterns file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BulletPattern {
+    pub shape: PatternShape,
+
+    /// The number of bullets in every volley, and how fast they travel, in
+    /// pixels per second.
+    pub count: usize,
+    pub speed: f64,
+
+    /// The angle between two bullets of a spread, and by which every volley
+    /// is rotated from the previous one, in radians.
+    pub angle: f64,
+    pub turn: f64,
+
+    /// The number of volleys, the time between two of them, and the time
+    /// before the first one, in seconds.
+    pub volleys: u32,
+    pub every: f64,
+    pub delay: f64,
+
+    /// Whether the volleys are aimed at the ship, rather than straight to the
+    /// left.
+    pub aimed: bool,
+}
+
+impl BulletPattern {
+    /// A single bullet, aimed at the ship.
+    pub const SINGLE: BulletPattern = BulletPattern {
+        shape: PatternShape::Spread,
+        count: 1,
+        speed: 200.0,
+        angle: 0.175,
+        turn: 0.0,
+        volleys: 1,
+        every: 0.1,
+        delay: 0.0,
+        aimed: true,
+    };
+
+    /// Reads a pattern from the words which follow its name, e.g.
+    /// `ring count=8 speed=150`. Parameters which are left out keep the value
+    /// they have in `SINGLE`.
+    fn parse(words: &[&str]) -> Option<BulletPattern> {
+        let mut pattern = BulletPattern {
+            shape: match *words.first()? {
+                "ring" => PatternShape::Ring,
+                "spread" => PatternShape::Spread,
+                _ => return None,
+            },
+            ..BulletPattern::SINGLE
+        };
+
+        for word in &words[1..] {
+            let (key, value) = word.split_once('=')?;
+            let number = || value.parse::<f64>().ok().filter(|number| number.is_finite() && *number >= 0.0);
+
+            match key {
+                "count" => pattern.count = value.parse().ok().filter(|&count| count > 0)?,
+                "speed" => pattern.speed = number()?,
+                "angle" => pattern.angle = number()?.to_radians(),
+                "turn" => pattern.turn = value.parse::<f64>().ok().filter(|turn| turn.is_finite())?.to_radians(),
+                "volleys" => pattern.volleys = value.parse().ok().filter(|&volleys| volleys > 0)?,
+                "every" => pattern.every = number()?,
+                "delay" => pattern.delay = number()?,
+                "aimed" => pattern.aimed = match value {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+
+        Some(pattern)
+    }
+}
+
+/// Every pattern of the patterns file, by name.
+#[derive(Clone, Debug)]
+pub struct BulletPatterns {
+    path: String,
+    patterns: HashMap<String, BulletPattern>,
+}
+
+impl BulletPatterns {
+    /// Reads the patterns at `path`, one per line:
+    ///     <name> ring|spread [<key>=<value>...]
+    /// where keys are `count`, `speed`, `angle` and `turn` in degrees,
+    /// `volleys`, `every` and `delay` in seconds, and `aimed=yes|no`. Lines
+    /// which start with `#` are ignored.
+    pub fn load(path: &str) -> Result<BulletPatterns, PhiError> {
+        let contents = fs::read_to_string(path).map_err(PhiError::asset(path))?;
+        let mut patterns = HashMap::new();
+
+        for (i, line) in contents.lines().map(|line| line.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let pattern = BulletPattern::parse(&words[1..]).ok_or_else(|| PhiError::Asset {
+                path: path.to_string(),
+                reason: format!("line {} does not describe a bullet pattern", i + 1),
+            })?;
+            patterns.insert(words[0].to_string(), pattern);
+        }
+
+        Ok(BulletPatterns { path: path.to_string(), patterns })
+    }
+
+    /// The pattern called `name`, or an error if there is none.
+    pub fn get(&self, name: &str) -> Result<BulletPattern, PhiError> {
+        self.patterns.get(name).copied().ok_or_else(|| PhiError::Asset {
+            path: self.path.clone(),
+            reason: format!("there is no bullet pattern called {}", name),
+        })
+    }
+}
+
+/// A pattern which is being fired, volley after volley.
+#[derive(Clone, Copy, Debug)]
+pub struct PatternPlayer {
+    pattern: BulletPattern,
+
+    // The time since the pattern started, in seconds, and the number of
+    // volleys fired so far.
+    time: f64,
+    fired: u32,
+
+    // Where the first volley was aimed at, which the next ones turn from.
+    aim: Option<Vec2>,
+}
+
+impl PatternPlayer {
+    pub fn new(pattern: BulletPattern) -> PatternPlayer {
+        PatternPlayer {
+            pattern,
+            time: 0.0,
+            fired: 0,
+            aim: None,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.fired >= self.pattern.volleys
+    }
+
+    /// Moves the pattern forward by `dt` seconds, firing the volleys which are
+    /// due from `from`, at `target`, into `pool`.
+    pub fn update(&mut self, dt: f64, ids: &mut IdAllocator, from: (f64, f64), target: (f64, f64), pool: &mut BulletPool) {
+        self.time += dt;
+        let pattern = self.pattern;
+
+        while !self.is_over() && self.time >= pattern.delay + pattern.every * self.fired as f64 {
+            //? Later volleys turn from the aim of the first one rather than
+            //? aim again, so that they draw spirals around the shooter.
+            let aim = *self.aim.get_or_insert_with(|| {
+                let towards = Vec2::from(target) - Vec2::from(from);
+                if pattern.aimed && towards != Vec2::ZERO { towards.normalize() } else { Vec2::new(-1.0, 0.0) }
+            });
+            let aim = aim.rotate(pattern.turn * self.fired as f64);
+
+            for i in 0..pattern.count {
+                let angle = match pattern.shape {
+                    PatternShape::Ring => 2.0 * ::std::f64::consts::PI * i as f64 / pattern.count as f64,
+                    PatternShape::Spread => (i as f64 - (pattern.count - 1) as f64 / 2.0) * pattern.angle,
+                };
+
+                let towards = Vec2::from(from) + aim.rotate(angle);
+                spawn_aimed_bullet(ids, from, towards.into(), pattern.speed, pool);
+            }
+
+            self.fired += 1;
+        }
+    }
+}
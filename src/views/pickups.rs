@@ -0,0 +1,216 @@
+//! What destroyed enemies leave behind: score gems, which drift to the left
+//! until the ship comes close enough to draw them in, and now and then a
+//! magnet, which draws them in from much farther away for a while.
+//!
+//! Gems which are picked up in quick succession are worth more and more.
+
+use crate::phi::Phi;
+use crate::phi::data::{Rectangle, Vec2};
+use crate::phi::gfx::{draw, Camera};
+use rand::Rng;
+use sdl2::pixels::Color;
+use std::f64::consts::PI;
+
+/// How fast pickups drift to the left once they settled, in pixels per
+/// second, and how fast they are thrown apart when dropped.
+const DRIFT_SPEED: f64 = 60.0;
+const SCATTER_SPEED: f64 = 120.0;
+
+/// How quickly pickups go from the speed at which they were thrown to their
+/// drift, or to the speed at which they are drawn in, per second.
+const DAMPING: f64 = 3.0;
+
+/// The distance from the center of the ship within which gems are drawn to
+/// it, without and with a magnet, in pixels, and the speed at which they are
+/// drawn in, which is highest right next to the ship.
+const PULL_RADIUS: f64 = 80.0;
+const MAGNET_RADIUS: f64 = 260.0;
+const PULL_SPEED: (f64, f64) = (160.0, 520.0);
+
+/// How long a magnet lasts once picked up, and the chance that a destroyed
+/// enemy drops one.
+const MAGNET_TIME: f64 = 10.0;
+const MAGNET_CHANCE: f64 = 0.08;
+
+/// The points which a gem is worth on its own, the time within which the
+/// next one must be picked up to keep the combo going, in seconds, and the
+/// most by which a combo multiplies the worth of a gem.
+const GEM_SCORE: u64 = 10;
+const COMBO_WINDOW: f64 = 0.6;
+const MAX_COMBO: u32 = 10;
+
+/// The size of the pickups, in pixels.
+const GEM_SIDE: f64 = 10.0;
+const MAGNET_SIDE: f64 = 18.0;
+
+const GEM_COLOR: Color = Color::RGB(90, 230, 200);
+const MAGNET_COLOR: Color = Color::RGB(230, 70, 70);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    Gem,
+    Magnet,
+}
+
+#[derive(Clone, Copy)]
+struct Pickup {
+    kind: PickupKind,
+
+    // The center of the pickup, now and before the last tick, and its
+    // velocity, in pixels per second.
+    pos: Vec2,
+    prev_pos: Vec2,
+    vel: Vec2,
+}
+
+impl Pickup {
+    fn side(&self) -> f64 {
+        match self.kind {
+            PickupKind::Gem => GEM_SIDE,
+            PickupKind::Magnet => MAGNET_SIDE,
+        }
+    }
+
+    fn rect(&self) -> Rectangle {
+        Rectangle::with_size(self.side(), self.side()).center_at(self.pos.into())
+    }
+}
+
+/// Every pickup on screen, and what the ship picked up lately.
+#[derive(Clone, Default)]
+pub struct Pickups {
+    items: Vec<Pickup>,
+
+    // Time left before the magnet wears off, in seconds.
+    magnet: f64,
+
+    // The number of gems picked up in a row, and the time left to pick up
+    // the next one before the combo is over, in seconds.
+    combo: u32,
+    combo_left: f64,
+}
+
+impl Pickups {
+    pub fn new() -> Pickups {
+        Pickups::default()
+    }
+
+    /// The distance from the center of the ship within which gems are drawn
+    /// to it.
+    fn pull_radius(&self) -> f64 {
+        if self.magnet > 0.0 { MAGNET_RADIUS } else { PULL_RADIUS }
+    }
+
+    fn drop(&mut self, phi: &mut Phi, kind: PickupKind, at: (f64, f64)) {
+        let angle = phi.rng().gen::<f64>() * 2.0 * PI;
+        let speed = SCATTER_SPEED * (0.5 + phi.rng().gen::<f64>() * 0.5);
+        let pos = Vec2::from(at);
+
+        self.items.push(Pickup {
+            kind,
+            pos,
+            prev_pos: pos,
+            vel: Vec2::new(angle.cos(), angle.sin()) * speed,
+        });
+    }
+
+    /// Scatters `count` gems around `at`, along with a magnet once in a
+    /// while.
+    pub fn drop_loot(&mut self, phi: &mut Phi, at: (f64, f64), count: usize) {
+        for _ in 0..count {
+            self.drop(phi, PickupKind::Gem, at);
+        }
+
+        if phi.rng().gen_bool(MAGNET_CHANCE) {
+            self.drop(phi, PickupKind::Magnet, at);
+        }
+    }
+
+    /// Moves the pickups by `dt` seconds, drawing the gems towards the `ship`
+    /// if it is close enough, and picking up those which it touches. Returns
+    /// the points earned by the gems which were picked up.
+    pub fn update(&mut self, dt: f64, ship: Option<Rectangle>) -> u64 {
+        self.magnet -= dt;
+        self.combo_left -= dt;
+        if self.combo_left <= 0.0 {
+            self.combo = 0;
+        }
+
+        let radius = self.pull_radius();
+        let damping = (DAMPING * dt).min(1.0);
+        let mut earned = 0;
+
+        for item in &mut self.items {
+            item.prev_pos = item.pos;
+
+            // Gems within reach are drawn to the ship, the faster the closer
+            // they are. Magnets are picked up by flying into them.
+            let center = ship.map(|ship| Vec2::from(ship.center()));
+            let towards = center
+                .filter(|_| item.kind == PickupKind::Gem)
+                .map(|center| center - item.pos)
+                .filter(|towards| towards.length() < radius);
+
+            let target = match towards {
+                Some(towards) => {
+                    let closeness = 1.0 - towards.length() / radius;
+                    let speed = PULL_SPEED.0 + (PULL_SPEED.1 - PULL_SPEED.0) * closeness;
+                    towards.normalize() * speed
+                },
+                None => Vec2::new(-DRIFT_SPEED, 0.0),
+            };
+
+            item.vel += (target - item.vel) * damping;
+            item.pos += item.vel * dt;
+        }
+
+        let mut picked = vec![];
+        self.items.retain(|item| {
+            let touched = ship.is_some_and(|ship| item.rect().overlaps(ship));
+            let gone = item.pos.x < -item.side();
+            if touched {
+                picked.push(item.kind);
+            }
+            !touched && !gone
+        });
+
+        for kind in picked {
+            match kind {
+                PickupKind::Gem => {
+                    self.combo = (self.combo + 1).min(MAX_COMBO);
+                    self.combo_left = COMBO_WINDOW;
+                    earned += GEM_SCORE * self.combo as u64;
+                },
+                PickupKind::Magnet => self.magnet = MAGNET_TIME,
+            }
+        }
+
+        earned
+    }
+
+    /// Draws the gems as diamonds and the magnets as rings, along with the
+    /// reach of the magnet around the `ship` while it lasts.
+    pub fn render(&self, phi: &mut Phi, camera: &Camera, alpha: f64, ship: Rectangle) {
+        if self.magnet > 0.0 {
+            let center = camera.view_point(ship.center());
+            draw::outline_circle(&mut phi.renderer, center, MAGNET_RADIUS * camera.zoom, Color::RGBA(230, 70, 70, 60), true);
+        }
+
+        for item in &self.items {
+            let pos = item.prev_pos + (item.pos - item.prev_pos) * alpha;
+            let (x, y) = camera.view_point(pos.into());
+            let half = item.side() / 2.0 * camera.zoom;
+
+            match item.kind {
+                PickupKind::Gem => {
+                    let points = [(x, y - half), (x + half * 0.7, y), (x, y + half), (x - half * 0.7, y)];
+                    draw::fill_polygon(&mut phi.renderer, &points, GEM_COLOR, true);
+                },
+                PickupKind::Magnet => {
+                    draw::fill_circle(&mut phi.renderer, (x, y), half, MAGNET_COLOR, true);
+                    draw::fill_circle(&mut phi.renderer, (x, y), half * 0.5, Color::RGB(255, 255, 255), true);
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! The details of a run, shown once its summary was counted up: how well the
+//! player shot, what they shot down, and where the score came from.
+
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, Transition, View, ViewAction};
+use crate::views::high_scores::Leaderboard;
+use crate::views::shared::{ParallaxBackground, RestartHold, STARFIELD_PATH};
+use crate::views::stats::RunStats;
+use crate::views::summary::{after_summary, Restart, RunSummary, RESTART_FADE};
+use sdl2::pixels::Color;
+
+/// How many rows of the list are shown at once.
+const MAX_ROWS: usize = 11;
+
+pub struct ResultsView {
+    summary: RunSummary,
+
+    // How to start the run over, if it may be, when the restart key is held.
+    restart: Option<Restart>,
+    restart_hold: RestartHold,
+
+    menu: Menu,
+    continue_button: WidgetId,
+
+    background: ParallaxBackground,
+}
+
+impl ResultsView {
+    pub fn new(phi: &mut Phi, summary: RunSummary, stats: RunStats, restart: Option<Restart>) -> Result<ResultsView, PhiError> {
+        let mut menu = Menu::scrolling(560.0, 36.0, MAX_ROWS);
+
+        menu.add(phi, "Results", WidgetKind::Label)?;
+        for (label, value) in ResultsView::rows(&summary, &stats) {
+            menu.add(phi, &format!("{}: {}", label, value), WidgetKind::Label)?;
+        }
+
+        Ok(ResultsView {
+            summary,
+            restart,
+            restart_hold: RestartHold::new(phi),
+            continue_button: menu.add(phi, "Continue", WidgetKind::Button)?,
+            menu,
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    /// The details of the run, then the parts of its score, along with their
+    /// label.
+    fn rows(summary: &RunSummary, stats: &RunStats) -> Vec<(&'static str, String)> {
+        vec![
+            ("Shots fired", stats.shots_fired.to_string()),
+            ("Accuracy", format!("{}%", (stats.accuracy() * 100.0).round())),
+            ("Asteroids destroyed", stats.asteroids_destroyed.to_string()),
+            ("Enemies killed", stats.enemies_killed.to_string()),
+            ("Hits taken", stats.hits_taken.to_string()),
+            ("Time survived", Leaderboard::Survival.format(stats.time as u64)),
+
+            ("Asteroids", stats.asteroid_points.to_string()),
+            ("Enemies", stats.enemy_points.to_string()),
+            ("Boss", stats.boss_points.to_string()),
+            ("Combo bonus", stats.combo_bonus.to_string()),
+            ("Gems", stats.gem_points.to_string()),
+            ("Grazes", stats.graze_points.to_string()),
+            ("Final score", summary.score.to_string()),
+        ]
+    }
+}
+
+impl View for ResultsView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if self.restart_hold.update(phi) {
+            if let Some(ref restart) = self.restart {
+                return match restart(phi) {
+                    Ok(to) => ViewAction::ChangeViewWith { transition: Transition::Fade(RESTART_FADE), from: self, to },
+                    Err(err) => ViewAction::Error(err),
+                };
+            }
+        }
+
+        // As on the summary, Select on a gamepad confirms once released, as
+        // holding it restarts the run instead.
+        let confirmed = phi.events.now.key_enter == Some(true) ||
+            phi.events.now.key_escape == Some(false) ||
+            self.menu.update(phi) == Some(UiEvent::Clicked(self.continue_button));
+        if confirmed {
+            let summary = self.summary;
+            return after_summary(phi, self, &summary);
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+
+        if self.restart.is_some() {
+            self.restart_hold.render(phi);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::phi::difficulty::Difficulty;
+use crate::phi::storage::Storage;
+use crate::views::level::EnemyPattern;
+use crate::views::mutators::Mutators;
+use crate::views::shared::{install_key, profile_path, BOOKMARKS_DIR, SAVE_FILE};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Plain data describing an asteroid, without its sprites.
+#[derive(Clone, Copy, Debug)]
+pub struct SavedAsteroid {
+    /// From 0 for the largest asteroids to 2 for the smallest ones.
+    pub size: usize,
+    pub x: f64,
+    pub y: f64,
+    pub vel: f64,
+    pub drift: f64,
+
+    /// The part of its health which is left, in `[0, 1]`.
+    pub health: f64,
+}
+
+/// Plain data describing an enemy, without its sprites.
+#[derive(Clone, Copy, Debug)]
+pub struct SavedEnemy {
+    pub pattern: EnemyPattern,
+    pub x: f64,
+    pub y: f64,
+    pub origin_y: f64,
+    pub time: f64,
+
+    /// The part of its health which is left, in `[0, 1]`.
+    pub health: f64,
+}
+
+/// How far the player got into the waves of the level.
+#[derive(Clone, Copy, Debug)]
+pub struct SavedDirector {
+    pub started: u32,
+    pub asteroids_left: u32,
+    pub enemies_left: u32,
+    pub timer: f64,
+
+    /// The number of the wave after which the last boss came, or 0 if the
+    /// boss of the current wave is still to come.
+    pub boss_wave: u32,
+}
+
+/// A run which was left in progress, so that it may be resumed later. Bullets
+/// and explosions are not kept, since they are gone within a few seconds.
+#[derive(Clone)]
+pub struct SavedGame {
+    pub arena: bool,
+    pub mutators: Mutators,
+
+    /// The index of the player's ship in `SHIPS`.
+    pub ship: usize,
+
+    pub difficulty: Difficulty,
+
+    /// The hits which the ship may still take once its shield is down.
+    pub lives: u32,
+
+    /// The bombs which the player has left, or `None` for runs saved before
+    /// there were bombs, which get as many as a run starts with.
+    pub bombs: Option<u32>,
+
+    pub score: u64,
+    pub player: (f64, f64),
+
+    /// The index of the player's cannon in the game's list of cannons.
+    pub cannon: usize,
+
+    pub director: SavedDirector,
+
+    /// The time survived so far, in seconds, if the run is one of survival
+    /// rather than of waves.
+    pub survival: Option<f64>,
+
+    /// The seed from which the run goes on, for bookmarks which should play
+    /// out the same way every time they are restored.
+    pub seed: Option<u64>,
+
+    pub asteroids: Vec<SavedAsteroid>,
+    pub enemies: Vec<SavedEnemy>,
+}
+
+fn path(profile: &str) -> PathBuf {
+    profile_path(profile, SAVE_FILE)
+}
+
+/// Where the bookmark called `name` is saved for `profile`.
+pub fn bookmark_path(profile: &str, name: &str) -> PathBuf {
+    profile_path(profile, &format!("{}/{}.cfg", BOOKMARKS_DIR, name))
+}
+
+/// Parses the numbers separated by spaces in `value`, or returns `None` if
+/// there are not exactly `N` of them.
+fn numbers<const N: usize>(value: &str) -> Option<[f64; N]> {
+    let numbers: Vec<f64> = value.split_whitespace()
+        .map(|number| number.parse().ok())
+        .collect::<Option<_>>()?;
+
+    numbers.try_into().ok()
+}
+
+/// Parses a list of entities separated by `;`, each of them with `parse`.
+fn list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    value.split(';')
+        .filter(|entity| !entity.trim().is_empty())
+        .map(parse)
+        .collect()
+}
+
+impl SavedGame {
+    /// Whether `profile` has a run in progress which may be resumed.
+    pub fn exists(profile: &str) -> bool {
+        SavedGame::load(profile).is_some()
+    }
+
+    /// Reads the run which `profile` left in progress. Returns `None` if there
+    /// is none, or if the file could not be understood.
+    pub fn load(profile: &str) -> Option<SavedGame> {
+        SavedGame::load_from(path(profile))
+    }
+
+    /// Reads a run saved at `path`, e.g. a bookmark. A file whose signature
+    /// does not match, e.g. because it was edited by hand, holds no run.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Option<SavedGame> {
+        let storage = Storage::load_signed(path, install_key());
+
+        let [x, y, cannon, score] = numbers(storage.get("player")?)?;
+        let [started, asteroids_left, enemies_left, timer] = numbers(storage.get("director")?)?;
+
+        // Runs saved before bombs existed have no mutator for them.
+        let mutators = storage.get("mutators")?;
+        let [speed, one_hit, tiny, core, bombs] = numbers(mutators)
+            .or_else(|| numbers(mutators).map(|[speed, one_hit, tiny, core]| [speed, one_hit, tiny, core, 0.0]))?;
+
+        Some(SavedGame {
+            arena: storage.get("arena")? == "true",
+            mutators: Mutators {
+                double_speed_asteroids: speed != 0.0,
+                one_hit_death: one_hit != 0.0,
+                tiny_ship: tiny != 0.0,
+                core_hitbox: core != 0.0,
+                infinite_bombs: bombs != 0.0,
+            },
+            // Runs saved before ships could be chosen were flown with the
+            // first one.
+            ship: storage.get("ship").and_then(|ship| ship.parse().ok()).unwrap_or(0),
+            difficulty: storage.get("difficulty").and_then(Difficulty::parse).unwrap_or_default(),
+            lives: storage.get("lives").and_then(|lives| lives.parse().ok())
+                .unwrap_or_else(|| Difficulty::default().lives()),
+            bombs: storage.get("bombs").and_then(|bombs| bombs.parse().ok()),
+            score: score as u64,
+            player: (x, y),
+            cannon: cannon as usize,
+            director: SavedDirector {
+                started: started as u32,
+                asteroids_left: asteroids_left as u32,
+                enemies_left: enemies_left as u32,
+                timer,
+                boss_wave: storage.get("boss_wave").and_then(|wave| wave.parse().ok()).unwrap_or(0),
+            },
+            survival: storage.get("survival").and_then(|time| time.parse().ok()),
+            seed: storage.get("seed").and_then(|seed| seed.parse().ok()),
+            asteroids: list(storage.get("asteroids").unwrap_or(""), |asteroid| {
+                // Runs saved before health was kept had every asteroid and enemy
+                // intact.
+                let [size, x, y, vel, drift, health] = numbers(asteroid)
+                    .or_else(|| numbers(asteroid).map(|[size, x, y, vel, drift]| [size, x, y, vel, drift, 1.0]))?;
+                Some(SavedAsteroid { size: size as usize, x, y, vel, drift, health })
+            })?,
+            enemies: list(storage.get("enemies").unwrap_or(""), |enemy| {
+                let (pattern, rest) = enemy.trim().split_once(' ')?;
+                let [x, y, origin_y, time, health] = numbers(rest)
+                    .or_else(|| numbers(rest).map(|[x, y, origin_y, time]| [x, y, origin_y, time, 1.0]))?;
+                Some(SavedEnemy { pattern: EnemyPattern::parse(pattern)?, x, y, origin_y, time, health })
+            })?,
+        })
+    }
+
+    /// Writes the run to the save file of `profile`, replacing the previous
+    /// one if any.
+    pub fn save(&self, profile: &str) -> io::Result<()> {
+        self.save_to(path(profile))
+    }
+
+    /// Writes the run to `path`, replacing whatever was saved there.
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        //? Saved runs are signed like the rest of the player's progress, as
+        //? whatever they hold ends up in the scores and stats once finished.
+        let mut storage = Storage::load_signed(path, install_key());
+        let flag = |on: bool| if on { 1 } else { 0 };
+        let mutators = &self.mutators;
+
+        storage.set("arena", &self.arena.to_string());
+        storage.set("mutators", &format!("{} {} {} {} {}",
+            flag(mutators.double_speed_asteroids), flag(mutators.one_hit_death),
+            flag(mutators.tiny_ship), flag(mutators.core_hitbox), flag(mutators.infinite_bombs)));
+        storage.set("ship", &self.ship.to_string());
+        storage.set("difficulty", self.difficulty.name());
+        storage.set("lives", &self.lives.to_string());
+        if let Some(bombs) = self.bombs {
+            storage.set("bombs", &bombs.to_string());
+        }
+        storage.set("player", &format!("{} {} {} {}", self.player.0, self.player.1, self.cannon, self.score));
+
+        let director = &self.director;
+        storage.set("director", &format!("{} {} {} {}",
+            director.started, director.asteroids_left, director.enemies_left, director.timer));
+        storage.set("boss_wave", &director.boss_wave.to_string());
+
+        // The file may hold a run of survival from before, which this one
+        // replaces.
+        match self.survival {
+            Some(time) => storage.set("survival", &time.to_string()),
+            None => storage.remove("survival"),
+        }
+
+        if let Some(seed) = self.seed {
+            storage.set("seed", &seed.to_string());
+        }
+
+        let asteroids: Vec<String> = self.asteroids.iter()
+            .map(|a| format!("{} {} {} {} {} {}", a.size, a.x, a.y, a.vel, a.drift, a.health))
+            .collect();
+        storage.set("asteroids", &asteroids.join(";"));
+
+        let enemies: Vec<String> = self.enemies.iter()
+            .map(|e| format!("{} {} {} {} {} {}", e.pattern.name(), e.x, e.y, e.origin_y, e.time, e.health))
+            .collect();
+        storage.set("enemies", &enemies.join(";"));
+
+        storage.save()
+    }
+
+    /// Forgets the run which `profile` left in progress, e.g. once it is over.
+    pub fn delete(profile: &str) {
+        if let Err(err) = fs::remove_file(path(profile)) {
+            if err.kind() != io::ErrorKind::NotFound {
+                println!("Could not delete the saved game: {}", err);
+            }
+        }
+    }
+}
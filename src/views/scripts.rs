@@ -0,0 +1,262 @@
+//! Scripts which change how enemies move and fire, and what the boss fires on
+//! top of its patterns, so that new behaviors may be tried without rebuilding
+//! the game. Dev builds reload them as soon as they change.
+//!
+//! A script is read from `assets/scripts/<name>.script`, where the name is
+//! that of the movement pattern of the enemies which it drives, e.g. `sine`,
+//! or `boss`. It lists statements, one per line:
+//!
+//!     vx = -120
+//!     vy = 60 * sin(time * 3) + (py - y) * 0.4
+//!     every 1.5 spawn_bullet(atan2(py - y, px - x), 200)
+//!
+//! `vx` and `vy` are the velocity of the enemy, in pixels per second, and
+//! replace the way its pattern moves it, along that axis. `every` fires a
+//! bullet every so many seconds, in the direction of the given angle, in
+//! radians, at the given speed, in pixels per second; scripts which fire
+//! replace the bullet pattern of the enemy. Expressions may read `x` and `y`,
+//! the center of the enemy, `px` and `py`, that of the ship, and `time`, the
+//! seconds since the enemy appeared, see `phi::expr` for the rest.
+
+use crate::phi::PhiError;
+use crate::phi::expr::Expr;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const SCRIPTS_DIR: &str = "assets/scripts";
+
+/// The variables which scripts may read, in the order of `ScriptInputs::values`.
+const VARS: [&str; 5] = ["x", "y", "px", "py", "time"];
+
+/// The time between two checks for scripts which changed, in seconds.
+const RELOAD_DELAY: f64 = 1.0;
+
+/// What a script may know of the game.
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptInputs {
+    /// The center of what the script drives, and that of the ship.
+    pub pos: (f64, f64),
+    pub player: (f64, f64),
+
+    /// The seconds since what the script drives appeared.
+    pub time: f64,
+}
+
+impl ScriptInputs {
+    fn values(&self) -> [f64; 5] {
+        [self.pos.0, self.pos.1, self.player.0, self.player.1, self.time]
+    }
+}
+
+/// Splits the arguments of a call, i.e. what is between its parentheses, at
+/// the commas which are not nested in other parentheses.
+fn split_args(args: &str) -> Vec<&str> {
+    let (mut depth, mut start, mut parts) = (0, 0, vec![]);
+
+    for (i, ch) in args.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+
+    parts.push(&args[start..]);
+    parts
+}
+
+pub struct BehaviorScript {
+    path: PathBuf,
+
+    // When the file was last changed, as of when it was read.
+    modified: Option<SystemTime>,
+
+    vx: Option<Expr>,
+    vy: Option<Expr>,
+
+    // The bullets fired, as the time between two of them, in seconds, and
+    // their angle and speed.
+    bullets: Vec<(f64, Expr, Expr)>,
+}
+
+impl BehaviorScript {
+    /// Reads the script at `path`. Returns an error which mentions the line
+    /// at fault if it is not valid.
+    pub fn load(path: &Path) -> Result<BehaviorScript, PhiError> {
+        let name = path.display().to_string();
+        let contents = fs::read_to_string(path).map_err(PhiError::asset(&name))?;
+        let mut script = BehaviorScript {
+            path: path.to_path_buf(),
+            modified: fs::metadata(path).and_then(|meta| meta.modified()).ok(),
+            vx: None,
+            vy: None,
+            bullets: vec![],
+        };
+
+        for (i, line) in contents.lines().map(|line| line.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            script.parse_line(line).map_err(|reason| PhiError::Asset {
+                path: name.clone(),
+                reason: format!("line {}: {}", i + 1, reason),
+            })?;
+        }
+
+        Ok(script)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        if let Some((target, expr)) = line.split_once('=') {
+            let expr = Some(Expr::parse(expr, &VARS)?);
+            match target.trim() {
+                "vx" => self.vx = expr,
+                "vy" => self.vy = expr,
+                target => return Err(format!("{} may not be set", target)),
+            }
+            return Ok(());
+        }
+
+        let (delay, call) = line.strip_prefix("every ")
+            .and_then(|rest| rest.trim().split_once(char::is_whitespace))
+            .ok_or("expected `every <seconds> spawn_bullet(<angle>, <speed>)`")?;
+        let delay: f64 = delay.parse().ok().filter(|&delay: &f64| delay > 0.0)
+            .ok_or("the time between two bullets must be a positive number")?;
+        let args = call.trim().strip_prefix("spawn_bullet(").and_then(|rest| rest.strip_suffix(')'))
+            .ok_or("only spawn_bullet may be called")?;
+
+        match split_args(args)[..] {
+            [angle, speed] => self.bullets.push((delay, Expr::parse(angle, &VARS)?, Expr::parse(speed, &VARS)?)),
+            _ => return Err("spawn_bullet takes an angle and a speed".to_string()),
+        }
+
+        Ok(())
+    }
+
+    /// The velocity given by the script, along the axes for which it gives
+    /// one, and `default` along the others.
+    pub fn velocity(&self, inputs: ScriptInputs, default: (f64, f64)) -> (f64, f64) {
+        //? Expressions may divide by zero, and an entity moving at an infinite
+        //? or undefined speed would never leave the screen, holding its wave
+        //? back forever. Such results fall back to the default.
+        let values = inputs.values();
+        let eval = |expr: &Option<Expr>, default: f64| expr.as_ref()
+            .map(|expr| expr.eval(&values))
+            .filter(|value| value.is_finite())
+            .unwrap_or(default);
+
+        (eval(&self.vx, default.0), eval(&self.vy, default.1))
+    }
+
+    pub fn moves(&self) -> (bool, bool) {
+        (self.vx.is_some(), self.vy.is_some())
+    }
+
+    pub fn fires(&self) -> bool {
+        !self.bullets.is_empty()
+    }
+
+    /// The bullets fired during the last `dt` seconds, up to `inputs.time`,
+    /// as their angle and speed. Those whose angle or speed is not a finite
+    /// number, e.g. after a division by zero, are not fired at all.
+    pub fn fire(&self, inputs: ScriptInputs, dt: f64) -> Vec<(f64, f64)> {
+        let values = inputs.values();
+        self.bullets.iter()
+            .filter(|(delay, _, _)| (inputs.time / delay).floor() > ((inputs.time - dt) / delay).floor())
+            .map(|(_, angle, speed)| (angle.eval(&values), speed.eval(&values)))
+            .filter(|(angle, speed)| angle.is_finite() && speed.is_finite())
+            .collect()
+    }
+
+    fn has_changed(&self) -> bool {
+        fs::metadata(&self.path).and_then(|meta| meta.modified()).ok() != self.modified
+    }
+}
+
+/// The scripts which exist for a set of names, e.g. those of the movement
+/// patterns of the enemies.
+pub struct BehaviorScripts {
+    names: Vec<String>,
+    scripts: HashMap<String, BehaviorScript>,
+
+    // The time since the scripts were last checked for changes, in seconds.
+    since_check: f64,
+}
+
+impl BehaviorScripts {
+    fn path(name: &str) -> PathBuf {
+        Path::new(SCRIPTS_DIR).join(format!("{}.script", name))
+    }
+
+    /// Reads the script of every name for which there is one. Returns an
+    /// error if one of them is not valid.
+    pub fn load(names: &[&str]) -> Result<BehaviorScripts, PhiError> {
+        let mut scripts = HashMap::new();
+
+        for &name in names {
+            let path = BehaviorScripts::path(name);
+            if path.is_file() {
+                scripts.insert(name.to_string(), BehaviorScript::load(&path)?);
+            }
+        }
+
+        Ok(BehaviorScripts {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            scripts,
+            since_check: 0.0,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BehaviorScript> {
+        self.scripts.get(name)
+    }
+
+    /// Reloads the scripts which were changed, added or removed, every now
+    /// and then. A script which is not valid anymore is kept as it was, so
+    /// that a typo does not end the run.
+    pub fn update(&mut self, dt: f64) {
+        self.since_check += dt;
+        if self.since_check < RELOAD_DELAY {
+            return;
+        }
+        self.since_check = 0.0;
+
+        for name in &self.names {
+            let path = BehaviorScripts::path(name);
+            let changed = match self.scripts.get(name) {
+                Some(script) => script.has_changed(),
+                None => path.is_file(),
+            };
+            if !changed {
+                continue;
+            }
+
+            if !path.is_file() {
+                println!("Removed the script of {}", name);
+                self.scripts.remove(name);
+                continue;
+            }
+
+            match BehaviorScript::load(&path) {
+                Ok(script) => {
+                    println!("Reloaded {}", path.display());
+                    self.scripts.insert(name.clone(), script);
+                },
+                Err(err) => {
+                    println!("Could not reload a script: {}", err);
+                    // Do not report the same error every second.
+                    if let Some(script) = self.scripts.get_mut(name) {
+                        script.modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+                    }
+                },
+            }
+        }
+    }
+}
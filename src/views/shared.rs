@@ -1,49 +1,351 @@
+use crate::phi::{Key, Phi, PhiError};
 use crate::phi::data::Rectangle;
-use crate::phi::gfx::{Sprite, CopySprite};
+use crate::phi::gfx::{draw, Sprite, CopySprite};
+use crate::phi::gfx::noise::ValueNoise;
+use crate::phi::signing;
+use crate::views::high_scores::Leaderboard;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sdl2::pixels::Color;
 use sdl2::render::WindowCanvas;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+
+/// The profile whose settings are used when none was chosen.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The directory under which the files of every profile are saved.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// The file, in `PROFILES_DIR`, holding the key of this install.
+pub const INSTALL_KEY_FILE: &str = "install.key";
+
+/// The files of a profile holding its lifetime stats, its achievements and
+/// the run it left in progress.
+pub const STATS_FILE: &str = "stats.cfg";
+pub const ACHIEVEMENTS_FILE: &str = "achievements.cfg";
+pub const SAVE_FILE: &str = "save.cfg";
+
+/// The directory of a profile holding its bookmarks, which are all signed.
+pub const BOOKMARKS_DIR: &str = "bookmarks";
+
+/// The files of a profile which are signed with the key of the install, and
+/// so read with `Storage::load_signed`, besides its bookmarks.
+pub fn signed_files() -> Vec<&'static str> {
+    Leaderboard::ALL.iter().map(|board| board.file())
+        .chain([STATS_FILE, ACHIEVEMENTS_FILE, SAVE_FILE])
+        .collect()
+}
+
+/// Where the file called `name` is saved for `profile`.
+pub fn profile_path(profile: &str, name: &str) -> PathBuf {
+    PathBuf::from(PROFILES_DIR).join(profile).join(name)
+}
+
+/// The key with which this install signs the files players may share, e.g.
+/// their stats. It is generated the first time it is needed.
+pub fn install_key() -> Vec<u8> {
+    let path = PathBuf::from(PROFILES_DIR).join(INSTALL_KEY_FILE);
+
+    signing::load_or_create_key(&path).unwrap_or_else(|err| {
+        println!("Could not save the install key: {}", err);
+        signing::generate_key()
+    })
+}
+
+/// The layers of the starry background shared by every view.
+pub const STARFIELD_PATH: &str = "assets/starfield.layers";
+
+/// The size of the nebulae which are generated, in pixels. They are
+/// stretched to the height of the screen, which their blur does not mind.
+const NEBULA_SIZE: (u32, u32) = (512, 256);
+
+/// The number of cells across the coarsest noise of a nebula, along either
+/// axis, and the number of finer noises added on top of it.
+const NEBULA_CELLS: (usize, usize) = (6, 3);
+const NEBULA_OCTAVES: usize = 5;
+
+/// The densities of the noise between which a nebula fades in, and how
+/// opaque it is where it is the thickest.
+const NEBULA_EDGES: (f64, f64) = (0.45, 0.8);
+const NEBULA_ALPHA: f64 = 0.5;
+
+/// The pairs of colors which a nebula blends between, one of which is picked
+/// for every nebula.
+const NEBULA_COLORS: [(Color, Color); 4] = [
+    (Color::RGB(90, 40, 160), Color::RGB(30, 110, 200)),
+    (Color::RGB(170, 40, 110), Color::RGB(90, 30, 160)),
+    (Color::RGB(30, 120, 130), Color::RGB(40, 60, 170)),
+    (Color::RGB(180, 80, 40), Color::RGB(120, 30, 90)),
+];
+
+/// What a layer of a `ParallaxBackground` shows.
+pub enum LayerSource {
+    /// The image at the given path.
+    Image(String),
+
+    /// A nebula, generated from the seed of the background, so that it looks
+    /// different in every run.
+    Nebula,
+}
+
+/// Describes a layer of a `ParallaxBackground`.
+pub struct LayerDescr {
+    pub source: LayerSource,
+
+    /// The amount of pixels, relative to the image, moved every second.
+    pub speed: f64,
+
+    /// The direction in which the layer scrolls, e.g. `(-1.0, 0.0)` to the
+    /// left. It is normalized when the layer is created.
+    pub direction: (f64, f64),
+
+    /// Whether the layer is drawn in front of the entities rather than
+    /// behind them.
+    pub front: bool,
+}
 
 #[derive(Clone)]
-pub struct Background {
-    pub pos: f64,
-    // The amount of pixels moved to the left every second
-    pub vel: f64,
-    pub sprite: Sprite,
+struct ParallaxLayer {
+    // The position of the image's origin, relative to the image, and how
+    // much it moves every second.
+    pos: (f64, f64),
+    vel: (f64, f64),
+    sprite: Sprite,
+    front: bool,
 }
 
-impl Background {
-    /// Move the background proportionally to the elapsed time since the last
-    /// frame and the background's velocity.
-    pub fn update(&mut self, elapsed: f64) {
-        // We define a logical position as depending solely on the time and the
-        // dimensions of the image, not on the screen's size.
+impl ParallaxLayer {
+    /// Render the layer at its current position, and as many times as
+    /// required to fill the screen.
+    fn render(&self, renderer: &mut WindowCanvas) {
+        // The image is scaled so that its height matches the window's.
         let size = self.sprite.size();
-        self.pos += self.vel * elapsed;
-        if self.pos > size.0 {
-            self.pos -= size.0;
+        let (win_w, win_h) = renderer.logical_size();
+        let scale = win_h as f64 / size.1;
+        let (tile_w, tile_h) = (size.0 * scale, size.1 * scale);
+
+        // We render as many copies of the image as necessary to fill the
+        // screen, starting from the one which overlaps its top-left corner.
+        let mut y = (self.pos.1 * scale).rem_euclid(tile_h) - tile_h;
+        while y < win_h as f64 {
+            let mut x = (self.pos.0 * scale).rem_euclid(tile_w) - tile_w;
+            while x < win_w as f64 {
+                renderer.copy_sprite(&self.sprite, Rectangle { x, y, w: tile_w, h: tile_h });
+                x += tile_w;
+            }
+
+            y += tile_h;
         }
     }
+}
 
-    /// Render the background at ist current position, and as many times as
-    /// required to fill the screen.
+/// A background made of several images, scrolling at different speeds to
+/// give an impression of depth.
+#[derive(Clone)]
+pub struct ParallaxBackground {
+    layers: Vec<ParallaxLayer>,
+}
+
+impl ParallaxBackground {
+    /// Creates a background out of `layers`, the farthest one first, whose
+    /// nebulae, if any, are generated from `seed`.
+    pub fn new(renderer: &WindowCanvas, layers: &[LayerDescr], seed: u64) -> Result<ParallaxBackground, PhiError> {
+        let layers = layers.iter()
+            .map(|descr| {
+                let (dx, dy) = descr.direction;
+                let norm = (dx * dx + dy * dy).sqrt();
+                let (dx, dy) = if norm > 0.0 { (dx / norm, dy / norm) } else { (0.0, 0.0) };
+
+                Ok(ParallaxLayer {
+                    pos: (0.0, 0.0),
+                    vel: (dx * descr.speed, dy * descr.speed),
+                    sprite: match descr.source {
+                        LayerSource::Image(ref path) => Sprite::load(renderer, path)?,
+                        LayerSource::Nebula => nebula(renderer, seed)?,
+                    },
+                    front: descr.front,
+                })
+            })
+            .collect::<Result<_, PhiError>>()?;
+
+        Ok(ParallaxBackground { layers })
+    }
+
+    /// Creates a background out of the layers described in the file at
+    /// `path`, one per line, farthest first, in the form:
+    ///     image_path speed direction_x direction_y [front]
+    /// where `image_path` may be `nebula` for a generated one. Empty lines
+    /// and lines starting with `#` are ignored.
+    ///
+    /// The nebulae look the same every time, so that menus do not change
+    /// from one visit to the next. See `load_seeded` for runs.
+    pub fn load(renderer: &WindowCanvas, path: &str) -> Result<ParallaxBackground, PhiError> {
+        ParallaxBackground::load_seeded(renderer, path, 0)
+    }
+
+    /// Like `load`, but the nebulae are generated from `seed`.
+    pub fn load_seeded(renderer: &WindowCanvas, path: &str, seed: u64) -> Result<ParallaxBackground, PhiError> {
+        let contents = fs::read_to_string(path).map_err(PhiError::asset(path))?;
+
+        let layers = contents.lines()
+            .map(|line| line.trim())
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| ParallaxBackground::parse_line(line).ok_or_else(|| PhiError::Asset {
+                path: path.to_string(),
+                reason: format!("line {} does not describe a layer", i + 1),
+            }))
+            .collect::<Result<Vec<_>, PhiError>>()?;
+
+        ParallaxBackground::new(renderer, &layers, seed)
+    }
+
+    fn parse_line(line: &str) -> Option<LayerDescr> {
+        let mut words = line.split_whitespace();
+
+        let descr = LayerDescr {
+            source: match words.next()? {
+                "nebula" => LayerSource::Nebula,
+                path => LayerSource::Image(path.to_string()),
+            },
+            speed: words.next()?.parse().ok()?,
+            direction: (words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+            front: match words.next() {
+                None => false,
+                Some("front") => true,
+                Some(_) => return None,
+            },
+        };
+
+        Some(descr)
+    }
+
+    /// Move the layers proportionally to the elapsed time since the last
+    /// frame and their velocity.
+    pub fn update(&mut self, elapsed: f64) {
+        for layer in &mut self.layers {
+            // We define a logical position as depending solely on the time
+            // and the dimensions of the image, not on the screen's size.
+            let size = layer.sprite.size();
+            layer.pos.0 = (layer.pos.0 + layer.vel.0 * elapsed).rem_euclid(size.0);
+            layer.pos.1 = (layer.pos.1 + layer.vel.1 * elapsed).rem_euclid(size.1);
+        }
+    }
+
+    /// Render every layer.
     pub fn render(&self, renderer: &mut WindowCanvas) {
-        // We determine the scale ratio of the window to the sprte.
-        let size = self.sprite.size();
-        let (win_w, win_h) = renderer.output_size().unwrap();
-        let scale = win_h as f64 / size.1;
+        for layer in &self.layers {
+            layer.render(renderer);
+        }
+    }
+
+    /// Render the layers which are drawn behind the entities.
+    pub fn render_back(&self, renderer: &mut WindowCanvas) {
+        for layer in self.layers.iter().filter(|layer| !layer.front) {
+            layer.render(renderer);
+        }
+    }
+
+    /// Render the layers which are drawn in front of the entities.
+    pub fn render_front(&self, renderer: &mut WindowCanvas) {
+        for layer in self.layers.iter().filter(|layer| layer.front) {
+            layer.render(renderer);
+        }
+    }
+}
+
+thread_local! {
+    //? Every menu loads its own background, so the last nebula is kept
+    //? around rather than generated again on every visit.
+    static LAST_NEBULA: RefCell<Option<(u64, Sprite)>> = const { RefCell::new(None) };
+}
 
-        // We render as many copies of the background as necessary to fill
-        // the screen.
-        let mut physical_left = -self.pos * scale;
+/// Generates a nebula from `seed`: clouds of value noise, fading out where it
+/// is too thin, whose colors blend between a pair picked from the same seed.
+fn nebula(renderer: &WindowCanvas, seed: u64) -> Result<Sprite, PhiError> {
+    let cached = LAST_NEBULA.with(|last| last.borrow().as_ref()
+        .filter(|&&(last_seed, _)| last_seed == seed)
+        .map(|(_, sprite)| sprite.clone()));
+    if let Some(sprite) = cached {
+        return Ok(sprite);
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let density = ValueNoise::new(rng.gen(), NEBULA_CELLS, NEBULA_OCTAVES);
+    let shade = ValueNoise::new(rng.gen(), NEBULA_CELLS, 2);
+    let (from, to) = NEBULA_COLORS[rng.gen_range(0..NEBULA_COLORS.len())];
+
+    let (w, h) = NEBULA_SIZE;
+    let mix = |a: u8, b: u8, t: f64| (a as f64 + (b as f64 - a as f64) * t) as u8;
+
+    let pixels: Vec<Color> = (0..w * h)
+        .map(|i| {
+            let (u, v) = ((i % w) as f64 / w as f64, (i / w) as f64 / h as f64);
+            let t = ((density.sample(u, v) - NEBULA_EDGES.0) / (NEBULA_EDGES.1 - NEBULA_EDGES.0)).clamp(0.0, 1.0);
+            let alpha = t * t * (3.0 - 2.0 * t) * NEBULA_ALPHA;
+
+            let s = shade.sample(u, v);
+            Color::RGBA(mix(from.r, to.r, s), mix(from.g, to.g, s), mix(from.b, to.b, s), (alpha * 255.0) as u8)
+        })
+        .collect();
+
+    let sprite = Sprite::from_pixels(renderer, NEBULA_SIZE, &pixels)?;
+    LAST_NEBULA.with(|last| *last.borrow_mut() = Some((seed, sprite.clone())));
+    Ok(sprite)
+}
+
+/// How long R, or Select on a gamepad, must be held to start the run over, in
+/// seconds.
+pub const RESTART_HOLD: f64 = 1.0;
+
+/// The radius of the disc which fills up as the restart key is held, and its
+/// distance to the bottom right corner of the screen, in pixels.
+const RESTART_RADIUS: f64 = 22.0;
+const RESTART_MARGIN: f64 = 20.0;
+
+/// Starts the run over once the player held the restart key long enough,
+/// showing how far along they are as a disc which fills up clockwise.
+pub struct RestartHold {
+    // Whether the key was released since the view started, so that holding
+    // it on to the next run does not restart that one as well.
+    armed: bool,
+    progress: f64,
+}
 
-        while physical_left < win_w as f64 {
-            renderer.copy_sprite(&self.sprite, Rectangle {
-                x: physical_left,
-                y: 0.0,
-                w: size.0 * scale,
-                h: win_h as f64,
-            });
+impl RestartHold {
+    pub fn new(phi: &Phi) -> RestartHold {
+        RestartHold {
+            armed: !phi.events.key_restart,
+            progress: 0.0,
+        }
+    }
 
-            physical_left += size.0 * scale;
+    /// Whether the key was held long enough for the run to start over.
+    pub fn update(&mut self, phi: &Phi) -> bool {
+        if !phi.events.key_restart {
+            self.armed = true;
         }
-    }   
-}
\ No newline at end of file
+
+        self.progress =
+            if self.armed { phi.events.held_for(Key::key_restart) / RESTART_HOLD }
+            else { 0.0 };
+
+        self.progress >= 1.0
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        if self.progress <= 0.0 {
+            return;
+        }
+
+        let (win_w, win_h) = phi.output_size();
+        let center = (win_w - RESTART_MARGIN - RESTART_RADIUS, win_h - RESTART_MARGIN - RESTART_RADIUS);
+
+        draw::fill_circle(&mut phi.renderer, center, RESTART_RADIUS, Color::RGBA(0, 0, 0, 160), true);
+        draw::fill_pie(&mut phi.renderer, center, RESTART_RADIUS - 3.0,
+            -PI / 2.0, 2.0 * PI * self.progress.min(1.0), Color::RGBA(255, 255, 255, 220), true);
+    }
+}
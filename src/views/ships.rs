@@ -0,0 +1,234 @@
+use crate::phi::data::Rectangle;
+use crate::phi::gfx::{CopySprite, Sprite, SpriteOptions};
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, View, ViewAction};
+use crate::views::bullets::CannonType;
+use crate::views::shared::{ParallaxBackground, STARFIELD_PATH};
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+
+/// The frame of a ship's spritesheet which is shown when it flies straight.
+const PREVIEW_FRAME: usize = 3;
+
+/// The shades of red of the stripes along the hull of `spaceship.png`, and
+/// the lightest shade of its plating, which the ships recolor.
+const HULL_STRIPE_DARK: Color = Color::RGB(132, 0, 0);
+const HULL_STRIPE_LIGHT: Color = Color::RGB(255, 0, 0);
+const HULL_PLATE_LIGHT: Color = Color::RGB(165, 173, 148);
+
+/// What sets a ship apart from the others which the player may fly.
+#[derive(Clone, Copy)]
+pub struct ShipSpec {
+    pub name: &'static str,
+
+    /// The spritesheet of the ship, holding 3x3 frames of `w` by `h` pixels.
+    pub path: &'static str,
+    pub w: f64,
+    pub h: f64,
+
+    /// The factor by which the ship is scaled relative to its sprite.
+    pub scale: f64,
+
+    /// How fast the ship flies, in pixels per second.
+    pub speed: f64,
+
+    /// The cannon with which the ship starts the run.
+    pub cannon: CannonType,
+
+    /// Colors of the spritesheet which are replaced by others, so that ships
+    /// which share a spritesheet may still look different.
+    pub palette: &'static [(Color, Color)],
+}
+
+/// The ships which the player may choose from, in order. The first one is
+/// flown when nobody chose, e.g. by the bot.
+pub const SHIPS: [ShipSpec; 3] = [
+    ShipSpec {
+        name: "Falcon",
+        path: "assets/spaceship.png",
+        w: 43.0,
+        h: 39.0,
+        scale: 1.0,
+        speed: 180.0,
+        cannon: CannonType::RectBullet,
+        // The colors of the spritesheet as it was drawn.
+        palette: &[],
+    },
+    ShipSpec {
+        name: "Dart",
+        path: "assets/spaceship.png",
+        w: 43.0,
+        h: 39.0,
+        scale: 0.75,
+        speed: 240.0,
+        cannon: CannonType::SineBullet { amplitude: 10.0, angular_vel: 15.0 },
+        palette: &[
+            (HULL_STRIPE_DARK, Color::RGB(0, 110, 40)),
+            (HULL_STRIPE_LIGHT, Color::RGB(40, 220, 90)),
+        ],
+    },
+    ShipSpec {
+        name: "Bulwark",
+        path: "assets/spaceship.png",
+        w: 43.0,
+        h: 39.0,
+        scale: 1.3,
+        speed: 135.0,
+        cannon: CannonType::DevergentBullet { a: 100.0, b: 1.2 },
+        palette: &[
+            (HULL_STRIPE_DARK, Color::RGB(140, 80, 0)),
+            (HULL_STRIPE_LIGHT, Color::RGB(255, 170, 0)),
+            (HULL_PLATE_LIGHT, Color::RGB(150, 150, 160)),
+        ],
+    },
+];
+
+impl ShipSpec {
+    /// Cuts the spritesheet of the ship into its frames, from left to right,
+    /// then top to bottom.
+    pub fn frames(&self, renderer: &WindowCanvas) -> Result<Vec<Sprite>, PhiError> {
+        let options = SpriteOptions { palette: self.palette.to_vec(), ..SpriteOptions::default() };
+        let spritesheet = Sprite::load_with(renderer, self.path, &options)?;
+        let mut frames = Vec::with_capacity(9);
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let frame = spritesheet.region(Rectangle {
+                    w: self.w,
+                    h: self.h,
+                    x: self.w * x as f64,
+                    y: self.h * y as f64,
+                }).ok_or_else(|| PhiError::Asset {
+                    path: self.path.to_string(),
+                    reason: "the spritesheet is too small".to_string(),
+                })?;
+
+                frames.push(frame);
+            }
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Starts a run with the ship at the given index in `SHIPS`, or whatever
+/// comes before it.
+type StartRun = Box<dyn Fn(&mut Phi, usize) -> Result<Box<dyn View>, PhiError>>;
+
+/// Lets the player pick the ship with which to play before starting a run.
+pub struct ShipSelectView {
+    menu: Menu,
+    ships: Vec<WidgetId>,
+    back: WidgetId,
+    start: StartRun,
+
+    // The sprite and stats of every ship, shown while its button is focused.
+    previews: Vec<Sprite>,
+    stats: Vec<Sprite>,
+
+    background: ParallaxBackground,
+}
+
+impl ShipSelectView {
+    pub fn new(phi: &mut Phi, start: StartRun) -> Result<ShipSelectView, PhiError> {
+        let mut menu = Menu::new(360.0, 50.0);
+        let mut ships = vec![];
+        let mut previews = vec![];
+        let mut stats = vec![];
+
+        for ship in &SHIPS {
+            ships.push(menu.add(phi, ship.name, WidgetKind::Button)?);
+            previews.push(ship.frames(&phi.renderer)?.swap_remove(PREVIEW_FRAME));
+
+            let text = format!("Speed: {}   Size: {}%   Cannon: {}",
+                ship.speed, (ship.scale * 100.0).round(), ship.cannon.name());
+            stats.push(phi.ttf_str_sprite(&text, FONT_PATH, 16, Color::RGB(220, 220, 220))?);
+        }
+
+        Ok(ShipSelectView {
+            back: menu.add(phi, "Back", WidgetKind::Button)?,
+            menu,
+            ships,
+            start,
+            previews,
+            stats,
+
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    /// The index in `SHIPS` of the ship whose button is focused, if any.
+    fn focused_ship(&self) -> Option<usize> {
+        let focused = self.menu.focused()?;
+        self.ships.iter().position(|&id| id == focused)
+    }
+}
+
+impl View for ShipSelectView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi));
+        }
+
+        match self.menu.update(phi) {
+            Some(UiEvent::Clicked(id)) if id == self.back =>
+                return ViewAction::switch_to(self, crate::views::main_menu::MainMenuView::new(phi)),
+
+            Some(UiEvent::Clicked(id)) => {
+                if let Some(ship) = self.ships.iter().position(|&ship| ship == id) {
+                    return match (self.start)(phi, ship) {
+                        Ok(to) => ViewAction::ChangeView { from: self, to },
+                        Err(err) => ViewAction::Error(err),
+                    };
+                }
+            },
+
+            _ => {},
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+
+        // Render the focused ship above the menu, to scale, and its stats at
+        // the bottom of the screen.
+        if let Some(ship) = self.focused_ship() {
+            let (win_w, win_h) = phi.output_size();
+            let spec = &SHIPS[ship];
+            let (w, h) = (spec.w * spec.scale * 2.0, spec.h * spec.scale * 2.0);
+            phi.renderer.copy_sprite(&self.previews[ship], Rectangle {
+                w,
+                h,
+                x: (win_w - w) / 2.0,
+                y: win_h / 6.0 - h / 2.0,
+            });
+
+            let (w, h) = self.stats[ship].size();
+            phi.renderer.copy_sprite(&self.stats[ship], Rectangle {
+                w,
+                h,
+                x: (win_w - w) / 2.0,
+                y: win_h - h - 6.0,
+            });
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// How many heights are drawn by `BlueNoise`, of which the one farthest from
+/// the previous spawns is kept.
+const BLUE_NOISE_CANDIDATES: usize = 6;
+
+/// How many previous spawns `BlueNoise` keeps away from.
+const BLUE_NOISE_MEMORY: usize = 3;
+
+/// The standard deviation of `Gaussian`, relative to the height of the area
+/// in which things spawn.
+const GAUSSIAN_SPREAD: f64 = 0.2;
+
+/// How the height at which asteroids enter the screen is picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnDistribution {
+    /// Any height is as likely as any other.
+    Uniform,
+
+    /// Most asteroids come through the middle of the screen, fewer near its
+    /// edges.
+    Gaussian,
+
+    /// Never in line with the player's ship, however slow the asteroid is.
+    AvoidPlayer,
+
+    /// Spread out: every asteroid comes as far as possible from the last few
+    /// ones, so that they do not clump together.
+    BlueNoise,
+}
+
+impl SpawnDistribution {
+    pub fn parse(name: &str) -> Option<SpawnDistribution> {
+        match name {
+            "uniform" => Some(SpawnDistribution::Uniform),
+            "gaussian" => Some(SpawnDistribution::Gaussian),
+            "avoid-player" => Some(SpawnDistribution::AvoidPlayer),
+            "blue-noise" => Some(SpawnDistribution::BlueNoise),
+            _ => None,
+        }
+    }
+}
+
+/// Picks spawn heights following a `SpawnDistribution`, remembering what it
+/// needs to about the previous ones.
+#[derive(Clone)]
+pub struct Spawner {
+    pub distribution: SpawnDistribution,
+
+    // The most recent heights, as fractions of the area, newest last.
+    recent: Vec<f64>,
+}
+
+impl Spawner {
+    pub fn new(distribution: SpawnDistribution) -> Spawner {
+        Spawner {
+            distribution,
+            recent: vec![],
+        }
+    }
+
+    /// Picks a height in `[0, max_y]` which is not in the `lane` between its
+    /// top and bottom. Asteroids are always kept out of the lane if `avoid`
+    /// is set, e.g. because they are too fast to be dodged, and regardless of
+    /// it with `AvoidPlayer`. Returns `None` if there is no room outside of
+    /// the lane.
+    pub fn pick(&mut self, rng: &mut impl Rng, max_y: f64, (lane_top, lane_bottom): (f64, f64), avoid: bool) -> Option<f64> {
+        let (lane_top, lane_bottom) =
+            if avoid || self.distribution == SpawnDistribution::AvoidPlayer {
+                (lane_top, lane_bottom)
+            } else {
+                (0.0, 0.0)
+            };
+
+        let room = max_y - (lane_bottom - lane_top);
+        if room <= 0.0 {
+            return None;
+        }
+
+        // Pick a fraction of the room which is left, then skip over the lane.
+        let fraction = self.fraction(rng);
+        let mut y = fraction * room;
+        if y > lane_top {
+            y += lane_bottom - lane_top;
+        }
+
+        Some(y)
+    }
+
+    /// A number in `[0, 1)` following the distribution.
+    fn fraction(&mut self, rng: &mut impl Rng) -> f64 {
+        match self.distribution {
+            SpawnDistribution::Uniform | SpawnDistribution::AvoidPlayer =>
+                rng.gen::<f64>(),
+
+            SpawnDistribution::Gaussian => {
+                //? The Box-Muller transform turns two uniform numbers into
+                //? one which follows a normal distribution. Whatever falls
+                //? outside of the screen is pulled back to its edges.
+                let u = 1.0 - rng.gen::<f64>();
+                let v = rng.gen::<f64>();
+                let normal = (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos();
+                (0.5 + normal * GAUSSIAN_SPREAD).clamp(0.0, 0.999)
+            },
+
+            SpawnDistribution::BlueNoise => {
+                //? Mitchell's best-candidate algorithm: among a few random
+                //? candidates, keep the one whose nearest neighbour is the
+                //? farthest away.
+                let distance = |y: f64| self.recent.iter()
+                    .map(|&other| (y - other).abs())
+                    .fold(1.0, f64::min);
+
+                let best = (0..BLUE_NOISE_CANDIDATES)
+                    .map(|_| rng.gen::<f64>())
+                    .max_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+                    .unwrap();
+
+                if self.recent.len() == BLUE_NOISE_MEMORY {
+                    self.recent.remove(0);
+                }
+                self.recent.push(best);
+                best
+            },
+        }
+    }
+}
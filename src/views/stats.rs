@@ -0,0 +1,221 @@
+use crate::phi::storage::Storage;
+use crate::views::shared::{install_key, profile_path, STATS_FILE};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Totals kept across every run a player made. The file is signed, so that
+/// totals which were edited by hand are discarded rather than trusted.
+pub struct LifetimeStats {
+    storage: Storage,
+}
+
+impl LifetimeStats {
+    pub fn load(profile: &str) -> LifetimeStats {
+        LifetimeStats {
+            storage: Storage::load_signed(profile_path(profile, STATS_FILE), install_key()),
+        }
+    }
+
+    fn get(&self, key: &str) -> u64 {
+        self.storage.get(key).and_then(|value| value.parse().ok()).unwrap_or(0)
+    }
+
+    fn add(&mut self, key: &str, amount: u64) {
+        let total = self.get(key) + amount;
+        self.storage.set(key, &total.to_string());
+    }
+
+    /// Adds a finished run, in which `destroyed` asteroids were shot down, to
+    /// the totals.
+    pub fn record_run(&mut self, destroyed: u64) {
+        self.add("runs", 1);
+        self.add("asteroids_destroyed", destroyed);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.storage.save()
+    }
+}
+
+/// What the player shot down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Asteroid,
+    Enemy,
+    Boss,
+}
+
+/// The numbers of a single run, shown once it is over, along with where its
+/// score came from.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunStats {
+    /// The bullets which the ship fired, and those which hit something.
+    pub shots_fired: u64,
+    pub shots_hit: u64,
+
+    pub asteroids_destroyed: u64,
+    pub enemies_killed: u64,
+
+    /// The hits which the ship took, whether its shield absorbed them or not.
+    pub hits_taken: u64,
+
+    /// How long the run lasted, in seconds.
+    pub time: f64,
+
+    /// The points earned by shooting every kind of target down, as they are
+    /// worth on their own, and the extra points earned by kills in a row.
+    pub asteroid_points: u64,
+    pub enemy_points: u64,
+    pub boss_points: u64,
+    pub combo_bonus: u64,
+
+    /// The points earned by picking gems up, and by grazing the ship.
+    pub gem_points: u64,
+    pub graze_points: u64,
+}
+
+impl RunStats {
+    /// The part of the bullets fired which hit something, in `[0, 1]`.
+    pub fn accuracy(&self) -> f64 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            (self.shots_hit as f64 / self.shots_fired as f64).min(1.0)
+        }
+    }
+
+    /// Counts `target` as shot down, for `points` on its own, which `earned`
+    /// once multiplied by the combo.
+    pub fn record_kill(&mut self, target: Target, points: u64, earned: u64) {
+        match target {
+            Target::Asteroid => {
+                self.asteroids_destroyed += 1;
+                self.asteroid_points += points;
+            },
+            Target::Enemy => {
+                self.enemies_killed += 1;
+                self.enemy_points += points;
+            },
+            Target::Boss => {
+                self.enemies_killed += 1;
+                self.boss_points += points;
+            },
+        }
+
+        self.combo_bonus += earned.saturating_sub(points);
+    }
+}
+
+/// Something notable which happened during a run.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RunEvent {
+    /// The player shot an asteroid down.
+    Kill,
+
+    /// The player's ship was hit.
+    Hit,
+
+    /// An asteroid brushed past the player's ship without hitting it.
+    Graze,
+}
+
+impl RunEvent {
+    fn name(self) -> &'static str {
+        match self {
+            RunEvent::Kill => "kill",
+            RunEvent::Hit => "hit",
+            RunEvent::Graze => "graze",
+        }
+    }
+}
+
+/// Everything which happened during a run, in order, so that players may
+/// analyze their runs with other tools.
+pub struct RunTimeline {
+    // How long the run has lasted, in seconds.
+    time: f64,
+    events: Vec<(f64, RunEvent)>,
+}
+
+impl RunTimeline {
+    pub fn new() -> RunTimeline {
+        RunTimeline {
+            time: 0.0,
+            events: vec![],
+        }
+    }
+
+    /// How long the run has lasted so far, in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn add_time(&mut self, dt: f64) {
+        self.time += dt;
+    }
+
+    pub fn record(&mut self, event: RunEvent) {
+        self.events.push((self.time, event));
+    }
+
+    /// How many times `event` happened so far.
+    pub fn count(&self, event: RunEvent) -> u64 {
+        self.events.iter().filter(|&&(_, e)| e == event).count() as u64
+    }
+
+    /// Every event along with the running totals of kills and hits.
+    fn rows(&self) -> Vec<(f64, RunEvent, u64, u64)> {
+        let (mut kills, mut hits) = (0, 0);
+
+        self.events.iter().map(|&(time, event)| {
+            match event {
+                RunEvent::Kill => kills += 1,
+                RunEvent::Hit => hits += 1,
+                RunEvent::Graze => {},
+            }
+
+            (time, event, kills, hits)
+        }).collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,event,kills,hits\n");
+
+        for (time, event, kills, hits) in self.rows() {
+            csv.push_str(&format!("{:.3},{},{},{}\n", time, event.name(), kills, hits));
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let events: Vec<String> = self.rows().into_iter()
+            .map(|(time, event, kills, hits)| format!(
+                "    {{ \"time\": {:.3}, \"event\": \"{}\", \"kills\": {}, \"hits\": {} }}",
+                time, event.name(), kills, hits))
+            .collect();
+
+        format!("{{\n  \"duration\": {:.3},\n  \"events\": [\n{}\n  ]\n}}\n",
+            self.time, events.join(",\n"))
+    }
+
+    /// Writes the timeline as both JSON and CSV to the `runs` directory of
+    /// `profile`, named after the time at which the run ended. Returns the
+    /// path of the files, without their extension.
+    pub fn export(&self, profile: &str) -> io::Result<PathBuf> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+
+        let dir = profile_path(profile, "runs");
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("run-{}", now));
+        fs::write(path.with_extension("json"), self.to_json())?;
+        fs::write(path.with_extension("csv"), self.to_csv())?;
+
+        Ok(path)
+    }
+}
@@ -0,0 +1,274 @@
+//! The summary shown once a run is over, whose numbers count up one after the
+//! other, each with a tick as it grows and a chime once it is reached.
+
+use crate::phi::audio;
+use crate::phi::tween::{Ease, Tween};
+use crate::phi::ui::{Menu, UiEvent, WidgetId, WidgetKind};
+use crate::phi::{Phi, PhiError, Transition, View, ViewAction};
+use crate::views::high_scores::{HighScores, Leaderboard, NameEntryView};
+use crate::views::results::ResultsView;
+use crate::views::shared::{ParallaxBackground, RestartHold, DEFAULT_PROFILE, STARFIELD_PATH};
+use crate::views::stats::RunStats;
+use sdl2::mixer::{Channel, Chunk};
+use sdl2::pixels::Color;
+
+/// How long every number takes to count up, and the pause before the next
+/// one starts, in seconds.
+const TALLY_TIME: f64 = 0.9;
+const TALLY_PAUSE: f64 = 0.35;
+
+/// The shortest time between two ticks, in seconds, so that large numbers do
+/// not play one every frame.
+const TICK_DELAY: f64 = 0.04;
+
+// The sounds played as a number grows and once it is reached, as their pitch
+// in Hz and length in seconds.
+const TICK_SOUND: (f64, f64) = (880.0, 0.03);
+const DONE_SOUND: (f64, f64) = (1320.0, 0.15);
+
+/// How long the fade to the run which starts over takes, in seconds.
+pub const RESTART_FADE: f64 = 0.3;
+
+/// What the player achieved during a run.
+#[derive(Clone, Copy, Debug)]
+pub struct RunSummary {
+    pub destroyed: u64,
+    pub grazes: u64,
+    pub waves: u32,
+    pub score: u64,
+
+    /// The number of seconds survived, for runs of survival, which have no
+    /// waves.
+    pub survived: Option<u64>,
+
+    /// The details of the run, for those which kept track of them.
+    pub stats: Option<RunStats>,
+
+    /// Whether the run was played with some mutators, which its entry in the
+    /// leaderboard is marked with.
+    pub modified: bool,
+}
+
+impl RunSummary {
+    /// The numbers which are counted up, in order, along with their label.
+    fn rows(&self) -> [(&'static str, u64); 4] {
+        [
+            ("Destroyed", self.destroyed),
+            ("Near misses", self.grazes),
+            match self.survived {
+                Some(seconds) => ("Seconds survived", seconds),
+                None => ("Waves reached", self.waves as u64),
+            },
+            ("Score", self.score),
+        ]
+    }
+
+    /// The leaderboard into which the run may make it, and what it is ranked
+    /// by there.
+    pub fn entry(&self) -> (Leaderboard, u64) {
+        match self.survived {
+            Some(seconds) => (Leaderboard::Survival, seconds),
+            None => (Leaderboard::Waves, self.score),
+        }
+    }
+}
+
+/// Starts the run which the summary is about over, as the view shown next.
+pub type Restart = Box<dyn Fn(&mut Phi) -> Result<Box<dyn View>, PhiError>>;
+
+pub struct RunSummaryView {
+    summary: RunSummary,
+
+    // How to start the run over, if it may be, when the restart key is held.
+    restart: Option<Restart>,
+    restart_hold: RestartHold,
+
+    menu: Menu,
+    rows: Vec<WidgetId>,
+    continue_button: WidgetId,
+
+    // The row which is being counted up, as the index of the row and how far
+    // along its number is, and the time left before it starts, in seconds.
+    // Once every row was counted up, `current` is past the last one.
+    current: usize,
+    tally: Tween,
+    pause: f64,
+
+    // The value shown by the current row, and the time since the last tick,
+    // in seconds.
+    shown: u64,
+    since_tick: f64,
+
+    tick: Chunk,
+    done: Chunk,
+
+    background: ParallaxBackground,
+}
+
+impl RunSummaryView {
+    pub fn new(phi: &mut Phi, summary: RunSummary, restart: Option<Restart>) -> Result<RunSummaryView, PhiError> {
+        let mut menu = Menu::new(480.0, 50.0);
+
+        menu.add(phi, "Game Over", WidgetKind::Label)?;
+        let rows = summary.rows().iter()
+            .map(|(label, _)| menu.add(phi, &format!("{}: 0", label), WidgetKind::Label))
+            .collect::<Result<_, _>>()?;
+
+        Ok(RunSummaryView {
+            summary,
+            restart,
+            restart_hold: RestartHold::new(phi),
+            continue_button: menu.add(phi, "Continue", WidgetKind::Button)?,
+            menu,
+            rows,
+            current: 0,
+            tally: RunSummaryView::tally(summary.rows()[0].1),
+            pause: TALLY_PAUSE,
+            shown: 0,
+            since_tick: 0.0,
+            tick: audio::tone(TICK_SOUND.0, TICK_SOUND.1)?,
+            done: audio::tone(DONE_SOUND.0, DONE_SOUND.1)?,
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    /// Counts up to `value`, fast at first, then slowing down.
+    fn tally(value: u64) -> Tween {
+        Tween::new(0.0, value as f64, TALLY_TIME, Ease::Out)
+    }
+
+    fn is_over(&self) -> bool {
+        self.current >= self.rows.len()
+    }
+
+    fn show(&mut self, phi: &mut Phi, row: usize, value: u64) -> Result<(), PhiError> {
+        let (label, _) = self.summary.rows()[row];
+        self.menu.set_text(phi, self.rows[row], &format!("{}: {}", label, value))
+    }
+
+    /// Moves the tally forward by `dt` seconds.
+    fn count(&mut self, phi: &mut Phi, dt: f64) -> Result<(), PhiError> {
+        if self.pause > 0.0 {
+            self.pause -= dt;
+            return Ok(());
+        }
+
+        self.tally.update(dt);
+        self.since_tick += dt;
+
+        let value = self.tally.value().round() as u64;
+        if value != self.shown {
+            self.shown = value;
+            self.show(phi, self.current, value)?;
+
+            if self.since_tick >= TICK_DELAY {
+                self.since_tick = 0.0;
+                let _ = Channel::all().play(&self.tick, 0);
+            }
+        }
+
+        if self.tally.is_done() {
+            let _ = Channel::all().play(&self.done, 0);
+
+            self.current += 1;
+            self.shown = 0;
+            self.pause = TALLY_PAUSE;
+            if let Some(&(_, value)) = self.summary.rows().get(self.current) {
+                self.tally = RunSummaryView::tally(value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shows every number as it ends up, right away.
+    fn skip(&mut self, phi: &mut Phi) -> Result<(), PhiError> {
+        for (row, (_, value)) in self.summary.rows().iter().enumerate() {
+            self.show(phi, row, *value)?;
+        }
+
+        self.current = self.rows.len();
+        Ok(())
+    }
+
+    /// The view shown next: the details of the run if it kept track of them,
+    /// and whatever follows them otherwise.
+    fn next(mut self: Box<Self>, phi: &mut Phi) -> ViewAction {
+        let summary = self.summary;
+        match summary.stats {
+            Some(stats) => {
+                let view = ResultsView::new(phi, summary, stats, self.restart.take());
+                ViewAction::switch_to(self, view)
+            },
+            None => after_summary(phi, self, &summary),
+        }
+    }
+}
+
+/// The view shown once the player is done looking at how a run went: the
+/// name entry screen if its score made it into the high scores, and the main
+/// menu otherwise, to which `from` gives way.
+pub fn after_summary(phi: &mut Phi, from: Box<dyn View>, summary: &RunSummary) -> ViewAction {
+    let (board, value) = summary.entry();
+    if HighScores::load(DEFAULT_PROFILE, board).qualifies(value) {
+        ViewAction::switch_to(from, NameEntryView::new(phi, DEFAULT_PROFILE, board, value, summary.modified))
+    } else {
+        ViewAction::switch_to(from, crate::views::main_menu::MainMenuView::new(phi))
+    }
+}
+
+impl View for RunSummaryView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if self.restart_hold.update(phi) {
+            if let Some(ref restart) = self.restart {
+                return match restart(phi) {
+                    Ok(to) => ViewAction::ChangeViewWith { transition: Transition::Fade(RESTART_FADE), from: self, to },
+                    Err(err) => ViewAction::Error(err),
+                };
+            }
+        }
+
+        // Confirming skips the tally if it is still going on, and moves on
+        // otherwise. Select on a gamepad confirms once released, as holding
+        // it restarts the run instead.
+        let confirmed = phi.events.now.key_enter == Some(true) ||
+            phi.events.now.key_escape == Some(false) ||
+            self.menu.update(phi) == Some(UiEvent::Clicked(self.continue_button));
+
+        let result = match (confirmed, self.is_over()) {
+            (true, true) => return self.next(phi),
+            (true, false) => self.skip(phi),
+            (false, false) => self.count(phi, elapsed),
+            (false, true) => Ok(()),
+        };
+
+        if let Err(err) = result {
+            return ViewAction::Error(err);
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, _: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render(&mut phi.renderer);
+
+        // Render the menu
+        self.menu.render(phi);
+
+        if self.restart.is_some() {
+            self.restart_hold.render(phi);
+        }
+    }
+}
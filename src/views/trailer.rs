@@ -0,0 +1,202 @@
+//! Timed scripts which direct a run on their own, so that the same footage may
+//! be captured again and again, e.g. for a trailer.
+//!
+//! A script lists cues, one per line, each with the time at which it happens,
+//! in seconds since the start of the run:
+//!
+//!     0.0 hud off
+//!     0.0 waves off
+//!     0.5 spawn asteroid 1.2
+//!     1.0 spawn enemy sine
+//!     2.0 zoom 1.5 1.0
+//!     2.0 pan 0.3 0.5 1.0
+//!     3.5 shake 8 0.4
+//!     5.0 pilot player
+//!     12.0 end
+//!
+//! The run is seeded with `--seed`, so that the asteroids and enemies which a
+//! script spawns always come the same way, and the ship may be handed over to
+//! the player, whose inputs may in turn come from `--replay`.
+
+use crate::phi::PhiError;
+use crate::phi::gfx::Camera;
+use crate::phi::tween::{Ease, Tween};
+use crate::views::level::EnemyPattern;
+use std::fs;
+
+/// What a script asks the game to do, other than moving the camera, which it
+/// does on its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrailerCue {
+    /// Spawn an asteroid, whose velocity is multiplied by `speed`.
+    Asteroid { speed: f64 },
+
+    /// Spawn an enemy following the given pattern.
+    Enemy(EnemyPattern),
+
+    /// Shake the camera by up to `intensity` pixels, during `duration`
+    /// seconds.
+    Shake { intensity: f64, duration: f64 },
+
+    /// Show or hide the HUD and the wave banners.
+    Hud(bool),
+
+    /// Let the waves of the level spawn, or hold them back so that only what
+    /// the script spawns shows up.
+    Waves(bool),
+
+    /// Have the bot fly the ship, or hand it over to the player.
+    Bot(bool),
+
+    /// The script is over.
+    End,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    /// Move the center of the camera to the given point, as fractions of the
+    /// size of the screen, over `over` seconds.
+    Pan { to: (f64, f64), over: f64 },
+
+    /// Change the zoom of the camera, over `over` seconds.
+    Zoom { to: f64, over: f64 },
+
+    Cue(TrailerCue),
+}
+
+/// Plays a script, cue after cue, as the run goes on.
+pub struct TrailerScript {
+    // The steps of the script, ordered by when they happen.
+    steps: Vec<(f64, Step)>,
+
+    // The time since the script started, in seconds, and the index of the
+    // next step to happen.
+    time: f64,
+    next: usize,
+
+    // The moves of the camera which are still going on, if any.
+    pan: Option<(Tween, Tween)>,
+    zoom: Option<Tween>,
+}
+
+impl TrailerScript {
+    /// Reads the script at `path`, one cue per line:
+    ///     <time> <cue> [args...]
+    /// where cues are
+    ///     pan <x> <y> <seconds>, with `x` and `y` as fractions of the screen
+    ///     zoom <factor> <seconds>
+    ///     shake <pixels> <seconds>
+    ///     spawn asteroid <speed>
+    ///     spawn enemy straight|sine|dive
+    ///     hud on|off
+    ///     waves on|off
+    ///     pilot bot|player
+    ///     end
+    /// Cues may come in any order. Lines which start with `#` are ignored.
+    pub fn load(path: &str) -> Result<TrailerScript, PhiError> {
+        let contents = fs::read_to_string(path).map_err(PhiError::asset(path))?;
+        let mut steps = vec![];
+
+        for (i, line) in contents.lines().map(|line| line.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            steps.push(TrailerScript::parse_line(line).ok_or_else(|| PhiError::Asset {
+                path: path.to_string(),
+                reason: format!("line {} does not describe a cue", i + 1),
+            })?);
+        }
+
+        // Cues which happen at the same time keep the order they were
+        // written in.
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(TrailerScript {
+            steps,
+            time: 0.0,
+            next: 0,
+            pan: None,
+            zoom: None,
+        })
+    }
+
+    fn parse_line(line: &str) -> Option<(f64, Step)> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let time: f64 = words.first()?.parse().ok()?;
+        let number = |i: usize| words.get(i).and_then(|word| word.parse::<f64>().ok());
+        let switch = |i: usize| match words.get(i) {
+            Some(&"on") => Some(true),
+            Some(&"off") => Some(false),
+            _ => None,
+        };
+
+        let (step, len) = match words.get(1..)? {
+            ["pan", ..] => (Step::Pan { to: (number(2)?, number(3)?), over: number(4)? }, 5),
+            ["zoom", ..] => (Step::Zoom { to: number(2)?, over: number(3)? }, 4),
+            ["shake", ..] => (Step::Cue(TrailerCue::Shake { intensity: number(2)?, duration: number(3)? }), 4),
+            ["spawn", "asteroid", ..] => (Step::Cue(TrailerCue::Asteroid { speed: number(3)? }), 4),
+            ["spawn", "enemy", pattern, ..] => (Step::Cue(TrailerCue::Enemy(EnemyPattern::parse(pattern)?)), 4),
+            ["hud", ..] => (Step::Cue(TrailerCue::Hud(switch(2)?)), 3),
+            ["waves", ..] => (Step::Cue(TrailerCue::Waves(switch(2)?)), 3),
+            ["pilot", "bot"] => (Step::Cue(TrailerCue::Bot(true)), 3),
+            ["pilot", "player"] => (Step::Cue(TrailerCue::Bot(false)), 3),
+            ["end"] => (Step::Cue(TrailerCue::End), 2),
+            _ => return None,
+        };
+
+        let valid = match step {
+            Step::Pan { over, .. } => over >= 0.0,
+            Step::Zoom { to, over } => to > 0.0 && over >= 0.0,
+            _ => true,
+        };
+
+        if words.len() != len || time < 0.0 || !valid {
+            return None;
+        }
+
+        Some((time, step))
+    }
+
+    /// Moves the script forward by `dt` seconds, moving `camera` through a
+    /// scene of size `(w, h)`. Returns the cues which the game should act on,
+    /// in order.
+    pub fn update(&mut self, dt: f64, camera: &mut Camera, (w, h): (f64, f64)) -> Vec<TrailerCue> {
+        self.time += dt;
+        let mut cues = vec![];
+
+        while let Some(&(time, step)) = self.steps.get(self.next) {
+            if time > self.time {
+                break;
+            }
+            self.next += 1;
+
+            //? Moves start from wherever the camera is, so that a new move
+            //? takes over smoothly from one which is not over yet. They ease
+            //? in and out, so that the camera does not start or stop abruptly.
+            match step {
+                Step::Pan { to: (x, y), over } => self.pan = Some((
+                    Tween::new(camera.center.0, x * w, over, Ease::InOut),
+                    Tween::new(camera.center.1, y * h, over, Ease::InOut),
+                )),
+                Step::Zoom { to, over } =>
+                    self.zoom = Some(Tween::new(camera.zoom, to, over, Ease::InOut)),
+                Step::Cue(cue) =>
+                    cues.push(cue),
+            }
+        }
+
+        if let Some((ref mut x, ref mut y)) = self.pan {
+            x.update(dt);
+            y.update(dt);
+            camera.center = (x.value(), y.value());
+        }
+
+        if let Some(ref mut zoom) = self.zoom {
+            zoom.update(dt);
+            camera.zoom = zoom.value();
+        }
+
+        cues
+    }
+}
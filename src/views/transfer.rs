@@ -0,0 +1,157 @@
+//! Moves the files of every profile, e.g. saves, settings, stats and the
+//! history of runs, from one machine to another through a single archive.
+
+use crate::phi::{archive, assets, storage};
+use crate::views::shared::{install_key, signed_files, BOOKMARKS_DIR, INSTALL_KEY_FILE, PROFILES_DIR};
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+
+/// Where the data is exported to, and imported from.
+pub const EXPORT_PATH: &str = "arcaders-data.zip";
+
+/// The entry of the archive which tells which version of the format it uses.
+const VERSION_ENTRY: &str = "export.cfg";
+
+/// The version of the format of the archives written by this build. Archives
+/// written by older builds are migrated when they are imported.
+///
+/// Version 2 signs the files which are signed on disk with `EXPORT_KEY`.
+const VERSION: u32 = 2;
+
+/// The key with which signed files travel in an archive, which every build of
+/// the game shares, so that any install may check them before it signs them
+/// again with its own key.
+const EXPORT_KEY: &[u8] = b"arcaders-export-v2";
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
+
+/// Whether `name`, relative to `PROFILES_DIR`, is signed with the key of the
+/// install.
+fn is_signed(name: &str) -> bool {
+    match name.split('/').collect::<Vec<_>>()[..] {
+        [_, file] => signed_files().contains(&file),
+        [_, dir, _] => dir == BOOKMARKS_DIR,
+        _ => false,
+    }
+}
+
+/// Whether `name` may be written under `PROFILES_DIR` without going outside
+/// of it, or overwriting the key of the install.
+fn is_safe(name: &str) -> bool {
+    let path = Path::new(name);
+    name != INSTALL_KEY_FILE && !name.contains('\\') &&
+        path.components().count() > 0 &&
+        path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Writes the files of every profile to an archive at `path`. Returns how many
+/// files were exported.
+pub fn export_data<P: AsRef<Path>>(path: P) -> io::Result<usize> {
+    let files = match assets::files(Path::new(PROFILES_DIR)) {
+        Ok(files) => files,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err),
+    };
+
+    //? Signatures made with the key of the install are only valid on the
+    //? machine which made them, so signed files are checked here, and signed
+    //? again with the key shared by every build. As with the key of the
+    //? install, this keeps honest players honest rather than stopping anyone
+    //? who reads the key out of the game.
+    let key = install_key();
+    let mut entries = vec![(VERSION_ENTRY.to_string(), format!("version={}\n", VERSION).into_bytes())];
+
+    for file in files {
+        let name = file.strip_prefix(PROFILES_DIR).unwrap_or(&file)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if name == INSTALL_KEY_FILE {
+            continue;
+        }
+
+        let mut contents = fs::read(&file)?;
+        if is_signed(&name) {
+            match ::std::str::from_utf8(&contents).ok().and_then(|text| storage::verified(text, &key)) {
+                Some(data) => {
+                    let mut text = data.to_string();
+                    storage::append_signature(&mut text, EXPORT_KEY);
+                    contents = text.into_bytes();
+                },
+                None => {
+                    println!("Not exporting {}: its signature does not match its contents", name);
+                    continue;
+                },
+            }
+        }
+
+        entries.push((name, contents));
+    }
+
+    archive::write(path, &entries)?;
+    Ok(entries.len() - 1)
+}
+
+/// Reads the files exported to the archive at `path`, and writes them over
+/// those of the profiles. Nothing is written unless the whole archive is
+/// valid. Returns how many files were imported.
+pub fn import_data<P: AsRef<Path>>(path: P) -> io::Result<usize> {
+    let entries = archive::read(path)?;
+
+    let version = entries.iter()
+        .find(|(name, _)| name == VERSION_ENTRY)
+        .and_then(|(_, contents)| ::std::str::from_utf8(contents).ok())
+        .and_then(|text| text.trim().strip_prefix("version="))
+        .and_then(|version| version.parse::<u32>().ok())
+        .ok_or_else(|| invalid("this is not an export of the game's data"))?;
+
+    if version > VERSION {
+        return Err(invalid("this export comes from a newer version of the game"));
+    }
+
+    let files: Vec<_> = entries.into_iter().filter(|(name, _)| name != VERSION_ENTRY).collect();
+    if let Some((name, _)) = files.iter().find(|(name, _)| !is_safe(name)) {
+        return Err(invalid(&format!("{} may not be imported", name)));
+    }
+
+    //? Version 1 exported signed files without any signature, so there is
+    //? no telling whether they were edited since: they are left out. Later
+    //? versions should convert the files of older exports here, before they
+    //? are checked and written.
+    let key = install_key();
+    let files = files.into_iter()
+        .filter(|(name, _)| {
+            let unverifiable = version < 2 && is_signed(name);
+            if unverifiable {
+                println!("Not importing {}: exports of version {} do not sign it", name, version);
+            }
+            !unverifiable
+        })
+        .map(|(name, contents)| {
+            if !is_signed(&name) {
+                return Ok((name, contents));
+            }
+
+            let data = ::std::str::from_utf8(&contents).ok()
+                .and_then(|text| storage::verified(text, EXPORT_KEY))
+                .ok_or_else(|| invalid(&format!("{} was changed since it was exported", name)))?;
+
+            let mut text = data.to_string();
+            storage::append_signature(&mut text, &key);
+            Ok((name, text.into_bytes()))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let count = files.len();
+    for (name, contents) in files {
+        let target = Path::new(PROFILES_DIR).join(&name);
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(target, contents)?;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,200 @@
+//! Walks new players through flying the ship, firing and switching cannons,
+//! one prompt at a time, before their first run starts. Every prompt waits
+//! for the player to actually do what it asks.
+
+use crate::phi::data::{Rectangle, Vec2};
+use crate::phi::gfx::{Camera, CopySprite, Sprite};
+use crate::phi::{Phi, PhiError, Transition, View, ViewAction};
+use crate::views::bullets::{Bullet, BulletPool};
+use crate::views::game::{Controls, GameView, Player};
+use crate::views::shared::{ParallaxBackground, STARFIELD_PATH};
+use crate::views::ships::SHIPS;
+use sdl2::pixels::Color;
+
+const PROMPT_FONT_PATH: &str = "assets/belligerent.ttf";
+const PROMPT_FONT_SIZE: i32 = 28;
+const HINT_FONT_SIZE: i32 = 16;
+
+/// How far the ship must fly, in pixels, how many times it must fire, and
+/// how many cannons it must try, for the first three steps to be done.
+const MOVE_DISTANCE: f64 = 400.0;
+const VOLLEYS: u32 = 6;
+const CANNONS_TRIED: usize = 3;
+
+/// How long the last prompt is shown before the run starts, and how long the
+/// fade to it takes, in seconds.
+const READY_TIME: f64 = 1.5;
+const START_FADE: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Move,
+    Fire,
+    Cannons,
+    Ready,
+}
+
+pub struct TutorialView {
+    // The ship which the player picked, as its index in `SHIPS`, which the
+    // run is started with once the tutorial is over.
+    ship: usize,
+
+    player: Player,
+    bullets: BulletPool,
+
+    step: Step,
+    prompt: Sprite,
+    hint: Sprite,
+
+    // The progress made towards the current step: the distance flown, the
+    // volleys fired, and the names of the cannons tried, in order.
+    distance: f64,
+    volleys: u32,
+    cannons: Vec<&'static str>,
+
+    // Time left before the run starts, once the last prompt is shown.
+    ready: f64,
+
+    camera: Camera,
+    background: ParallaxBackground,
+}
+
+impl TutorialView {
+    pub fn new(phi: &mut Phi, ship: usize) -> Result<TutorialView, PhiError> {
+        let player = Player::new(phi, SHIPS.get(ship).copied().unwrap_or(SHIPS[0]), 1.0, 1.0)?;
+        let cannon = player.cannon().name();
+
+        let skip = format!("Press {} to skip the tutorial", phi.events.bindings.key_escape.primary_name());
+
+        Ok(TutorialView {
+            ship,
+            player,
+            bullets: BulletPool::default(),
+            step: Step::Move,
+            prompt: TutorialView::prompt(phi, Step::Move)?,
+            hint: phi.ttf_str_sprite(&skip, PROMPT_FONT_PATH, HINT_FONT_SIZE, Color::RGB(160, 160, 160))?,
+            distance: 0.0,
+            volleys: 0,
+            cannons: vec![cannon],
+            ready: READY_TIME,
+            camera: Camera::new(phi.output_size()),
+            background: ParallaxBackground::load(&phi.renderer, STARFIELD_PATH)?,
+        })
+    }
+
+    /// What the player is asked to do at `step`, with the keys to which the
+    /// actions are bound.
+    fn prompt(phi: &mut Phi, step: Step) -> Result<Sprite, PhiError> {
+        let b = &phi.events.bindings;
+        let text = match step {
+            Step::Move => format!("Fly around with {} {} {} {}",
+                b.key_up.primary_name(), b.key_left.primary_name(),
+                b.key_down.primary_name(), b.key_right.primary_name()),
+            Step::Fire => format!("Hold {} to fire", b.key_space.primary_name()),
+            Step::Cannons => format!("Switch cannons with {} to {}",
+                b.key_1.primary_name(), b.key_4.primary_name()),
+            Step::Ready => "Get ready!".to_string(),
+        };
+
+        phi.ttf_str_sprite(&text, PROMPT_FONT_PATH, PROMPT_FONT_SIZE, Color::RGB(255, 255, 255))
+    }
+
+    /// Whether the player did what the current step asks.
+    fn is_step_done(&self) -> bool {
+        match self.step {
+            Step::Move => self.distance >= MOVE_DISTANCE,
+            Step::Fire => self.volleys >= VOLLEYS,
+            Step::Cannons => self.cannons.len() >= CANNONS_TRIED,
+            Step::Ready => self.ready <= 0.0,
+        }
+    }
+
+    fn start_run(self: Box<Self>, phi: &mut Phi) -> ViewAction {
+        let ship = self.ship;
+        ViewAction::change_view_with(Transition::Fade(START_FADE), self, GameView::new(phi, ship))
+    }
+}
+
+impl View for TutorialView {
+    fn update(mut self: Box<Self>, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            return self.start_run(phi);
+        }
+
+        // Fly the ship around, as in a run, but with nothing to shoot at.
+        let (w, h) = phi.output_size();
+        let controls = Controls::from_events(phi);
+        let from = Vec2::from(self.player.hitbox().center());
+
+        self.player.update(Rectangle::with_size(w, h), controls, elapsed);
+        self.distance += (Vec2::from(self.player.hitbox().center()) - from).length();
+
+        self.bullets.update(phi, elapsed, &[], &[]);
+        if phi.events.key_space {
+            let before = self.bullets.len();
+            self.player.fire(&mut phi.ids, &mut self.bullets);
+            if self.bullets.len() > before {
+                self.volleys += 1;
+            }
+        }
+
+        let cannon = self.player.cannon().name();
+        if !self.cannons.contains(&cannon) {
+            self.cannons.push(cannon);
+        }
+
+        if self.step == Step::Ready {
+            self.ready -= elapsed;
+        }
+
+        // Move on to the next prompt once the player did what this one asks.
+        if self.is_step_done() {
+            self.step = match self.step {
+                Step::Move => Step::Fire,
+                Step::Fire => Step::Cannons,
+                Step::Cannons => Step::Ready,
+                Step::Ready => return self.start_run(phi),
+            };
+
+            match TutorialView::prompt(phi, self.step) {
+                Ok(prompt) => self.prompt = prompt,
+                Err(err) => return ViewAction::Error(err),
+            }
+        }
+
+        // Update the backgrounds
+        self.background.update(elapsed);
+        self.camera.update(elapsed);
+
+        ViewAction::Render(self)
+    }
+
+    fn render(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen.
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        // Render the backgrounds
+        self.background.render_back(&mut phi.renderer);
+
+        self.player.render(phi, &self.camera, alpha);
+        for bullet in self.bullets.iter() {
+            bullet.render(phi, &self.camera);
+        }
+
+        self.background.render_front(&mut phi.renderer);
+
+        // Render the prompt at the top of the screen, and the hint at the
+        // bottom.
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.prompt.size();
+        phi.renderer.copy_sprite(&self.prompt, Rectangle { x: (win_w - w) / 2.0, y: 60.0, w, h });
+
+        let (w, h) = self.hint.size();
+        phi.renderer.copy_sprite(&self.hint, Rectangle { x: (win_w - w) / 2.0, y: win_h - h - 20.0, w, h });
+    }
+}